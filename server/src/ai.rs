@@ -1,8 +1,15 @@
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
 use crate::config::Config;
-use crate::forecast::types::HourlyData;
 use crate::scoring::{DryingScore, WeatherFeatures};
+use crate::tips::heuristic_tips;
+use moka::future::Cache;
 use reqwest::Client;
 use serde::{Deserialize, Serialize};
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
+use std::sync::{Arc, Mutex};
 use std::time::Duration;
 use thiserror::Error;
 
@@ -16,6 +23,87 @@ pub enum AiError {
     ApiError(String),
     #[error("Rate limited")]
     RateLimited,
+    #[error("Model {model} unavailable: HTTP {status}")]
+    ModelUnavailable { model: String, status: u16 },
+    #[error("Model {model} not found: {message}")]
+    ModelNotFound { model: String, message: String },
+    #[error("Prompt exceeds the model's context length: {0}")]
+    ContextLengthExceeded(String),
+    #[error("Insufficient OpenRouter credits: {0}")]
+    InsufficientCredits(String),
+    #[error("Model {0} is not in OR_ALLOWED_MODELS")]
+    ModelNotAllowed(String),
+    #[error("Model {0} is cooling down after repeated failures")]
+    CircuitOpen(String),
+}
+
+impl AiError {
+    /// Whether the model (or the way to it) misbehaved, as opposed to the
+    /// request being refused or the reply being unparseable
+    fn is_upstream_failure(&self) -> bool {
+        matches!(
+            self,
+            AiError::RequestFailed(_)
+                | AiError::ApiError(_)
+                | AiError::RateLimited
+                | AiError::ModelUnavailable { .. }
+                | AiError::ModelNotFound { .. }
+        )
+    }
+
+    /// Whether another model might succeed where this one failed
+    fn tries_next_model(&self) -> bool {
+        matches!(self, AiError::ModelUnavailable { .. } | AiError::ModelNotFound { .. })
+    }
+
+    /// The error for a non-200 reply from `model`, read from OpenRouter's
+    /// `{ "error": { "message", "code", "type" } }` body when it has one
+    fn from_response(model: &str, status: reqwest::StatusCode, body: &str) -> Self {
+        let Ok(ErrorResponse { error }) = serde_json::from_str::<ErrorResponse>(body) else {
+            return match status {
+                reqwest::StatusCode::TOO_MANY_REQUESTS => AiError::RateLimited,
+                reqwest::StatusCode::NOT_FOUND | reqwest::StatusCode::SERVICE_UNAVAILABLE => AiError::ModelUnavailable {
+                    model: model.to_string(),
+                    status: status.as_u16(),
+                },
+                status => AiError::ApiError(format!("HTTP {}: {}", status, body)),
+            };
+        };
+
+        // OpenRouter's `code` is the HTTP status; OpenAI-style providers put a
+        // name like `context_length_exceeded` in `code` or `type` instead
+        let names: Vec<String> = [error.code.as_ref().and_then(|code| code.as_str()), error.kind.as_deref()]
+            .into_iter()
+            .flatten()
+            .map(str::to_lowercase)
+            .collect();
+        let named = |name: &str| names.iter().any(|n| n == name);
+        let code = error.code.as_ref().and_then(serde_json::Value::as_u64).unwrap_or(status.as_u16().into());
+        let message = error.message.to_lowercase();
+
+        if code == 402 || named("insufficient_quota") {
+            AiError::InsufficientCredits(error.message)
+        } else if named("context_length_exceeded") || message.contains("context length") {
+            AiError::ContextLengthExceeded(error.message)
+        } else if code == 404 || named("model_not_found") || message.contains("not a valid model") {
+            AiError::ModelNotFound {
+                model: model.to_string(),
+                message: error.message,
+            }
+        } else if code == 429 {
+            AiError::RateLimited
+        } else if code == 503 {
+            AiError::ModelUnavailable {
+                model: model.to_string(),
+                status: 503,
+            }
+        } else {
+            match error.kind {
+                Some(kind) => AiError::ApiError(format!("HTTP {} ({}): {}", status, kind, error.message)),
+                None => AiError::ApiError(format!("HTTP {}: {}", status, error.message)),
+            }
+        }
+    }
 }
 
 #[derive(Debug, Serialize)]
@@ -36,15 +124,18 @@ struct ChatMessage {
 #[derive(Debug, Deserialize)]
 struct ChatResponse {
     choices: Vec<ChatChoice>,
+    #[allow(dead_code)]
     usage: Option<Usage>,
 }
 
 #[derive(Debug, Deserialize)]
 struct ChatChoice {
     message: ChatMessage,
+    #[allow(dead_code)]
     finish_reason: Option<String>,
 }
 
+#[allow(dead_code)]
 #[derive(Debug, Deserialize)]
 struct Usage {
     prompt_tokens: u32,
@@ -52,63 +143,240 @@ struct Usage {
     total_tokens: u32,
 }
 
+/// Rough characters per token for English prompts
+const CHARS_PER_TOKEN: usize = 4;
+
+/// Shortest cut of the user's preferences worth sending; less is dropped
+const MIN_PREFERENCE_CHARS: usize = 40;
+
+/// Tokens `text` will take, estimated from its length
+fn estimate_tokens(text: &str) -> usize {
+    text.chars().count().div_ceil(CHARS_PER_TOKEN)
+}
+
+/// One window's block in the explanation prompt
+fn describe_window((window_id, score, weather): &(String, DryingScore, WeatherFeatures)) -> String {
+    let mut block = format!("Window {}: Score {:.2}\n", window_id, score.score);
+    block.push_str(&format!("- Temperature: {:.1}°C\n", weather.temp_c));
+    block.push_str(&format!("- Humidity: {:.1}%\n", weather.rh));
+    block.push_str(&format!("- Wind: {:.1} m/s\n", weather.wind_ms));
+    block.push_str(&format!("- Cloud cover: {:.1}%\n", weather.cloud * 100.0));
+    block.push_str(&format!("- Rain probability: {:.1}%\n", weather.rain_p * 100.0));
+    if weather.rain_mm > 0.0 {
+        block.push_str(&format!("- Expected rain: {:.1}mm\n", weather.rain_mm));
+    }
+    block.push('\n');
+    block
+}
+
+/// OpenRouter's error envelope
+#[derive(Debug, Deserialize)]
+struct ErrorResponse {
+    error: ErrorDetail,
+}
+
+#[derive(Debug, Deserialize)]
+struct ErrorDetail {
+    message: String,
+    /// A number (OpenRouter) or a name (some upstream providers)
+    #[serde(default)]
+    code: Option<serde_json::Value>,
+    #[serde(default, rename = "type")]
+    kind: Option<String>,
+}
+
+/// Text generation backend for explanations, tips and feedback analysis.
+/// Implemented by the OpenRouter client and by a deterministic mock.
+#[async_trait]
+pub trait AiProvider: Send + Sync {
+    async fn explain_recommendation(
+        &self,
+        window_data: &[(String, DryingScore, WeatherFeatures)],
+        user_preferences: Option<&str>,
+    ) -> Result<String, AiError>;
+
+    async fn generate_drying_tips(
+        &self,
+        weather: &WeatherFeatures,
+        score: &DryingScore,
+    ) -> Result<String, AiError>;
+
+    async fn analyze_feedback(
+        &self,
+        feedback_text: &str,
+        weather_context: &WeatherFeatures,
+    ) -> Result<FeedbackAnalysis, AiError>;
+
+    async fn generate_laundry_recommendation(
+        &self,
+        weather: &WeatherFeatures,
+    ) -> Result<String, AiError>;
+
+    /// Circuit state of each model the provider calls, for `/health`
+    fn circuits(&self) -> Vec<ModelCircuit> {
+        Vec::new()
+    }
+}
+
+/// Whether requests currently reach a model
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CircuitState {
+    Closed,
+    /// Skipped until `retry_at`
+    Open,
+    /// Cooled down; the next request tries it again
+    HalfOpen,
+}
+
+/// A model's circuit as reported by `/health`
+#[derive(Debug, Clone, Serialize)]
+pub struct ModelCircuit {
+    pub model: String,
+    pub state: CircuitState,
+    pub consecutive_failures: u32,
+    pub retry_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Default)]
+struct ModelFailures {
+    consecutive: u32,
+    open_until: Option<DateTime<Utc>>,
+}
+
+/// Per-model negative cache of failures. After `threshold` consecutive
+/// failures a model is skipped for `cooldown`; then a single request tries it
+/// again, closing the circuit on success and re-opening it on failure.
+pub struct CircuitBreaker {
+    threshold: u32,
+    cooldown: chrono::Duration,
+    models: Mutex<HashMap<String, ModelFailures>>,
+}
+
+impl CircuitBreaker {
+    /// A `threshold` of 0 never opens the circuit
+    pub fn new(threshold: u32, cooldown: Duration) -> Self {
+        Self {
+            threshold,
+            cooldown: chrono::Duration::from_std(cooldown).unwrap_or(chrono::Duration::MAX),
+            models: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Whether `model` may be called at `now`. Letting a cooled-down model
+    /// through restarts its cooldown, so only one request makes the trial.
+    pub fn allows(&self, model: &str, now: DateTime<Utc>) -> bool {
+        let mut models = self.models.lock().unwrap();
+        let Some(failures) = models.get_mut(model) else {
+            return true;
+        };
+        match failures.open_until {
+            Some(until) if now < until => false,
+            Some(_) => {
+                failures.open_until = Some(now + self.cooldown);
+                true
+            }
+            None => true,
+        }
+    }
+
+    pub fn record_success(&self, model: &str) {
+        self.models.lock().unwrap().remove(model);
+    }
+
+    pub fn record_failure(&self, model: &str, now: DateTime<Utc>) {
+        let mut models = self.models.lock().unwrap();
+        let failures = models.entry(model.to_string()).or_default();
+        failures.consecutive += 1;
+        if self.threshold > 0 && failures.consecutive >= self.threshold {
+            failures.open_until = Some(now + self.cooldown);
+        }
+    }
+
+    pub fn status(&self, model: &str, now: DateTime<Utc>) -> ModelCircuit {
+        let models = self.models.lock().unwrap();
+        let failures = models.get(model);
+        let open_until = failures.and_then(|f| f.open_until);
+        ModelCircuit {
+            model: model.to_string(),
+            state: match open_until {
+                Some(until) if now < until => CircuitState::Open,
+                Some(_) => CircuitState::HalfOpen,
+                None => CircuitState::Closed,
+            },
+            consecutive_failures: failures.map(|f| f.consecutive).unwrap_or(0),
+            retry_at: open_until.filter(|until| now < *until),
+        }
+    }
+}
+
 pub struct AiClient {
     client: Client,
     config: Config,
+    circuits: CircuitBreaker,
 }
 
 impl AiClient {
     pub fn new(config: Config) -> Self {
         let client = Client::builder()
             .user_agent("LaundryDayOptimizer/1.0")
-            .timeout(Duration::from_secs(60))
+            .timeout(Duration::from_secs(config.ai_timeout_secs))
             .build()
             .expect("Failed to create HTTP client");
 
-        Self { client, config }
-    }
+        let circuits =
+            CircuitBreaker::new(config.ai_circuit_failures, Duration::from_secs(config.ai_circuit_cooldown_secs));
 
-    pub async fn explain_recommendation(
-        &self,
-        window_data: &[(String, DryingScore, WeatherFeatures)],
-        user_preferences: Option<&str>,
-    ) -> Result<String, AiError> {
-        let prompt = self.build_explanation_prompt(window_data, user_preferences);
-        self.chat_completion(&prompt).await
+        Self { client, config, circuits }
     }
 
-    pub async fn generate_drying_tips(
-        &self,
-        weather: &WeatherFeatures,
-        _score: &DryingScore,
-    ) -> Result<String, AiError> {
-        let prompt = self.build_tips_prompt(weather, _score);
-        self.chat_completion(&prompt).await
+    fn models(&self) -> impl Iterator<Item = &String> {
+        std::iter::once(&self.config.or_model).chain(&self.config.or_fallback_models)
     }
 
-    pub async fn analyze_feedback(
-        &self,
-        feedback_text: &str,
-        weather_context: &WeatherFeatures,
-    ) -> Result<FeedbackAnalysis, AiError> {
-        let prompt = self.build_feedback_analysis_prompt(feedback_text, weather_context);
-        let response = self.chat_completion(&prompt).await?;
-        
-        // Parse the structured response
-        self.parse_feedback_analysis(&response)
+    /// Minimal round trip through the configured models, for deployment checks
+    pub async fn ping(&self) -> Result<(), AiError> {
+        self.chat_completion("Reply with OK.").await.map(|_| ())
     }
 
-    pub async fn generate_laundry_recommendation(
-        &self,
-        weather: &WeatherFeatures,
-    ) -> Result<String, AiError> {
-        let prompt = self.build_laundry_recommendation_prompt(weather);
-        self.chat_completion(&prompt).await
+    /// Ask the primary model, falling through `or_fallback_models` in order
+    /// when a model is unknown, overloaded or cooling down after repeated
+    /// failures. Other errors return immediately.
+    async fn chat_completion(&self, prompt: &str) -> Result<String, AiError> {
+        let mut last_error = None;
+
+        for model in self.models() {
+            if !self.circuits.allows(model, Utc::now()) {
+                last_error = Some(AiError::CircuitOpen(model.clone()));
+                continue;
+            }
+
+            let result = self.chat_completion_with_model(prompt, model).await;
+            match &result {
+                Ok(_) => self.circuits.record_success(model),
+                Err(e) if e.is_upstream_failure() => self.circuits.record_failure(model, Utc::now()),
+                Err(_) => {}
+            }
+            match result {
+                Err(e) if e.tries_next_model() => {
+                    tracing::warn!("{}, trying next model", e);
+                    last_error = Some(e);
+                }
+                result => return result,
+            }
+        }
+
+        Err(last_error.expect("at least the primary model was tried"))
     }
 
-    async fn chat_completion(&self, prompt: &str) -> Result<String, AiError> {
+    async fn chat_completion_with_model(&self, prompt: &str, model: &str) -> Result<String, AiError> {
+        // Checked per call too, so no override path can reach a pricier model
+        if !self.config.is_model_allowed(model) {
+            return Err(AiError::ModelNotAllowed(model.to_string()));
+        }
+
         let request = ChatRequest {
-            model: self.config.or_model.clone(),
+            model: model.to_string(),
             messages: vec![
                 ChatMessage {
                     role: "system".to_string(),
@@ -124,14 +392,23 @@ impl AiClient {
             stream: false,
         };
 
-        let response = self
+        let request_builder = self
             .client
             .post(&self.config.openrouter_base_url)
             .header("Authorization", format!("Bearer {}", self.config.openrouter_api_key))
-            .header("Content-Type", "application/json")
-            .json(&request)
-            .send()
-            .await?;
+            .header("Content-Type", "application/json");
+
+        // Optional app attribution recommended by OpenRouter
+        let request_builder = match &self.config.openrouter_referer {
+            Some(referer) => request_builder.header("HTTP-Referer", referer),
+            None => request_builder,
+        };
+        let request_builder = match &self.config.openrouter_title {
+            Some(title) => request_builder.header("X-Title", title),
+            None => request_builder,
+        };
+
+        let response = request_builder.json(&request).send().await?;
 
         match response.status() {
             reqwest::StatusCode::OK => {
@@ -142,49 +419,56 @@ impl AiClient {
                     Err(AiError::ApiError("No response choices".to_string()))
                 }
             }
-            reqwest::StatusCode::TOO_MANY_REQUESTS => Err(AiError::RateLimited),
             status => {
                 let error_text = response.text().await.unwrap_or_default();
-                Err(AiError::ApiError(format!("HTTP {}: {}", status, error_text)))
+                Err(AiError::from_response(model, status, &error_text))
             }
         }
     }
 
+    /// The explanation prompt for up to three windows and the user's
+    /// preferences, trimmed to about `budget_tokens`: later windows go
+    /// first, then the preferences are cut short. The best window stays.
     fn build_explanation_prompt(
         &self,
         window_data: &[(String, DryingScore, WeatherFeatures)],
         user_preferences: Option<&str>,
+        budget_tokens: usize,
     ) -> String {
-        let mut prompt = String::from(
-            "Explain why these laundry drying time windows are recommended based on weather conditions:\n\n",
-        );
-
-        for (window_id, score, weather) in window_data.iter().take(3) {
-            prompt.push_str(&format!(
-                "Window {}: Score {:.2}\n",
-                window_id, score.score
-            ));
-            prompt.push_str(&format!(
-                "- Temperature: {:.1}°C\n",
-                weather.temp_c
-            ));
-            prompt.push_str(&format!("- Humidity: {:.1}%\n", weather.rh));
-            prompt.push_str(&format!("- Wind: {:.1} m/s\n", weather.wind_ms));
-            prompt.push_str(&format!("- Cloud cover: {:.1}%\n", weather.cloud * 100.0));
-            prompt.push_str(&format!("- Rain probability: {:.1}%\n", weather.rain_p * 100.0));
-            if weather.rain_mm > 0.0 {
-                prompt.push_str(&format!("- Expected rain: {:.1}mm\n", weather.rain_mm));
+        const HEADER: &str = "Explain why these laundry drying time windows are recommended based on weather conditions:\n\n";
+        const FOOTER: &str = "Provide a concise explanation (2-3 sentences) focusing on the key weather factors that make these windows optimal for drying clothes.";
+
+        let mut prompt = String::from(HEADER);
+        let windows: Vec<String> = window_data.iter().take(3).map(describe_window).collect();
+        let mut kept = 0;
+        for window in &windows {
+            if kept > 0 && estimate_tokens(&prompt) + estimate_tokens(window) + estimate_tokens(FOOTER) > budget_tokens {
+                break;
             }
-            prompt.push('\n');
+            prompt.push_str(window);
+            kept += 1;
+        }
+        if kept < windows.len() {
+            tracing::warn!("Explanation prompt over {} tokens, dropped {} of {} windows", budget_tokens, windows.len() - kept, windows.len());
         }
 
         if let Some(prefs) = user_preferences {
-            prompt.push_str(&format!("User preferences: {}\n\n", prefs));
+            let label = "User preferences: \n\n";
+            let room = budget_tokens.saturating_sub(estimate_tokens(&prompt) + estimate_tokens(FOOTER) + estimate_tokens(label));
+            let max_chars = room * CHARS_PER_TOKEN;
+            let chars = prefs.chars().count();
+            if chars <= max_chars {
+                prompt.push_str(&format!("User preferences: {}\n\n", prefs));
+            } else {
+                tracing::warn!("Explanation prompt over {} tokens, cut preferences from {} to {} characters", budget_tokens, chars, max_chars);
+                if max_chars >= MIN_PREFERENCE_CHARS {
+                    let cut: String = prefs.chars().take(max_chars.saturating_sub(1)).collect();
+                    prompt.push_str(&format!("User preferences: {}…\n\n", cut));
+                }
+            }
         }
 
-        prompt.push_str(
-            "Provide a concise explanation (2-3 sentences) focusing on the key weather factors that make these windows optimal for drying clothes.",
-        );
+        prompt.push_str(FOOTER);
 
         prompt
     }
@@ -269,12 +553,7 @@ impl AiClient {
                     _ => FeedbackSatisfaction::Neutral,
                 };
             } else if let Some(value) = line.strip_prefix("DRYING_RESULT: ") {
-                drying_result = match value.trim() {
-                    "completely_dry" => DryingResult::CompletelyDry,
-                    "mostly_dry" => DryingResult::MostlyDry,
-                    "not_dry" => DryingResult::NotDry,
-                    _ => DryingResult::PartiallyDry,
-                };
+                drying_result = DryingResult::parse(value).unwrap_or(DryingResult::PartiallyDry);
             } else if let Some(value) = line.strip_prefix("KEY_FACTORS: ") {
                 key_factors = value
                     .split(',')
@@ -299,6 +578,61 @@ impl AiClient {
     }
 }
 
+#[async_trait]
+impl AiProvider for AiClient {
+    async fn explain_recommendation(
+        &self,
+        window_data: &[(String, DryingScore, WeatherFeatures)],
+        user_preferences: Option<&str>,
+    ) -> Result<String, AiError> {
+        let budget = self.config.ai_prompt_token_budget;
+        let prompt = self.build_explanation_prompt(window_data, user_preferences, budget);
+        match self.chat_completion(&prompt).await {
+            // Our estimate was too generous for this model, so try once with half
+            Err(AiError::ContextLengthExceeded(message)) => {
+                tracing::warn!("Explanation prompt too long ({}), retrying with a smaller one", message);
+                let prompt = self.build_explanation_prompt(window_data, user_preferences, budget / 2);
+                self.chat_completion(&prompt).await
+            }
+            result => result,
+        }
+    }
+
+    async fn generate_drying_tips(
+        &self,
+        weather: &WeatherFeatures,
+        _score: &DryingScore,
+    ) -> Result<String, AiError> {
+        let prompt = self.build_tips_prompt(weather, _score);
+        self.chat_completion(&prompt).await
+    }
+
+    async fn analyze_feedback(
+        &self,
+        feedback_text: &str,
+        weather_context: &WeatherFeatures,
+    ) -> Result<FeedbackAnalysis, AiError> {
+        let prompt = self.build_feedback_analysis_prompt(feedback_text, weather_context);
+        let response = self.chat_completion(&prompt).await?;
+        
+        // Parse the structured response
+        self.parse_feedback_analysis(&response)
+    }
+
+    async fn generate_laundry_recommendation(
+        &self,
+        weather: &WeatherFeatures,
+    ) -> Result<String, AiError> {
+        let prompt = self.build_laundry_recommendation_prompt(weather);
+        self.chat_completion(&prompt).await
+    }
+
+    fn circuits(&self) -> Vec<ModelCircuit> {
+        let now = Utc::now();
+        self.models().map(|model| self.circuits.status(model, now)).collect()
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct FeedbackAnalysis {
     pub satisfaction: FeedbackSatisfaction,
@@ -314,7 +648,12 @@ pub enum FeedbackSatisfaction {
     Dissatisfied,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// How dry a load ended up. Stored and serialized as the snake_case name;
+/// input is normalized (case, spaces, hyphens and a few synonyms) and
+/// anything else is rejected.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, sqlx::Type)]
+#[serde(rename_all = "snake_case", try_from = "String")]
+#[sqlx(rename_all = "snake_case")]
 pub enum DryingResult {
     CompletelyDry,
     MostlyDry,
@@ -322,6 +661,82 @@ pub enum DryingResult {
     NotDry,
 }
 
+impl DryingResult {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            DryingResult::CompletelyDry => "completely_dry",
+            DryingResult::MostlyDry => "mostly_dry",
+            DryingResult::PartiallyDry => "partially_dry",
+            DryingResult::NotDry => "not_dry",
+        }
+    }
+
+    /// Parse a canonical name or a common variant ("Completely dry", "wet")
+    pub fn parse(value: &str) -> Option<Self> {
+        match canonical(value).as_str() {
+            "completely_dry" | "fully_dry" | "dry" => Some(DryingResult::CompletelyDry),
+            "mostly_dry" => Some(DryingResult::MostlyDry),
+            "partially_dry" | "damp" => Some(DryingResult::PartiallyDry),
+            "not_dry" | "wet" | "still_wet" => Some(DryingResult::NotDry),
+            _ => None,
+        }
+    }
+
+    /// Whether the user would count this result as a success
+    pub fn is_dry_enough(&self) -> bool {
+        matches!(self, DryingResult::CompletelyDry | DryingResult::MostlyDry)
+    }
+}
+
+impl TryFrom<String> for DryingResult {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        Self::parse(&value).ok_or_else(|| {
+            format!("unknown drying result {:?}, expected completely_dry, mostly_dry, partially_dry or not_dry", value)
+        })
+    }
+}
+
+/// Measured outcome of a drying window, e.g. from a sensor
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, sqlx::Type)]
+#[serde(rename_all = "snake_case", try_from = "String")]
+#[sqlx(rename_all = "snake_case")]
+pub enum ActualOutcome {
+    Dry,
+    NotDry,
+}
+
+impl ActualOutcome {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ActualOutcome::Dry => "dry",
+            ActualOutcome::NotDry => "not_dry",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match canonical(value).as_str() {
+            "dry" | "completely_dry" | "fully_dry" => Some(ActualOutcome::Dry),
+            "not_dry" | "wet" | "damp" => Some(ActualOutcome::NotDry),
+            _ => None,
+        }
+    }
+}
+
+impl TryFrom<String> for ActualOutcome {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        Self::parse(&value).ok_or_else(|| format!("unknown outcome {:?}, expected dry or not_dry", value))
+    }
+}
+
+/// Lowercase with spaces and hyphens as underscores
+fn canonical(value: &str) -> String {
+    value.trim().to_lowercase().replace([' ', '-'], "_")
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum AnalysisConfidence {
     High,
@@ -329,15 +744,114 @@ pub enum AnalysisConfidence {
     Low,
 }
 
+/// Language AI responses are generated in; part of the cache key so
+/// localized responses never collide.
+pub const DEFAULT_LANGUAGE: &str = "en";
+
+/// Caches explanations and tips from another provider, keyed by rounded
+/// weather, score band and language so near-identical windows share a reply.
+/// Feedback analysis and free-form recommendations are passed through.
+pub struct CachedAiClient {
+    inner: Arc<dyn AiProvider>,
+    cache: Cache<u64, String>,
+}
+
+impl CachedAiClient {
+    pub fn new(inner: Arc<dyn AiProvider>, ttl: Duration) -> Self {
+        Self {
+            inner,
+            cache: Cache::builder().max_capacity(10_000).time_to_live(ttl).build(),
+        }
+    }
+
+    fn key(kind: &str, parts: &[String]) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        (kind, DEFAULT_LANGUAGE, parts).hash(&mut hasher);
+        hasher.finish()
+    }
+}
+
+/// Bucketed weather plus a 0.2-wide score band, as a cache key component
+fn weather_band(weather: &WeatherFeatures, score: &DryingScore) -> String {
+    format!(
+        "{}|{}|{}",
+        weather.cache_key(),
+        (score.score * 5.0).floor() as i32,
+        score.unsafe_window
+    )
+}
+
+#[async_trait]
+impl AiProvider for CachedAiClient {
+    async fn explain_recommendation(
+        &self,
+        window_data: &[(String, DryingScore, WeatherFeatures)],
+        user_preferences: Option<&str>,
+    ) -> Result<String, AiError> {
+        let mut parts: Vec<String> = window_data
+            .iter()
+            .take(3)
+            .map(|(window_id, score, weather)| format!("{}|{}", window_id, weather_band(weather, score)))
+            .collect();
+        parts.push(user_preferences.unwrap_or_default().to_string());
+        let key = Self::key("explain", &parts);
+
+        if let Some(cached) = self.cache.get(&key).await {
+            return Ok(cached);
+        }
+        let explanation = self.inner.explain_recommendation(window_data, user_preferences).await?;
+        self.cache.insert(key, explanation.clone()).await;
+        Ok(explanation)
+    }
+
+    async fn generate_drying_tips(
+        &self,
+        weather: &WeatherFeatures,
+        score: &DryingScore,
+    ) -> Result<String, AiError> {
+        let key = Self::key("tips", &[weather_band(weather, score)]);
+
+        if let Some(cached) = self.cache.get(&key).await {
+            return Ok(cached);
+        }
+        let tips = self.inner.generate_drying_tips(weather, score).await?;
+        self.cache.insert(key, tips.clone()).await;
+        Ok(tips)
+    }
+
+    async fn analyze_feedback(
+        &self,
+        feedback_text: &str,
+        weather_context: &WeatherFeatures,
+    ) -> Result<FeedbackAnalysis, AiError> {
+        self.inner.analyze_feedback(feedback_text, weather_context).await
+    }
+
+    async fn generate_laundry_recommendation(
+        &self,
+        weather: &WeatherFeatures,
+    ) -> Result<String, AiError> {
+        self.inner.generate_laundry_recommendation(weather).await
+    }
+
+    fn circuits(&self) -> Vec<ModelCircuit> {
+        self.inner.circuits()
+    }
+}
+
 // Mock AI client for testing
+#[derive(Default)]
 pub struct MockAiClient;
 
 impl MockAiClient {
     pub fn new() -> Self {
         Self
     }
+}
 
-    pub async fn explain_recommendation(
+#[async_trait]
+impl AiProvider for MockAiClient {
+    async fn explain_recommendation(
         &self,
         window_data: &[(String, DryingScore, WeatherFeatures)],
         _user_preferences: Option<&str>,
@@ -355,35 +869,15 @@ impl MockAiClient {
         }
     }
 
-    pub async fn generate_drying_tips(
+    async fn generate_drying_tips(
         &self,
         weather: &WeatherFeatures,
         _score: &DryingScore,
     ) -> Result<String, AiError> {
-        let mut tips = Vec::new();
-
-        if weather.wind_ms > 3.0 {
-            tips.push("Take advantage of the strong wind by hanging clothes in open areas.");
-        } else if weather.wind_ms < 1.0 {
-            tips.push("With low wind, space clothes well apart for better air circulation.");
-        }
-
-        if weather.rh > 80.0 {
-            tips.push("High humidity may slow drying - consider using a covered but ventilated area.");
-        }
-
-        if weather.rain_p > 0.3 {
-            tips.push("Keep an eye on the sky and be ready to bring clothes in if rain starts.");
-        }
-
-        if tips.is_empty() {
-            tips.push("Conditions look good for drying - hang clothes evenly spaced for best results.");
-        }
-
-        Ok(tips.join(" "))
+        Ok(heuristic_tips(weather).join(" "))
     }
 
-    pub async fn analyze_feedback(
+    async fn analyze_feedback(
         &self,
         feedback_text: &str,
         _weather_context: &WeatherFeatures,
@@ -415,4 +909,367 @@ impl MockAiClient {
             confidence: AnalysisConfidence::Medium,
         })
     }
-}
\ No newline at end of file
+
+    async fn generate_laundry_recommendation(
+        &self,
+        weather: &WeatherFeatures,
+    ) -> Result<String, AiError> {
+        if weather.rain_p > 0.5 || weather.rain_mm > 0.2 {
+            Ok("Rain is likely, so it's best to dry indoors today.".to_string())
+        } else {
+            Ok(format!(
+                "Good time to hang clothes outside: {}°C, {}% humidity and {} m/s wind.",
+                weather.temp_c as i32,
+                weather.rh as i32,
+                weather.wind_ms as i32
+            ))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Mutex;
+    use std::time::Instant;
+
+    /// Serve `app` on an ephemeral port, returning the chat completions URL
+    async fn serve_stub(app: axum::Router) -> String {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+        format!("http://{}/chat/completions", addr)
+    }
+
+    /// Serve a chat endpoint that takes `delay` to answer
+    async fn slow_stub_server(delay: Duration) -> String {
+        serve_stub(axum::Router::new().route(
+            "/chat/completions",
+            axum::routing::post(move || async move {
+                tokio::time::sleep(delay).await;
+                "{}"
+            }),
+        ))
+        .await
+    }
+
+    fn chat_reply(content: &str) -> serde_json::Value {
+        serde_json::json!({
+            "choices": [{ "message": { "role": "assistant", "content": content }, "finish_reason": "stop" }]
+        })
+    }
+
+    #[tokio::test]
+    async fn test_ai_timeout_is_applied() {
+        let config = Config {
+            openrouter_base_url: slow_stub_server(Duration::from_secs(5)).await,
+            ai_timeout_secs: 1,
+            ..Config::for_tests()
+        };
+        let client = AiClient::new(config);
+
+        let started = Instant::now();
+        let result = client.explain_recommendation(&[], None).await;
+
+        assert!(matches!(result, Err(AiError::RequestFailed(ref e)) if e.is_timeout()));
+        assert!(started.elapsed() < Duration::from_secs(3));
+    }
+
+    #[tokio::test]
+    async fn test_attribution_headers_sent_when_configured() {
+        let seen = Arc::new(Mutex::new(Vec::<axum::http::HeaderMap>::new()));
+        let recorder = seen.clone();
+        let url = serve_stub(axum::Router::new().route(
+            "/chat/completions",
+            axum::routing::post(move |headers: axum::http::HeaderMap| {
+                let recorder = recorder.clone();
+                async move {
+                    recorder.lock().unwrap().push(headers);
+                    axum::Json(chat_reply("ok"))
+                }
+            }),
+        ))
+        .await;
+
+        let attributed = AiClient::new(Config {
+            openrouter_base_url: url.clone(),
+            openrouter_referer: Some("https://laundry.example".to_string()),
+            openrouter_title: Some("Laundry Day Optimizer".to_string()),
+            ..Config::for_tests()
+        });
+        let plain = AiClient::new(Config {
+            openrouter_base_url: url,
+            ..Config::for_tests()
+        });
+
+        assert_eq!(attributed.explain_recommendation(&[], None).await.unwrap(), "ok");
+        plain.explain_recommendation(&[], None).await.unwrap();
+
+        let seen = seen.lock().unwrap();
+        assert_eq!(seen[0]["http-referer"], "https://laundry.example");
+        assert_eq!(seen[0]["x-title"], "Laundry Day Optimizer");
+        assert!(!seen[1].contains_key("http-referer"));
+        assert!(!seen[1].contains_key("x-title"));
+    }
+
+    /// Stub that answers 503 for `overloaded` models and replies with the model name otherwise
+    async fn model_stub_server(overloaded: &'static [&'static str], calls: Arc<Mutex<Vec<String>>>) -> String {
+        serve_stub(axum::Router::new().route(
+            "/chat/completions",
+            axum::routing::post(move |axum::Json(body): axum::Json<serde_json::Value>| {
+                let calls = calls.clone();
+                async move {
+                    let model = body["model"].as_str().unwrap_or_default().to_string();
+                    calls.lock().unwrap().push(model.clone());
+                    if overloaded.contains(&model.as_str()) {
+                        Err(axum::http::StatusCode::SERVICE_UNAVAILABLE)
+                    } else {
+                        Ok(axum::Json(chat_reply(&model)))
+                    }
+                }
+            }),
+        ))
+        .await
+    }
+
+    #[tokio::test]
+    async fn test_primary_model_failure_falls_back() {
+        let calls = Arc::new(Mutex::new(Vec::new()));
+        let client = AiClient::new(Config {
+            openrouter_base_url: model_stub_server(&["primary", "backup-1"], calls.clone()).await,
+            or_model: "primary".to_string(),
+            or_fallback_models: vec!["backup-1".to_string(), "backup-2".to_string()],
+            ..Config::for_tests()
+        });
+
+        let reply = client.explain_recommendation(&[], None).await.unwrap();
+
+        assert_eq!(reply, "backup-2");
+        assert_eq!(*calls.lock().unwrap(), vec!["primary", "backup-1", "backup-2"]);
+    }
+
+    #[tokio::test]
+    async fn test_all_models_unavailable_returns_last_error() {
+        let calls = Arc::new(Mutex::new(Vec::new()));
+        let client = AiClient::new(Config {
+            openrouter_base_url: model_stub_server(&["primary", "backup"], calls.clone()).await,
+            or_model: "primary".to_string(),
+            or_fallback_models: vec!["backup".to_string()],
+            ..Config::for_tests()
+        });
+
+        let result = client.explain_recommendation(&[], None).await;
+
+        assert!(matches!(result, Err(AiError::ModelUnavailable { ref model, status: 503 }) if model == "backup"));
+        assert_eq!(calls.lock().unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_openrouter_error_bodies_are_classified() {
+        let classify = |status: u16, body: serde_json::Value| {
+            AiError::from_response("primary", reqwest::StatusCode::from_u16(status).unwrap(), &body.to_string())
+        };
+
+        let credits = classify(402, serde_json::json!({ "error": { "message": "Insufficient credits", "code": 402 } }));
+        assert!(matches!(credits, AiError::InsufficientCredits(ref m) if m == "Insufficient credits"));
+
+        let context = classify(400, serde_json::json!({ "error": {
+            "message": "This endpoint's maximum context length is 8192 tokens. However, you requested about 9000 tokens",
+            "code": 400
+        } }));
+        assert!(matches!(context, AiError::ContextLengthExceeded(_)));
+        let named = classify(400, serde_json::json!({ "error": {
+            "message": "Too long", "type": "invalid_request_error", "code": "context_length_exceeded"
+        } }));
+        assert!(matches!(named, AiError::ContextLengthExceeded(ref m) if m == "Too long"));
+
+        let not_found = classify(400, serde_json::json!({ "error": { "message": "foo/bar is not a valid model ID", "code": 400 } }));
+        assert!(matches!(not_found, AiError::ModelNotFound { ref model, .. } if model == "primary"));
+        let no_endpoints = classify(404, serde_json::json!({ "error": { "message": "No endpoints found for foo/bar", "code": 404 } }));
+        assert!(matches!(no_endpoints, AiError::ModelNotFound { ref message, .. } if message.contains("No endpoints")));
+
+        let other = classify(400, serde_json::json!({ "error": { "message": "Bad input", "type": "invalid_request_error" } }));
+        assert!(matches!(other, AiError::ApiError(ref m) if m.contains("invalid_request_error") && m.contains("Bad input")));
+        assert!(matches!(classify(429, serde_json::json!({ "error": { "message": "Slow down", "code": 429 } })), AiError::RateLimited));
+
+        // Unstructured bodies still map by status
+        let raw = AiError::from_response("primary", reqwest::StatusCode::BAD_GATEWAY, "<html>bad gateway</html>");
+        assert!(matches!(raw, AiError::ApiError(ref m) if m.contains("<html>")));
+        let raw = AiError::from_response("primary", reqwest::StatusCode::NOT_FOUND, "");
+        assert!(matches!(raw, AiError::ModelUnavailable { status: 404, .. }));
+    }
+
+    #[tokio::test]
+    async fn test_unknown_model_falls_back() {
+        let url = serve_stub(axum::Router::new().route(
+            "/chat/completions",
+            axum::routing::post(|axum::Json(body): axum::Json<serde_json::Value>| async move {
+                match body["model"].as_str() {
+                    Some("retired") => Err((
+                        axum::http::StatusCode::BAD_REQUEST,
+                        axum::Json(serde_json::json!({ "error": { "message": "retired is not a valid model ID", "code": 400 } })),
+                    )),
+                    _ => Ok(axum::Json(chat_reply("backup"))),
+                }
+            }),
+        ))
+        .await;
+        let client = AiClient::new(Config {
+            openrouter_base_url: url,
+            or_model: "retired".to_string(),
+            or_fallback_models: vec!["backup".to_string()],
+            ..Config::for_tests()
+        });
+
+        assert_eq!(client.explain_recommendation(&[], None).await.unwrap(), "backup");
+    }
+
+    #[tokio::test]
+    async fn test_failing_model_is_skipped_until_cooled_down() {
+        let failing = Arc::new(std::sync::atomic::AtomicBool::new(true));
+        let calls = Arc::new(AtomicUsize::new(0));
+        let (stub_failing, stub_calls) = (failing.clone(), calls.clone());
+        let url = serve_stub(axum::Router::new().route(
+            "/chat/completions",
+            axum::routing::post(move || {
+                let (failing, calls) = (stub_failing.clone(), stub_calls.clone());
+                async move {
+                    calls.fetch_add(1, Ordering::SeqCst);
+                    if failing.load(Ordering::SeqCst) {
+                        Err(axum::http::StatusCode::INTERNAL_SERVER_ERROR)
+                    } else {
+                        Ok(axum::Json(chat_reply("recovered")))
+                    }
+                }
+            }),
+        ))
+        .await;
+        let mut client = AiClient::new(Config {
+            openrouter_base_url: url,
+            or_model: "primary".to_string(),
+            ..Config::for_tests()
+        });
+        client.circuits = CircuitBreaker::new(2, Duration::from_millis(200));
+
+        for _ in 0..2 {
+            assert!(matches!(client.explain_recommendation(&[], None).await, Err(AiError::ApiError(_))));
+        }
+        // Tripped: answered without touching the model
+        let result = client.explain_recommendation(&[], None).await;
+        assert!(matches!(result, Err(AiError::CircuitOpen(ref model)) if model == "primary"));
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+        let circuit = &AiProvider::circuits(&client)[0];
+        assert_eq!(circuit.state, CircuitState::Open);
+        assert_eq!(circuit.consecutive_failures, 2);
+        assert!(circuit.retry_at.is_some());
+
+        failing.store(false, Ordering::SeqCst);
+        tokio::time::sleep(Duration::from_millis(250)).await;
+        assert_eq!(AiProvider::circuits(&client)[0].state, CircuitState::HalfOpen);
+
+        assert_eq!(client.explain_recommendation(&[], None).await.unwrap(), "recovered");
+        let circuit = &AiProvider::circuits(&client)[0];
+        assert_eq!(circuit.state, CircuitState::Closed);
+        assert_eq!(circuit.consecutive_failures, 0);
+        assert_eq!(calls.load(Ordering::SeqCst), 3);
+    }
+
+    #[tokio::test]
+    async fn test_disallowed_model_override_is_rejected() {
+        let calls = Arc::new(Mutex::new(Vec::new()));
+        let client = AiClient::new(Config {
+            openrouter_base_url: model_stub_server(&[], calls.clone()).await,
+            or_model: "primary".to_string(),
+            or_allowed_models: vec!["primary".to_string()],
+            ..Config::for_tests()
+        });
+
+        let result = client.chat_completion_with_model("hi", "pricey/model").await;
+
+        assert!(matches!(result, Err(AiError::ModelNotAllowed(ref model)) if model == "pricey/model"));
+        assert!(calls.lock().unwrap().is_empty());
+        assert_eq!(client.chat_completion_with_model("hi", "primary").await.unwrap(), "primary");
+    }
+
+    fn window(temp_c: f64) -> (String, DryingScore, WeatherFeatures) {
+        let weather = WeatherFeatures {
+            temp_c,
+            rh: 55.0,
+            wind_ms: 3.0,
+            cloud: 0.2,
+            rain_p: 0.1,
+            rain_mm: 0.0,
+            gust_ms: None,
+            is_daylight: None,
+            wind_deg: None,
+        };
+        let score = crate::scoring::calculate_drying_score(&weather, &Default::default());
+        ("2026-10-16 09:00".to_string(), score, weather)
+    }
+
+    #[test]
+    fn test_oversized_explanation_prompt_is_truncated_to_budget() {
+        let client = AiClient::new(Config::for_tests());
+        let windows = [window(28.0), window(30.0), window(32.0)];
+        let prefs = "I prefer mornings. ".repeat(500);
+
+        let prompt = client.build_explanation_prompt(&windows, Some(&prefs), 300);
+        assert!(estimate_tokens(&prompt) <= 300, "{} tokens", estimate_tokens(&prompt));
+        assert_eq!(prompt.matches("Window ").count(), 3);
+        assert!(prompt.contains("User preferences: I prefer mornings.") && prompt.contains('…'));
+        assert!(prompt.ends_with("optimal for drying clothes."));
+
+        // Too tight for all windows: the best one stays, the preferences go
+        let prompt = client.build_explanation_prompt(&windows, Some(&prefs), 100);
+        assert!(estimate_tokens(&prompt) <= 100, "{} tokens", estimate_tokens(&prompt));
+        assert_eq!(prompt.matches("Window ").count(), 1);
+        assert!(prompt.contains("Temperature: 28.0°C"));
+        assert!(!prompt.contains("User preferences"));
+
+        // Within budget: nothing changes
+        let prompt = client.build_explanation_prompt(&windows, Some("mornings"), 2000);
+        assert_eq!(prompt.matches("Window ").count(), 3);
+        assert!(prompt.contains("User preferences: mornings\n"));
+    }
+
+    #[tokio::test]
+    async fn test_context_length_error_retries_with_smaller_prompt() {
+        let prompts = Arc::new(Mutex::new(Vec::new()));
+        let seen = prompts.clone();
+        let url = serve_stub(axum::Router::new().route(
+            "/chat/completions",
+            axum::routing::post(move |axum::Json(body): axum::Json<serde_json::Value>| {
+                let seen = seen.clone();
+                async move {
+                    let prompt = body["messages"][1]["content"].as_str().unwrap_or_default().to_string();
+                    let length = prompt.chars().count();
+                    seen.lock().unwrap().push(length);
+                    if length > 4000 {
+                        Err((
+                            axum::http::StatusCode::BAD_REQUEST,
+                            axum::Json(serde_json::json!({ "error": {
+                                "message": "This endpoint's maximum context length is 1000 tokens", "code": 400
+                            } })),
+                        ))
+                    } else {
+                        Ok(axum::Json(chat_reply("fits")))
+                    }
+                }
+            }),
+        ))
+        .await;
+        let client = AiClient::new(Config {
+            openrouter_base_url: url,
+            ai_prompt_token_budget: 2000,
+            ..Config::for_tests()
+        });
+
+        let prefs = "x".repeat(20_000);
+        assert_eq!(client.explain_recommendation(&[window(28.0)], Some(&prefs)).await.unwrap(), "fits");
+        let prompts = prompts.lock().unwrap();
+        assert_eq!(prompts.len(), 2);
+        assert!(prompts[1] <= 4000 && prompts[0] > 4000);
+    }
+}
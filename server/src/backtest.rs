@@ -0,0 +1,281 @@
+use crate::database::FeedbackRecord;
+use crate::routes::WeatherConditions;
+use crate::scoring::{
+    calculate_drying_score, normalize_features, update_weights_minibatch, DryingWeights, WeatherFeatures,
+    SGD_LEARNING_RATE, SGD_REGULARIZATION,
+};
+use serde::{Deserialize, Serialize};
+
+/// Predicted score bands used for the calibration breakdown
+const SCORE_BANDS: [(&str, f64, f64); 5] = [
+    ("0.0-0.2", f64::NEG_INFINITY, 0.2),
+    ("0.2-0.4", 0.2, 0.4),
+    ("0.4-0.6", 0.4, 0.6),
+    ("0.6-0.8", 0.6, 0.8),
+    ("0.8+", 0.8, f64::INFINITY),
+];
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BacktestReport {
+    pub total_records: usize,
+    pub evaluated: usize,
+    pub skipped: usize,
+    pub threshold: f64,
+    pub accuracy: f64,
+    pub brier_score: f64,
+    pub true_positives: usize,
+    pub false_positives: usize,
+    pub true_negatives: usize,
+    pub false_negatives: usize,
+    pub bands: Vec<ScoreBand>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScoreBand {
+    pub band: String,
+    pub satisfied: usize,
+    pub dissatisfied: usize,
+    pub mean_predicted: f64,
+    pub observed_rate: f64,
+}
+
+/// Derive a satisfied/dissatisfied label from a feedback record.
+/// The explicit rating wins; otherwise fall back to the drying result.
+pub fn feedback_label(record: &FeedbackRecord) -> Option<bool> {
+    if let Some(rating) = record.satisfaction_rating {
+        return match rating {
+            4..=5 => Some(true),
+            1..=2 => Some(false),
+            _ => None,
+        };
+    }
+
+    record.drying_result.map(|result| result.is_dry_enough())
+}
+
+/// Rebuild the weather features stored with a feedback record; `None` unless
+/// all of them were stored, as defaults would skew the comparison
+pub fn feedback_weather(record: &FeedbackRecord) -> Option<WeatherFeatures> {
+    let conditions = WeatherConditions::from(record);
+    conditions.is_complete().then(|| WeatherFeatures::from(&conditions))
+}
+
+/// Fit global weights to every labelled feedback record, one averaged
+/// minibatch step per epoch starting from `weights`. This is the batch
+/// counterpart to the per-outcome updates in `POST /outcomes`.
+pub fn train_global_weights(records: &[FeedbackRecord], weights: &DryingWeights, epochs: usize) -> DryingWeights {
+    let samples: Vec<_> = records
+        .iter()
+        .filter_map(|record| {
+            let label = if feedback_label(record)? { 1.0 } else { 0.0 };
+            let (features, _) = normalize_features(&feedback_weather(record)?);
+            Some((features, label))
+        })
+        .collect();
+
+    let mut trained = weights.clone();
+    for _ in 0..epochs {
+        update_weights_minibatch(&mut trained, &samples, SGD_LEARNING_RATE, SGD_REGULARIZATION);
+    }
+    trained
+}
+
+/// Replay feedback through the current scoring and compare predicted vs actual
+pub fn run_backtest(
+    records: &[FeedbackRecord],
+    weights: &DryingWeights,
+    threshold: f64,
+) -> BacktestReport {
+    // (predicted probability, satisfied)
+    let samples: Vec<(f64, bool)> = records
+        .iter()
+        .filter_map(|record| {
+            let label = feedback_label(record)?;
+            let weather = feedback_weather(record)?;
+            let score = calculate_drying_score(&weather, weights);
+            // Safe scores are already clamped to 0-1; a veto predicts failure
+            let predicted = if score.unsafe_window { 0.0 } else { score.score };
+            Some((predicted, label))
+        })
+        .collect();
+
+    let mut true_positives = 0;
+    let mut false_positives = 0;
+    let mut true_negatives = 0;
+    let mut false_negatives = 0;
+    let mut squared_error = 0.0;
+
+    for &(predicted, satisfied) in &samples {
+        match (predicted >= threshold, satisfied) {
+            (true, true) => true_positives += 1,
+            (true, false) => false_positives += 1,
+            (false, false) => true_negatives += 1,
+            (false, true) => false_negatives += 1,
+        }
+        let y = if satisfied { 1.0 } else { 0.0 };
+        squared_error += (predicted - y).powi(2);
+    }
+
+    let evaluated = samples.len();
+    let (accuracy, brier_score) = if evaluated > 0 {
+        (
+            (true_positives + true_negatives) as f64 / evaluated as f64,
+            squared_error / evaluated as f64,
+        )
+    } else {
+        (0.0, 0.0)
+    };
+
+    let bands = SCORE_BANDS
+        .iter()
+        .map(|&(name, low, high)| {
+            let in_band: Vec<&(f64, bool)> = samples
+                .iter()
+                .filter(|(predicted, _)| *predicted >= low && *predicted < high)
+                .collect();
+            let satisfied = in_band.iter().filter(|(_, s)| *s).count();
+            let count = in_band.len();
+
+            ScoreBand {
+                band: name.to_string(),
+                satisfied,
+                dissatisfied: count - satisfied,
+                mean_predicted: if count > 0 {
+                    in_band.iter().map(|(p, _)| p).sum::<f64>() / count as f64
+                } else {
+                    0.0
+                },
+                observed_rate: if count > 0 {
+                    satisfied as f64 / count as f64
+                } else {
+                    0.0
+                },
+            }
+        })
+        .collect();
+
+    BacktestReport {
+        total_records: records.len(),
+        evaluated,
+        skipped: records.len() - evaluated,
+        threshold,
+        accuracy,
+        brier_score,
+        true_positives,
+        false_positives,
+        true_negatives,
+        false_negatives,
+        bands,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ai::DryingResult;
+    use uuid::Uuid;
+
+    fn record(
+        temp_c: f64,
+        humidity: f64,
+        wind_ms: f64,
+        rain_mm: f64,
+        rating: Option<i32>,
+    ) -> FeedbackRecord {
+        FeedbackRecord {
+            id: Uuid::new_v4(),
+            user_id: None,
+            window_id: "window_0_3".to_string(),
+            feedback_text: "test".to_string(),
+            satisfaction_rating: rating,
+            drying_result: None,
+            weather_temp_c: Some(temp_c),
+            weather_humidity: Some(humidity),
+            weather_wind_ms: Some(wind_ms),
+            weather_rain_mm: Some(rain_mm),
+            predicted_score: None,
+            actual_outcome: None,
+            created_at: chrono::Utc::now(),
+            recomputed_score: None,
+            analysis: None,
+        }
+    }
+
+    #[test]
+    fn test_backtest_small_labeled_dataset() {
+        let mut unlabeled = record(28.0, 50.0, 3.0, 0.0, Some(3));
+        unlabeled.weather_humidity = None;
+
+        let records = vec![
+            // Hot, dry and breezy: predicted good, user satisfied
+            record(32.0, 35.0, 4.0, 0.0, Some(5)),
+            // Rainy: vetoed, user dissatisfied
+            record(24.0, 90.0, 1.0, 3.0, Some(1)),
+            // Cool and still: predicted poor, but the user was happy
+            record(15.0, 80.0, 0.5, 0.0, Some(4)),
+            // Missing weather: skipped
+            unlabeled,
+        ];
+
+        let report = run_backtest(&records, &DryingWeights::default(), 0.5);
+
+        assert_eq!(report.total_records, 4);
+        assert_eq!(report.evaluated, 3);
+        assert_eq!(report.skipped, 1);
+        assert_eq!(report.true_positives, 1);
+        assert_eq!(report.true_negatives, 1);
+        assert_eq!(report.false_negatives, 1);
+        assert_eq!(report.false_positives, 0);
+        assert!((report.accuracy - 2.0 / 3.0).abs() < 1e-9);
+        assert!(report.brier_score > 0.0 && report.brier_score < 1.0);
+
+        let lowest = &report.bands[0];
+        assert_eq!(lowest.satisfied + lowest.dissatisfied, 2);
+        assert_eq!(lowest.dissatisfied, 1);
+    }
+
+    #[test]
+    fn test_train_global_weights_separates_good_from_bad_weather() {
+        let good = record(32.0, 35.0, 4.0, 0.0, Some(5));
+        let bad = record(14.0, 90.0, 0.5, 0.0, Some(1));
+        let mut unlabeled = record(14.0, 90.0, 0.5, 0.0, Some(3));
+        unlabeled.weather_temp_c = None;
+        let records = vec![good.clone(), bad.clone(), unlabeled.clone()];
+
+        let gap = |weights: &DryingWeights| {
+            let score = |r: &FeedbackRecord| calculate_drying_score(&feedback_weather(r).unwrap(), weights).score;
+            score(&good) - score(&bad)
+        };
+        let start = DryingWeights::default();
+        let trained = train_global_weights(&records, &start, 50);
+        assert!(gap(&trained) > gap(&start));
+
+        // Nothing usable to learn from leaves the weights alone
+        assert_eq!(train_global_weights(&[unlabeled], &start, 50), start);
+        assert_eq!(train_global_weights(&records, &start, 0), start);
+    }
+
+    #[test]
+    fn test_feedback_label_falls_back_to_drying_result() {
+        let mut r = record(25.0, 60.0, 2.0, 0.0, None);
+        r.drying_result = Some(DryingResult::NotDry);
+        assert_eq!(feedback_label(&r), Some(false));
+
+        r.drying_result = Some(DryingResult::MostlyDry);
+        assert_eq!(feedback_label(&r), Some(true));
+
+        r.drying_result = None;
+        assert_eq!(feedback_label(&r), None);
+    }
+
+    #[test]
+    fn test_feedback_weather_needs_every_stored_condition() {
+        let mut r = record(25.0, 60.0, 2.0, 1.2, Some(2));
+        let weather = feedback_weather(&r).unwrap();
+        assert_eq!((weather.temp_c, weather.rh, weather.wind_ms), (25.0, 60.0, 2.0));
+        assert_eq!((weather.rain_p, weather.rain_mm), (0.8, 1.2));
+
+        r.weather_wind_ms = None;
+        assert!(feedback_weather(&r).is_none());
+    }
+}
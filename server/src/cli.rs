@@ -0,0 +1,255 @@
+use serde::Serialize;
+use std::fmt;
+
+use crate::ai::AiClient;
+use crate::backtest::train_global_weights;
+use crate::config::Config;
+use crate::database::{DataStore, DatabaseError};
+use crate::error::{validate_range, AppError};
+use crate::forecast::{openweather::OpenWeatherClient, WeatherProvider};
+use crate::routes::{
+    build_drying_windows, find_next_good_window, DryingWindow, LocationInfo, NextGoodWindow, MAX_WINDOW_HOURS,
+};
+use crate::scoring::DryingWeights;
+
+/// What `laundry-optimizer score` prints
+#[derive(Debug, Serialize)]
+pub struct ScoreReport {
+    pub location: LocationInfo,
+    pub best_window: Option<DryingWindow>,
+    pub next_good_window: Option<NextGoodWindow>,
+    pub generated_at: chrono::DateTime<chrono::Utc>,
+}
+
+/// Score the forecast for one location the same way `/drying-windows` does
+/// and keep only the best window, without touching the database or the AI.
+pub async fn score_location(
+    config: &Config,
+    weather_client: &dyn WeatherProvider,
+    lat: f64,
+    lon: f64,
+    window_hours: Option<u32>,
+) -> Result<ScoreReport, AppError> {
+    let window_hours = validate_range("window_hours", window_hours, 3, MAX_WINDOW_HOURS)?;
+    let windows = build_drying_windows(config, weather_client, lat, lon, window_hours, &config.scoring_config(None)).await?;
+
+    let now = chrono::Utc::now();
+    let next_good_window = find_next_good_window(&windows, &config.score_thresholds, now);
+    let best_window = windows.into_iter().next().map(|mut window| {
+        window.round(&config.output_precision);
+        window
+    });
+
+    Ok(ScoreReport {
+        location: LocationInfo {
+            lat,
+            lon,
+            name: None,
+            country: None,
+        },
+        best_window,
+        next_good_window,
+        generated_at: now,
+    })
+}
+
+/// What `laundry-optimizer train-weights` prints
+#[derive(Debug, Serialize)]
+pub struct TrainReport {
+    /// Feedback records loaded; unlabelled ones and those without weather
+    /// are left out of training
+    pub records: usize,
+    pub epochs: usize,
+    pub weights: DryingWeights,
+    pub trained_at: chrono::DateTime<chrono::Utc>,
+}
+
+/// Fit global weights to the stored feedback, starting from the defaults.
+/// The result is only reported, never saved.
+pub async fn train_weights(database: &dyn DataStore, epochs: usize) -> Result<TrainReport, DatabaseError> {
+    let records = database.get_feedback_with_weather(Some(10_000)).await?;
+    let weights = train_global_weights(&records, &DryingWeights::default(), epochs);
+
+    Ok(TrainReport {
+        records: records.len(),
+        epochs,
+        weights,
+        trained_at: chrono::Utc::now(),
+    })
+}
+
+/// One line of the `check-config` report
+#[derive(Debug, Serialize)]
+pub struct Check {
+    pub name: String,
+    pub passed: bool,
+    pub detail: String,
+}
+
+impl Check {
+    fn new(name: &str, result: Result<String, String>) -> Self {
+        let (passed, detail) = match result {
+            Ok(detail) => (true, detail),
+            Err(detail) => (false, detail),
+        };
+        Self {
+            name: name.to_string(),
+            passed,
+            detail,
+        }
+    }
+}
+
+/// Outcome of `laundry-optimizer check-config`
+#[derive(Debug, Serialize)]
+pub struct ConfigReport {
+    pub checks: Vec<Check>,
+}
+
+impl ConfigReport {
+    pub fn passed(&self) -> bool {
+        self.checks.iter().all(|check| check.passed)
+    }
+
+    /// Process exit code: 0 when every check passed, 1 otherwise
+    pub fn exit_code(&self) -> i32 {
+        if self.passed() {
+            0
+        } else {
+            1
+        }
+    }
+}
+
+impl fmt::Display for ConfigReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for check in &self.checks {
+            let status = if check.passed { "PASS" } else { "FAIL" };
+            writeln!(f, "[{}] {}: {}", status, check.name, check.detail)?;
+        }
+        write!(f, "{}", if self.passed() { "configuration OK" } else { "configuration has problems" })
+    }
+}
+
+/// Load the configuration from `lookup` and report every problem found.
+/// With `ping`, also make one real call to the weather and AI providers.
+pub async fn check_config(lookup: impl Fn(&str) -> Option<String>, ping: bool) -> ConfigReport {
+    let config = match Config::from_lookup(lookup) {
+        Ok(config) => config,
+        Err(e) => {
+            let checks = e
+                .problems
+                .into_iter()
+                .map(|problem| Check::new("config", Err(problem)))
+                .collect();
+            return ConfigReport { checks };
+        }
+    };
+
+    let mut checks = vec![Check::new(
+        "config",
+        Ok(format!("loaded (timezone {}, model {})", config.app_timezone, config.or_model)),
+    )];
+
+    if ping {
+        let weather = OpenWeatherClient::new(config.clone());
+        checks.push(Check::new(
+            "weather",
+            weather
+                .get_forecast3h(0.0, 0.0)
+                .await
+                .map(|_| format!("reachable at {}", config.openweather_base_url))
+                .map_err(|e| e.to_string()),
+        ));
+
+        let ai = AiClient::new(config.clone());
+        checks.push(Check::new(
+            "ai",
+            ai.ping()
+                .await
+                .map(|_| format!("reachable at {}", config.openrouter_base_url))
+                .map_err(|e| e.to_string()),
+        ));
+    }
+
+    ConfigReport { checks }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::forecast::mock::MockWeatherClient;
+
+    #[tokio::test]
+    async fn test_score_location_returns_best_window() {
+        let config = Config::for_tests();
+        let report = score_location(&config, &MockWeatherClient::new(), 13.75, 100.5, Some(4))
+            .await
+            .unwrap();
+
+        let best = report.best_window.expect("mock forecast has windows");
+        assert_eq!(best.duration_hours, 4);
+        assert!((0.0..=1.0).contains(&best.score.score));
+        assert_eq!(report.location.lat, 13.75);
+
+        let json = serde_json::to_value(&ScoreReport { best_window: Some(best), ..report }).unwrap();
+        assert!(json["best_window"]["id"].as_str().unwrap().starts_with("window_"));
+    }
+
+    #[tokio::test]
+    async fn test_score_location_rejects_bad_window() {
+        let config = Config::for_tests();
+        let err = score_location(&config, &MockWeatherClient::new(), 13.75, 100.5, Some(0))
+            .await
+            .unwrap_err();
+        assert_eq!(err.code, "invalid_parameter");
+    }
+
+    #[tokio::test]
+    async fn test_train_weights_fits_stored_feedback() {
+        let database = crate::database::MockDatabase::new();
+        for (temp_c, humidity, rating) in [(32.0, 35.0, 5), (14.0, 90.0, 1)] {
+            database
+                .create_feedback(crate::database::CreateFeedback {
+                    user_id: None,
+                    window_id: "window_0_3".to_string(),
+                    feedback_text: "rated".to_string(),
+                    satisfaction_rating: Some(rating),
+                    drying_result: None,
+                    weather_temp_c: Some(temp_c),
+                    weather_humidity: Some(humidity),
+                    weather_wind_ms: Some(2.0),
+                    weather_rain_mm: Some(0.0),
+                    predicted_score: None,
+                    actual_outcome: None,
+                })
+                .await
+                .unwrap();
+        }
+
+        let report = train_weights(&database, 20).await.unwrap();
+        assert_eq!(report.records, 2);
+        assert_eq!(report.epochs, 20);
+        assert_ne!(report.weights, DryingWeights::default());
+
+        let empty = train_weights(&crate::database::MockDatabase::new(), 20).await.unwrap();
+        assert_eq!(empty.records, 0);
+        assert_eq!(empty.weights, DryingWeights::default());
+    }
+
+    #[tokio::test]
+    async fn test_check_config_fails_on_missing_variable() {
+        let vars = [("OPENROUTER_API_KEY", "key"), ("APP_TIMEZONE", "Asia/Bangkok")];
+        let lookup = |name: &str| vars.iter().find(|(k, _)| *k == name).map(|(_, v)| v.to_string());
+
+        let report = check_config(lookup, false).await;
+        assert!(!report.passed());
+        assert_ne!(report.exit_code(), 0);
+        assert!(report.to_string().contains("[FAIL] config: OPENWEATHER_API_KEY is not set"));
+
+        let vars = [("OPENROUTER_API_KEY", "key"), ("OPENWEATHER_API_KEY", "key")];
+        let lookup = |name: &str| vars.iter().find(|(k, _)| *k == name).map(|(_, v)| v.to_string());
+        let report = check_config(lookup, false).await;
+        assert_eq!(report.exit_code(), 0);
+    }
+}
@@ -1,11 +1,30 @@
+use chrono::{Offset, Utc};
 use serde::{Deserialize, Serialize};
 use std::env;
+use thiserror::Error;
+
+use crate::database::RetentionPolicy;
+use crate::forecast::types::Forecast3hMode;
+use crate::scoring::{
+    HeatPenalty, NormalizationConfig, RainThresholds, ScoreThresholds, ScoringConfig, ScoringFeatures, ScoringModel, DEW_PENALTY,
+    SGD_LR_DECAY,
+};
+use crate::tips::TipsEngine;
+use crate::utils::{parse_timezone, OutputPrecision};
+
+/// Smallest `AI_PROMPT_TOKEN_BUDGET`: room for the instructions and one window
+const MIN_PROMPT_TOKEN_BUDGET: usize = 200;
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct Config {
     pub openrouter_api_key: String,
     pub openrouter_base_url: String,
     pub or_model: String,
+    pub or_fallback_models: Vec<String>,
+    /// Models the server may call; empty allows any
+    pub or_allowed_models: Vec<String>,
+    pub openrouter_referer: Option<String>,
+    pub openrouter_title: Option<String>,
     pub openweather_api_key: String,
     pub openweather_base_url: String,
     pub openweather_onecall_path: String,
@@ -13,31 +32,679 @@ pub struct Config {
     pub openweather_geocode_direct_path: String,
     pub openweather_geocode_reverse_path: String,
     pub app_timezone: String,
+    pub features: ScoringFeatures,
+    pub rain: RainThresholds,
+    pub heat: HeatPenalty,
+    /// Score lost by a clear night near the dew point, as a negative number
+    pub dew_penalty: f64,
+    pub clamp_scores: bool,
+    pub normalization: NormalizationConfig,
+    pub score_thresholds: ScoreThresholds,
+    pub output_precision: OutputPrecision,
+    pub debug_endpoints: bool,
+    /// Have `/readyz` also check the weather provider answers
+    pub readiness_check_weather: bool,
+    /// Seconds `/readyz` reports draining before the server stops accepting
+    /// connections on shutdown
+    pub shutdown_drain_secs: u64,
+    /// Key for `/admin/*` endpoints, sent as `X-API-Key`; unset disables them
+    pub admin_api_key: Option<String>,
+    pub max_forecast_hours: u32,
+    pub max_concurrent_requests: usize,
+    /// Largest request body accepted, in bytes
+    pub max_body_bytes: usize,
+    pub nearby_feedback: bool,
+    pub smoothing_window: usize,
+    pub ai_timeout_secs: u64,
+    pub ai_cache_ttl_secs: u64,
+    /// Estimated tokens the explanation prompt may use before it is trimmed
+    pub ai_prompt_token_budget: usize,
+    /// Consecutive failures after which a model is skipped; `0` never skips
+    pub ai_circuit_failures: u32,
+    /// How long a failing model is skipped before it is tried again
+    pub ai_circuit_cooldown_secs: u64,
+    pub recommendation_cache_ttl_secs: u64,
+    /// How long fetched forecasts are reused for the same ~1 km location
+    pub weather_cache_ttl_secs: u64,
+    pub sgd_lr_decay: f64,
+    pub weather_timeout_secs: u64,
+    pub tips_engine: TipsEngine,
+    pub forecast3h_mode: Forecast3hMode,
+    /// Keep a copy of every window served so feedback can be linked to it
+    pub persist_windows: bool,
+    pub window_retention_hours: u32,
+    /// Days feedback is kept; `None` keeps it forever
+    pub feedback_retention_days: Option<u32>,
+    /// Shortest `feedback_text` accepted, in characters
+    pub feedback_min_text_length: usize,
+    /// Feedback a user, or anonymous senders on one window, may leave in any 24 hours
+    pub feedback_daily_limit: u32,
+    /// Most points an hourly score sparkline is downsampled to
+    pub sparkline_max_points: usize,
+    /// How much stricter recommendations get for a user whose recent
+    /// satisfaction is low; `0` disables the adjustment
+    pub conservative_adjustment: f64,
+}
+
+/// Every missing or invalid setting found while loading the configuration
+#[derive(Debug, Error)]
+#[error("invalid configuration:\n  - {}", .problems.join("\n  - "))]
+pub struct ConfigError {
+    pub problems: Vec<String>,
 }
 
 impl Config {
-    pub fn from_env() -> anyhow::Result<Self> {
+    pub fn from_env() -> Result<Self, ConfigError> {
+        Self::from_lookup(|name| env::var(name).ok())
+    }
+
+    /// Build the configuration from any variable source, collecting every
+    /// problem instead of stopping at the first one.
+    pub fn from_lookup(lookup: impl Fn(&str) -> Option<String>) -> Result<Self, ConfigError> {
+        let mut problems = Vec::new();
+
+        let mut required = |name: &str| {
+            lookup(name).filter(|v| !v.trim().is_empty()).unwrap_or_else(|| {
+                problems.push(format!("{} is not set", name));
+                String::new()
+            })
+        };
+        let openrouter_api_key = required("OPENROUTER_API_KEY");
+        let openweather_api_key = required("OPENWEATHER_API_KEY");
+
+        let or_default = |name: &str, default: &str| lookup(name).unwrap_or_else(|| default.to_string());
+
+        let openrouter_base_url =
+            or_default("OPENROUTER_BASE_URL", "https://openrouter.ai/api/v1/chat/completions");
+        let openweather_base_url = or_default("OPENWEATHER_BASE_URL", "https://api.openweathermap.org");
+        for (name, url) in [
+            ("OPENROUTER_BASE_URL", &openrouter_base_url),
+            ("OPENWEATHER_BASE_URL", &openweather_base_url),
+        ] {
+            if let Err(e) = reqwest::Url::parse(url) {
+                problems.push(format!("{} is not a valid URL ({}): {:?}", name, e, url));
+            }
+        }
+
+        let app_timezone = or_default("APP_TIMEZONE", "Asia/Jakarta");
+        if let Err(e) = parse_timezone(&app_timezone) {
+            problems.push(format!("APP_TIMEZONE: {}", e));
+        }
+
+        let mut positive_secs = |name: &str, default: u64| match lookup(name) {
+            Some(value) => match value.trim().parse::<u64>() {
+                Ok(secs) if secs > 0 => secs,
+                _ => {
+                    problems.push(format!("{} must be a positive number of seconds, got {:?}", name, value));
+                    default
+                }
+            },
+            None => default,
+        };
+        let ai_timeout_secs = positive_secs("AI_TIMEOUT_SECS", 60);
+        let weather_timeout_secs = positive_secs("WEATHER_TIMEOUT_SECS", 30);
+        let ai_circuit_cooldown_secs = positive_secs("AI_CIRCUIT_COOLDOWN_SECS", 60);
+
+        // `0` means none: no drain, or no cache beyond concurrent requests
+        let mut secs = |name: &str, default: u64| match lookup(name) {
+            Some(value) => value.trim().parse::<u64>().unwrap_or_else(|_| {
+                problems.push(format!("{} must be a non-negative number of seconds, got {:?}", name, value));
+                default
+            }),
+            None => default,
+        };
+        let shutdown_drain_secs = secs("SHUTDOWN_DRAIN_SECS", 5);
+        let ai_cache_ttl_secs = secs("AI_CACHE_TTL_SECS", 3600);
+        let recommendation_cache_ttl_secs = secs("RECOMMENDATION_CACHE_TTL_SECS", 60);
+        let weather_cache_ttl_secs = secs("WEATHER_CACHE_TTL_SECS", 1800);
+
+        let ai_circuit_failures = match lookup("AI_CIRCUIT_FAILURES") {
+            Some(value) => value.trim().parse::<u32>().unwrap_or_else(|_| {
+                problems.push(format!("AI_CIRCUIT_FAILURES must be a non-negative integer, got {:?}", value));
+                3
+            }),
+            None => 3,
+        };
+
+        let max_concurrent_requests = match lookup("MAX_CONCURRENT_REQUESTS") {
+            Some(value) => match value.trim().parse::<usize>() {
+                Ok(max) if max > 0 => max,
+                _ => {
+                    problems.push(format!("MAX_CONCURRENT_REQUESTS must be a positive integer, got {:?}", value));
+                    64
+                }
+            },
+            None => 64,
+        };
+
+        let max_body_bytes = match lookup("MAX_BODY_BYTES") {
+            Some(value) => match value.trim().parse::<usize>() {
+                Ok(max) if max > 0 => max,
+                _ => {
+                    problems.push(format!("MAX_BODY_BYTES must be a positive integer, got {:?}", value));
+                    64 * 1024
+                }
+            },
+            None => 64 * 1024,
+        };
+
+        // The merged series only ever covers 7 days
+        let max_forecast_hours = match lookup("MAX_FORECAST_HOURS") {
+            Some(value) => match value.trim().parse::<u32>() {
+                Ok(hours) if (1..=168).contains(&hours) => hours,
+                _ => {
+                    problems.push(format!("MAX_FORECAST_HOURS must be between 1 and 168, got {:?}", value));
+                    168
+                }
+            },
+            None => 168,
+        };
+
+        let defaults = RainThresholds::default();
+        let mut rain_threshold = |name: &str, default: f64, max: f64| match lookup(name) {
+            Some(value) => match value.trim().parse::<f64>() {
+                Ok(v) if (0.0..=max).contains(&v) => v,
+                _ => {
+                    let range = if max.is_finite() { format!("between 0 and {}", max) } else { "non-negative".to_string() };
+                    problems.push(format!("{} must be a {} number, got {:?}", name, range, value));
+                    default
+                }
+            },
+            None => default,
+        };
+        let rain = RainThresholds {
+            penalty_probability: rain_threshold("RAIN_PENALTY_PROBABILITY", defaults.penalty_probability, 1.0),
+            veto_mm: rain_threshold("RAIN_VETO_MM", defaults.veto_mm, f64::INFINITY),
+            gap_tolerance_hours: match lookup("RAIN_GAP_TOLERANCE_HOURS") {
+                Some(value) => value.trim().parse::<u32>().unwrap_or_else(|_| {
+                    problems.push(format!(
+                        "RAIN_GAP_TOLERANCE_HOURS must be a non-negative integer, got {:?}",
+                        value
+                    ));
+                    defaults.gap_tolerance_hours
+                }),
+                None => defaults.gap_tolerance_hours,
+            },
+        };
+
+        let heat_defaults = HeatPenalty::default();
+        let heat = HeatPenalty {
+            threshold_c: match lookup("HEAT_PENALTY_THRESHOLD_C") {
+                Some(value) => match value.trim().parse::<f64>() {
+                    Ok(t) if t.is_finite() => t,
+                    _ => {
+                        problems.push(format!("HEAT_PENALTY_THRESHOLD_C must be a number, got {:?}", value));
+                        heat_defaults.threshold_c
+                    }
+                },
+                None => heat_defaults.threshold_c,
+            },
+            ..heat_defaults
+        };
+
+        let dew_penalty = match lookup("DEW_PENALTY") {
+            Some(value) => match value.trim().parse::<f64>() {
+                Ok(penalty) if (0.0..=1.0).contains(&penalty) => -penalty,
+                _ => {
+                    problems.push(format!("DEW_PENALTY must be a number between 0 and 1, got {:?}", value));
+                    DEW_PENALTY
+                }
+            },
+            None => DEW_PENALTY,
+        };
+
+        let clamp_scores = match lookup("SCORE_CLAMP") {
+            Some(value) => match value.trim().to_ascii_lowercase().as_str() {
+                "true" => true,
+                "false" => false,
+                _ => {
+                    problems.push(format!("SCORE_CLAMP must be true or false, got {:?}", value));
+                    true
+                }
+            },
+            None => true,
+        };
+
+        let sgd_lr_decay = match lookup("SGD_LR_DECAY") {
+            Some(value) => match value.trim().parse::<f64>() {
+                Ok(decay) if decay >= 0.0 && decay.is_finite() => decay,
+                _ => {
+                    problems.push(format!("SGD_LR_DECAY must be a non-negative number, got {:?}", value));
+                    SGD_LR_DECAY
+                }
+            },
+            None => SGD_LR_DECAY,
+        };
+
+        let score_thresholds = match lookup("SCORE_THRESHOLDS") {
+            Some(value) => ScoreThresholds::parse(&value).unwrap_or_else(|| {
+                problems.push(format!(
+                    "SCORE_THRESHOLDS must be three descending numbers (excellent,good,fair), got {:?}",
+                    value
+                ));
+                ScoreThresholds::default()
+            }),
+            None => ScoreThresholds::default(),
+        };
+
+        let (normalization, invalid_normalization) =
+            NormalizationConfig::parse(&lookup("NORMALIZATION").unwrap_or_default());
+        if !invalid_normalization.is_empty() {
+            problems.push(format!(
+                "NORMALIZATION entries must be name=positive number for temp_baseline_c, temp_range_c, humidity_exponent, wind_scale_ms or vpd_scale_kpa, got {}",
+                invalid_normalization.join(", ")
+            ));
+        }
+
+        let (output_precision, invalid_precision) =
+            OutputPrecision::parse(&lookup("OUTPUT_PRECISION").unwrap_or_default());
+        if !invalid_precision.is_empty() {
+            problems.push(format!(
+                "OUTPUT_PRECISION entries must be name=decimals (0-6) for temperature, humidity, wind, rain, probability or score, got {}",
+                invalid_precision.join(", ")
+            ));
+        }
+
+        let (features, unknown_features) =
+            ScoringFeatures::parse(&lookup("FEATURES").unwrap_or_default());
+        if !unknown_features.is_empty() {
+            tracing::warn!("Ignoring unknown FEATURES flags: {}", unknown_features.join(", "));
+        }
+
+        let tips_engine = match lookup("TIPS_ENGINE") {
+            Some(value) => TipsEngine::parse(&value).unwrap_or_else(|| {
+                problems.push(format!("TIPS_ENGINE must be ai or heuristic, got {:?}", value));
+                TipsEngine::default()
+            }),
+            None => TipsEngine::default(),
+        };
+
+        let window_retention_hours = match lookup("WINDOW_RETENTION_HOURS") {
+            Some(value) => match value.trim().parse::<u32>() {
+                Ok(hours) if hours > 0 => hours,
+                _ => {
+                    problems.push(format!("WINDOW_RETENTION_HOURS must be a positive integer, got {:?}", value));
+                    168
+                }
+            },
+            None => 168,
+        };
+
+        let feedback_retention_days = match lookup("FEEDBACK_RETENTION_DAYS") {
+            Some(value) if value.trim().is_empty() => None,
+            Some(value) => match value.trim().parse::<u32>() {
+                Ok(days) if days > 0 => Some(days),
+                _ => {
+                    problems.push(format!("FEEDBACK_RETENTION_DAYS must be a positive integer, got {:?}", value));
+                    None
+                }
+            },
+            None => None,
+        };
+
+        let forecast3h_mode = match lookup("FORECAST3H_MODE") {
+            Some(value) => Forecast3hMode::parse(&value).unwrap_or_else(|| {
+                problems.push(format!("FORECAST3H_MODE must be flat or interpolate, got {:?}", value));
+                Forecast3hMode::default()
+            }),
+            None => Forecast3hMode::default(),
+        };
+
+        let feedback_min_text_length = match lookup("FEEDBACK_MIN_TEXT_LENGTH") {
+            Some(value) => value.trim().parse::<usize>().unwrap_or_else(|_| {
+                problems.push(format!("FEEDBACK_MIN_TEXT_LENGTH must be a non-negative integer, got {:?}", value));
+                3
+            }),
+            None => 3,
+        };
+
+        let feedback_daily_limit = match lookup("FEEDBACK_DAILY_LIMIT") {
+            Some(value) => match value.trim().parse::<u32>() {
+                Ok(limit) if limit > 0 => limit,
+                _ => {
+                    problems.push(format!("FEEDBACK_DAILY_LIMIT must be a positive integer, got {:?}", value));
+                    20
+                }
+            },
+            None => 20,
+        };
+
+        let sparkline_max_points = match lookup("SPARKLINE_MAX_POINTS") {
+            Some(value) => match value.trim().parse::<usize>() {
+                Ok(points) if points >= 2 => points,
+                _ => {
+                    problems.push(format!("SPARKLINE_MAX_POINTS must be an integer of at least 2, got {:?}", value));
+                    48
+                }
+            },
+            None => 48,
+        };
+
+        let ai_prompt_token_budget = match lookup("AI_PROMPT_TOKEN_BUDGET") {
+            Some(value) => match value.trim().parse::<usize>() {
+                Ok(tokens) if tokens >= MIN_PROMPT_TOKEN_BUDGET => tokens,
+                _ => {
+                    problems.push(format!(
+                        "AI_PROMPT_TOKEN_BUDGET must be an integer of at least {}, got {:?}",
+                        MIN_PROMPT_TOKEN_BUDGET, value
+                    ));
+                    2000
+                }
+            },
+            None => 2000,
+        };
+
+        let conservative_adjustment = match lookup("CONSERVATIVE_ADJUSTMENT") {
+            Some(value) => match value.trim().parse::<f64>() {
+                Ok(by) if (0.0..=0.5).contains(&by) => by,
+                _ => {
+                    problems.push(format!("CONSERVATIVE_ADJUSTMENT must be between 0 and 0.5, got {:?}", value));
+                    0.0
+                }
+            },
+            None => 0.0,
+        };
+
+        let model_list = |name: &str| -> Vec<String> {
+            lookup(name)
+                .map(|v| {
+                    v.split(',')
+                        .map(str::trim)
+                        .filter(|m| !m.is_empty())
+                        .map(str::to_string)
+                        .collect()
+                })
+                .unwrap_or_default()
+        };
+        let or_model = or_default("OR_MODEL", "deepseek/deepseek-chat-v3-0324:free");
+        let or_fallback_models = model_list("OR_FALLBACK_MODELS");
+        let or_allowed_models = model_list("OR_ALLOWED_MODELS");
+        if !or_allowed_models.is_empty() {
+            for model in std::iter::once(&or_model).chain(&or_fallback_models) {
+                if !or_allowed_models.contains(model) {
+                    problems.push(format!("AI model {:?} is not in OR_ALLOWED_MODELS", model));
+                }
+            }
+        }
+
+        if !problems.is_empty() {
+            return Err(ConfigError { problems });
+        }
+
         Ok(Config {
-            openrouter_api_key: env::var("OPENROUTER_API_KEY")
-                .map_err(|_| anyhow::anyhow!("OPENROUTER_API_KEY not set"))?,
-            openrouter_base_url: env::var("OPENROUTER_BASE_URL")
-                .unwrap_or_else(|_| "https://openrouter.ai/api/v1/chat/completions".to_string()),
-            or_model: env::var("OR_MODEL")
-                .unwrap_or_else(|_| "deepseek/deepseek-chat-v3-0324:free".to_string()),
-            openweather_api_key: env::var("OPENWEATHER_API_KEY")
-                .map_err(|_| anyhow::anyhow!("OPENWEATHER_API_KEY not set"))?,
-            openweather_base_url: env::var("OPENWEATHER_BASE_URL")
-                .unwrap_or_else(|_| "https://api.openweathermap.org".to_string()),
-            openweather_onecall_path: env::var("OPENWEATHER_ONECALL_PATH")
-                .unwrap_or_else(|_| "/data/3.0/onecall".to_string()),
-            openweather_forecast3h_path: env::var("OPENWEATHER_FORECAST3H_PATH")
-                .unwrap_or_else(|_| "/data/2.5/forecast".to_string()),
-            openweather_geocode_direct_path: env::var("OPENWEATHER_GEOCODE_DIRECT_PATH")
-                .unwrap_or_else(|_| "/geo/1.0/direct".to_string()),
-            openweather_geocode_reverse_path: env::var("OPENWEATHER_GEOCODE_REVERSE_PATH")
-                .unwrap_or_else(|_| "/geo/1.0/reverse".to_string()),
-            app_timezone: env::var("APP_TIMEZONE")
-                .unwrap_or_else(|_| "Asia/Jakarta".to_string()),
+            openrouter_api_key,
+            openrouter_base_url,
+            or_model,
+            or_fallback_models,
+            or_allowed_models,
+            openrouter_referer: lookup("OPENROUTER_REFERER").filter(|v| !v.trim().is_empty()),
+            openrouter_title: lookup("OPENROUTER_TITLE").filter(|v| !v.trim().is_empty()),
+            openweather_api_key,
+            openweather_base_url,
+            openweather_onecall_path: or_default("OPENWEATHER_ONECALL_PATH", "/data/3.0/onecall"),
+            openweather_forecast3h_path: or_default("OPENWEATHER_FORECAST3H_PATH", "/data/2.5/forecast"),
+            openweather_geocode_direct_path: or_default("OPENWEATHER_GEOCODE_DIRECT_PATH", "/geo/1.0/direct"),
+            openweather_geocode_reverse_path: or_default("OPENWEATHER_GEOCODE_REVERSE_PATH", "/geo/1.0/reverse"),
+            app_timezone,
+            features,
+            rain,
+            heat,
+            dew_penalty,
+            clamp_scores,
+            normalization,
+            score_thresholds,
+            output_precision,
+            debug_endpoints: lookup("DEBUG_ENDPOINTS")
+                .map(|v| v.eq_ignore_ascii_case("true"))
+                .unwrap_or(false),
+            readiness_check_weather: lookup("READINESS_CHECK_WEATHER")
+                .map(|v| v.eq_ignore_ascii_case("true"))
+                .unwrap_or(false),
+            shutdown_drain_secs,
+            admin_api_key: lookup("ADMIN_API_KEY")
+                .map(|v| v.trim().to_string())
+                .filter(|v| !v.is_empty()),
+            max_forecast_hours,
+            max_concurrent_requests,
+            max_body_bytes,
+            nearby_feedback: lookup("NEARBY_FEEDBACK")
+                .map(|v| v.eq_ignore_ascii_case("true"))
+                .unwrap_or(false),
+            smoothing_window: lookup("SMOOTHING_WINDOW")
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(3),
+            ai_timeout_secs,
+            ai_cache_ttl_secs,
+            ai_prompt_token_budget,
+            ai_circuit_failures,
+            ai_circuit_cooldown_secs,
+            recommendation_cache_ttl_secs,
+            weather_cache_ttl_secs,
+            sgd_lr_decay,
+            weather_timeout_secs,
+            tips_engine,
+            forecast3h_mode,
+            persist_windows: lookup("PERSIST_WINDOWS")
+                .map(|v| v.eq_ignore_ascii_case("true"))
+                .unwrap_or(false),
+            window_retention_hours,
+            feedback_retention_days,
+            feedback_min_text_length,
+            feedback_daily_limit,
+            sparkline_max_points,
+            conservative_adjustment,
         })
     }
-}
\ No newline at end of file
+
+    /// Whether `OR_ALLOWED_MODELS` (when set) permits calling `model`
+    pub fn is_model_allowed(&self, model: &str) -> bool {
+        self.or_allowed_models.is_empty() || self.or_allowed_models.iter().any(|allowed| allowed == model)
+    }
+
+    /// What the hourly cleanup job removes
+    pub fn retention_policy(&self) -> RetentionPolicy {
+        RetentionPolicy {
+            feedback: self
+                .feedback_retention_days
+                .map(|days| chrono::Duration::days(days.into())),
+            served_windows: self
+                .persist_windows
+                .then(|| chrono::Duration::hours(self.window_retention_hours.into())),
+        }
+    }
+
+    /// Scoring configuration for this deployment, with an optional model override
+    pub fn scoring_config(&self, model: Option<ScoringModel>) -> ScoringConfig {
+        ScoringConfig {
+            model: model.unwrap_or_default(),
+            features: self.features,
+            rain: self.rain,
+            normalization: self.normalization,
+            heat: self.heat,
+            fabric: None,
+            clamp_scores: self.clamp_scores,
+            dew_penalty: self.dew_penalty,
+        }
+    }
+
+    /// Current UTC offset of `app_timezone` in seconds, used as the merge timezone
+    pub fn timezone_offset_secs(&self) -> i32 {
+        parse_timezone(&self.app_timezone)
+            .map(|tz| Utc::now().with_timezone(&tz).offset().fix().local_minus_utc())
+            .unwrap_or(0)
+    }
+
+    /// Fixed configuration for unit tests; every upstream points at a closed port
+    #[cfg(test)]
+    pub(crate) fn for_tests() -> Self {
+        Config {
+            openrouter_api_key: "test".to_string(),
+            openrouter_base_url: "http://127.0.0.1:9/chat/completions".to_string(),
+            or_model: "test-model".to_string(),
+            or_fallback_models: Vec::new(),
+            or_allowed_models: Vec::new(),
+            openrouter_referer: None,
+            openrouter_title: None,
+            openweather_api_key: "test".to_string(),
+            openweather_base_url: "http://127.0.0.1:9".to_string(),
+            openweather_onecall_path: "/data/3.0/onecall".to_string(),
+            openweather_forecast3h_path: "/data/2.5/forecast".to_string(),
+            openweather_geocode_direct_path: "/geo/1.0/direct".to_string(),
+            openweather_geocode_reverse_path: "/geo/1.0/reverse".to_string(),
+            app_timezone: "Asia/Jakarta".to_string(),
+            features: ScoringFeatures::default(),
+            rain: RainThresholds::default(),
+            heat: HeatPenalty::default(),
+            dew_penalty: DEW_PENALTY,
+            clamp_scores: true,
+            normalization: NormalizationConfig::default(),
+            score_thresholds: ScoreThresholds::default(),
+            output_precision: OutputPrecision::default(),
+            debug_endpoints: false,
+            readiness_check_weather: false,
+            shutdown_drain_secs: 0,
+            admin_api_key: None,
+            max_forecast_hours: 168,
+            max_concurrent_requests: 64,
+            max_body_bytes: 64 * 1024,
+            nearby_feedback: false,
+            smoothing_window: 3,
+            ai_timeout_secs: 60,
+            ai_cache_ttl_secs: 0,
+            ai_prompt_token_budget: 2000,
+            ai_circuit_failures: 3,
+            ai_circuit_cooldown_secs: 60,
+            recommendation_cache_ttl_secs: 0,
+            weather_cache_ttl_secs: 0,
+            sgd_lr_decay: SGD_LR_DECAY,
+            weather_timeout_secs: 30,
+            tips_engine: TipsEngine::default(),
+            forecast3h_mode: Forecast3hMode::default(),
+            persist_windows: false,
+            window_retention_hours: 168,
+            feedback_retention_days: None,
+            feedback_min_text_length: 3,
+            feedback_daily_limit: 20,
+            sparkline_max_points: 48,
+            conservative_adjustment: 0.0,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    fn lookup_from(vars: &[(&str, &str)]) -> impl Fn(&str) -> Option<String> {
+        let vars: HashMap<String, String> = vars
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect();
+        move |name| vars.get(name).cloned()
+    }
+
+    #[test]
+    fn test_missing_required_vars_are_all_reported() {
+        let err = Config::from_lookup(lookup_from(&[])).unwrap_err();
+
+        assert_eq!(err.problems.len(), 2);
+        let message = err.to_string();
+        assert!(message.contains("OPENROUTER_API_KEY is not set"));
+        assert!(message.contains("OPENWEATHER_API_KEY is not set"));
+    }
+
+    #[test]
+    fn test_invalid_values_are_aggregated() {
+        let err = Config::from_lookup(lookup_from(&[
+            ("OPENROUTER_API_KEY", "key"),
+            ("OPENWEATHER_API_KEY", "key"),
+            ("OPENWEATHER_BASE_URL", "not a url"),
+            ("AI_TIMEOUT_SECS", "0"),
+        ]))
+        .unwrap_err();
+
+        assert_eq!(err.problems.len(), 2);
+        assert!(err.problems[0].starts_with("OPENWEATHER_BASE_URL"));
+        assert!(err.problems[1].starts_with("AI_TIMEOUT_SECS"));
+
+        let config = Config::from_lookup(lookup_from(&[
+            ("OPENROUTER_API_KEY", "key"),
+            ("OPENWEATHER_API_KEY", "key"),
+        ]))
+        .unwrap();
+        assert_eq!(config.ai_timeout_secs, 60);
+        assert_eq!(config.app_timezone, "Asia/Jakarta");
+        assert_eq!(config.dew_penalty, DEW_PENALTY);
+    }
+
+    #[test]
+    fn test_invalid_drain_and_cache_durations_are_reported() {
+        let err = Config::from_lookup(lookup_from(&[
+            ("OPENROUTER_API_KEY", "key"),
+            ("OPENWEATHER_API_KEY", "key"),
+            ("SHUTDOWN_DRAIN_SECS", "5s"),
+            ("AI_CACHE_TTL_SECS", "-1"),
+            ("RECOMMENDATION_CACHE_TTL_SECS", "1m"),
+            ("WEATHER_CACHE_TTL_SECS", "half an hour"),
+        ]))
+        .unwrap_err();
+
+        assert_eq!(err.problems.len(), 4);
+        for (problem, name) in err.problems.iter().zip([
+            "SHUTDOWN_DRAIN_SECS",
+            "AI_CACHE_TTL_SECS",
+            "RECOMMENDATION_CACHE_TTL_SECS",
+            "WEATHER_CACHE_TTL_SECS",
+        ]) {
+            assert!(problem.starts_with(name), "{}", problem);
+        }
+
+        // `0` is still a valid setting
+        let config = Config::from_lookup(lookup_from(&[
+            ("OPENROUTER_API_KEY", "key"),
+            ("OPENWEATHER_API_KEY", "key"),
+            ("SHUTDOWN_DRAIN_SECS", "0"),
+            ("AI_CACHE_TTL_SECS", " 0 "),
+        ]))
+        .unwrap();
+        assert_eq!(config.shutdown_drain_secs, 0);
+        assert_eq!(config.ai_cache_ttl_secs, 0);
+        assert_eq!(config.weather_cache_ttl_secs, 1800);
+    }
+
+    #[test]
+    fn test_models_outside_allowlist_are_rejected_at_startup() {
+        let err = Config::from_lookup(lookup_from(&[
+            ("OPENROUTER_API_KEY", "key"),
+            ("OPENWEATHER_API_KEY", "key"),
+            ("OR_MODEL", "cheap/model"),
+            ("OR_FALLBACK_MODELS", "cheap/backup,pricey/model"),
+            ("OR_ALLOWED_MODELS", "cheap/model, cheap/backup"),
+        ]))
+        .unwrap_err();
+        assert_eq!(err.problems, vec!["AI model \"pricey/model\" is not in OR_ALLOWED_MODELS"]);
+
+        let config = Config::from_lookup(lookup_from(&[
+            ("OPENROUTER_API_KEY", "key"),
+            ("OPENWEATHER_API_KEY", "key"),
+        ]))
+        .unwrap();
+        assert!(config.is_model_allowed("anything/at-all"));
+    }
+
+    #[test]
+    fn test_invalid_timezone_fails_with_clear_message() {
+        let err = Config::from_lookup(lookup_from(&[
+            ("OPENROUTER_API_KEY", "key"),
+            ("OPENWEATHER_API_KEY", "key"),
+            ("APP_TIMEZONE", "Mars/Olympus_Mons"),
+        ]))
+        .unwrap_err();
+
+        assert_eq!(err.problems, vec!["APP_TIMEZONE: Invalid timezone: Mars/Olympus_Mons".to_string()]);
+
+        // Jakarta has no DST, so its offset is fixed at UTC+7
+        assert_eq!(Config::for_tests().timezone_offset_secs(), 7 * 3600);
+    }
+}
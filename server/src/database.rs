@@ -1,9 +1,15 @@
-use sqlx::{SqlitePool, Row};
-use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use async_trait::async_trait;
+use sqlx::{types::Json, QueryBuilder, Row, Sqlite, SqlitePool};
+use serde::{Deserialize, Deserializer, Serialize};
+use std::collections::{HashMap, HashSet};
 use thiserror::Error;
 use uuid::Uuid;
 
+use crate::ai::{ActualOutcome, DryingResult, FeedbackAnalysis};
+use crate::backtest::feedback_weather;
+use crate::scoring::{calculate_drying_score, DryingScore, DryingWeights, ScoringModel, TrainedWeights};
+use crate::utils::{exponential_moving_average, haversine_distance};
+
 #[derive(Error, Debug)]
 pub enum DatabaseError {
     #[error("Database connection failed: {0}")]
@@ -12,6 +18,9 @@ pub enum DatabaseError {
     UserNotFound,
     #[error("Invalid data: {0}")]
     InvalidData(String),
+    /// A uniqueness constraint rejected the write
+    #[error("Already exists")]
+    AlreadyExists,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
@@ -26,6 +35,7 @@ pub struct UserPreferences {
     pub location_name: Option<String>,
     pub timezone: Option<String>,
     pub notification_preferences: Option<serde_json::Value>,
+    pub scoring_model: Option<ScoringModel>,
     pub created_at: chrono::DateTime<chrono::Utc>,
     pub updated_at: chrono::DateTime<chrono::Utc>,
 }
@@ -37,17 +47,105 @@ pub struct FeedbackRecord {
     pub window_id: String,
     pub feedback_text: String,
     pub satisfaction_rating: Option<i32>, // 1-5 scale
-    pub drying_result: Option<String>,
+    pub drying_result: Option<DryingResult>,
     pub weather_temp_c: Option<f64>,
     pub weather_humidity: Option<f64>,
     pub weather_wind_ms: Option<f64>,
     pub weather_rain_mm: Option<f64>,
     pub predicted_score: Option<f64>,
-    pub actual_outcome: Option<String>,
+    pub actual_outcome: Option<ActualOutcome>,
     pub created_at: chrono::DateTime<chrono::Utc>,
+    /// `predicted_score` recalculated under the current scoring, if ever run
+    #[serde(default)]
+    pub recomputed_score: Option<f64>,
+    /// AI reading of `feedback_text`, stored as JSON once it has run
+    #[serde(default)]
+    pub analysis: Option<Json<FeedbackAnalysis>>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+impl FeedbackRecord {
+    /// Whether the stored AI analysis reads the outcome differently (dry
+    /// enough or not) from the user's own `drying_result`. The user's value
+    /// stays the label; disagreements are only kept for review.
+    pub fn ai_disagrees(&self) -> bool {
+        match (self.drying_result, &self.analysis) {
+            (Some(user), Some(analysis)) => user.is_dry_enough() != analysis.drying_result.is_dry_enough(),
+            _ => false,
+        }
+    }
+}
+
+/// One of a user's saved places, such as home or a holiday flat, picked by
+/// its label
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct UserLocation {
+    pub user_id: Uuid,
+    pub label: String,
+    pub lat: f64,
+    pub lon: f64,
+    pub timezone: Option<String>,
+    pub updated_at: chrono::DateTime<chrono::Utc>,
+}
+
+/// A location to save under a label
+#[derive(Debug, Clone, Deserialize)]
+pub struct SaveUserLocation {
+    pub lat: f64,
+    pub lon: f64,
+    pub timezone: Option<String>,
+}
+
+/// A drying window as it was served, so feedback quoting its `window_id` can
+/// be joined to the exact prediction. Window ids only encode the start time
+/// and length, so the location is part of the key.
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct ServedWindow {
+    pub window_id: String,
+    pub lat: f64,
+    pub lon: f64,
+    pub start_time: chrono::DateTime<chrono::Utc>,
+    pub end_time: chrono::DateTime<chrono::Utc>,
+    /// Score snapshot, including the raw weather it was computed from
+    pub score: Json<DryingScore>,
+    pub served_at: chrono::DateTime<chrono::Utc>,
+}
+
+/// How long rows are kept; `None` keeps them forever
+#[derive(Debug, Clone, Copy, Default)]
+pub struct RetentionPolicy {
+    pub feedback: Option<chrono::Duration>,
+    pub served_windows: Option<chrono::Duration>,
+}
+
+/// Apply `policy` once now and then every hour, logging what was removed
+pub fn spawn_retention_cleanup(
+    store: std::sync::Arc<dyn DataStore>,
+    policy: RetentionPolicy,
+) -> tokio::task::JoinHandle<()> {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(std::time::Duration::from_secs(3600));
+        loop {
+            interval.tick().await;
+            let now = chrono::Utc::now();
+            if let Some(retention) = policy.feedback {
+                match store.delete_old_feedback(now - retention).await {
+                    Ok(0) => {}
+                    Ok(removed) => tracing::info!("Removed {} feedback records past retention", removed),
+                    Err(e) => tracing::warn!("Failed to clean up old feedback: {}", e),
+                }
+            }
+            if let Some(retention) = policy.served_windows {
+                match store.delete_served_windows_before(now - retention).await {
+                    Ok(0) => {}
+                    Ok(removed) => tracing::info!("Removed {} expired served windows", removed),
+                    Err(e) => tracing::warn!("Failed to clean up served windows: {}", e),
+                }
+            }
+        }
+    })
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct CreateUserPreferences {
     pub preferred_drying_hours: Option<i32>,
     pub min_temperature: Option<f64>,
@@ -58,6 +156,80 @@ pub struct CreateUserPreferences {
     pub location_name: Option<String>,
     pub timezone: Option<String>,
     pub notification_preferences: Option<serde_json::Value>,
+    pub scoring_model: Option<ScoringModel>,
+}
+
+/// Partial update for `PATCH /preferences/:user_id`. An absent field is left
+/// unchanged while an explicit `null` clears it, which the COALESCE-based
+/// `update_user_preferences` can't express.
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct PatchUserPreferences {
+    #[serde(default, deserialize_with = "present")]
+    pub preferred_drying_hours: Option<Option<i32>>,
+    #[serde(default, deserialize_with = "present")]
+    pub min_temperature: Option<Option<f64>>,
+    #[serde(default, deserialize_with = "present")]
+    pub max_humidity: Option<Option<f64>>,
+    #[serde(default, deserialize_with = "present")]
+    pub avoid_rain_probability: Option<Option<f64>>,
+    #[serde(default, deserialize_with = "present")]
+    pub location_lat: Option<Option<f64>>,
+    #[serde(default, deserialize_with = "present")]
+    pub location_lon: Option<Option<f64>>,
+    #[serde(default, deserialize_with = "present")]
+    pub location_name: Option<Option<String>>,
+    #[serde(default, deserialize_with = "present")]
+    pub timezone: Option<Option<String>>,
+    #[serde(default, deserialize_with = "present")]
+    pub notification_preferences: Option<Option<serde_json::Value>>,
+    #[serde(default, deserialize_with = "present")]
+    pub scoring_model: Option<Option<ScoringModel>>,
+}
+
+/// Mark a field as present, keeping `null` as `Some(None)`; absent fields fall
+/// back to `None` through `#[serde(default)]`
+fn present<'de, D, T>(deserializer: D) -> Result<Option<Option<T>>, D::Error>
+where
+    D: Deserializer<'de>,
+    T: Deserialize<'de>,
+{
+    Option::<T>::deserialize(deserializer).map(Some)
+}
+
+impl PatchUserPreferences {
+    /// Apply the present fields to `prefs`
+    pub fn apply(self, prefs: &mut UserPreferences) {
+        if let Some(value) = self.preferred_drying_hours {
+            prefs.preferred_drying_hours = value;
+        }
+        if let Some(value) = self.min_temperature {
+            prefs.min_temperature = value;
+        }
+        if let Some(value) = self.max_humidity {
+            prefs.max_humidity = value;
+        }
+        if let Some(value) = self.avoid_rain_probability {
+            prefs.avoid_rain_probability = value;
+        }
+        if let Some(value) = self.location_lat {
+            prefs.location_lat = value;
+        }
+        if let Some(value) = self.location_lon {
+            prefs.location_lon = value;
+        }
+        if let Some(value) = self.location_name {
+            prefs.location_name = value;
+        }
+        if let Some(value) = self.timezone {
+            prefs.timezone = value;
+        }
+        if let Some(value) = self.notification_preferences {
+            prefs.notification_preferences = value;
+        }
+        if let Some(value) = self.scoring_model {
+            prefs.scoring_model = value;
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -66,13 +238,238 @@ pub struct CreateFeedback {
     pub window_id: String,
     pub feedback_text: String,
     pub satisfaction_rating: Option<i32>,
-    pub drying_result: Option<String>,
+    pub drying_result: Option<DryingResult>,
     pub weather_temp_c: Option<f64>,
     pub weather_humidity: Option<f64>,
     pub weather_wind_ms: Option<f64>,
     pub weather_rain_mm: Option<f64>,
     pub predicted_score: Option<f64>,
-    pub actual_outcome: Option<String>,
+    pub actual_outcome: Option<ActualOutcome>,
+}
+
+/// Persistence operations used by the HTTP layer. Implemented by the SQLite
+/// backed `Database` and by the in-memory `MockDatabase`.
+#[async_trait]
+pub trait DataStore: Send + Sync {
+    async fn create_user_preferences(
+        &self,
+        prefs: CreateUserPreferences,
+    ) -> Result<UserPreferences, DatabaseError> {
+        self.create_user_preferences_with_id(Uuid::new_v4(), prefs).await
+    }
+
+    /// Create preferences under a caller-chosen id, e.g. one a client already holds
+    async fn create_user_preferences_with_id(
+        &self,
+        user_id: Uuid,
+        prefs: CreateUserPreferences,
+    ) -> Result<UserPreferences, DatabaseError>;
+
+    async fn get_user_preferences(&self, user_id: Uuid) -> Result<UserPreferences, DatabaseError>;
+
+    async fn update_user_preferences(
+        &self,
+        user_id: Uuid,
+        prefs: CreateUserPreferences,
+    ) -> Result<UserPreferences, DatabaseError>;
+
+    /// Update only the fields present in `patch`, clearing those sent as null
+    async fn patch_user_preferences(
+        &self,
+        user_id: Uuid,
+        patch: PatchUserPreferences,
+    ) -> Result<UserPreferences, DatabaseError>;
+
+    /// Users whose saved location lies within `radius_km`, nearest first
+    async fn find_users_near(
+        &self,
+        lat: f64,
+        lon: f64,
+        radius_km: f64,
+    ) -> Result<Vec<UserPreferences>, DatabaseError>;
+
+    /// The latest `limit` feedback records left by users whose saved location
+    /// lies within `radius_km`, newest first
+    async fn find_feedback_near(
+        &self,
+        lat: f64,
+        lon: f64,
+        radius_km: f64,
+        limit: i64,
+    ) -> Result<Vec<FeedbackRecord>, DatabaseError>;
+
+    /// Save a location under `label`, replacing any the user already has there
+    async fn save_user_location(
+        &self,
+        user_id: Uuid,
+        label: &str,
+        location: SaveUserLocation,
+    ) -> Result<UserLocation, DatabaseError>;
+
+    /// The user's saved locations, by label
+    async fn get_user_locations(&self, user_id: Uuid) -> Result<Vec<UserLocation>, DatabaseError>;
+
+    async fn get_user_location(&self, user_id: Uuid, label: &str) -> Result<Option<UserLocation>, DatabaseError>;
+
+    /// Forget a saved location; false when there was none under `label`
+    async fn delete_user_location(&self, user_id: Uuid, label: &str) -> Result<bool, DatabaseError>;
+
+    async fn create_feedback(&self, feedback: CreateFeedback) -> Result<FeedbackRecord, DatabaseError>;
+
+    /// One stored feedback record, if it exists
+    async fn get_feedback(&self, id: Uuid) -> Result<Option<FeedbackRecord>, DatabaseError>;
+
+    /// Store the AI analysis of a feedback record, replacing any earlier one
+    async fn save_feedback_analysis(&self, id: Uuid, analysis: &FeedbackAnalysis) -> Result<(), DatabaseError>;
+
+    /// Feedback where the AI analysis disagrees with the user's own
+    /// `drying_result` (see [`FeedbackRecord::ai_disagrees`]), newest first
+    async fn get_feedback_disagreements(&self, limit: Option<i64>) -> Result<Vec<FeedbackRecord>, DatabaseError>;
+
+    async fn get_user_feedback(
+        &self,
+        user_id: Uuid,
+        limit: Option<i64>,
+    ) -> Result<Vec<FeedbackRecord>, DatabaseError>;
+
+    /// Exponential moving average of the user's latest satisfaction ratings
+    /// (1-5), run oldest to newest so recent experiences weigh most; `None`
+    /// until the user has rated anything
+    async fn get_user_satisfaction_ema(&self, user_id: Uuid) -> Result<Option<f64>, DatabaseError> {
+        let recent = self.get_user_feedback(user_id, Some(SATISFACTION_EMA_WINDOW)).await?;
+        let ratings: Vec<f64> = recent
+            .iter()
+            .rev()
+            .filter_map(|f| f.satisfaction_rating.map(f64::from))
+            .collect();
+        Ok(exponential_moving_average(&ratings, SATISFACTION_EMA_ALPHA).last().copied())
+    }
+
+    async fn get_recent_feedback(
+        &self,
+        days: i32,
+        limit: Option<i64>,
+    ) -> Result<Vec<FeedbackRecord>, DatabaseError>;
+
+    /// The user's earlier feedback on a window, if any
+    async fn find_user_window_feedback(
+        &self,
+        user_id: Uuid,
+        window_id: &str,
+    ) -> Result<Option<FeedbackRecord>, DatabaseError>;
+
+    /// How much feedback the user has left since `since`
+    async fn count_user_feedback_since(
+        &self,
+        user_id: Uuid,
+        since: chrono::DateTime<chrono::Utc>,
+    ) -> Result<i64, DatabaseError>;
+
+    /// How much anonymous feedback has been left on a window since `since`
+    async fn count_anonymous_feedback_since(
+        &self,
+        window_id: &str,
+        since: chrono::DateTime<chrono::Utc>,
+    ) -> Result<i64, DatabaseError>;
+
+    async fn get_feedback_with_weather(
+        &self,
+        limit: Option<i64>,
+    ) -> Result<Vec<FeedbackRecord>, DatabaseError>;
+
+    async fn get_feedback_stats(&self) -> Result<HashMap<String, serde_json::Value>, DatabaseError>;
+
+    /// Scoring weights learned from a user's outcomes, if any
+    async fn get_user_weights(&self, user_id: Uuid) -> Result<Option<TrainedWeights>, DatabaseError>;
+
+    async fn save_user_weights(&self, user_id: Uuid, weights: &TrainedWeights) -> Result<(), DatabaseError>;
+
+    /// Drop feedback created before `cutoff`; returns how many rows were
+    /// removed. Feedback from users whose weights were trained since the
+    /// cutoff is kept, as their training is still active.
+    async fn delete_old_feedback(&self, cutoff: chrono::DateTime<chrono::Utc>) -> Result<u64, DatabaseError>;
+
+    /// Record windows as served, replacing earlier snapshots of the same
+    /// window at the same location
+    async fn save_served_windows(&self, windows: &[ServedWindow]) -> Result<(), DatabaseError>;
+
+    /// The snapshot of a window served at a location, with coordinates
+    /// rounded the way they were stored
+    async fn get_served_window(
+        &self,
+        window_id: &str,
+        lat: f64,
+        lon: f64,
+    ) -> Result<Option<ServedWindow>, DatabaseError>;
+
+    /// Drop snapshots served before `cutoff`; returns how many were removed
+    async fn delete_served_windows_before(
+        &self,
+        cutoff: chrono::DateTime<chrono::Utc>,
+    ) -> Result<u64, DatabaseError>;
+
+    /// Check the store can answer a query, for readiness probes
+    async fn health_check(&self) -> Result<(), DatabaseError>;
+}
+
+/// How many of a user's latest feedback records feed their satisfaction EMA
+const SATISFACTION_EMA_WINDOW: i64 = 20;
+/// Weight of each new rating in the satisfaction EMA
+const SATISFACTION_EMA_ALPHA: f64 = 0.3;
+
+/// Timestamps are stored as fixed-width RFC3339 UTC text
+/// (`2024-05-01T06:00:00.000000Z`) so they decode as `DateTime<Utc>` and
+/// compare correctly as strings in `ORDER BY` and range filters.
+fn db_timestamp(at: chrono::DateTime<chrono::Utc>) -> String {
+    at.to_rfc3339_opts(chrono::SecondsFormat::Micros, true)
+}
+
+/// Lat/lon bounds that contain every point within `radius_km` of the centre.
+/// Falls back to the full longitude range near the poles or the antimeridian.
+fn bounding_box(lat: f64, lon: f64, radius_km: f64) -> (f64, f64, f64, f64) {
+    const KM_PER_DEGREE: f64 = 111.32;
+
+    let lat_delta = radius_km / KM_PER_DEGREE;
+    let min_lat = (lat - lat_delta).max(-90.0);
+    let max_lat = (lat + lat_delta).min(90.0);
+
+    let cos_lat = lat.to_radians().cos();
+    let lon_delta = if cos_lat > 1e-6 { radius_km / (KM_PER_DEGREE * cos_lat) } else { 360.0 };
+    let (min_lon, max_lon) = if lon - lon_delta < -180.0 || lon + lon_delta > 180.0 {
+        (-180.0, 180.0)
+    } else {
+        (lon - lon_delta, lon + lon_delta)
+    };
+
+    (min_lat, max_lat, min_lon, max_lon)
+}
+
+/// Keep users within `radius_km` of the centre and sort them nearest first
+fn refine_by_distance(
+    users: impl IntoIterator<Item = UserPreferences>,
+    lat: f64,
+    lon: f64,
+    radius_km: f64,
+) -> Vec<UserPreferences> {
+    let mut nearby: Vec<(f64, UserPreferences)> = users
+        .into_iter()
+        .filter_map(|user| {
+            let distance = haversine_distance(lat, lon, user.location_lat?, user.location_lon?);
+            (distance <= radius_km).then_some((distance, user))
+        })
+        .collect();
+
+    nearby.sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap());
+    nearby.into_iter().map(|(_, user)| user).collect()
+}
+
+/// A feedback record with where its author saved their location
+#[derive(sqlx::FromRow)]
+struct LocatedFeedback {
+    #[sqlx(flatten)]
+    feedback: FeedbackRecord,
+    location_lat: f64,
+    location_lon: f64,
 }
 
 pub struct Database {
@@ -99,14 +496,18 @@ impl Database {
                 location_name TEXT,
                 timezone TEXT,
                 notification_preferences TEXT,
-                created_at TEXT DEFAULT CURRENT_TIMESTAMP,
-                updated_at TEXT DEFAULT CURRENT_TIMESTAMP
+                scoring_model TEXT,
+                created_at TEXT NOT NULL,
+                updated_at TEXT NOT NULL
             )
             "#,
         )
         .execute(&self.pool)
         .await?;
 
+        // Columns added after the initial schema
+        self.ensure_column("user_preferences", "scoring_model", "TEXT").await?;
+
         // Create feedback table
         sqlx::query(
             r#"
@@ -116,20 +517,93 @@ impl Database {
                 window_id TEXT NOT NULL,
                 feedback_text TEXT NOT NULL,
                 satisfaction_rating INTEGER CHECK (satisfaction_rating >= 1 AND satisfaction_rating <= 5),
-                drying_result TEXT,
+                drying_result TEXT CHECK (drying_result IN ('completely_dry', 'mostly_dry', 'partially_dry', 'not_dry')),
                 weather_temp_c REAL,
                 weather_humidity REAL,
                 weather_wind_ms REAL,
                 weather_rain_mm REAL,
                 predicted_score REAL,
-                actual_outcome TEXT,
-                created_at TEXT DEFAULT CURRENT_TIMESTAMP
+                actual_outcome TEXT CHECK (actual_outcome IN ('dry', 'not_dry')),
+                created_at TEXT NOT NULL,
+                recomputed_score REAL,
+                analysis TEXT
+            )
+            "#,
+        )
+        .execute(&self.pool)
+        .await?;
+
+        self.ensure_column("feedback", "recomputed_score", "REAL").await?;
+        self.ensure_column("feedback", "analysis", "TEXT").await?;
+        self.normalize_feedback_column("drying_result", |v| DryingResult::parse(v).map(|r| r.as_str()))
+            .await?;
+        self.normalize_feedback_column("actual_outcome", |v| ActualOutcome::parse(v).map(|o| o.as_str()))
+            .await?;
+
+        // Per-user weights, stored as JSON so the weight set can grow
+        sqlx::query(
+            r#"
+            CREATE TABLE IF NOT EXISTS user_weights (
+                user_id TEXT PRIMARY KEY REFERENCES user_preferences(user_id),
+                weights TEXT NOT NULL,
+                updated_at TEXT NOT NULL
+            )
+            "#,
+        )
+        .execute(&self.pool)
+        .await?;
+
+        // Labelled places beyond the single location kept in user_preferences
+        sqlx::query(
+            r#"
+            CREATE TABLE IF NOT EXISTS user_locations (
+                user_id TEXT NOT NULL REFERENCES user_preferences(user_id),
+                label TEXT NOT NULL,
+                lat REAL NOT NULL,
+                lon REAL NOT NULL,
+                timezone TEXT,
+                updated_at TEXT NOT NULL,
+                PRIMARY KEY (user_id, label)
+            )
+            "#,
+        )
+        .execute(&self.pool)
+        .await?;
+
+        sqlx::query(
+            r#"
+            CREATE TABLE IF NOT EXISTS served_windows (
+                window_id TEXT NOT NULL,
+                lat REAL NOT NULL,
+                lon REAL NOT NULL,
+                start_time TEXT NOT NULL,
+                end_time TEXT NOT NULL,
+                score TEXT NOT NULL,
+                served_at TEXT NOT NULL,
+                PRIMARY KEY (window_id, lat, lon)
             )
             "#,
         )
         .execute(&self.pool)
         .await?;
 
+        // Rows written by older builds may hold SQLite's `YYYY-MM-DD HH:MM:SS`
+        // or chrono's `+00:00` form; rewrite them in the canonical format
+        for (table, column) in [
+            ("user_preferences", "created_at"),
+            ("user_preferences", "updated_at"),
+            ("feedback", "created_at"),
+            ("user_weights", "updated_at"),
+        ] {
+            sqlx::query(&format!(
+                "UPDATE {table} SET {column} = strftime('%Y-%m-%dT%H:%M:%f000Z', {column}) \
+                 WHERE {column} NOT LIKE '____-__-__T__:__:__.______Z' \
+                   AND strftime('%Y', {column}) IS NOT NULL"
+            ))
+            .execute(&self.pool)
+            .await?;
+        }
+
         // Create indexes
         sqlx::query("CREATE INDEX IF NOT EXISTS idx_feedback_user_id ON feedback(user_id)")
             .execute(&self.pool)
@@ -139,25 +613,141 @@ impl Database {
             .execute(&self.pool)
             .await?;
 
+        // One piece of feedback per user and window, even for concurrent posts.
+        // Older builds could store duplicates, which would fail the index;
+        // keep each pair's first
+        sqlx::query(
+            "DELETE FROM feedback WHERE user_id IS NOT NULL AND rowid NOT IN \
+             (SELECT MIN(rowid) FROM feedback WHERE user_id IS NOT NULL GROUP BY user_id, window_id)",
+        )
+        .execute(&self.pool)
+        .await?;
+        sqlx::query(
+            "CREATE UNIQUE INDEX IF NOT EXISTS idx_feedback_user_window ON feedback(user_id, window_id) \
+             WHERE user_id IS NOT NULL",
+        )
+        .execute(&self.pool)
+        .await?;
+
+        sqlx::query("CREATE INDEX IF NOT EXISTS idx_served_windows_served_at ON served_windows(served_at)")
+            .execute(&self.pool)
+            .await?;
+
+        sqlx::query(
+            "CREATE INDEX IF NOT EXISTS idx_user_preferences_location ON user_preferences(location_lat, location_lon)"
+        )
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Re-score every feedback row that has weather data under the current
+    /// default scoring. `predicted_score` is never touched; with `write` the
+    /// new value is saved to `recomputed_score`. Returns `(id, score)` pairs.
+    pub async fn recompute_predicted_scores(&self, write: bool) -> Result<Vec<(Uuid, f64)>, DatabaseError> {
+        let records = sqlx::query_as::<_, FeedbackRecord>(
+            r#"
+            SELECT * FROM feedback
+            WHERE weather_temp_c IS NOT NULL
+              AND weather_humidity IS NOT NULL
+              AND weather_wind_ms IS NOT NULL
+              AND weather_rain_mm IS NOT NULL
+            "#,
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        let weights = DryingWeights::default();
+        let mut rescored = Vec::with_capacity(records.len());
+        for record in &records {
+            let Some(weather) = feedback_weather(record) else {
+                continue;
+            };
+            let score = calculate_drying_score(&weather, &weights).score;
+
+            if write {
+                sqlx::query("UPDATE feedback SET recomputed_score = $1 WHERE id = $2")
+                    .bind(score)
+                    .bind(record.id)
+                    .execute(&self.pool)
+                    .await?;
+            }
+            rescored.push((record.id, score));
+        }
+
+        Ok(rescored)
+    }
+
+    async fn ensure_column(
+        &self,
+        table: &str,
+        column: &str,
+        definition: &str,
+    ) -> Result<(), DatabaseError> {
+        let columns: Vec<String> =
+            sqlx::query_scalar(&format!("SELECT name FROM pragma_table_info('{}')", table))
+                .fetch_all(&self.pool)
+                .await?;
+
+        if !columns.iter().any(|c| c == column) {
+            sqlx::query(&format!("ALTER TABLE {} ADD COLUMN {} {}", table, column, definition))
+                .execute(&self.pool)
+                .await?;
+        }
+
+        Ok(())
+    }
+
+    /// Rewrite free-text values left by older builds ("Dry", "completely dry")
+    /// into their canonical names, clearing any that don't parse
+    async fn normalize_feedback_column(
+        &self,
+        column: &str,
+        canonical: impl Fn(&str) -> Option<&'static str>,
+    ) -> Result<(), DatabaseError> {
+        let values: Vec<String> =
+            sqlx::query_scalar(&format!("SELECT DISTINCT {column} FROM feedback WHERE {column} IS NOT NULL"))
+                .fetch_all(&self.pool)
+                .await?;
+
+        for value in values {
+            let normalized = canonical(&value);
+            if normalized == Some(value.as_str()) {
+                continue;
+            }
+            if normalized.is_none() {
+                tracing::warn!("Clearing unrecognized feedback {} {:?}", column, value);
+            }
+            sqlx::query(&format!("UPDATE feedback SET {column} = $1 WHERE {column} = $2"))
+                .bind(normalized)
+                .bind(value)
+                .execute(&self.pool)
+                .await?;
+        }
+
         Ok(())
     }
+}
 
+#[async_trait]
+impl DataStore for Database {
     // User Preferences CRUD
-    pub async fn create_user_preferences(
+    async fn create_user_preferences_with_id(
         &self,
+        user_id: Uuid,
         prefs: CreateUserPreferences,
     ) -> Result<UserPreferences, DatabaseError> {
-        let user_id = Uuid::new_v4();
-        let now = chrono::Utc::now();
+        let now = db_timestamp(chrono::Utc::now());
 
         let result = sqlx::query_as::<_, UserPreferences>(
             r#"
             INSERT INTO user_preferences (
                 user_id, preferred_drying_hours, min_temperature, max_humidity,
                 avoid_rain_probability, location_lat, location_lon, location_name,
-                timezone, notification_preferences, created_at, updated_at
+                timezone, notification_preferences, scoring_model, created_at, updated_at
             )
-            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13)
             RETURNING *
             "#,
         )
@@ -171,7 +761,8 @@ impl Database {
         .bind(prefs.location_name)
         .bind(prefs.timezone)
         .bind(prefs.notification_preferences)
-        .bind(now)
+        .bind(prefs.scoring_model)
+        .bind(now.clone())
         .bind(now)
         .fetch_one(&self.pool)
         .await?;
@@ -179,7 +770,7 @@ impl Database {
         Ok(result)
     }
 
-    pub async fn get_user_preferences(
+    async fn get_user_preferences(
         &self,
         user_id: Uuid,
     ) -> Result<UserPreferences, DatabaseError> {
@@ -194,12 +785,12 @@ impl Database {
         Ok(result)
     }
 
-    pub async fn update_user_preferences(
+    async fn update_user_preferences(
         &self,
         user_id: Uuid,
         prefs: CreateUserPreferences,
     ) -> Result<UserPreferences, DatabaseError> {
-        let now = chrono::Utc::now();
+        let now = db_timestamp(chrono::Utc::now());
 
         let result = sqlx::query_as::<_, UserPreferences>(
             r#"
@@ -213,7 +804,8 @@ impl Database {
                 location_name = COALESCE($8, location_name),
                 timezone = COALESCE($9, timezone),
                 notification_preferences = COALESCE($10, notification_preferences),
-                updated_at = $11
+                scoring_model = COALESCE($11, scoring_model),
+                updated_at = $12
             WHERE user_id = $1
             RETURNING *
             "#,
@@ -228,6 +820,7 @@ impl Database {
         .bind(prefs.location_name)
         .bind(prefs.timezone)
         .bind(prefs.notification_preferences)
+        .bind(prefs.scoring_model)
         .bind(now)
         .fetch_optional(&self.pool)
         .await?
@@ -236,69 +829,324 @@ impl Database {
         Ok(result)
     }
 
-    // Feedback CRUD
-    pub async fn create_feedback(
+    async fn patch_user_preferences(
         &self,
-        feedback: CreateFeedback,
-    ) -> Result<FeedbackRecord, DatabaseError> {
-        let id = Uuid::new_v4();
-        let now = chrono::Utc::now();
+        user_id: Uuid,
+        patch: PatchUserPreferences,
+    ) -> Result<UserPreferences, DatabaseError> {
+        let mut query = QueryBuilder::<Sqlite>::new("UPDATE user_preferences SET updated_at = ");
+        query.push_bind(db_timestamp(chrono::Utc::now()));
 
-        let result = sqlx::query_as::<_, FeedbackRecord>(
+        // Columns share the field names; only present fields are written
+        macro_rules! set_present {
+            ($($field:ident),*) => {$(
+                if let Some(value) = patch.$field {
+                    query.push(concat!(", ", stringify!($field), " = ")).push_bind(value);
+                }
+            )*};
+        }
+        set_present!(
+            preferred_drying_hours,
+            min_temperature,
+            max_humidity,
+            avoid_rain_probability,
+            location_lat,
+            location_lon,
+            location_name,
+            timezone,
+            notification_preferences,
+            scoring_model
+        );
+
+        query.push(" WHERE user_id = ").push_bind(user_id).push(" RETURNING *");
+        let result = query
+            .build_query_as::<UserPreferences>()
+            .fetch_optional(&self.pool)
+            .await?
+            .ok_or(DatabaseError::UserNotFound)?;
+
+        Ok(result)
+    }
+
+    async fn find_users_near(
+        &self,
+        lat: f64,
+        lon: f64,
+        radius_km: f64,
+    ) -> Result<Vec<UserPreferences>, DatabaseError> {
+        let (min_lat, max_lat, min_lon, max_lon) = bounding_box(lat, lon, radius_km);
+
+        // The bounding box uses the location index; haversine trims the corners
+        let candidates = sqlx::query_as::<_, UserPreferences>(
             r#"
-            INSERT INTO feedback (
-                id, user_id, window_id, feedback_text, satisfaction_rating,
-                drying_result, weather_temp_c, weather_humidity, weather_wind_ms,
-                weather_rain_mm, predicted_score, actual_outcome, created_at
-            )
-            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13)
-            RETURNING *
+            SELECT * FROM user_preferences
+            WHERE location_lat BETWEEN $1 AND $2
+              AND location_lon BETWEEN $3 AND $4
             "#,
         )
-        .bind(id)
-        .bind(feedback.user_id)
-        .bind(feedback.window_id)
-        .bind(feedback.feedback_text)
-        .bind(feedback.satisfaction_rating)
-        .bind(feedback.drying_result)
-        .bind(feedback.weather_temp_c)
-        .bind(feedback.weather_humidity)
-        .bind(feedback.weather_wind_ms)
-        .bind(feedback.weather_rain_mm)
-        .bind(feedback.predicted_score)
-        .bind(feedback.actual_outcome)
-        .bind(now)
-        .fetch_one(&self.pool)
+        .bind(min_lat)
+        .bind(max_lat)
+        .bind(min_lon)
+        .bind(max_lon)
+        .fetch_all(&self.pool)
         .await?;
 
-        Ok(result)
+        Ok(refine_by_distance(candidates, lat, lon, radius_km))
     }
 
-    pub async fn get_user_feedback(
+    async fn find_feedback_near(
         &self,
-        user_id: Uuid,
-        limit: Option<i64>,
+        lat: f64,
+        lon: f64,
+        radius_km: f64,
+        limit: i64,
     ) -> Result<Vec<FeedbackRecord>, DatabaseError> {
-        let limit = limit.unwrap_or(50).min(100); // Cap at 100
+        let (min_lat, max_lat, min_lon, max_lon) = bounding_box(lat, lon, radius_km);
 
-        let results = sqlx::query_as::<_, FeedbackRecord>(
-            "SELECT * FROM feedback WHERE user_id = $1 ORDER BY created_at DESC LIMIT $2",
+        // One query over the bounding box; haversine trims the corners
+        let candidates = sqlx::query_as::<_, LocatedFeedback>(
+            r#"
+            SELECT feedback.*, user_preferences.location_lat, user_preferences.location_lon
+            FROM feedback
+            JOIN user_preferences ON user_preferences.user_id = feedback.user_id
+            WHERE user_preferences.location_lat BETWEEN $1 AND $2
+              AND user_preferences.location_lon BETWEEN $3 AND $4
+            ORDER BY feedback.created_at DESC
+            LIMIT $5
+            "#,
         )
-        .bind(user_id)
+        .bind(min_lat)
+        .bind(max_lat)
+        .bind(min_lon)
+        .bind(max_lon)
         .bind(limit)
         .fetch_all(&self.pool)
         .await?;
 
-        Ok(results)
+        Ok(candidates
+            .into_iter()
+            .filter(|c| haversine_distance(lat, lon, c.location_lat, c.location_lon) <= radius_km)
+            .map(|c| c.feedback)
+            .collect())
     }
 
-    pub async fn get_recent_feedback(
+    // Feedback CRUD
+    async fn save_user_location(
         &self,
-        days: i32,
-        limit: Option<i64>,
-    ) -> Result<Vec<FeedbackRecord>, DatabaseError> {
-        let limit = limit.unwrap_or(100).min(500); // Cap at 500
-        let since = chrono::Utc::now() - chrono::Duration::days(days as i64);
+        user_id: Uuid,
+        label: &str,
+        location: SaveUserLocation,
+    ) -> Result<UserLocation, DatabaseError> {
+        let result = sqlx::query_as::<_, UserLocation>(
+            r#"
+            INSERT INTO user_locations (user_id, label, lat, lon, timezone, updated_at)
+            VALUES ($1, $2, $3, $4, $5, $6)
+            ON CONFLICT(user_id, label) DO UPDATE SET
+                lat = excluded.lat,
+                lon = excluded.lon,
+                timezone = excluded.timezone,
+                updated_at = excluded.updated_at
+            RETURNING *
+            "#,
+        )
+        .bind(user_id)
+        .bind(label)
+        .bind(location.lat)
+        .bind(location.lon)
+        .bind(location.timezone)
+        .bind(db_timestamp(chrono::Utc::now()))
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(result)
+    }
+
+    async fn get_user_locations(&self, user_id: Uuid) -> Result<Vec<UserLocation>, DatabaseError> {
+        let result = sqlx::query_as::<_, UserLocation>(
+            "SELECT * FROM user_locations WHERE user_id = $1 ORDER BY label",
+        )
+        .bind(user_id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(result)
+    }
+
+    async fn get_user_location(&self, user_id: Uuid, label: &str) -> Result<Option<UserLocation>, DatabaseError> {
+        let result = sqlx::query_as::<_, UserLocation>(
+            "SELECT * FROM user_locations WHERE user_id = $1 AND label = $2",
+        )
+        .bind(user_id)
+        .bind(label)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(result)
+    }
+
+    async fn delete_user_location(&self, user_id: Uuid, label: &str) -> Result<bool, DatabaseError> {
+        let result = sqlx::query("DELETE FROM user_locations WHERE user_id = $1 AND label = $2")
+            .bind(user_id)
+            .bind(label)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    async fn create_feedback(
+        &self,
+        feedback: CreateFeedback,
+    ) -> Result<FeedbackRecord, DatabaseError> {
+        let id = Uuid::new_v4();
+        let now = db_timestamp(chrono::Utc::now());
+
+        let result = sqlx::query_as::<_, FeedbackRecord>(
+            r#"
+            INSERT INTO feedback (
+                id, user_id, window_id, feedback_text, satisfaction_rating,
+                drying_result, weather_temp_c, weather_humidity, weather_wind_ms,
+                weather_rain_mm, predicted_score, actual_outcome, created_at
+            )
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13)
+            RETURNING *
+            "#,
+        )
+        .bind(id)
+        .bind(feedback.user_id)
+        .bind(feedback.window_id)
+        .bind(feedback.feedback_text)
+        .bind(feedback.satisfaction_rating)
+        .bind(feedback.drying_result)
+        .bind(feedback.weather_temp_c)
+        .bind(feedback.weather_humidity)
+        .bind(feedback.weather_wind_ms)
+        .bind(feedback.weather_rain_mm)
+        .bind(feedback.predicted_score)
+        .bind(feedback.actual_outcome)
+        .bind(now)
+        .fetch_one(&self.pool)
+        .await
+        .map_err(|e| match e {
+            sqlx::Error::Database(ref db) if db.is_unique_violation() => DatabaseError::AlreadyExists,
+            e => e.into(),
+        })?;
+
+        Ok(result)
+    }
+
+    async fn get_feedback(&self, id: Uuid) -> Result<Option<FeedbackRecord>, DatabaseError> {
+        let result = sqlx::query_as::<_, FeedbackRecord>("SELECT * FROM feedback WHERE id = $1")
+            .bind(id)
+            .fetch_optional(&self.pool)
+            .await?;
+
+        Ok(result)
+    }
+
+    async fn save_feedback_analysis(&self, id: Uuid, analysis: &FeedbackAnalysis) -> Result<(), DatabaseError> {
+        sqlx::query("UPDATE feedback SET analysis = $1 WHERE id = $2")
+            .bind(Json(analysis))
+            .bind(id)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(())
+    }
+
+    async fn get_feedback_disagreements(&self, limit: Option<i64>) -> Result<Vec<FeedbackRecord>, DatabaseError> {
+        let limit = limit.unwrap_or(50).min(100); // Cap at 100
+
+        // Same rule as `FeedbackRecord::ai_disagrees`: compare dry-enough classes
+        let results = sqlx::query_as::<_, FeedbackRecord>(
+            r#"
+            SELECT * FROM feedback
+            WHERE drying_result IS NOT NULL
+              AND analysis IS NOT NULL
+              AND (drying_result IN ('completely_dry', 'mostly_dry'))
+                  != (json_extract(analysis, '$.drying_result') IN ('completely_dry', 'mostly_dry'))
+            ORDER BY created_at DESC
+            LIMIT $1
+            "#,
+        )
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(results)
+    }
+
+    async fn get_user_feedback(
+        &self,
+        user_id: Uuid,
+        limit: Option<i64>,
+    ) -> Result<Vec<FeedbackRecord>, DatabaseError> {
+        let limit = limit.unwrap_or(50).min(100); // Cap at 100
+
+        let results = sqlx::query_as::<_, FeedbackRecord>(
+            "SELECT * FROM feedback WHERE user_id = $1 ORDER BY created_at DESC LIMIT $2",
+        )
+        .bind(user_id)
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(results)
+    }
+
+    async fn find_user_window_feedback(
+        &self,
+        user_id: Uuid,
+        window_id: &str,
+    ) -> Result<Option<FeedbackRecord>, DatabaseError> {
+        let result = sqlx::query_as::<_, FeedbackRecord>(
+            "SELECT * FROM feedback WHERE user_id = $1 AND window_id = $2 ORDER BY created_at LIMIT 1",
+        )
+        .bind(user_id)
+        .bind(window_id)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(result)
+    }
+
+    async fn count_user_feedback_since(
+        &self,
+        user_id: Uuid,
+        since: chrono::DateTime<chrono::Utc>,
+    ) -> Result<i64, DatabaseError> {
+        let count: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM feedback WHERE user_id = $1 AND created_at >= $2")
+            .bind(user_id)
+            .bind(db_timestamp(since))
+            .fetch_one(&self.pool)
+            .await?;
+
+        Ok(count)
+    }
+
+    async fn count_anonymous_feedback_since(
+        &self,
+        window_id: &str,
+        since: chrono::DateTime<chrono::Utc>,
+    ) -> Result<i64, DatabaseError> {
+        let count: i64 = sqlx::query_scalar(
+            "SELECT COUNT(*) FROM feedback WHERE user_id IS NULL AND window_id = $1 AND created_at >= $2",
+        )
+        .bind(window_id)
+        .bind(db_timestamp(since))
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(count)
+    }
+
+    async fn get_recent_feedback(
+        &self,
+        days: i32,
+        limit: Option<i64>,
+    ) -> Result<Vec<FeedbackRecord>, DatabaseError> {
+        let limit = limit.unwrap_or(100).min(500); // Cap at 500
+        let since = db_timestamp(chrono::Utc::now() - chrono::Duration::days(days as i64));
 
         let results = sqlx::query_as::<_, FeedbackRecord>(
             "SELECT * FROM feedback WHERE created_at >= $1 ORDER BY created_at DESC LIMIT $2",
@@ -311,8 +1159,32 @@ impl Database {
         Ok(results)
     }
 
+    async fn get_feedback_with_weather(
+        &self,
+        limit: Option<i64>,
+    ) -> Result<Vec<FeedbackRecord>, DatabaseError> {
+        let limit = limit.unwrap_or(1000).min(10000); // Cap at 10000
+
+        let results = sqlx::query_as::<_, FeedbackRecord>(
+            r#"
+            SELECT * FROM feedback
+            WHERE weather_temp_c IS NOT NULL
+              AND weather_humidity IS NOT NULL
+              AND weather_wind_ms IS NOT NULL
+              AND weather_rain_mm IS NOT NULL
+            ORDER BY created_at DESC
+            LIMIT $1
+            "#,
+        )
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(results)
+    }
+
     // Analytics queries
-    pub async fn get_feedback_stats(&self) -> Result<HashMap<String, serde_json::Value>, DatabaseError> {
+    async fn get_feedback_stats(&self) -> Result<HashMap<String, serde_json::Value>, DatabaseError> {
         let mut stats = HashMap::new();
 
         // Total feedback count
@@ -323,7 +1195,7 @@ impl Database {
 
         // Average satisfaction rating
         let avg_satisfaction: Option<f64> = sqlx::query_scalar(
-            "SELECT AVG(satisfaction_rating::FLOAT) FROM feedback WHERE satisfaction_rating IS NOT NULL"
+            "SELECT AVG(CAST(satisfaction_rating AS REAL)) FROM feedback WHERE satisfaction_rating IS NOT NULL"
         )
         .fetch_one(&self.pool)
         .await?;
@@ -353,7 +1225,104 @@ impl Database {
         Ok(stats)
     }
 
-    pub async fn health_check(&self) -> Result<(), DatabaseError> {
+    async fn get_user_weights(&self, user_id: Uuid) -> Result<Option<TrainedWeights>, DatabaseError> {
+        let weights: Option<String> = sqlx::query_scalar("SELECT weights FROM user_weights WHERE user_id = $1")
+            .bind(user_id)
+            .fetch_optional(&self.pool)
+            .await?;
+
+        weights
+            .map(|json| serde_json::from_str(&json).map_err(|e| DatabaseError::InvalidData(e.to_string())))
+            .transpose()
+    }
+
+    async fn save_user_weights(&self, user_id: Uuid, weights: &TrainedWeights) -> Result<(), DatabaseError> {
+        let json = serde_json::to_string(weights).map_err(|e| DatabaseError::InvalidData(e.to_string()))?;
+
+        sqlx::query(
+            r#"
+            INSERT INTO user_weights (user_id, weights, updated_at)
+            VALUES ($1, $2, $3)
+            ON CONFLICT(user_id) DO UPDATE SET weights = excluded.weights, updated_at = excluded.updated_at
+            "#,
+        )
+        .bind(user_id)
+        .bind(json)
+        .bind(db_timestamp(chrono::Utc::now()))
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn delete_old_feedback(&self, cutoff: chrono::DateTime<chrono::Utc>) -> Result<u64, DatabaseError> {
+        let result = sqlx::query(
+            r#"
+            DELETE FROM feedback
+            WHERE created_at < $1
+              AND (user_id IS NULL OR user_id NOT IN (SELECT user_id FROM user_weights WHERE updated_at >= $1))
+            "#,
+        )
+        .bind(db_timestamp(cutoff))
+        .execute(&self.pool)
+        .await?;
+
+        Ok(result.rows_affected())
+    }
+
+    async fn save_served_windows(&self, windows: &[ServedWindow]) -> Result<(), DatabaseError> {
+        if windows.is_empty() {
+            return Ok(());
+        }
+
+        let mut query: QueryBuilder<Sqlite> = QueryBuilder::new(
+            "INSERT OR REPLACE INTO served_windows (window_id, lat, lon, start_time, end_time, score, served_at) ",
+        );
+        query.push_values(windows, |mut row, window| {
+            row.push_bind(&window.window_id)
+                .push_bind(window.lat)
+                .push_bind(window.lon)
+                .push_bind(db_timestamp(window.start_time))
+                .push_bind(db_timestamp(window.end_time))
+                .push_bind(&window.score)
+                .push_bind(db_timestamp(window.served_at));
+        });
+        query.build().execute(&self.pool).await?;
+
+        Ok(())
+    }
+
+    async fn get_served_window(
+        &self,
+        window_id: &str,
+        lat: f64,
+        lon: f64,
+    ) -> Result<Option<ServedWindow>, DatabaseError> {
+        let result = sqlx::query_as::<_, ServedWindow>(
+            "SELECT * FROM served_windows WHERE window_id = $1 AND lat = $2 AND lon = $3",
+        )
+        .bind(window_id)
+        .bind(lat)
+        .bind(lon)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(result)
+    }
+
+    async fn delete_served_windows_before(
+        &self,
+        cutoff: chrono::DateTime<chrono::Utc>,
+    ) -> Result<u64, DatabaseError> {
+        let result = sqlx::query("DELETE FROM served_windows WHERE served_at < $1")
+            .bind(db_timestamp(cutoff))
+            .execute(&self.pool)
+            .await?;
+
+        Ok(result.rows_affected())
+    }
+
+    async fn health_check(&self) -> Result<(), DatabaseError> {
         sqlx::query("SELECT 1")
             .fetch_one(&self.pool)
             .await?;
@@ -361,10 +1330,17 @@ impl Database {
     }
 }
 
+type MockWeights = (TrainedWeights, chrono::DateTime<chrono::Utc>);
+
 // Mock database for testing
+#[derive(Default)]
 pub struct MockDatabase {
     users: std::sync::Arc<tokio::sync::RwLock<HashMap<Uuid, UserPreferences>>>,
     feedback: std::sync::Arc<tokio::sync::RwLock<Vec<FeedbackRecord>>>,
+    /// Weights with when they were last trained
+    weights: std::sync::Arc<tokio::sync::RwLock<HashMap<Uuid, MockWeights>>>,
+    served_windows: std::sync::Arc<tokio::sync::RwLock<Vec<ServedWindow>>>,
+    locations: std::sync::Arc<tokio::sync::RwLock<HashMap<(Uuid, String), UserLocation>>>,
 }
 
 impl MockDatabase {
@@ -372,14 +1348,20 @@ impl MockDatabase {
         Self {
             users: std::sync::Arc::new(tokio::sync::RwLock::new(HashMap::new())),
             feedback: std::sync::Arc::new(tokio::sync::RwLock::new(Vec::new())),
+            weights: std::sync::Arc::new(tokio::sync::RwLock::new(HashMap::new())),
+            served_windows: std::sync::Arc::new(tokio::sync::RwLock::new(Vec::new())),
+            locations: std::sync::Arc::new(tokio::sync::RwLock::new(HashMap::new())),
         }
     }
+}
 
-    pub async fn create_user_preferences(
+#[async_trait]
+impl DataStore for MockDatabase {
+    async fn create_user_preferences_with_id(
         &self,
+        user_id: Uuid,
         prefs: CreateUserPreferences,
     ) -> Result<UserPreferences, DatabaseError> {
-        let user_id = Uuid::new_v4();
         let now = chrono::Utc::now();
 
         let user_prefs = UserPreferences {
@@ -393,6 +1375,7 @@ impl MockDatabase {
             location_name: prefs.location_name,
             timezone: prefs.timezone,
             notification_preferences: prefs.notification_preferences,
+            scoring_model: prefs.scoring_model,
             created_at: now,
             updated_at: now,
         };
@@ -401,7 +1384,7 @@ impl MockDatabase {
         Ok(user_prefs)
     }
 
-    pub async fn get_user_preferences(
+    async fn get_user_preferences(
         &self,
         user_id: Uuid,
     ) -> Result<UserPreferences, DatabaseError> {
@@ -413,10 +1396,159 @@ impl MockDatabase {
             .ok_or(DatabaseError::UserNotFound)
     }
 
-    pub async fn create_feedback(
+    async fn update_user_preferences(
+        &self,
+        user_id: Uuid,
+        prefs: CreateUserPreferences,
+    ) -> Result<UserPreferences, DatabaseError> {
+        let mut users = self.users.write().await;
+        let existing = users.get_mut(&user_id).ok_or(DatabaseError::UserNotFound)?;
+
+        // Same COALESCE semantics as the SQL update: only provided fields change
+        if prefs.preferred_drying_hours.is_some() {
+            existing.preferred_drying_hours = prefs.preferred_drying_hours;
+        }
+        if prefs.min_temperature.is_some() {
+            existing.min_temperature = prefs.min_temperature;
+        }
+        if prefs.max_humidity.is_some() {
+            existing.max_humidity = prefs.max_humidity;
+        }
+        if prefs.avoid_rain_probability.is_some() {
+            existing.avoid_rain_probability = prefs.avoid_rain_probability;
+        }
+        if prefs.location_lat.is_some() {
+            existing.location_lat = prefs.location_lat;
+        }
+        if prefs.location_lon.is_some() {
+            existing.location_lon = prefs.location_lon;
+        }
+        if prefs.location_name.is_some() {
+            existing.location_name = prefs.location_name;
+        }
+        if prefs.timezone.is_some() {
+            existing.timezone = prefs.timezone;
+        }
+        if prefs.notification_preferences.is_some() {
+            existing.notification_preferences = prefs.notification_preferences;
+        }
+        if prefs.scoring_model.is_some() {
+            existing.scoring_model = prefs.scoring_model;
+        }
+        existing.updated_at = chrono::Utc::now();
+
+        Ok(existing.clone())
+    }
+
+    async fn patch_user_preferences(
+        &self,
+        user_id: Uuid,
+        patch: PatchUserPreferences,
+    ) -> Result<UserPreferences, DatabaseError> {
+        let mut users = self.users.write().await;
+        let existing = users.get_mut(&user_id).ok_or(DatabaseError::UserNotFound)?;
+
+        patch.apply(existing);
+        existing.updated_at = chrono::Utc::now();
+
+        Ok(existing.clone())
+    }
+
+    async fn find_users_near(
+        &self,
+        lat: f64,
+        lon: f64,
+        radius_km: f64,
+    ) -> Result<Vec<UserPreferences>, DatabaseError> {
+        let (min_lat, max_lat, min_lon, max_lon) = bounding_box(lat, lon, radius_km);
+        let users = self.users.read().await;
+
+        let candidates = users.values().filter(|user| {
+            matches!(
+                (user.location_lat, user.location_lon),
+                (Some(user_lat), Some(user_lon))
+                    if (min_lat..=max_lat).contains(&user_lat) && (min_lon..=max_lon).contains(&user_lon)
+            )
+        });
+
+        Ok(refine_by_distance(candidates.cloned(), lat, lon, radius_km))
+    }
+
+    async fn find_feedback_near(
+        &self,
+        lat: f64,
+        lon: f64,
+        radius_km: f64,
+        limit: i64,
+    ) -> Result<Vec<FeedbackRecord>, DatabaseError> {
+        let nearby: HashSet<Uuid> = self
+            .find_users_near(lat, lon, radius_km)
+            .await?
+            .into_iter()
+            .map(|user| user.user_id)
+            .collect();
+        let mut feedback: Vec<FeedbackRecord> = self
+            .feedback
+            .read()
+            .await
+            .iter()
+            .filter(|f| f.user_id.is_some_and(|user_id| nearby.contains(&user_id)))
+            .cloned()
+            .collect();
+        feedback.sort_by_key(|f| std::cmp::Reverse(f.created_at));
+        feedback.truncate(limit.max(0) as usize);
+        Ok(feedback)
+    }
+
+    async fn save_user_location(
+        &self,
+        user_id: Uuid,
+        label: &str,
+        location: SaveUserLocation,
+    ) -> Result<UserLocation, DatabaseError> {
+        let saved = UserLocation {
+            user_id,
+            label: label.to_string(),
+            lat: location.lat,
+            lon: location.lon,
+            timezone: location.timezone,
+            updated_at: chrono::Utc::now(),
+        };
+        self.locations.write().await.insert((user_id, saved.label.clone()), saved.clone());
+        Ok(saved)
+    }
+
+    async fn get_user_locations(&self, user_id: Uuid) -> Result<Vec<UserLocation>, DatabaseError> {
+        let mut locations: Vec<UserLocation> = self
+            .locations
+            .read()
+            .await
+            .values()
+            .filter(|location| location.user_id == user_id)
+            .cloned()
+            .collect();
+        locations.sort_by(|a, b| a.label.cmp(&b.label));
+        Ok(locations)
+    }
+
+    async fn get_user_location(&self, user_id: Uuid, label: &str) -> Result<Option<UserLocation>, DatabaseError> {
+        Ok(self.locations.read().await.get(&(user_id, label.to_string())).cloned())
+    }
+
+    async fn delete_user_location(&self, user_id: Uuid, label: &str) -> Result<bool, DatabaseError> {
+        Ok(self.locations.write().await.remove(&(user_id, label.to_string())).is_some())
+    }
+
+    async fn create_feedback(
         &self,
         feedback: CreateFeedback,
     ) -> Result<FeedbackRecord, DatabaseError> {
+        let mut stored = self.feedback.write().await;
+        if feedback.user_id.is_some()
+            && stored.iter().any(|f| f.user_id == feedback.user_id && f.window_id == feedback.window_id)
+        {
+            return Err(DatabaseError::AlreadyExists);
+        }
         let id = Uuid::new_v4();
         let now = chrono::Utc::now();
 
@@ -434,13 +1566,37 @@ impl MockDatabase {
             predicted_score: feedback.predicted_score,
             actual_outcome: feedback.actual_outcome,
             created_at: now,
+            recomputed_score: None,
+            analysis: None,
         };
 
-        self.feedback.write().await.push(feedback_record.clone());
+        stored.push(feedback_record.clone());
         Ok(feedback_record)
     }
 
-    pub async fn get_user_feedback(
+    async fn get_feedback(&self, id: Uuid) -> Result<Option<FeedbackRecord>, DatabaseError> {
+        Ok(self.feedback.read().await.iter().find(|f| f.id == id).cloned())
+    }
+
+    async fn save_feedback_analysis(&self, id: Uuid, analysis: &FeedbackAnalysis) -> Result<(), DatabaseError> {
+        if let Some(record) = self.feedback.write().await.iter_mut().find(|f| f.id == id) {
+            record.analysis = Some(Json(analysis.clone()));
+        }
+        Ok(())
+    }
+
+    async fn get_feedback_disagreements(&self, limit: Option<i64>) -> Result<Vec<FeedbackRecord>, DatabaseError> {
+        let limit = limit.unwrap_or(50).min(100) as usize;
+        let feedback = self.feedback.read().await;
+
+        let mut disagreements: Vec<_> = feedback.iter().filter(|f| f.ai_disagrees()).cloned().collect();
+        disagreements.sort_by_key(|f| std::cmp::Reverse(f.created_at));
+        disagreements.truncate(limit);
+
+        Ok(disagreements)
+    }
+
+    async fn get_user_feedback(
         &self,
         user_id: Uuid,
         limit: Option<i64>,
@@ -454,9 +1610,770 @@ impl MockDatabase {
             .cloned()
             .collect();
         
-        user_feedback.sort_by(|a, b| b.created_at.cmp(&a.created_at));
+        user_feedback.sort_by_key(|f| std::cmp::Reverse(f.created_at));
         user_feedback.truncate(limit);
         
         Ok(user_feedback)
     }
-}
\ No newline at end of file
+
+    async fn find_user_window_feedback(
+        &self,
+        user_id: Uuid,
+        window_id: &str,
+    ) -> Result<Option<FeedbackRecord>, DatabaseError> {
+        Ok(self
+            .feedback
+            .read()
+            .await
+            .iter()
+            .filter(|f| f.user_id == Some(user_id) && f.window_id == window_id)
+            .min_by_key(|f| f.created_at)
+            .cloned())
+    }
+
+    async fn count_user_feedback_since(
+        &self,
+        user_id: Uuid,
+        since: chrono::DateTime<chrono::Utc>,
+    ) -> Result<i64, DatabaseError> {
+        Ok(self
+            .feedback
+            .read()
+            .await
+            .iter()
+            .filter(|f| f.user_id == Some(user_id) && f.created_at >= since)
+            .count() as i64)
+    }
+
+    async fn count_anonymous_feedback_since(
+        &self,
+        window_id: &str,
+        since: chrono::DateTime<chrono::Utc>,
+    ) -> Result<i64, DatabaseError> {
+        Ok(self
+            .feedback
+            .read()
+            .await
+            .iter()
+            .filter(|f| f.user_id.is_none() && f.window_id == window_id && f.created_at >= since)
+            .count() as i64)
+    }
+
+    async fn get_recent_feedback(
+        &self,
+        days: i32,
+        limit: Option<i64>,
+    ) -> Result<Vec<FeedbackRecord>, DatabaseError> {
+        let limit = limit.unwrap_or(100).min(500) as usize;
+        let since = chrono::Utc::now() - chrono::Duration::days(days as i64);
+        let feedback = self.feedback.read().await;
+
+        let mut recent: Vec<_> = feedback
+            .iter()
+            .filter(|f| f.created_at >= since)
+            .cloned()
+            .collect();
+
+        recent.sort_by_key(|f| std::cmp::Reverse(f.created_at));
+        recent.truncate(limit);
+
+        Ok(recent)
+    }
+
+    async fn get_feedback_with_weather(
+        &self,
+        limit: Option<i64>,
+    ) -> Result<Vec<FeedbackRecord>, DatabaseError> {
+        let limit = limit.unwrap_or(1000).min(10000) as usize;
+        let feedback = self.feedback.read().await;
+
+        let mut with_weather: Vec<_> = feedback
+            .iter()
+            .filter(|f| {
+                f.weather_temp_c.is_some()
+                    && f.weather_humidity.is_some()
+                    && f.weather_wind_ms.is_some()
+                    && f.weather_rain_mm.is_some()
+            })
+            .cloned()
+            .collect();
+
+        with_weather.sort_by_key(|f| std::cmp::Reverse(f.created_at));
+        with_weather.truncate(limit);
+
+        Ok(with_weather)
+    }
+
+    async fn get_feedback_stats(&self) -> Result<HashMap<String, serde_json::Value>, DatabaseError> {
+        let feedback = self.feedback.read().await;
+        let mut stats = HashMap::new();
+
+        stats.insert("total_feedback".to_string(), serde_json::Value::Number((feedback.len() as i64).into()));
+
+        let ratings: Vec<f64> = feedback
+            .iter()
+            .filter_map(|f| f.satisfaction_rating.map(f64::from))
+            .collect();
+        if !ratings.is_empty() {
+            let avg = ratings.iter().sum::<f64>() / ratings.len() as f64;
+            stats.insert("avg_satisfaction".to_string(), serde_json::Value::Number(
+                serde_json::Number::from_f64(avg).unwrap_or_else(|| serde_json::Number::from(0))
+            ));
+        }
+
+        let mut result_counts: HashMap<String, i64> = HashMap::new();
+        for result in feedback.iter().filter_map(|f| f.drying_result) {
+            *result_counts.entry(result.as_str().to_string()).or_insert(0) += 1;
+        }
+        stats.insert("drying_results".to_string(), serde_json::Value::Object(
+            result_counts
+                .into_iter()
+                .map(|(result, count)| (result, serde_json::Value::Number(count.into())))
+                .collect()
+        ));
+
+        Ok(stats)
+    }
+
+    async fn get_user_weights(&self, user_id: Uuid) -> Result<Option<TrainedWeights>, DatabaseError> {
+        Ok(self.weights.read().await.get(&user_id).map(|(weights, _)| weights.clone()))
+    }
+
+    async fn save_user_weights(&self, user_id: Uuid, weights: &TrainedWeights) -> Result<(), DatabaseError> {
+        self.weights
+            .write()
+            .await
+            .insert(user_id, (weights.clone(), chrono::Utc::now()));
+        Ok(())
+    }
+
+    async fn delete_old_feedback(&self, cutoff: chrono::DateTime<chrono::Utc>) -> Result<u64, DatabaseError> {
+        let weights = self.weights.read().await;
+        let training = |user_id: Option<Uuid>| {
+            user_id
+                .and_then(|id| weights.get(&id))
+                .is_some_and(|(_, updated_at)| *updated_at >= cutoff)
+        };
+        let mut feedback = self.feedback.write().await;
+        let before = feedback.len();
+        feedback.retain(|f| f.created_at >= cutoff || training(f.user_id));
+        Ok((before - feedback.len()) as u64)
+    }
+
+    async fn save_served_windows(&self, windows: &[ServedWindow]) -> Result<(), DatabaseError> {
+        let mut served = self.served_windows.write().await;
+        for window in windows {
+            served.retain(|w| (&w.window_id, w.lat, w.lon) != (&window.window_id, window.lat, window.lon));
+            served.push(window.clone());
+        }
+        Ok(())
+    }
+
+    async fn get_served_window(
+        &self,
+        window_id: &str,
+        lat: f64,
+        lon: f64,
+    ) -> Result<Option<ServedWindow>, DatabaseError> {
+        Ok(self
+            .served_windows
+            .read()
+            .await
+            .iter()
+            .find(|w| w.window_id == window_id && w.lat == lat && w.lon == lon)
+            .cloned())
+    }
+
+    async fn delete_served_windows_before(
+        &self,
+        cutoff: chrono::DateTime<chrono::Utc>,
+    ) -> Result<u64, DatabaseError> {
+        let mut served = self.served_windows.write().await;
+        let before = served.len();
+        served.retain(|w| w.served_at >= cutoff);
+        Ok((before - served.len()) as u64)
+    }
+
+    async fn health_check(&self) -> Result<(), DatabaseError> {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+
+    /// Feedback on a window of its own, as a user may only rate each once
+    fn feedback(user_id: Option<Uuid>, rating: Option<i32>) -> CreateFeedback {
+        CreateFeedback {
+            user_id,
+            window_id: format!("window_{}_3", Uuid::new_v4().as_u128() % 1_000_000_000),
+            feedback_text: "test".to_string(),
+            satisfaction_rating: rating,
+            drying_result: None,
+            weather_temp_c: None,
+            weather_humidity: None,
+            weather_wind_ms: None,
+            weather_rain_mm: None,
+            predicted_score: None,
+            actual_outcome: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_mock_through_data_store_trait() {
+        let store: Arc<dyn DataStore> = Arc::new(MockDatabase::new());
+
+        let created = store
+            .create_user_preferences(CreateUserPreferences {
+                preferred_drying_hours: Some(4),
+                min_temperature: Some(20.0),
+                max_humidity: None,
+                avoid_rain_probability: None,
+                location_lat: Some(13.75),
+                location_lon: Some(100.5),
+                location_name: Some("Bangkok".to_string()),
+                timezone: None,
+                notification_preferences: None,
+                scoring_model: None,
+            })
+            .await
+            .unwrap();
+
+        let updated = store
+            .update_user_preferences(created.user_id, CreateUserPreferences {
+                preferred_drying_hours: None,
+                min_temperature: None,
+                max_humidity: Some(70.0),
+                avoid_rain_probability: None,
+                location_lat: None,
+                location_lon: None,
+                location_name: None,
+                timezone: None,
+                notification_preferences: None,
+                scoring_model: Some(ScoringModel::Vpd),
+            })
+            .await
+            .unwrap();
+        assert_eq!(updated.min_temperature, Some(20.0));
+        assert_eq!(updated.max_humidity, Some(70.0));
+        assert_eq!(updated.scoring_model, Some(ScoringModel::Vpd));
+        assert_eq!(store.get_user_preferences(created.user_id).await.unwrap().max_humidity, Some(70.0));
+
+        store.create_feedback(feedback(Some(created.user_id), Some(5))).await.unwrap();
+        store.create_feedback(feedback(None, Some(2))).await.unwrap();
+        assert_eq!(store.get_user_feedback(created.user_id, None).await.unwrap().len(), 1);
+        assert_eq!(store.get_recent_feedback(7, None).await.unwrap().len(), 2);
+        assert_eq!(store.get_feedback_stats().await.unwrap()["total_feedback"], 2);
+        assert!(store.health_check().await.is_ok());
+    }
+
+    fn empty_prefs() -> CreateUserPreferences {
+        CreateUserPreferences {
+            preferred_drying_hours: None,
+            min_temperature: None,
+            max_humidity: None,
+            avoid_rain_probability: None,
+            location_lat: None,
+            location_lon: None,
+            location_name: None,
+            timezone: None,
+            notification_preferences: None,
+            scoring_model: None,
+        }
+    }
+
+    /// Insert feedback and backdate it by `days_ago`
+    async fn seed_feedback(db: &MockDatabase, days_ago: i64, create: CreateFeedback) {
+        let record = db.create_feedback(create).await.unwrap();
+        let mut feedback = db.feedback.write().await;
+        let stored = feedback.iter_mut().find(|f| f.id == record.id).unwrap();
+        stored.created_at = chrono::Utc::now() - chrono::Duration::days(days_ago);
+    }
+
+    #[tokio::test]
+    async fn test_mock_user_locations_by_label() {
+        let db = MockDatabase::new();
+        let user_id = Uuid::new_v4();
+        let at = |lat: f64| SaveUserLocation { lat, lon: 100.5, timezone: None };
+
+        db.save_user_location(user_id, "home", at(13.75)).await.unwrap();
+        db.save_user_location(user_id, "beach", at(7.9)).await.unwrap();
+        db.save_user_location(user_id, "beach", at(8.0)).await.unwrap();
+        db.save_user_location(Uuid::new_v4(), "home", at(1.0)).await.unwrap();
+
+        let labels: Vec<String> = db.get_user_locations(user_id).await.unwrap().into_iter().map(|l| l.label).collect();
+        assert_eq!(labels, ["beach", "home"]);
+        assert_eq!(db.get_user_location(user_id, "beach").await.unwrap().unwrap().lat, 8.0);
+
+        assert!(db.delete_user_location(user_id, "beach").await.unwrap());
+        assert!(!db.delete_user_location(user_id, "beach").await.unwrap());
+        assert!(db.get_user_location(user_id, "beach").await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_mock_update_user_preferences() {
+        let db = MockDatabase::new();
+        let created = db
+            .create_user_preferences(CreateUserPreferences {
+                timezone: Some("Asia/Bangkok".to_string()),
+                ..empty_prefs()
+            })
+            .await
+            .unwrap();
+
+        let updated = db
+            .update_user_preferences(created.user_id, CreateUserPreferences {
+                min_temperature: Some(18.0),
+                ..empty_prefs()
+            })
+            .await
+            .unwrap();
+        assert_eq!(updated.timezone.as_deref(), Some("Asia/Bangkok"));
+        assert_eq!(updated.min_temperature, Some(18.0));
+        assert!(updated.updated_at >= created.updated_at);
+
+        let missing = db.update_user_preferences(Uuid::new_v4(), empty_prefs()).await;
+        assert!(matches!(missing, Err(DatabaseError::UserNotFound)));
+    }
+
+    #[tokio::test]
+    async fn test_satisfaction_ema_weighs_recent_ratings_most() {
+        let db = MockDatabase::new();
+        let user_id = Uuid::new_v4();
+        assert_eq!(db.get_user_satisfaction_ema(user_id).await.unwrap(), None);
+
+        // Oldest first: 5, unrated, 5, 1, 1
+        for (days_ago, rating) in [(4, Some(5)), (3, None), (2, Some(5)), (1, Some(1)), (0, Some(1))] {
+            seed_feedback(&db, days_ago, feedback(Some(user_id), rating)).await;
+        }
+        seed_feedback(&db, 0, feedback(None, Some(5))).await;
+
+        // 5 -> 5 -> 0.3 * 1 + 0.7 * 5 = 3.8 -> 0.3 * 1 + 0.7 * 3.8 = 2.96
+        let ema = db.get_user_satisfaction_ema(user_id).await.unwrap().unwrap();
+        assert!((ema - 2.96).abs() < 1e-9);
+    }
+
+    #[tokio::test]
+    async fn test_mock_get_recent_feedback_filters_days_and_limits() {
+        let db = MockDatabase::new();
+        for days_ago in [0, 1, 3, 10, 30] {
+            seed_feedback(&db, days_ago, feedback(None, None)).await;
+        }
+
+        let week = db.get_recent_feedback(7, None).await.unwrap();
+        assert_eq!(week.len(), 3);
+        assert!(week.windows(2).all(|pair| pair[0].created_at >= pair[1].created_at));
+
+        assert_eq!(db.get_recent_feedback(60, Some(2)).await.unwrap().len(), 2);
+        assert_eq!(db.get_recent_feedback(60, Some(10_000)).await.unwrap().len(), 5);
+    }
+
+    #[tokio::test]
+    async fn test_mock_get_feedback_with_weather() {
+        let db = MockDatabase::new();
+        seed_feedback(&db, 0, feedback(None, None)).await;
+        seed_feedback(&db, 0, CreateFeedback {
+            weather_temp_c: Some(30.0),
+            weather_humidity: Some(50.0),
+            weather_wind_ms: Some(3.0),
+            weather_rain_mm: Some(0.0),
+            ..feedback(None, Some(5))
+        })
+        .await;
+
+        let with_weather = db.get_feedback_with_weather(None).await.unwrap();
+        assert_eq!(with_weather.len(), 1);
+        assert_eq!(with_weather[0].satisfaction_rating, Some(5));
+    }
+
+    #[tokio::test]
+    async fn test_mock_get_feedback_stats() {
+        let db = MockDatabase::new();
+        for (rating, result) in [
+            (Some(5), DryingResult::CompletelyDry),
+            (Some(2), DryingResult::NotDry),
+            (None, DryingResult::NotDry),
+        ] {
+            seed_feedback(&db, 0, CreateFeedback {
+                drying_result: Some(result),
+                ..feedback(None, rating)
+            })
+            .await;
+        }
+
+        let stats = db.get_feedback_stats().await.unwrap();
+        assert_eq!(stats["total_feedback"], 3);
+        assert_eq!(stats["avg_satisfaction"], 3.5);
+        assert_eq!(stats["drying_results"]["not_dry"], 2);
+        assert_eq!(stats["drying_results"]["completely_dry"], 1);
+
+        let empty = MockDatabase::new().get_feedback_stats().await.unwrap();
+        assert_eq!(empty["total_feedback"], 0);
+        assert!(!empty.contains_key("avg_satisfaction"));
+    }
+
+    #[tokio::test]
+    async fn test_mock_health_check() {
+        assert!(MockDatabase::new().health_check().await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_find_users_near_radius() {
+        let pool = sqlx::sqlite::SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+        let db = Database::new(pool);
+        db.init_tables().await.unwrap();
+
+        let (lat, lon) = (13.75, 100.5);
+        let at = |lat: f64, lon: f64| CreateUserPreferences {
+            location_lat: Some(lat),
+            location_lon: Some(lon),
+            ..empty_prefs()
+        };
+        // Roughly 5 km north, and a second user 50 km away
+        let five_km = db.create_user_preferences(at(lat + 5.0 / 111.32, lon)).await.unwrap();
+        db.create_user_preferences(at(lat + 0.45, lon)).await.unwrap();
+        db.create_user_preferences(empty_prefs()).await.unwrap();
+
+        let within_ten = db.find_users_near(lat, lon, 10.0).await.unwrap();
+        assert_eq!(within_ten.len(), 1);
+        assert_eq!(within_ten[0].user_id, five_km.user_id);
+
+        assert!(db.find_users_near(lat, lon, 1.0).await.unwrap().is_empty());
+
+        // The mock applies the same filtering
+        let mock = MockDatabase::new();
+        mock.create_user_preferences(at(lat + 5.0 / 111.32, lon)).await.unwrap();
+        assert_eq!(mock.find_users_near(lat, lon, 10.0).await.unwrap().len(), 1);
+        assert!(mock.find_users_near(lat, lon, 1.0).await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_find_feedback_near_in_one_query() {
+        let pool = sqlx::sqlite::SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+        let db = Database::new(pool);
+        db.init_tables().await.unwrap();
+        let mock = MockDatabase::new();
+
+        let (lat, lon) = (13.75, 100.5);
+        let at = |lat: f64, lon: f64| CreateUserPreferences {
+            location_lat: Some(lat),
+            location_lon: Some(lon),
+            ..empty_prefs()
+        };
+        for store in [&db as &dyn DataStore, &mock] {
+            let near = store.create_user_preferences(at(lat + 5.0 / 111.32, lon)).await.unwrap();
+            let far = store.create_user_preferences(at(lat + 0.45, lon)).await.unwrap();
+            for rating in [1, 2, 3] {
+                store.create_feedback(feedback(Some(near.user_id), Some(rating))).await.unwrap();
+            }
+            store.create_feedback(feedback(Some(far.user_id), Some(5))).await.unwrap();
+            store.create_feedback(feedback(None, Some(5))).await.unwrap();
+
+            let nearby = store.find_feedback_near(lat, lon, 10.0, 10).await.unwrap();
+            assert_eq!(nearby.len(), 3);
+            assert!(nearby.iter().all(|f| f.user_id == Some(near.user_id)));
+            // Capped to the latest
+            let latest = store.find_feedback_near(lat, lon, 10.0, 2).await.unwrap();
+            assert_eq!(latest.len(), 2);
+            assert_eq!(latest[0].satisfaction_rating, Some(3));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_timestamps_round_trip_in_one_format() {
+        let pool = sqlx::sqlite::SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+        let db = Database::new(pool.clone());
+        db.init_tables().await.unwrap();
+
+        let created = db.create_user_preferences(empty_prefs()).await.unwrap();
+        let fetched = db.get_user_preferences(created.user_id).await.unwrap();
+        assert_eq!(fetched.created_at, created.created_at);
+        assert_eq!(fetched.updated_at, created.updated_at);
+
+        let record = db.create_feedback(feedback(Some(created.user_id), Some(4))).await.unwrap();
+        let stored = db.get_user_feedback(created.user_id, None).await.unwrap();
+        assert_eq!(stored[0].created_at, record.created_at);
+        assert_eq!(db.get_recent_feedback(1, None).await.unwrap().len(), 1);
+
+        let raw: String = sqlx::query_scalar("SELECT created_at FROM feedback")
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        assert_eq!(raw, db_timestamp(record.created_at));
+        assert!(raw.ends_with('Z') && raw.len() == 27);
+
+        // A row in SQLite's CURRENT_TIMESTAMP format is rewritten on startup
+        let legacy = Uuid::new_v4();
+        sqlx::query("INSERT INTO user_preferences (user_id, created_at, updated_at) VALUES ($1, $2, $2)")
+            .bind(legacy)
+            .bind("2024-05-01 06:00:00")
+            .execute(&pool)
+            .await
+            .unwrap();
+        db.init_tables().await.unwrap();
+        let migrated = db.get_user_preferences(legacy).await.unwrap();
+        assert_eq!(migrated.created_at.to_rfc3339(), "2024-05-01T06:00:00+00:00");
+    }
+
+    #[tokio::test]
+    async fn test_duplicate_feedback_is_dropped_before_the_unique_index() {
+        let pool = sqlx::sqlite::SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+        // Feedback as created by builds without the unique index
+        sqlx::query(
+            "CREATE TABLE feedback (id TEXT PRIMARY KEY, user_id TEXT, window_id TEXT NOT NULL, \
+             feedback_text TEXT NOT NULL, satisfaction_rating INTEGER, drying_result TEXT, \
+             weather_temp_c REAL, weather_humidity REAL, weather_wind_ms REAL, weather_rain_mm REAL, \
+             predicted_score REAL, actual_outcome TEXT, created_at TEXT)",
+        )
+        .execute(&pool)
+        .await
+        .unwrap();
+        let user = Uuid::new_v4();
+        for (user_id, window_id, text) in [
+            (Some(user), "window_0_3", "first"),
+            (Some(user), "window_0_3", "second"),
+            (Some(user), "window_3_6", "other window"),
+            (None, "window_0_3", "anonymous"),
+            (None, "window_0_3", "anonymous again"),
+        ] {
+            sqlx::query(
+                "INSERT INTO feedback (id, user_id, window_id, feedback_text, created_at) \
+                 VALUES ($1, $2, $3, $4, '2024-05-01 06:00:00')",
+            )
+            .bind(Uuid::new_v4())
+            .bind(user_id)
+            .bind(window_id)
+            .bind(text)
+            .execute(&pool)
+            .await
+            .unwrap();
+        }
+
+        let db = Database::new(pool.clone());
+        db.init_tables().await.unwrap();
+
+        let mut texts: Vec<String> = sqlx::query_scalar("SELECT feedback_text FROM feedback")
+            .fetch_all(&pool)
+            .await
+            .unwrap();
+        texts.sort();
+        assert_eq!(texts, ["anonymous", "anonymous again", "first", "other window"]);
+        // Running again leaves the survivors alone
+        db.init_tables().await.unwrap();
+        let count: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM feedback").fetch_one(&pool).await.unwrap();
+        assert_eq!(count, 4);
+    }
+
+    #[tokio::test]
+    async fn test_legacy_drying_results_are_normalized() {
+        let pool = sqlx::sqlite::SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+        // Free-text columns as created by older builds
+        sqlx::query(
+            "CREATE TABLE feedback (id TEXT PRIMARY KEY, user_id TEXT, window_id TEXT NOT NULL, \
+             feedback_text TEXT NOT NULL, satisfaction_rating INTEGER, drying_result TEXT, \
+             weather_temp_c REAL, weather_humidity REAL, weather_wind_ms REAL, weather_rain_mm REAL, \
+             predicted_score REAL, actual_outcome TEXT, created_at TEXT)",
+        )
+        .execute(&pool)
+        .await
+        .unwrap();
+        for (result, outcome) in [("Dry", "Dry"), ("completely dry", "wet"), ("soaked", "meh")] {
+            sqlx::query(
+                "INSERT INTO feedback (id, window_id, feedback_text, drying_result, actual_outcome, created_at) \
+                 VALUES ($1, 'window_0_3', 'ok', $2, $3, '2024-05-01 06:00:00')",
+            )
+            .bind(Uuid::new_v4())
+            .bind(result)
+            .bind(outcome)
+            .execute(&pool)
+            .await
+            .unwrap();
+        }
+
+        let db = Database::new(pool.clone());
+        db.init_tables().await.unwrap();
+
+        let stats = db.get_feedback_stats().await.unwrap();
+        assert_eq!(stats["drying_results"]["completely_dry"], 2);
+        assert_eq!(stats["drying_results"].as_object().unwrap().len(), 1);
+        let outcomes: Vec<Option<ActualOutcome>> = db
+            .get_recent_feedback(10_000, None)
+            .await
+            .unwrap()
+            .iter()
+            .map(|f| f.actual_outcome)
+            .collect();
+        assert_eq!(outcomes.iter().filter(|o| **o == Some(ActualOutcome::Dry)).count(), 1);
+        assert_eq!(outcomes.iter().filter(|o| **o == Some(ActualOutcome::NotDry)).count(), 1);
+        assert_eq!(outcomes.iter().filter(|o| o.is_none()).count(), 1);
+
+        // Input goes through the same normalization, and unknown values are rejected
+        let parsed: DryingResult = serde_json::from_value(serde_json::json!("Mostly-Dry")).unwrap();
+        assert_eq!(parsed, DryingResult::MostlyDry);
+        assert_eq!(serde_json::to_value(parsed).unwrap(), "mostly_dry");
+        assert!(serde_json::from_value::<DryingResult>(serde_json::json!("soaked")).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_recompute_predicted_scores() {
+        let pool = sqlx::sqlite::SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+        let db = Database::new(pool);
+        db.init_tables().await.unwrap();
+
+        let with_weather = |temp_c: f64, rain_mm: f64| CreateFeedback {
+            weather_temp_c: Some(temp_c),
+            weather_humidity: Some(50.0),
+            weather_wind_ms: Some(3.0),
+            weather_rain_mm: Some(rain_mm),
+            predicted_score: Some(0.42), // from an older model
+            ..feedback(None, Some(4))
+        };
+        let warm = db.create_feedback(with_weather(30.0, 0.0)).await.unwrap();
+        let rainy = db.create_feedback(with_weather(22.0, 3.0)).await.unwrap();
+        db.create_feedback(feedback(None, Some(2))).await.unwrap();
+
+        // Dry run reports without writing
+        let rescored = db.recompute_predicted_scores(false).await.unwrap();
+        assert_eq!(rescored.len(), 2);
+        let stored = db.get_feedback_with_weather(None).await.unwrap();
+        assert!(stored.iter().all(|r| r.recomputed_score.is_none()));
+
+        db.recompute_predicted_scores(true).await.unwrap();
+        let stored = db.get_feedback_with_weather(None).await.unwrap();
+        let score_of = |id: Uuid| stored.iter().find(|r| r.id == id).unwrap();
+        assert_eq!(score_of(rainy.id).recomputed_score, Some(-1.0));
+        assert!(score_of(warm.id).recomputed_score.unwrap() > 0.42);
+        assert!(stored.iter().all(|r| r.predicted_score == Some(0.42)));
+    }
+
+    #[tokio::test]
+    async fn test_served_windows_keep_latest_and_expire() {
+        let pool = sqlx::sqlite::SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+        let db = Database::new(pool);
+        db.init_tables().await.unwrap();
+
+        let now = chrono::Utc::now();
+        let weather = crate::scoring::WeatherFeatures {
+            temp_c: 30.0,
+            rh: 50.0,
+            wind_ms: 3.0,
+            cloud: 0.2,
+            rain_p: 0.0,
+            rain_mm: 0.0,
+            gust_ms: None,
+            is_daylight: Some(true),
+            wind_deg: None,
+        };
+        let window = |lat: f64, served_at| ServedWindow {
+            window_id: "window_0_3".to_string(),
+            lat,
+            lon: 100.5,
+            start_time: now,
+            end_time: now + chrono::Duration::hours(3),
+            score: Json(calculate_drying_score(&weather, &DryingWeights::default())),
+            served_at,
+        };
+        let stale = now - chrono::Duration::hours(200);
+        db.save_served_windows(&[window(13.75, stale), window(18.79, now)]).await.unwrap();
+        // Serving the same window again replaces its snapshot
+        db.save_served_windows(&[window(18.79, now)]).await.unwrap();
+
+        let latest = db.get_served_window("window_0_3", 18.79, 100.5).await.unwrap().unwrap();
+        assert_eq!(latest.lat, 18.79);
+        assert_eq!(latest.served_at.timestamp(), now.timestamp());
+        assert_eq!(latest.score.raw.temp_c, 30.0);
+        // The same id served elsewhere is a different window
+        let other = db.get_served_window("window_0_3", 13.75, 100.5).await.unwrap().unwrap();
+        assert_eq!(other.served_at.timestamp(), stale.timestamp());
+        assert!(db.get_served_window("window_0_3", 18.79, 99.0).await.unwrap().is_none());
+
+        let cutoff = now - chrono::Duration::hours(168);
+        assert_eq!(db.delete_served_windows_before(cutoff).await.unwrap(), 1);
+        assert_eq!(db.delete_served_windows_before(cutoff).await.unwrap(), 0);
+        assert!(db.get_served_window("window_0_3", 13.75, 100.5).await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_delete_old_feedback_keeps_recent_and_training() {
+        let pool = sqlx::sqlite::SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+        let db = Database::new(pool.clone());
+        db.init_tables().await.unwrap();
+
+        let now = chrono::Utc::now();
+        let old = now - chrono::Duration::days(120);
+        let idle = db.create_user_preferences(empty_prefs()).await.unwrap().user_id;
+        let training = db.create_user_preferences(empty_prefs()).await.unwrap().user_id;
+        db.save_user_weights(training, &TrainedWeights::default()).await.unwrap();
+
+        let mut ids = Vec::new();
+        for (user_id, created_at) in [(Some(idle), old), (None, old), (Some(training), old), (Some(idle), now)] {
+            let record = db.create_feedback(feedback(user_id, Some(3))).await.unwrap();
+            sqlx::query("UPDATE feedback SET created_at = $1 WHERE id = $2")
+                .bind(db_timestamp(created_at))
+                .bind(record.id)
+                .execute(&pool)
+                .await
+                .unwrap();
+            ids.push(record.id);
+        }
+
+        let removed = db.delete_old_feedback(now - chrono::Duration::days(90)).await.unwrap();
+        assert_eq!(removed, 2);
+        assert!(db.get_feedback(ids[0]).await.unwrap().is_none());
+        assert!(db.get_feedback(ids[1]).await.unwrap().is_none());
+        assert!(db.get_feedback(ids[2]).await.unwrap().is_some());
+        assert!(db.get_feedback(ids[3]).await.unwrap().is_some());
+    }
+
+    #[tokio::test]
+    async fn test_mock_user_weights_round_trip() {
+        let db = MockDatabase::new();
+        let user_id = Uuid::new_v4();
+        assert!(db.get_user_weights(user_id).await.unwrap().is_none());
+
+        let weights = TrainedWeights {
+            weights: DryingWeights {
+                w1: 0.3,
+                ..Default::default()
+            },
+            step: 4,
+            loss: Some(0.6),
+        };
+        db.save_user_weights(user_id, &weights).await.unwrap();
+        assert_eq!(db.get_user_weights(user_id).await.unwrap(), Some(weights));
+    }
+}
@@ -0,0 +1,79 @@
+use axum::{
+    http::StatusCode,
+    response::{IntoResponse, Json, Response},
+};
+
+use crate::utils::ErrorResponse;
+
+/// Handler error that renders as an `ErrorResponse` body. Bare status codes
+/// convert into it, so existing `StatusCode` paths keep working with `?`.
+#[derive(Debug, Clone)]
+pub struct AppError {
+    pub status: StatusCode,
+    pub code: String,
+    pub message: String,
+}
+
+impl AppError {
+    pub fn new(status: StatusCode, code: &str, message: impl Into<String>) -> Self {
+        Self {
+            status,
+            code: code.to_string(),
+            message: message.into(),
+        }
+    }
+
+    pub fn bad_request(code: &str, message: impl Into<String>) -> Self {
+        Self::new(StatusCode::BAD_REQUEST, code, message)
+    }
+}
+
+impl From<StatusCode> for AppError {
+    fn from(status: StatusCode) -> Self {
+        let reason = status.canonical_reason().unwrap_or("Unknown error");
+        Self::new(status, &reason.to_lowercase().replace(' ', "_"), reason)
+    }
+}
+
+impl IntoResponse for AppError {
+    fn into_response(self) -> Response {
+        (self.status, Json(ErrorResponse::new(&self.message, &self.code))).into_response()
+    }
+}
+
+/// Validate an optional count parameter against `1..=max`, defaulting when absent
+pub fn validate_range(name: &str, value: Option<u32>, default: u32, max: u32) -> Result<u32, AppError> {
+    match value {
+        None => Ok(default),
+        Some(v) if (1..=max).contains(&v) => Ok(v),
+        Some(v) => Err(AppError::bad_request(
+            "invalid_parameter",
+            format!("{} must be between 1 and {} (got {})", name, max, v),
+        )),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validate_range() {
+        assert_eq!(validate_range("hours", None, 48, 168).unwrap(), 48);
+        assert_eq!(validate_range("hours", Some(168), 48, 168).unwrap(), 168);
+
+        let zero = validate_range("hours", Some(0), 48, 168).unwrap_err();
+        assert_eq!(zero.status, StatusCode::BAD_REQUEST);
+        assert_eq!(zero.message, "hours must be between 1 and 168 (got 0)");
+
+        let over = validate_range("max_windows", Some(21), 10, 20).unwrap_err();
+        assert_eq!(over.code, "invalid_parameter");
+    }
+
+    #[test]
+    fn test_status_code_conversion() {
+        let err = AppError::from(StatusCode::INTERNAL_SERVER_ERROR);
+        assert_eq!(err.code, "internal_server_error");
+        assert_eq!(err.message, "Internal Server Error");
+    }
+}
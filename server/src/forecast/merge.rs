@@ -1,4 +1,7 @@
 use super::types::*;
+use crate::scoring::RainThresholds;
+use crate::utils::moving_average;
+use crate::warnings::{Warning, WarningCode, GUSTY_WIND_MS};
 use chrono::{DateTime, Duration, FixedOffset, Timelike};
 use std::collections::HashMap;
 
@@ -6,11 +9,23 @@ pub fn merge_weather_data(
     onecall: Option<&OneCallResponse>,
     forecast3h: Option<&Forecast3hResponse>,
     timezone_offset: i32,
+) -> Vec<HourlyData> {
+    merge_weather_data_with(onecall, forecast3h, timezone_offset, Forecast3hMode::Flat)
+}
+
+/// Like [`merge_weather_data`], with a choice of how 3-hourly points are
+/// spread over their hours
+pub fn merge_weather_data_with(
+    onecall: Option<&OneCallResponse>,
+    forecast3h: Option<&Forecast3hResponse>,
+    timezone_offset: i32,
+    forecast3h_mode: Forecast3hMode,
 ) -> Vec<HourlyData> {
     let mut merged_hours = Vec::new();
     let now = chrono::Utc::now();
+    let timezone_offset = location_offset(onecall, forecast3h, timezone_offset);
     let target_offset = FixedOffset::east_opt(timezone_offset).unwrap_or(
-        FixedOffset::east_opt(7 * 3600).unwrap() // Default to UTC+7
+        FixedOffset::east_opt(0).unwrap() // Fall back to UTC for an out-of-range offset
     );
     
     // Create a map for quick lookup of 3-hour forecast data
@@ -32,35 +47,33 @@ pub fn merge_weather_data(
         let hourly_data = if hour_offset <= 120 && forecast3h_map.contains_key(&target_3h_ts) {
             // Prefer 3-hour forecast for exact 3-hour steps up to 120h
             let forecast_item = forecast3h_map[&target_3h_ts];
-            let hours_data = HourlyData::from_forecast3h_item(forecast_item, timezone_offset);
+            let next_item = match forecast3h_mode {
+                Forecast3hMode::Flat => None,
+                Forecast3hMode::Interpolate => forecast3h_map.get(&(target_3h_ts + 3 * 3600)).copied(),
+            };
+            let hours_data = HourlyData::from_forecast3h_interpolated(forecast_item, next_item, timezone_offset);
             let hour_index = ((target_ts - target_3h_ts) / 3600) as usize;
             hours_data.get(hour_index).cloned().unwrap_or_else(|| {
                 create_default_hourly_data(target_time.with_timezone(&target_offset))
             })
-        } else if hour_offset <= 48 {
+        } else if let Some(hourly) = onecall
+            .filter(|_| hour_offset <= 48)
+            .and_then(|onecall| onecall.hourly.get(hour_offset as usize))
+        {
             // Use OneCall hourly for 0-48h
-            if let Some(onecall) = onecall {
-                if let Some(hourly) = onecall.hourly.get(hour_offset as usize) {
-                    HourlyData::from(hourly)
-                } else {
-                    create_default_hourly_data(target_time.with_timezone(&target_offset))
-                }
-            } else {
+            let hour = HourlyData::from(hourly);
+            HourlyData { ts: hour.ts.with_timezone(&target_offset), ..hour }
+        } else if let Some(daily) =
+            onecall.and_then(|onecall| onecall.daily.get((hour_offset / 24) as usize))
+        {
+            // Synthesize from daily up to day 7 (or earlier if hourly is missing)
+            let hour_of_day = target_time.with_timezone(&target_offset).hour() as i32;
+            HourlyData::from_daily_synthesized(daily, timezone_offset, hour_of_day).unwrap_or_else(|e| {
+                tracing::warn!("Falling back to default hour: {}", e);
                 create_default_hourly_data(target_time.with_timezone(&target_offset))
-            }
+            })
         } else {
-            // Synthesize from daily up to day 7
-            if let Some(onecall) = onecall {
-                let day_index = hour_offset / 24;
-                if let Some(daily) = onecall.daily.get(day_index as usize) {
-                    let hour_of_day = (target_time.hour() as i32) % 24;
-                    HourlyData::from_daily_synthesized(daily, timezone_offset, hour_of_day)
-                } else {
-                    create_default_hourly_data(target_time.with_timezone(&target_offset))
-                }
-            } else {
-                create_default_hourly_data(target_time.with_timezone(&target_offset))
-            }
+            create_default_hourly_data(target_time.with_timezone(&target_offset))
         };
         
         merged_hours.push(hourly_data);
@@ -69,6 +82,28 @@ pub fn merge_weather_data(
     merged_hours
 }
 
+/// The location's UTC offset in seconds. OneCall's `timezone_offset` wins
+/// over forecast3h's `city.timezone` when the two disagree, and `fallback`
+/// is used only when neither upstream answered.
+fn location_offset(
+    onecall: Option<&OneCallResponse>,
+    forecast3h: Option<&Forecast3hResponse>,
+    fallback: i32,
+) -> i32 {
+    let onecall_offset = onecall.map(|onecall| onecall.timezone_offset);
+    let forecast3h_offset = forecast3h.map(|forecast| forecast.city.timezone);
+    if let (Some(onecall_offset), Some(forecast3h_offset)) = (onecall_offset, forecast3h_offset) {
+        if onecall_offset != forecast3h_offset {
+            tracing::warn!(
+                "Upstream timezones disagree (onecall: {}s, forecast3h: {}s); using onecall's",
+                onecall_offset,
+                forecast3h_offset
+            );
+        }
+    }
+    onecall_offset.or(forecast3h_offset).unwrap_or(fallback)
+}
+
 fn create_default_hourly_data(dt: DateTime<FixedOffset>) -> HourlyData {
     HourlyData {
         ts: dt,
@@ -78,6 +113,11 @@ fn create_default_hourly_data(dt: DateTime<FixedOffset>) -> HourlyData {
         cloud: 0.5,    // Default cloud cover
         rain_p: 0.0,   // No rain probability
         rain_mm: 0.0,  // No rain
+        wind_gust_ms: None,
+        source: DataSource::Fallback,
+        wind_deg: None,
+        uv_index: None,
+        temp_range: None,
     }
 }
 
@@ -85,11 +125,29 @@ pub fn group_into_windows(
     hourly_data: &[HourlyData],
     step_hours: i32,
 ) -> Vec<WindowData> {
+    group_into_windows_with_gaps(hourly_data, step_hours, &RainThresholds::default(), 0, 1)
+}
+
+/// Like [`group_into_windows`], but a window with at most `gap_tolerance`
+/// hours of vetoing rain is averaged over its dry hours instead, so a brief
+/// shower doesn't throw away an otherwise usable span. The skipped hours are
+/// recorded on the window so callers can warn about them.
+///
+/// Averages come from the hours smoothed over `smoothing_window`, while the
+/// min/max/spread come from the unsmoothed hours so they keep the real swing.
+pub fn group_into_windows_with_gaps(
+    hourly_data: &[HourlyData],
+    step_hours: i32,
+    rain: &RainThresholds,
+    gap_tolerance: u32,
+    smoothing_window: usize,
+) -> Vec<WindowData> {
+    let smoothed = smooth_hourly(hourly_data, smoothing_window);
     let mut windows = Vec::new();
     
-    for i in (0..hourly_data.len()).step_by(step_hours as usize) {
-        let end_index = (i + step_hours as usize).min(hourly_data.len());
-        let window_hours = &hourly_data[i..end_index];
+    for i in (0..smoothed.len()).step_by(step_hours as usize) {
+        let end_index = (i + step_hours as usize).min(smoothed.len());
+        let window_hours = &smoothed[i..end_index];
         
         if window_hours.is_empty() {
             continue;
@@ -98,21 +156,122 @@ pub fn group_into_windows(
         let start_time = window_hours[0].ts;
         let end_time = window_hours.last().unwrap().ts + Duration::hours(1);
         
-        // Average the weather conditions over the window
-        let avg_weather = average_weather_conditions(window_hours);
+        // Set aside the showers when there are few enough, and something is left
+        let is_gap = |h: &HourlyData| h.rain_mm > rain.veto_mm;
+        let gap_count = window_hours.iter().filter(|h| is_gap(h)).count();
+        let tolerated = gap_count > 0 && gap_count <= gap_tolerance as usize && gap_count < window_hours.len();
+        let kept = |hours: &[HourlyData]| -> Vec<HourlyData> {
+            hours.iter().filter(|h| !(tolerated && is_gap(h))).cloned().collect()
+        };
+        
+        // Summarize the weather conditions over the window
+        let kept_hours = kept(window_hours);
+        let weather = average_weather_conditions(&kept_hours);
+        let spread = window_statistics(&kept(&hourly_data[i..end_index]));
+        let (rain_gap_hours, rain_gap_mm) = if tolerated {
+            (gap_count as u32, window_hours.iter().filter(|h| is_gap(h)).map(|h| h.rain_mm).sum())
+        } else {
+            (0, 0.0)
+        };
         
         windows.push(WindowData {
             id: format!("window_{}_{}", start_time.timestamp(), step_hours),
             start_time,
             end_time,
-            weather: avg_weather,
+            weather,
+            temp_stats: spread.temp,
+            humidity_stats: spread.humidity,
             step_hours,
+            rain_gap_hours,
+            rain_gap_mm,
+            wind_resultant_ms: vector_average_wind(&wind_vectors(&kept_hours)).map(|(_, speed)| speed),
+            uv_index: window_hours
+                .iter()
+                .filter_map(|h| h.uv_index)
+                .fold(None, |max: Option<f64>, uv| Some(max.map_or(uv, |m| m.max(uv)))),
+            temp_range: window_hours
+                .iter()
+                .filter_map(|h| h.temp_range)
+                .reduce(TempRange::union),
+            synthetic_hours: window_hours.iter().filter(|h| h.source.is_synthetic()).count() as u32,
         });
     }
     
     windows
 }
 
+/// Smooth temperature, humidity and wind with a trailing moving average so
+/// adjacent windows don't flip on tiny forecast wiggles. Rain is left as-is
+/// so a short shower isn't averaged away.
+pub fn smooth_hourly(hourly_data: &[HourlyData], window_size: usize) -> Vec<HourlyData> {
+    if window_size <= 1 {
+        return hourly_data.to_vec();
+    }
+    
+    let series = |f: fn(&HourlyData) -> f64| -> Vec<f64> {
+        moving_average(&hourly_data.iter().map(f).collect::<Vec<_>>(), window_size)
+    };
+    let temp = series(|h| h.temp_c);
+    let rh = series(|h| h.rh);
+    let wind = series(|h| h.wind_ms);
+    
+    hourly_data
+        .iter()
+        .enumerate()
+        .map(|(i, hour)| HourlyData {
+            temp_c: temp[i],
+            rh: rh[i],
+            wind_ms: wind[i],
+            ..hour.clone()
+        })
+        .collect()
+}
+
+/// Spread of one weather variable over a window's hours
+#[derive(Debug, Clone, Copy, Default, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct SeriesStats {
+    pub mean: f64,
+    pub min: f64,
+    pub max: f64,
+    /// Population standard deviation
+    pub std_dev: f64,
+}
+
+impl SeriesStats {
+    fn of(values: impl Iterator<Item = f64> + Clone) -> Self {
+        let count = values.clone().count() as f64;
+        if count == 0.0 {
+            return Self::default();
+        }
+        let mean = values.clone().sum::<f64>() / count;
+        let variance = values.clone().map(|v| (v - mean).powi(2)).sum::<f64>() / count;
+        
+        Self {
+            mean,
+            min: values.clone().fold(f64::INFINITY, f64::min),
+            max: values.fold(f64::NEG_INFINITY, f64::max),
+            std_dev: variance.sqrt(),
+        }
+    }
+}
+
+/// Averages over a window plus the spread of temperature and humidity, so a
+/// steady window can be told apart from one that swings around the same mean
+#[derive(Debug, Clone)]
+pub struct WindowStats {
+    pub weather: crate::scoring::WeatherFeatures,
+    pub temp: SeriesStats,
+    pub humidity: SeriesStats,
+}
+
+pub fn window_statistics(hours: &[HourlyData]) -> WindowStats {
+    WindowStats {
+        weather: average_weather_conditions(hours),
+        temp: SeriesStats::of(hours.iter().map(|h| h.temp_c)),
+        humidity: SeriesStats::of(hours.iter().map(|h| h.rh)),
+    }
+}
+
 fn average_weather_conditions(hours: &[HourlyData]) -> crate::scoring::WeatherFeatures {
     let count = hours.len() as f64;
     
@@ -120,26 +279,153 @@ fn average_weather_conditions(hours: &[HourlyData]) -> crate::scoring::WeatherFe
     let rh_sum: f64 = hours.iter().map(|h| h.rh).sum();
     let wind_sum: f64 = hours.iter().map(|h| h.wind_ms).sum();
     let cloud_sum: f64 = hours.iter().map(|h| h.cloud).sum();
-    let rain_p_max: f64 = hours.iter().map(|h| h.rain_p).fold(0.0, f64::max);
-    let rain_mm_sum: f64 = hours.iter().map(|h| h.rain_mm).sum();
+    let gust_max = hours
+        .iter()
+        .filter_map(|h| h.wind_gust_ms)
+        .fold(None, |max: Option<f64>, g| Some(max.map_or(g, |m| m.max(g))));
+    let daylight_hours = hours.iter().filter(|h| h.is_daylight()).count();
+    let wind_vectors = wind_vectors(hours);
     
     crate::scoring::WeatherFeatures {
         temp_c: temp_sum / count,
         rh: rh_sum / count,
         wind_ms: wind_sum / count,
         cloud: cloud_sum / count,
-        rain_p: rain_p_max, // Use max rain probability in window
-        rain_mm: rain_mm_sum, // Sum total expected rain
+        rain_p: hourly_rain_probability(hours),
+        rain_mm: expected_rain_mm(hours),
+        gust_ms: gust_max, // Strongest gust in window
+        is_daylight: Some(daylight_hours * 2 >= hours.len()), // Mostly daylight
+        wind_deg: vector_average_wind(&wind_vectors).map(|(deg, _)| deg), // Vector mean direction
     }
 }
 
+/// Rain expected over `hours`: each hour's forecast amount weighted by the
+/// chance that it falls at all
+pub fn expected_rain_mm(hours: &[HourlyData]) -> f64 {
+    hours.iter().map(|h| h.rain_p * h.rain_mm).sum()
+}
+
+/// Chance of rain in at least one of `hours`, taking them as independent.
+/// Never less than the wettest single hour, and higher the longer the
+/// window stays at risk.
+pub fn combined_rain_probability(hours: &[HourlyData]) -> f64 {
+    1.0 - hours.iter().map(|h| 1.0 - h.rain_p.clamp(0.0, 1.0)).product::<f64>()
+}
+
+/// The [`combined_rain_probability`] spread evenly over `hours`: the chance
+/// of rain in a typical hour of the window. Unlike the combined chance it
+/// doesn't grow with the window's length, so it reads like the per-hour
+/// probability the rain penalty and trained weights are calibrated on.
+pub fn hourly_rain_probability(hours: &[HourlyData]) -> f64 {
+    if hours.is_empty() {
+        return 0.0;
+    }
+    1.0 - (1.0 - combined_rain_probability(hours)).powf(1.0 / hours.len() as f64)
+}
+
+/// `(speed_ms, direction_deg)` of the hours that report a direction
+fn wind_vectors(hours: &[HourlyData]) -> Vec<(f64, f64)> {
+    hours
+        .iter()
+        .filter_map(|h| h.wind_deg.map(|deg| (h.wind_ms, deg)))
+        .collect()
+}
+
+/// Average wind direction using u/v components, weighted by speed.
+///
+/// Takes `(speed_ms, direction_deg)` pairs and returns
+/// `(direction_deg, resultant_speed_ms)`. Returns `None` when there is no
+/// wind data or the vectors cancel out, since the direction is meaningless.
+pub fn vector_average_wind(winds: &[(f64, f64)]) -> Option<(f64, f64)> {
+    if winds.is_empty() {
+        return None;
+    }
+    
+    let count = winds.len() as f64;
+    let u: f64 = winds.iter().map(|(speed, deg)| speed * deg.to_radians().sin()).sum::<f64>() / count;
+    let v: f64 = winds.iter().map(|(speed, deg)| speed * deg.to_radians().cos()).sum::<f64>() / count;
+    
+    let resultant = (u * u + v * v).sqrt();
+    if resultant < 1e-6 {
+        return None;
+    }
+    
+    let direction = u.atan2(v).to_degrees().rem_euclid(360.0);
+    Some((direction, resultant))
+}
+
 #[derive(Debug, Clone, serde::Deserialize)]
 pub struct WindowData {
     pub id: String,
     pub start_time: DateTime<FixedOffset>,
     pub end_time: DateTime<FixedOffset>,
     pub weather: crate::scoring::WeatherFeatures,
+    /// Spread of temperature over the hours in `weather`
+    #[serde(default)]
+    pub temp_stats: SeriesStats,
+    /// Spread of humidity over the hours in `weather`
+    #[serde(default)]
+    pub humidity_stats: SeriesStats,
     pub step_hours: i32,
+    /// Hours of tolerated rain left out of `weather`
+    #[serde(default)]
+    pub rain_gap_hours: u32,
+    /// Rain (mm) forecast in those hours
+    #[serde(default)]
+    pub rain_gap_mm: f64,
+    /// Length of the mean wind vector over the hours in `weather`; shorter
+    /// than their average speed when the wind changes direction
+    #[serde(default)]
+    pub wind_resultant_ms: Option<f64>,
+    /// Peak UV index in the window, when the forecast has one
+    #[serde(default)]
+    pub uv_index: Option<f64>,
+    /// Temperature band covering any hours synthesized from daily data
+    #[serde(default)]
+    pub temp_range: Option<TempRange>,
+    /// Hours estimated from daily summaries or defaults
+    #[serde(default)]
+    pub synthetic_hours: u32,
+}
+
+/// The window's averaged weather, as scored
+impl From<&WindowData> for crate::scoring::WeatherFeatures {
+    fn from(window: &WindowData) -> Self {
+        window.weather.clone()
+    }
+}
+
+impl WindowData {
+    /// Concerns visible in the forecast itself: tolerated showers, gusts and
+    /// hours that had to be estimated
+    pub fn warnings(&self) -> Vec<Warning> {
+        let mut warnings = Vec::new();
+        if self.rain_gap_hours > 0 {
+            warnings.push(Warning::new(
+                WarningCode::RainGap,
+                format!(
+                    "Scored without {} rainy hour(s) ({:.1} mm forecast); only hang out if you can bring it in",
+                    self.rain_gap_hours, self.rain_gap_mm
+                ),
+            ));
+        }
+        if let Some(gust) = self.weather.gust_ms.filter(|g| *g > GUSTY_WIND_MS) {
+            warnings.push(Warning::new(
+                WarningCode::Gusty,
+                format!("Gusts up to {:.0} m/s - peg items at both ends", gust),
+            ));
+        }
+        if self.synthetic_hours > 0 {
+            warnings.push(Warning::new(
+                WarningCode::SyntheticData,
+                format!(
+                    "{} of {} hour(s) estimated from a daily forecast",
+                    self.synthetic_hours, self.step_hours
+                ),
+            ));
+        }
+        warnings
+    }
 }
 
 #[cfg(test)]
@@ -159,6 +445,11 @@ mod tests {
                 cloud: 0.3,
                 rain_p: 0.1,
                 rain_mm: 0.0,
+                wind_gust_ms: None,
+                source: DataSource::OneCall,
+                wind_deg: None,
+                uv_index: None,
+                temp_range: None,
             },
             HourlyData {
                 ts: now + Duration::hours(1),
@@ -168,6 +459,11 @@ mod tests {
                 cloud: 0.2,
                 rain_p: 0.0,
                 rain_mm: 0.0,
+                wind_gust_ms: None,
+                source: DataSource::OneCall,
+                wind_deg: None,
+                uv_index: None,
+                temp_range: None,
             },
             HourlyData {
                 ts: now + Duration::hours(2),
@@ -177,6 +473,11 @@ mod tests {
                 cloud: 0.1,
                 rain_p: 0.0,
                 rain_mm: 0.0,
+                wind_gust_ms: None,
+                source: DataSource::OneCall,
+                wind_deg: None,
+                uv_index: None,
+                temp_range: None,
             },
         ];
         
@@ -185,7 +486,169 @@ mod tests {
         assert_eq!(windows.len(), 1);
         assert_eq!(windows[0].step_hours, 3);
         assert!((windows[0].weather.temp_c - 26.0).abs() < 0.1); // Average of 25, 26, 27
-        assert_eq!(windows[0].weather.rain_p, 0.1); // Max rain probability
+        assert!((windows[0].weather.rain_p - (1.0 - 0.9_f64.cbrt())).abs() < 1e-9); // The one hour at risk, spread over three
+    }
+    
+    #[test]
+    fn test_rain_gap_tolerance_keeps_window_with_brief_shower() {
+        let now = Utc::now().with_timezone(&FixedOffset::east_opt(7 * 3600).unwrap());
+        let hour = |offset: i64, rain_mm: f64| HourlyData {
+            ts: now + Duration::hours(offset),
+            temp_c: 28.0,
+            rh: 55.0,
+            wind_ms: 3.0,
+            cloud: 0.2,
+            rain_p: if rain_mm > 0.0 { 0.8 } else { 0.0 },
+            rain_mm,
+            wind_gust_ms: None,
+            source: DataSource::OneCall,
+            wind_deg: None,
+            uv_index: None,
+            temp_range: None,
+        };
+        let hourly_data = vec![hour(0, 0.0), hour(1, 0.4), hour(2, 0.0)];
+        let rain = RainThresholds::default();
+        
+        // Zero tolerance: the trace shower vetoes the whole span
+        let strict = group_into_windows_with_gaps(&hourly_data, 3, &rain, 0, 1);
+        assert_eq!(strict[0].rain_gap_hours, 0);
+        assert!(rain.veto_reason(&strict[0].weather).is_some());
+        
+        // Tolerance 1: scored on the dry hours, with the shower recorded
+        let tolerant = group_into_windows_with_gaps(&hourly_data, 3, &rain, 1, 1);
+        assert_eq!(tolerant.len(), 1);
+        assert_eq!(tolerant[0].rain_gap_hours, 1);
+        assert!((tolerant[0].rain_gap_mm - 0.4).abs() < 1e-9);
+        assert!(rain.veto_reason(&tolerant[0].weather).is_none());
+        assert_eq!(tolerant[0].end_time - tolerant[0].start_time, Duration::hours(3));
+        
+        // Two wet hours exceed a tolerance of 1
+        let wetter = vec![hour(0, 0.0), hour(1, 0.4), hour(2, 0.5)];
+        let windows = group_into_windows_with_gaps(&wetter, 3, &rain, 1, 1);
+        assert_eq!(windows[0].rain_gap_hours, 0);
+        assert!(rain.veto_reason(&windows[0].weather).is_some());
+    }
+    
+    #[test]
+    fn test_window_converts_to_its_weather_including_rain_probability() {
+        let now = Utc::now().with_timezone(&FixedOffset::east_opt(7 * 3600).unwrap());
+        let hourly_data: Vec<HourlyData> = (0..3)
+            .map(|offset| HourlyData {
+                rain_p: 0.2 * offset as f64,
+                ..create_default_hourly_data(now + Duration::hours(offset))
+            })
+            .collect();
+        let window = &group_into_windows(&hourly_data, 3)[0];
+        
+        let features = crate::scoring::WeatherFeatures::from(window);
+        
+        assert!((features.rain_p - (1.0 - 0.48_f64.cbrt())).abs() < 1e-9); // 1 - ∛(1.0 * 0.8 * 0.6)
+        assert_eq!(features.temp_c, window.weather.temp_c);
+        assert_eq!(features.is_daylight, window.weather.is_daylight);
+    }
+    
+    #[tokio::test]
+    async fn test_merge_daily_synthesis_from_mock() {
+        use crate::forecast::{mock::MockWeatherClient, WeatherProvider};
+        
+        // Default mock: 48 hourly entries, the rest of the week from daily
+        let onecall = MockWeatherClient::new().get_onecall(13.75, 100.5).await.unwrap();
+        let merged = merge_weather_data(Some(&onecall), None, 7 * 3600);
+        
+        assert_eq!(merged.len(), 168);
+        assert_eq!(merged.iter().filter(|h| h.source == DataSource::OneCall).count(), 48);
+        assert!(merged[48..].iter().all(|h| h.source == DataSource::DailySynthesized));
+        
+        // No hourly block at all: every hour comes from daily synthesis
+        let onecall = MockWeatherClient::new()
+            .with_horizon(0, 7)
+            .get_onecall(13.75, 100.5)
+            .await
+            .unwrap();
+        let merged = merge_weather_data(Some(&onecall), None, 7 * 3600);
+        
+        assert!(merged.iter().all(|h| h.source == DataSource::DailySynthesized));
+        
+        // Too few days: the tail falls back to defaults
+        let onecall = MockWeatherClient::new()
+            .with_horizon(0, 3)
+            .get_onecall(13.75, 100.5)
+            .await
+            .unwrap();
+        let merged = merge_weather_data(Some(&onecall), None, 7 * 3600);
+        
+        assert!(merged[..72].iter().all(|h| h.source == DataSource::DailySynthesized));
+        assert!(merged[72..].iter().all(|h| h.source == DataSource::Fallback));
+    }
+
+    #[tokio::test]
+    async fn test_mismatched_upstream_offsets_resolve_to_onecall() {
+        use crate::forecast::{mock::MockWeatherClient, WeatherProvider};
+
+        let client = MockWeatherClient::new();
+        let mut onecall = client.get_onecall(13.75, 100.5).await.unwrap();
+        let mut forecast3h = client.get_forecast3h(13.75, 100.5).await.unwrap();
+        onecall.timezone_offset = 8 * 3600;
+        forecast3h.city.timezone = 5 * 3600;
+
+        let merged = merge_weather_data(Some(&onecall), Some(&forecast3h), 0);
+        let onecall_offset = FixedOffset::east_opt(8 * 3600).unwrap();
+
+        // 3-hourly and daily-synthesized hours alike land on onecall's offset
+        assert!(merged.iter().any(|h| h.source == DataSource::Forecast3h));
+        assert!(merged.iter().any(|h| h.source == DataSource::DailySynthesized));
+        assert!(merged.iter().all(|h| *h.ts.offset() == onecall_offset));
+
+        // Without onecall, forecast3h's city timezone is next in line
+        let merged = merge_weather_data(None, Some(&forecast3h), 0);
+        let city_offset = FixedOffset::east_opt(5 * 3600).unwrap();
+        assert!(merged.iter().all(|h| *h.ts.offset() == city_offset));
+    }
+    
+    #[test]
+    fn test_vector_average_wind_wraps_around_north() {
+        let (deg, speed) = vector_average_wind(&[(3.0, 0.0), (3.0, 360.0)]).unwrap();
+        
+        assert!(deg.min(360.0 - deg) < 0.5, "expected ~0°, got {}", deg);
+        assert!((speed - 3.0).abs() < 1e-9);
+        
+        // Scalar averaging would have produced 180°
+        let (deg, _) = vector_average_wind(&[(2.0, 350.0), (2.0, 10.0)]).unwrap();
+        assert!(deg.min(360.0 - deg) < 0.5, "expected ~0°, got {}", deg);
+    }
+    
+    #[test]
+    fn test_vector_average_wind_opposing_cancels() {
+        assert!(vector_average_wind(&[(2.0, 90.0), (2.0, 270.0)]).is_none());
+        assert!(vector_average_wind(&[]).is_none());
+        
+        let (deg, speed) = vector_average_wind(&[(4.0, 90.0), (2.0, 180.0)]).unwrap();
+        assert!(deg > 90.0 && deg < 135.0); // Pulled toward the stronger easterly
+        assert!(speed < 3.0); // Resultant is shorter than the mean speed
+    }
+    
+    #[test]
+    fn test_windows_carry_the_resultant_wind_speed() {
+        let now = Utc::now().with_timezone(&FixedOffset::east_opt(7 * 3600).unwrap());
+        // 3 m/s from the east, then the west: no net wind
+        let backing: Vec<HourlyData> = [90.0, 270.0, 90.0, 270.0]
+            .into_iter()
+            .enumerate()
+            .map(|(offset, deg)| HourlyData {
+                wind_ms: 3.0,
+                wind_deg: Some(deg),
+                ..create_default_hourly_data(now + Duration::hours(offset as i64))
+            })
+            .collect();
+        let window = &group_into_windows(&backing, 4)[0];
+        assert_eq!(window.weather.wind_ms, 3.0);
+        assert_eq!(window.wind_resultant_ms, None);
+
+        // A steady easterly keeps its full speed
+        let steady: Vec<HourlyData> = backing.iter().map(|h| HourlyData { wind_deg: Some(90.0), ..h.clone() }).collect();
+        let window = &group_into_windows(&steady, 4)[0];
+        assert!((window.wind_resultant_ms.unwrap() - 3.0).abs() < 1e-9);
+        assert!((window.weather.wind_deg.unwrap() - 90.0).abs() < 1e-9);
     }
     
     #[test]
@@ -200,6 +663,11 @@ mod tests {
                 cloud: 0.3,
                 rain_p: 0.1,
                 rain_mm: 0.5,
+                wind_gust_ms: None,
+                source: DataSource::OneCall,
+                wind_deg: None,
+                uv_index: None,
+                temp_range: None,
             },
             HourlyData {
                 ts: now + Duration::hours(1),
@@ -209,6 +677,11 @@ mod tests {
                 cloud: 0.1,
                 rain_p: 0.3,
                 rain_mm: 1.0,
+                wind_gust_ms: None,
+                source: DataSource::OneCall,
+                wind_deg: None,
+                uv_index: None,
+                temp_range: None,
             },
         ];
         
@@ -218,7 +691,149 @@ mod tests {
         assert_eq!(avg.rh, 50.0); // (60 + 40) / 2
         assert_eq!(avg.wind_ms, 3.0); // (2 + 4) / 2
         assert_eq!(avg.cloud, 0.2); // (0.3 + 0.1) / 2
-        assert_eq!(avg.rain_p, 0.3); // max(0.1, 0.3)
-        assert_eq!(avg.rain_mm, 1.5); // 0.5 + 1.0
+        assert!((avg.rain_p - (1.0 - 0.63_f64.sqrt())).abs() < 1e-9); // 1 - √(0.9 * 0.7)
+        assert!((avg.rain_mm - 0.35).abs() < 1e-9); // 0.1 * 0.5 + 0.3 * 1.0
+    }
+    
+    #[test]
+    fn test_combined_rain_probability_exceeds_wettest_hour() {
+        let now = Utc::now().with_timezone(&FixedOffset::east_opt(7 * 3600).unwrap());
+        let hours: Vec<HourlyData> = [0.3, 0.3, 0.3, 0.3]
+            .into_iter()
+            .enumerate()
+            .map(|(offset, rain_p)| HourlyData {
+                rain_p,
+                rain_mm: 2.0,
+                ..create_default_hourly_data(now + Duration::hours(offset as i64))
+            })
+            .collect();
+        let max = hours.iter().map(|h| h.rain_p).fold(0.0, f64::max);
+        
+        // Four 30% hours make rain at some point more likely than not
+        let combined = combined_rain_probability(&hours);
+        assert!((combined - (1.0 - 0.7_f64.powi(4))).abs() < 1e-9);
+        assert!(combined > 0.75 && max == 0.3);
+        // ...while the 8 mm forecast is only expected to 30%
+        assert!((expected_rain_mm(&hours) - 2.4).abs() < 1e-9);
+        
+        // A single hour is its own probability; certain rain stays certain
+        assert!((combined_rain_probability(&hours[..1]) - 0.3).abs() < 1e-9);
+        let certain = HourlyData { rain_p: 1.0, ..hours[0].clone() };
+        assert_eq!(combined_rain_probability(&[hours[1].clone(), certain]), 1.0);
+        assert_eq!(combined_rain_probability(&[]), 0.0);
+        
+        // Spread back over the four hours, it's each hour's own chance
+        assert!((hourly_rain_probability(&hours) - 0.3).abs() < 1e-9);
+        assert_eq!(hourly_rain_probability(&[]), 0.0);
     }
-}
\ No newline at end of file
+    
+    #[test]
+    fn test_long_lightly_showery_window_is_not_penalized() {
+        use crate::scoring::{calculate_drying_score_with, model_contributions, DryingWeights, ScoringConfig};
+        
+        let now = Utc::now().with_timezone(&FixedOffset::east_opt(7 * 3600).unwrap());
+        let hourly_data: Vec<HourlyData> = (0..12)
+            .map(|offset| HourlyData {
+                rain_p: 0.2,
+                rain_mm: 0.05,
+                ..create_default_hourly_data(now + Duration::hours(offset))
+            })
+            .collect();
+        let window = &group_into_windows(&hourly_data, 12)[0];
+        
+        // Rain at some point in the 12 hours is all but certain...
+        assert!(combined_rain_probability(&hourly_data) > 0.9);
+        // ...but the window is scored on a 20% hour, like a short one
+        assert!((window.weather.rain_p - 0.2).abs() < 1e-9);
+        let (weights, config) = (DryingWeights::default(), ScoringConfig::default());
+        let score = calculate_drying_score_with(&window.weather, &weights, &config);
+        assert!(!score.unsafe_window);
+        assert_eq!(model_contributions(&score.raw, &score.features, &weights, &config).rain_penalty, 0.0);
+        let short = &group_into_windows(&hourly_data[..3], 3)[0];
+        assert!((calculate_drying_score_with(&short.weather, &weights, &config).score - score.score).abs() < 1e-6);
+    }
+    
+    #[test]
+    fn test_window_statistics_min_max_spread() {
+        let now = Utc::now().with_timezone(&FixedOffset::east_opt(7 * 3600).unwrap());
+        let hour = |offset: i64, temp_c: f64, rh: f64| HourlyData {
+            ts: now + Duration::hours(offset),
+            temp_c,
+            rh,
+            wind_ms: 2.0,
+            cloud: 0.3,
+            rain_p: 0.0,
+            rain_mm: 0.0,
+            wind_gust_ms: None,
+            source: DataSource::OneCall,
+            wind_deg: None,
+            uv_index: None,
+            temp_range: None,
+        };
+        let swinging = vec![hour(0, 18.0, 85.0), hour(1, 25.0, 60.0), hour(2, 32.0, 35.0), hour(3, 25.0, 60.0)];
+        let steady = vec![hour(0, 25.0, 60.0), hour(1, 25.0, 60.0), hour(2, 25.0, 60.0), hour(3, 25.0, 60.0)];
+        
+        let stats = window_statistics(&swinging);
+        assert_eq!(stats.weather.temp_c, 25.0);
+        assert_eq!((stats.temp.min, stats.temp.max), (18.0, 32.0));
+        assert_eq!((stats.humidity.min, stats.humidity.max), (35.0, 85.0));
+        assert!((stats.temp.std_dev - 24.5_f64.sqrt()).abs() < 1e-9);
+        
+        // Same mean, no spread
+        let stats = window_statistics(&steady);
+        assert_eq!(stats.weather.temp_c, 25.0);
+        assert_eq!((stats.temp.min, stats.temp.max, stats.temp.std_dev), (25.0, 25.0, 0.0));
+        assert_eq!(stats.temp.mean, 25.0);
+        
+        let windows = group_into_windows(&swinging, 4);
+        assert_eq!(windows[0].temp_stats, window_statistics(&swinging).temp);
+        
+        // Smoothing flattens the averages but not the reported swing
+        let smoothed = group_into_windows_with_gaps(&swinging, 4, &RainThresholds::default(), 0, 3);
+        assert_ne!(smoothed[0].weather.temp_c, windows[0].weather.temp_c);
+        assert_eq!(smoothed[0].temp_stats, windows[0].temp_stats);
+        assert_eq!(smoothed[0].humidity_stats, windows[0].humidity_stats);
+    }
+    
+    #[test]
+    fn test_smoothing_reduces_adjacent_window_variance() {
+        use crate::scoring::{calculate_drying_score, DryingWeights};
+        
+        let now = Utc::now().with_timezone(&FixedOffset::east_opt(7 * 3600).unwrap());
+        // Noisy series: alternating warm/dry and cool/damp blocks of 3 hours
+        let hourly_data: Vec<HourlyData> = (0..24)
+            .map(|i| {
+                let warm = (i / 3) % 2 == 0;
+                HourlyData {
+                    ts: now + Duration::hours(i),
+                    temp_c: if warm { 30.0 } else { 22.0 },
+                    rh: if warm { 45.0 } else { 75.0 },
+                    wind_ms: if warm { 3.5 } else { 1.5 },
+                    cloud: 0.3,
+                    rain_p: 0.0,
+                    rain_mm: if i == 10 { 0.1 } else { 0.0 },
+                    wind_gust_ms: None,
+                    wind_deg: None,
+                    source: DataSource::OneCall,
+                    uv_index: None,
+                    temp_range: None,
+                }
+            })
+            .collect();
+        
+        let adjacent_variance = |data: &[HourlyData]| -> f64 {
+            let scores: Vec<f64> = group_into_windows(data, 3)
+                .iter()
+                .map(|w| calculate_drying_score(&w.weather, &DryingWeights::default()).score)
+                .collect();
+            scores.windows(2).map(|p| (p[1] - p[0]).powi(2)).sum::<f64>() / (scores.len() - 1) as f64
+        };
+        
+        let smoothed = smooth_hourly(&hourly_data, 3);
+        assert!(adjacent_variance(&smoothed) < adjacent_variance(&hourly_data));
+        
+        // Rain is untouched, and a window size of 1 is a no-op
+        assert_eq!(smoothed[10].rain_mm, 0.1);
+        assert_eq!(smooth_hourly(&hourly_data, 1)[4].temp_c, hourly_data[4].temp_c);
+    }
+}
@@ -1,55 +1,186 @@
+use super::openweather::OpenWeatherError;
 use super::types::*;
+use super::WeatherProvider;
+use async_trait::async_trait;
 use chrono::{DateTime, Duration, FixedOffset, Timelike, Utc};
+use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
-pub struct MockWeatherClient;
+pub struct MockWeatherClient {
+    hourly_count: usize,
+    daily_count: usize,
+    scenario: Option<MockScenario>,
+}
+
+impl Default for MockWeatherClient {
+    fn default() -> Self {
+        Self {
+            hourly_count: 48,
+            daily_count: 7,
+            scenario: None,
+        }
+    }
+}
+
+/// Scripted weather the mock serves instead of random data, so demos,
+/// screenshots and tests see the same forecast every time
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum MockScenario {
+    /// Warm, dry, breezy and clear throughout
+    Perfect,
+    /// Steady rain under full cloud
+    Rainy,
+    /// Mild and humid with light wind and a chance of showers
+    Marginal,
+}
+
+/// One point of scripted weather
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Conditions {
+    temp: f64,
+    humidity: f64,
+    wind_speed: f64,
+    clouds: f64,
+    pop: f64,
+    /// Rain per hour, in mm
+    rain_mm: f64,
+}
+
+impl MockScenario {
+    /// The weather `hours_ahead` from now; only the temperature follows the day
+    fn conditions(self, hours_ahead: i64) -> Conditions {
+        let diurnal = (hours_ahead as f64 * 0.26).sin();
+        match self {
+            Self::Perfect => Conditions {
+                temp: 31.0 + 3.0 * diurnal,
+                humidity: 40.0,
+                wind_speed: 4.0,
+                clouds: 10.0,
+                pop: 0.0,
+                rain_mm: 0.0,
+            },
+            Self::Rainy => Conditions {
+                temp: 24.0 + 1.0 * diurnal,
+                humidity: 95.0,
+                wind_speed: 2.0,
+                clouds: 100.0,
+                pop: 0.95,
+                rain_mm: 3.0,
+            },
+            Self::Marginal => Conditions {
+                temp: 26.0 + 2.0 * diurnal,
+                humidity: 75.0,
+                wind_speed: 1.5,
+                clouds: 60.0,
+                pop: 0.35,
+                rain_mm: 0.0,
+            },
+        }
+    }
+}
 
 impl MockWeatherClient {
     pub fn new() -> Self {
-        Self
+        Self::default()
+    }
+
+    /// Control how many OneCall hourly and daily entries are returned.
+    /// Returning fewer hourly entries forces the daily-synthesis merge path.
+    pub fn with_horizon(mut self, hourly_count: usize, daily_count: usize) -> Self {
+        self.hourly_count = hourly_count;
+        self.daily_count = daily_count;
+        self
+    }
+
+    /// Serve `scenario`'s scripted weather instead of random data
+    pub fn with_scenario(mut self, scenario: MockScenario) -> Self {
+        self.scenario = Some(scenario);
+        self
+    }
+
+    /// A steady southerly for scripted weather, anything from S to W otherwise
+    fn wind_deg(&self) -> f64 {
+        match self.scenario {
+            Some(_) => 200.0,
+            None => 180.0 + 90.0 * fastrand::f64(),
+        }
+    }
+}
+
+#[async_trait]
+impl WeatherProvider for MockWeatherClient {
+    fn name(&self) -> &'static str {
+        "mock"
     }
 
-    pub async fn get_onecall(&self, lat: f64, lon: f64) -> Result<OneCallResponse, Box<dyn std::error::Error>> {
+    async fn get_onecall(&self, lat: f64, lon: f64) -> Result<OneCallResponse, OpenWeatherError> {
         let now = Utc::now();
         let timezone_offset = 7 * 3600; // UTC+7 for Thailand
         
-        // Generate 48 hours of mock hourly data
-        let hourly = (0..48)
+        // Generate mock hourly data (48 hours by default)
+        let hourly = (0..self.hourly_count as i64)
             .map(|hour| {
-                let base_temp = 25.0 + 5.0 * (hour as f64 * 0.26).sin(); // Diurnal temperature variation
-                let base_humidity = 60.0 + 20.0 * (hour as f64 * 0.13).cos(); // Humidity variation
-                let wind_speed = 2.0 + 3.0 * fastrand::f64(); // Random wind
-                let clouds = 30.0 + 40.0 * fastrand::f64(); // Random clouds
-                let pop = if hour % 8 == 0 { 0.3 } else { 0.1 }; // Occasional rain
+                let weather = match self.scenario {
+                    Some(scenario) => scenario.conditions(hour),
+                    None => {
+                        let pop = if hour % 8 == 0 { 0.3 } else { 0.1 }; // Occasional rain
+                        Conditions {
+                            temp: 25.0 + 5.0 * (hour as f64 * 0.26).sin(), // Diurnal temperature variation
+                            humidity: (60.0 + 20.0 * (hour as f64 * 0.13).cos()).clamp(30.0, 90.0), // Humidity variation
+                            wind_speed: 2.0 + 3.0 * fastrand::f64(), // Random wind
+                            clouds: 30.0 + 40.0 * fastrand::f64(), // Random clouds
+                            pop,
+                            rain_mm: if pop > 0.2 { 2.0 * fastrand::f64() } else { 0.0 },
+                        }
+                    }
+                };
                 
                 let mut rain = None;
-                if pop > 0.2 {
+                if weather.rain_mm > 0.0 {
                     let mut rain_map = HashMap::new();
-                    rain_map.insert("1h".to_string(), 2.0 * fastrand::f64());
-                    rain = Some(rain_map);
+                    rain_map.insert("1h".to_string(), weather.rain_mm);
+                    rain = Some(Precipitation::ByPeriod(rain_map));
                 }
                 
                 OneCallHourly {
                     dt: (now + Duration::hours(hour)).timestamp(),
-                    temp: base_temp,
-                    humidity: base_humidity.clamp(30.0, 90.0),
-                    wind_speed,
-                    clouds,
-                    pop,
+                    temp: weather.temp,
+                    humidity: weather.humidity,
+                    wind_speed: weather.wind_speed,
+                    wind_deg: Some(self.wind_deg()),
+                    wind_gust: Some(weather.wind_speed * 1.5),
+                    clouds: weather.clouds,
+                    pop: weather.pop,
                     rain,
+                    uvi: Some((8.0 * (hour as f64 * 0.26).sin()).max(0.0)), // Peaks with the temperature
                 }
             })
             .collect();
         
-        // Generate 7 days of mock daily data
-        let daily = (0..7)
+        // Generate mock daily data (7 days by default)
+        let daily = (0..self.daily_count as i64)
             .map(|day| {
-                let base_temp = 28.0 + 3.0 * (day as f64 * 0.5).sin();
-                let humidity = 65.0 + 15.0 * fastrand::f64();
-                let wind_speed = 2.5 + 2.0 * fastrand::f64();
-                let clouds = 40.0 + 30.0 * fastrand::f64();
-                let pop = if day % 3 == 0 { 0.4 } else { 0.2 };
-                let rain = if pop > 0.3 { Some(5.0 * fastrand::f64()) } else { None };
+                let weather = match self.scenario {
+                    // Scripted days look like their warmest hour, with a day's worth of rain
+                    Some(scenario) => {
+                        let noon = scenario.conditions(day * 24 + 6);
+                        Conditions { rain_mm: noon.rain_mm * 24.0, ..noon }
+                    }
+                    None => {
+                        let pop = if day % 3 == 0 { 0.4 } else { 0.2 };
+                        Conditions {
+                            temp: 28.0 + 3.0 * (day as f64 * 0.5).sin(),
+                            humidity: 65.0 + 15.0 * fastrand::f64(),
+                            wind_speed: 2.5 + 2.0 * fastrand::f64(),
+                            clouds: 40.0 + 30.0 * fastrand::f64(),
+                            pop,
+                            rain_mm: if pop > 0.3 { 5.0 * fastrand::f64() } else { 0.0 },
+                        }
+                    }
+                };
+                let base_temp = weather.temp;
+                let rain = (weather.rain_mm > 0.0).then_some(weather.rain_mm);
                 
                 OneCallDaily {
                     dt: (now + Duration::days(day)).timestamp(),
@@ -61,10 +192,11 @@ impl MockWeatherClient {
                         eve: base_temp + 2.0,
                         morn: base_temp - 2.0,
                     },
-                    humidity,
-                    wind_speed,
-                    clouds,
-                    pop,
+                    humidity: weather.humidity,
+                    wind_speed: weather.wind_speed,
+                    wind_deg: Some(self.wind_deg()),
+                    clouds: weather.clouds,
+                    pop: weather.pop,
                     rain,
                 }
             })
@@ -80,7 +212,7 @@ impl MockWeatherClient {
         })
     }
 
-    pub async fn get_forecast3h(&self, lat: f64, lon: f64) -> Result<Forecast3hResponse, Box<dyn std::error::Error>> {
+    async fn get_forecast3h(&self, lat: f64, lon: f64) -> Result<Forecast3hResponse, OpenWeatherError> {
         let now = Utc::now();
         
         // Generate 5 days of 3-hour forecast data (40 items)
@@ -89,17 +221,31 @@ impl MockWeatherClient {
                 let hours_ahead = i * 3;
                 let target_time = now + Duration::hours(hours_ahead);
                 
-                let base_temp = 26.0 + 4.0 * (hours_ahead as f64 * 0.26).sin();
-                let humidity = 65.0 + 20.0 * (hours_ahead as f64 * 0.13).cos();
-                let wind_speed = 2.5 + 2.5 * fastrand::f64();
-                let clouds = 35.0 + 35.0 * fastrand::f64();
-                let pop = if hours_ahead % 24 == 0 { 0.35 } else { 0.15 };
+                let weather = match self.scenario {
+                    Some(scenario) => {
+                        let hour = scenario.conditions(hours_ahead);
+                        Conditions { rain_mm: hour.rain_mm * 3.0, ..hour }
+                    }
+                    None => {
+                        let pop = if hours_ahead % 24 == 0 { 0.35 } else { 0.15 };
+                        Conditions {
+                            temp: 26.0 + 4.0 * (hours_ahead as f64 * 0.26).sin(),
+                            humidity: 65.0 + 20.0 * (hours_ahead as f64 * 0.13).cos(),
+                            wind_speed: 2.5 + 2.5 * fastrand::f64(),
+                            clouds: 35.0 + 35.0 * fastrand::f64(),
+                            pop,
+                            rain_mm: if pop > 0.25 { 3.0 * fastrand::f64() } else { 0.0 },
+                        }
+                    }
+                };
+                let (base_temp, pop, wind_speed) = (weather.temp, weather.pop, weather.wind_speed);
+                let raining = weather.rain_mm > 0.0;
                 
                 let mut rain = None;
-                if pop > 0.25 {
+                if raining {
                     let mut rain_map = HashMap::new();
-                    rain_map.insert("3h".to_string(), 3.0 * fastrand::f64());
-                    rain = Some(rain_map);
+                    rain_map.insert("3h".to_string(), weather.rain_mm);
+                    rain = Some(Precipitation::ByPeriod(rain_map));
                 }
                 
                 Forecast3hItem {
@@ -109,22 +255,22 @@ impl MockWeatherClient {
                         feels_like: base_temp + 2.0,
                         temp_min: base_temp - 2.0,
                         temp_max: base_temp + 2.0,
-                        pressure: 1013.0 + 10.0 * fastrand::f64(),
+                        pressure: if self.scenario.is_some() { 1013.0 } else { 1013.0 + 10.0 * fastrand::f64() },
                         sea_level: Some(1013.0),
                         grnd_level: Some(1010.0),
-                        humidity,
+                        humidity: weather.humidity,
                         temp_kf: Some(0.0),
                     },
                     weather: vec![Forecast3hWeather {
-                        id: if pop > 0.25 { 500 } else { 800 },
-                        main: if pop > 0.25 { "Rain".to_string() } else { "Clear".to_string() },
-                        description: if pop > 0.25 { "light rain".to_string() } else { "clear sky".to_string() },
-                        icon: if pop > 0.25 { "10d".to_string() } else { "01d".to_string() },
+                        id: if raining { 500 } else { 800 },
+                        main: if raining { "Rain".to_string() } else { "Clear".to_string() },
+                        description: if raining { "light rain".to_string() } else { "clear sky".to_string() },
+                        icon: if raining { "10d".to_string() } else { "01d".to_string() },
                     }],
-                    clouds: Forecast3hClouds { all: clouds },
+                    clouds: Forecast3hClouds { all: weather.clouds },
                     wind: Forecast3hWind {
                         speed: wind_speed,
-                        deg: 180.0 + 90.0 * fastrand::f64(),
+                        deg: self.wind_deg(),
                         gust: Some(wind_speed * 1.5),
                     },
                     visibility: Some(10000),
@@ -157,7 +303,7 @@ impl MockWeatherClient {
         })
     }
 
-    pub async fn geocode_direct(&self, query: &str) -> Result<Vec<GeocodeResponse>, Box<dyn std::error::Error>> {
+    async fn geocode_direct(&self, query: &str) -> Result<Vec<GeocodeResponse>, OpenWeatherError> {
         // Mock geocoding responses for common cities
         let mock_locations = vec![
             ("Bangkok", 13.7563, 100.5018, "TH", Some("Bangkok".to_string())),
@@ -166,10 +312,11 @@ impl MockWeatherClient {
             ("Pattaya", 12.9236, 100.8825, "TH", Some("Chonburi".to_string())),
         ];
         
+        // Like OpenWeather, an unknown place yields no results
         let query_lower = query.to_lowercase();
-        let result = mock_locations
+        let results = mock_locations
             .into_iter()
-            .find(|(name, _, _, _, _)| name.to_lowercase().contains(&query_lower))
+            .filter(|(name, _, _, _, _)| name.to_lowercase().contains(&query_lower))
             .map(|(name, lat, lon, country, state)| GeocodeResponse {
                 name: name.to_string(),
                 local_names: None,
@@ -178,19 +325,12 @@ impl MockWeatherClient {
                 country: country.to_string(),
                 state,
             })
-            .unwrap_or_else(|| GeocodeResponse {
-                name: "Unknown Location".to_string(),
-                local_names: None,
-                lat: 13.7563,
-                lon: 100.5018,
-                country: "TH".to_string(),
-                state: Some("Bangkok".to_string()),
-            });
+            .collect();
         
-        Ok(vec![result])
+        Ok(results)
     }
 
-    pub async fn geocode_reverse(&self, lat: f64, lon: f64) -> Result<Vec<GeocodeResponse>, Box<dyn std::error::Error>> {
+    async fn geocode_reverse(&self, lat: f64, lon: f64) -> Result<Vec<GeocodeResponse>, OpenWeatherError> {
         // Simple reverse geocoding mock
         let name = if (lat - 13.7563).abs() < 1.0 && (lon - 100.5018).abs() < 1.0 {
             "Bangkok"
@@ -213,7 +353,19 @@ impl MockWeatherClient {
 
 // Generate realistic mock data for testing
 pub fn generate_mock_hourly_data(hours: usize, start_time: DateTime<FixedOffset>) -> Vec<HourlyData> {
-    (0..hours)
+    generate_mock_series(hours, 1, start_time)
+}
+
+/// Mock series spanning `horizon_hours`, with one point every `resolution_hours`
+pub fn generate_mock_series(
+    horizon_hours: usize,
+    resolution_hours: usize,
+    start_time: DateTime<FixedOffset>,
+) -> Vec<HourlyData> {
+    let resolution_hours = resolution_hours.max(1);
+    
+    (0..horizon_hours)
+        .step_by(resolution_hours)
         .map(|hour| {
             let time = start_time + Duration::hours(hour as i64);
             let hour_of_day = time.hour() as f64;
@@ -244,7 +396,49 @@ pub fn generate_mock_hourly_data(hours: usize, start_time: DateTime<FixedOffset>
                 cloud,
                 rain_p,
                 rain_mm,
+                wind_gust_ms: None,
+                source: DataSource::OneCall,
+                wind_deg: None,
+                uv_index: None,
+                temp_range: None,
             }
         })
         .collect()
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_generate_mock_series_resolution() {
+        let start = Utc::now().with_timezone(&FixedOffset::east_opt(7 * 3600).unwrap());
+
+        let hourly = generate_mock_hourly_data(48, start);
+        assert_eq!(hourly.len(), 48);
+
+        let three_hourly = generate_mock_series(7 * 24, 3, start);
+        assert_eq!(three_hourly.len(), 56);
+        assert_eq!(three_hourly[1].ts - three_hourly[0].ts, Duration::hours(3));
+        assert_eq!(three_hourly.last().unwrap().ts, start + Duration::hours(165));
+    }
+
+    #[tokio::test]
+    async fn test_scenarios_are_deterministic() {
+        let client = MockWeatherClient::new().with_scenario(MockScenario::Marginal);
+        let (first, second) = (
+            client.get_onecall(13.75, 100.5).await.unwrap(),
+            client.get_onecall(13.75, 100.5).await.unwrap(),
+        );
+        for (a, b) in first.hourly.iter().zip(&second.hourly) {
+            assert_eq!((a.temp, a.humidity, a.wind_speed, a.clouds, a.pop), (b.temp, b.humidity, b.wind_speed, b.clouds, b.pop));
+        }
+
+        let rainy = MockWeatherClient::new().with_scenario(MockScenario::Rainy);
+        let forecast3h = rainy.get_forecast3h(13.75, 100.5).await.unwrap();
+        assert!(forecast3h.list.iter().all(|item| item.weather[0].main == "Rain"));
+        let perfect = MockWeatherClient::new().with_scenario(MockScenario::Perfect);
+        let onecall = perfect.get_onecall(13.75, 100.5).await.unwrap();
+        assert!(onecall.hourly.iter().all(|hour| hour.rain.is_none() && hour.pop == 0.0));
+    }
+}
@@ -3,16 +3,38 @@ pub mod mock;
 pub mod types;
 pub mod merge;
 
+use async_trait::async_trait;
 use moka::future::Cache;
+use openweather::OpenWeatherError;
 use std::time::Duration;
 use types::*;
 
+/// Source of raw forecast and geocoding data. Implemented by the live
+/// OpenWeather client and by the mock used in tests and local development.
+#[async_trait]
+pub trait WeatherProvider: Send + Sync {
+    /// Short name reported to clients as the source of their forecast
+    fn name(&self) -> &'static str {
+        "unknown"
+    }
+
+    async fn get_onecall(&self, lat: f64, lon: f64) -> Result<OneCallResponse, OpenWeatherError>;
+
+    async fn get_forecast3h(&self, lat: f64, lon: f64) -> Result<Forecast3hResponse, OpenWeatherError>;
+
+    async fn geocode_direct(&self, query: &str) -> Result<Vec<GeocodeResponse>, OpenWeatherError>;
+
+    async fn geocode_reverse(&self, lat: f64, lon: f64) -> Result<Vec<GeocodeResponse>, OpenWeatherError>;
+}
+
 pub type ForecastCache = Cache<String, CachedForecastData>;
 
-pub fn init_cache() -> ForecastCache {
+/// Forecasts kept for `ttl`; a zero TTL still lets concurrent requests for
+/// one location share a fetch
+pub fn init_cache(ttl: Duration) -> ForecastCache {
     Cache::builder()
         .max_capacity(1000)
-        .time_to_live(Duration::from_secs(30 * 60)) // 30 minutes
+        .time_to_live(ttl)
         .build()
 }
 
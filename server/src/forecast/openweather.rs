@@ -1,4 +1,6 @@
 use super::types::*;
+use super::WeatherProvider;
+use async_trait::async_trait;
 use crate::config::Config;
 use chrono::Timelike;
 use reqwest::Client;
@@ -19,6 +21,8 @@ pub enum OpenWeatherError {
     ApiError(String),
     #[error("Invalid coordinates")]
     InvalidCoordinates,
+    #[error("hour_of_day must be between 0 and 23 (got {0})")]
+    InvalidHour(i32),
 }
 
 pub struct OpenWeatherClient {
@@ -30,14 +34,99 @@ impl OpenWeatherClient {
     pub fn new(config: Config) -> Self {
         let client = Client::builder()
             .user_agent("LaundryDayOptimizer/1.0")
-            .timeout(Duration::from_secs(30))
+            .timeout(Duration::from_secs(config.weather_timeout_secs))
             .build()
             .expect("Failed to create HTTP client");
 
         Self { client, config }
     }
 
-    pub async fn get_onecall(
+    async fn make_request_with_retry(
+        &self,
+        url: &str,
+        params: &[(&str, &str)],
+    ) -> Result<Value, OpenWeatherError> {
+        let mut retry_count = 0;
+        let max_retries = 3;
+        let mut delay = Duration::from_millis(1000);
+
+        loop {
+            let response = self.client.get(url).query(params).send().await?;
+
+            // Errors sometimes arrive as a 200 with an error-shaped body
+            let (status, error_text) = match response.status() {
+                reqwest::StatusCode::OK => {
+                    let json: Value = response.json().await?;
+                    match body_error(&json) {
+                        None => return Ok(json),
+                        Some((code, message)) => {
+                            let status = reqwest::StatusCode::from_u16(code)
+                                .unwrap_or(reqwest::StatusCode::BAD_GATEWAY);
+                            (status, message)
+                        }
+                    }
+                }
+                status => (status, response.text().await.unwrap_or_default()),
+            };
+
+            match status {
+                reqwest::StatusCode::TOO_MANY_REQUESTS => {
+                    if retry_count >= max_retries {
+                        return Err(OpenWeatherError::RateLimited(delay.as_secs()));
+                    }
+
+                    tracing::warn!(
+                        "Rate limited by OpenWeather API, retrying in {}ms",
+                        delay.as_millis()
+                    );
+
+                    sleep(delay).await;
+                    delay = delay.mul_f32(2.0 + fastrand::f32() * 0.5); // Exponential backoff with jitter
+                    retry_count += 1;
+                }
+                status => {
+                    return Err(OpenWeatherError::ApiError(format!(
+                        "HTTP {}: {}",
+                        status, error_text
+                    )));
+                }
+            }
+        }
+    }
+
+    fn is_valid_coordinates(&self, lat: f64, lon: f64) -> bool {
+        (-90.0..=90.0).contains(&lat) && (-180.0..=180.0).contains(&lon)
+    }
+}
+
+/// Error code and message from a body like `{"cod":"429","message":"..."}`.
+/// `cod` may be a string or a number, and successful 5-day forecasts carry
+/// `"cod":"200"`, so only non-200 codes count.
+fn body_error(body: &Value) -> Option<(u16, String)> {
+    let code = match body.get("cod")? {
+        Value::String(code) => code.parse().ok()?,
+        Value::Number(code) => u16::try_from(code.as_u64()?).ok()?,
+        _ => return None,
+    };
+    if code == 200 {
+        return None;
+    }
+
+    let message = body
+        .get("message")
+        .and_then(Value::as_str)
+        .unwrap_or("unknown error")
+        .to_string();
+    Some((code, message))
+}
+
+#[async_trait]
+impl WeatherProvider for OpenWeatherClient {
+    fn name(&self) -> &'static str {
+        "openweather"
+    }
+
+    async fn get_onecall(
         &self,
         lat: f64,
         lon: f64,
@@ -65,7 +154,7 @@ impl OpenWeatherClient {
         Ok(onecall)
     }
 
-    pub async fn get_forecast3h(
+    async fn get_forecast3h(
         &self,
         lat: f64,
         lon: f64,
@@ -92,7 +181,7 @@ impl OpenWeatherClient {
         Ok(forecast)
     }
 
-    pub async fn geocode_direct(&self, query: &str) -> Result<Vec<GeocodeResponse>, OpenWeatherError> {
+    async fn geocode_direct(&self, query: &str) -> Result<Vec<GeocodeResponse>, OpenWeatherError> {
         let url = format!(
             "{}{}",
             self.config.openweather_base_url, self.config.openweather_geocode_direct_path
@@ -110,7 +199,7 @@ impl OpenWeatherClient {
         Ok(geocode)
     }
 
-    pub async fn geocode_reverse(
+    async fn geocode_reverse(
         &self,
         lat: f64,
         lon: f64,
@@ -136,63 +225,12 @@ impl OpenWeatherClient {
         let geocode: Vec<GeocodeResponse> = serde_json::from_value(response)?;
         Ok(geocode)
     }
-
-    async fn make_request_with_retry(
-        &self,
-        url: &str,
-        params: &[(&str, &str)],
-    ) -> Result<Value, OpenWeatherError> {
-        let mut retry_count = 0;
-        let max_retries = 3;
-        let mut delay = Duration::from_millis(1000);
-
-        loop {
-            let response = self.client.get(url).query(params).send().await?;
-
-            match response.status() {
-                reqwest::StatusCode::OK => {
-                    let json: Value = response.json().await?;
-                    return Ok(json);
-                }
-                reqwest::StatusCode::TOO_MANY_REQUESTS => {
-                    if retry_count >= max_retries {
-                        return Err(OpenWeatherError::RateLimited(delay.as_secs()));
-                    }
-
-                    tracing::warn!(
-                        "Rate limited by OpenWeather API, retrying in {}ms",
-                        delay.as_millis()
-                    );
-
-                    sleep(delay).await;
-                    delay = delay.mul_f32(2.0 + fastrand::f32() * 0.5); // Exponential backoff with jitter
-                    retry_count += 1;
-                }
-                status => {
-                    let error_text = response.text().await.unwrap_or_default();
-                    return Err(OpenWeatherError::ApiError(format!(
-                        "HTTP {}: {}",
-                        status, error_text
-                    )));
-                }
-            }
-        }
-    }
-
-    fn is_valid_coordinates(&self, lat: f64, lon: f64) -> bool {
-        lat >= -90.0 && lat <= 90.0 && lon >= -180.0 && lon <= 180.0
-    }
 }
 
 // Convert OpenWeather data to internal format
 impl From<&OneCallHourly> for HourlyData {
     fn from(hourly: &OneCallHourly) -> Self {
-        let rain_mm = hourly
-            .rain
-            .as_ref()
-            .and_then(|r| r.get("1h"))
-            .copied()
-            .unwrap_or(0.0);
+        let rain_mm = hourly.rain.as_ref().map_or(0.0, |r| r.mm("1h"));
 
         // Convert UTC timestamp to fixed offset
         let dt = chrono::DateTime::from_timestamp(hourly.dt, 0)
@@ -207,59 +245,83 @@ impl From<&OneCallHourly> for HourlyData {
             cloud: hourly.clouds / 100.0, // Convert percentage to 0-1
             rain_p: hourly.pop,
             rain_mm,
+            wind_gust_ms: hourly.wind_gust,
+            wind_deg: hourly.wind_deg,
+            uv_index: hourly.uvi,
+            temp_range: None,
+            source: DataSource::OneCall,
         }
     }
 }
 
 impl HourlyData {
     pub fn from_forecast3h_item(item: &Forecast3hItem, timezone_offset: i32) -> Vec<Self> {
-        let rain_mm = item
-            .rain
-            .as_ref()
-            .and_then(|r| r.get("3h"))
-            .copied()
-            .unwrap_or(0.0);
+        Self::from_forecast3h_interpolated(item, None, timezone_offset)
+    }
+
+    /// The three hours of `item`, with temperature, humidity and wind ramping
+    /// linearly towards `next` (the following 3-hour point) when given, and
+    /// held flat otherwise. Everything else is replicated as-is.
+    pub fn from_forecast3h_interpolated(
+        item: &Forecast3hItem,
+        next: Option<&Forecast3hItem>,
+        timezone_offset: i32,
+    ) -> Vec<Self> {
+        let next = next.unwrap_or(item);
+        let lerp = |from: f64, to: f64, hour_offset: i64| from + (to - from) * hour_offset as f64 / 3.0;
+        let rain_mm = item.rain.as_ref().map_or(0.0, |r| r.mm("3h"));
 
         let base_dt = chrono::DateTime::from_timestamp(item.dt, 0)
             .unwrap_or_default()
             .with_timezone(&chrono::FixedOffset::east_opt(timezone_offset).unwrap_or(
-                chrono::FixedOffset::east_opt(7 * 3600).unwrap()
+                chrono::FixedOffset::east_opt(0).unwrap()
             ));
 
-        // Replicate 3-hour data to 3 individual hours
+        // Spread 3-hour data over 3 individual hours
         (0..3)
             .map(|hour_offset| Self {
                 ts: base_dt + chrono::Duration::hours(hour_offset),
-                temp_c: item.main.temp,
-                rh: item.main.humidity,
-                wind_ms: item.wind.speed,
+                temp_c: lerp(item.main.temp, next.main.temp, hour_offset),
+                rh: lerp(item.main.humidity, next.main.humidity, hour_offset),
+                wind_ms: lerp(item.wind.speed, next.wind.speed, hour_offset),
                 cloud: item.clouds.all / 100.0,
                 rain_p: item.pop,
                 rain_mm: rain_mm / 3.0, // Distribute 3h rain over 3 hours
+                wind_gust_ms: item.wind.gust,
+                wind_deg: Some(item.wind.deg),
+                uv_index: None,
+                temp_range: None,
+                source: DataSource::Forecast3h,
             })
             .collect()
     }
 
+    /// One synthesized hour of `daily` at local `hour_of_day`, which must be
+    /// 0-23 rather than silently collapsing to a default timestamp.
     pub fn from_daily_synthesized(
         daily: &OneCallDaily,
         timezone_offset: i32,
         hour_of_day: i32,
-    ) -> Self {
+    ) -> Result<Self, OpenWeatherError> {
+        let hour = u32::try_from(hour_of_day)
+            .ok()
+            .filter(|hour| *hour < 24)
+            .ok_or(OpenWeatherError::InvalidHour(hour_of_day))?;
         let base_dt = chrono::DateTime::from_timestamp(daily.dt, 0)
             .unwrap_or_default()
             .with_timezone(&chrono::FixedOffset::east_opt(timezone_offset).unwrap_or(
-                chrono::FixedOffset::east_opt(7 * 3600).unwrap()
+                chrono::FixedOffset::east_opt(0).unwrap()
             ))
-            .with_hour(hour_of_day as u32)
-            .unwrap_or_default();
+            .with_hour(hour)
+            .ok_or(OpenWeatherError::InvalidHour(hour_of_day))?;
 
         // Apply diurnal adjustments
-        let is_daylight = hour_of_day >= 6 && hour_of_day < 18;
+        let is_daylight = (6..18).contains(&hour_of_day);
         let temp_adjustment = if is_daylight { 1.0 } else { -1.0 };
         let rh_adjustment = if is_daylight { -5.0 } else { 5.0 };
         let cloud_adjustment = if is_daylight { -0.1 } else { 0.1 };
 
-        Self {
+        Ok(Self {
             ts: base_dt,
             temp_c: daily.temp.day + temp_adjustment,
             rh: (daily.humidity + rh_adjustment).clamp(0.0, 100.0),
@@ -267,6 +329,116 @@ impl HourlyData {
             cloud: (daily.clouds / 100.0 + cloud_adjustment).clamp(0.0, 1.0),
             rain_p: daily.pop / 8.0, // Distribute daily pop over 8 bins
             rain_mm: daily.rain.unwrap_or(0.0) / 8.0, // Distribute daily rain over 8 bins
+            wind_gust_ms: None,
+            wind_deg: daily.wind_deg,
+            uv_index: None,
+            // The day's spread is the honest uncertainty for any one hour
+            temp_range: Some(TempRange {
+                min_c: daily.temp.min,
+                max_c: daily.temp.max,
+            }),
+            source: DataSource::DailySynthesized,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_body_error_detection() {
+        assert_eq!(
+            body_error(&json!({ "cod": "429", "message": "Your account is temporary blocked" })),
+            Some((429, "Your account is temporary blocked".to_string()))
+        );
+        assert_eq!(body_error(&json!({ "cod": 401, "message": "Invalid API key" })).unwrap().0, 401);
+        assert_eq!(body_error(&json!({ "cod": "200", "message": 0, "list": [] })), None);
+        assert_eq!(body_error(&json!({ "lat": 13.75, "hourly": [] })), None);
+        assert_eq!(body_error(&json!([{ "name": "Bangkok" }])), None);
+    }
+
+    #[test]
+    fn test_daily_synthesized_rejects_out_of_range_hour() {
+        let daily: OneCallDaily = serde_json::from_value(json!({
+            "dt": 1_700_000_000,
+            "temp": { "day": 30.0, "min": 25.0, "max": 34.0, "night": 26.0, "eve": 29.0, "morn": 27.0 },
+            "humidity": 60.0,
+            "wind_speed": 3.0,
+            "clouds": 40.0,
+            "pop": 0.2,
+            "rain": null
+        }))
+        .unwrap();
+
+        let hour = HourlyData::from_daily_synthesized(&daily, 7 * 3600, 14).unwrap();
+        assert_eq!(hour.ts.hour(), 14);
+        assert!(hour.ts.timestamp() > 0);
+
+        assert!(matches!(
+            HourlyData::from_daily_synthesized(&daily, 7 * 3600, 25),
+            Err(OpenWeatherError::InvalidHour(25))
+        ));
+        assert!(HourlyData::from_daily_synthesized(&daily, 7 * 3600, -1).is_err());
+    }
+
+    #[test]
+    fn test_forecast3h_interpolation_ramps_between_points() {
+        let item = |dt: i64, temp: f64, humidity: f64, speed: f64| -> Forecast3hItem {
+            serde_json::from_value(json!({
+                "dt": dt,
+                "main": {
+                    "temp": temp, "feels_like": temp, "temp_min": temp, "temp_max": temp,
+                    "pressure": 1010.0, "humidity": humidity
+                },
+                "weather": [],
+                "clouds": { "all": 40.0 },
+                "wind": { "speed": speed, "deg": 180.0 },
+                "pop": 0.1,
+                "rain": { "3h": 0.6 },
+                "sys": { "pod": "d" },
+                "dt_txt": ""
+            }))
+            .unwrap()
+        };
+        let first = item(1_700_000_000, 24.0, 80.0, 1.0);
+        let second = item(1_700_000_000 + 3 * 3600, 30.0, 62.0, 4.0);
+
+        let ramp = HourlyData::from_forecast3h_interpolated(&first, Some(&second), 0);
+        let temps: Vec<f64> = ramp.iter().map(|h| h.temp_c).collect();
+        assert_eq!(temps, vec![24.0, 26.0, 28.0]);
+        assert!(ramp.windows(2).all(|pair| pair[0].rh > pair[1].rh && pair[0].wind_ms < pair[1].wind_ms));
+        // Rain is still spread evenly
+        assert!(ramp.iter().all(|h| (h.rain_mm - 0.2).abs() < 1e-9));
+
+        let flat = HourlyData::from_forecast3h_item(&first, 0);
+        assert!(flat.iter().all(|h| h.temp_c == 24.0 && h.rh == 80.0));
+    }
+
+    #[tokio::test]
+    async fn test_error_body_with_http_200_maps_to_api_error() {
+        let app = axum::Router::new().route(
+            "/data/3.0/onecall",
+            axum::routing::get(|| async {
+                axum::Json(json!({ "cod": 401, "message": "Invalid API key. Please see https://openweathermap.org/faq#error401" }))
+            }),
+        );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+        let client = OpenWeatherClient::new(Config {
+            openweather_base_url: format!("http://{}", addr),
+            ..Config::for_tests()
+        });
+
+        let err = client.get_onecall(13.75, 100.5).await.unwrap_err();
+        match err {
+            OpenWeatherError::ApiError(message) => {
+                assert!(message.starts_with("HTTP 401 Unauthorized: Invalid API key"), "{}", message)
+            }
+            other => panic!("expected an API error, got {:?}", other),
         }
     }
-}
\ No newline at end of file
+}
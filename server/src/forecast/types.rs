@@ -10,6 +10,116 @@ pub struct HourlyData {
     pub cloud: f64,
     pub rain_p: f64,
     pub rain_mm: f64,
+    #[serde(default)]
+    pub wind_gust_ms: Option<f64>,
+    #[serde(default)]
+    pub wind_deg: Option<f64>,
+    #[serde(default)]
+    pub uv_index: Option<f64>,
+    /// Plausible temperature band for hours synthesized from a daily summary
+    #[serde(default)]
+    pub temp_range: Option<TempRange>,
+    #[serde(default)]
+    pub source: DataSource,
+}
+
+/// How the three hours covered by one 3-hourly forecast point are filled in
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Forecast3hMode {
+    /// Repeat the reading for all three hours
+    #[default]
+    Flat,
+    /// Ramp temperature, humidity and wind linearly towards the next point
+    Interpolate,
+}
+
+impl Forecast3hMode {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "flat" => Some(Forecast3hMode::Flat),
+            "interpolate" => Some(Forecast3hMode::Interpolate),
+            _ => None,
+        }
+    }
+}
+
+/// Lowest and highest temperature an hour or window could plausibly see
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct TempRange {
+    pub min_c: f64,
+    pub max_c: f64,
+}
+
+impl TempRange {
+    /// The smallest band covering both
+    pub fn union(self, other: TempRange) -> TempRange {
+        TempRange {
+            min_c: self.min_c.min(other.min_c),
+            max_c: self.max_c.max(other.max_c),
+        }
+    }
+}
+
+/// Where a merged hour came from
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DataSource {
+    OneCall,
+    Forecast3h,
+    DailySynthesized,
+    #[default]
+    Fallback,
+}
+
+impl DataSource {
+    /// Hours that weren't forecast directly at hourly/3-hourly resolution
+    pub fn is_synthetic(&self) -> bool {
+        matches!(self, DataSource::DailySynthesized | DataSource::Fallback)
+    }
+}
+
+impl HourlyData {
+    /// Rough daylight flag from the local hour (06:00–17:59)
+    pub fn is_daylight(&self) -> bool {
+        use chrono::Timelike;
+        (6..18).contains(&self.ts.hour())
+    }
+}
+
+impl From<&HourlyData> for crate::scoring::WeatherFeatures {
+    fn from(hour: &HourlyData) -> Self {
+        Self {
+            temp_c: hour.temp_c,
+            rh: hour.rh,
+            wind_ms: hour.wind_ms,
+            cloud: hour.cloud,
+            rain_p: hour.rain_p,
+            rain_mm: hour.rain_mm,
+            gust_ms: hour.wind_gust_ms,
+            is_daylight: Some(hour.is_daylight()),
+            wind_deg: hour.wind_deg,
+        }
+    }
+}
+
+/// Rain or snow volume. OpenWeather sends either a bare number of mm or an
+/// object keyed by period, e.g. `{"1h": 2.0}` or `{"3h": 0.6}`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum Precipitation {
+    Amount(f64),
+    ByPeriod(HashMap<String, f64>),
+}
+
+impl Precipitation {
+    /// Millimetres over `period`; a bare number is taken to cover it
+    pub fn mm(&self, period: &str) -> f64 {
+        match self {
+            Precipitation::Amount(mm) => *mm,
+            Precipitation::ByPeriod(periods) => periods.get(period).copied().unwrap_or(0.0),
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -28,9 +138,15 @@ pub struct OneCallHourly {
     pub temp: f64,
     pub humidity: f64,
     pub wind_speed: f64,
+    #[serde(default)]
+    pub wind_deg: Option<f64>,
+    #[serde(default)]
+    pub wind_gust: Option<f64>,
     pub clouds: f64,
     pub pop: f64,
-    pub rain: Option<HashMap<String, f64>>,
+    pub rain: Option<Precipitation>,
+    #[serde(default)]
+    pub uvi: Option<f64>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -39,6 +155,8 @@ pub struct OneCallDaily {
     pub temp: OneCallDailyTemp,
     pub humidity: f64,
     pub wind_speed: f64,
+    #[serde(default)]
+    pub wind_deg: Option<f64>,
     pub clouds: f64,
     pub pop: f64,
     pub rain: Option<f64>,
@@ -72,8 +190,8 @@ pub struct Forecast3hItem {
     pub wind: Forecast3hWind,
     pub visibility: Option<i32>,
     pub pop: f64,
-    pub rain: Option<HashMap<String, f64>>,
-    pub snow: Option<HashMap<String, f64>>,
+    pub rain: Option<Precipitation>,
+    pub snow: Option<Precipitation>,
     pub sys: Forecast3hSys,
     pub dt_txt: String,
 }
@@ -142,4 +260,59 @@ pub struct GeocodeResponse {
     pub lon: f64,
     pub country: String,
     pub state: Option<String>,
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_precipitation_accepts_number_or_object() {
+        let by_period: Precipitation = serde_json::from_str(r#"{"1h": 2.5}"#).unwrap();
+        assert_eq!(by_period.mm("1h"), 2.5);
+        assert_eq!(by_period.mm("3h"), 0.0);
+
+        let amount: Precipitation = serde_json::from_str("1.2").unwrap();
+        assert_eq!(amount, Precipitation::Amount(1.2));
+        assert_eq!(amount.mm("3h"), 1.2);
+
+        let hourly: OneCallHourly = serde_json::from_str(
+            r#"{"dt": 0, "temp": 30, "humidity": 60, "wind_speed": 3, "clouds": 40, "pop": 0.5, "rain": 0.8}"#,
+        )
+        .unwrap();
+        assert_eq!(hourly.rain.unwrap().mm("1h"), 0.8);
+    }
+
+    #[test]
+    fn test_hourly_data_converts_to_weather_features() {
+        let noon = chrono::DateTime::parse_from_rfc3339("2024-06-01T12:00:00+07:00").unwrap();
+        let hour = HourlyData {
+            ts: noon,
+            temp_c: 29.0,
+            rh: 62.0,
+            wind_ms: 3.5,
+            cloud: 0.4,
+            rain_p: 0.35,
+            rain_mm: 0.1,
+            wind_gust_ms: Some(7.0),
+            source: DataSource::OneCall,
+            wind_deg: Some(200.0),
+            uv_index: Some(8.0),
+            temp_range: None,
+        };
+
+        let features = crate::scoring::WeatherFeatures::from(&hour);
+
+        assert_eq!((features.temp_c, features.rh, features.wind_ms), (29.0, 62.0, 3.5));
+        assert_eq!((features.cloud, features.rain_p, features.rain_mm), (0.4, 0.35, 0.1));
+        assert_eq!(features.gust_ms, Some(7.0));
+        assert_eq!(features.wind_deg, Some(200.0));
+        assert_eq!(features.is_daylight, Some(true));
+
+        let midnight = HourlyData {
+            ts: noon - chrono::Duration::hours(12),
+            ..hour
+        };
+        assert_eq!(crate::scoring::WeatherFeatures::from(&midnight).is_daylight, Some(false));
+    }
+}
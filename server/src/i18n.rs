@@ -0,0 +1,209 @@
+use crate::scoring::Verdict;
+use serde::{Deserialize, Serialize};
+
+/// Languages the fixed labels in responses can be shown in
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Language {
+    #[default]
+    En,
+    Th,
+    Id,
+}
+
+impl Language {
+    /// Match a language tag such as `th` or `th-TH`; `None` if unsupported
+    pub fn parse(tag: &str) -> Option<Self> {
+        let primary = tag.trim().split(['-', '_']).next()?.to_ascii_lowercase();
+        match primary.as_str() {
+            "en" => Some(Language::En),
+            "th" => Some(Language::Th),
+            "id" => Some(Language::Id),
+            _ => None,
+        }
+    }
+
+    /// Pick from an explicit `lang` parameter, then the `Accept-Language`
+    /// header (highest `q` first), then English.
+    pub fn negotiate(lang: Option<&str>, accept_language: Option<&str>) -> Self {
+        if let Some(language) = lang.and_then(Language::parse) {
+            return language;
+        }
+
+        let mut ranges: Vec<(&str, f64)> = accept_language
+            .unwrap_or_default()
+            .split(',')
+            .filter_map(|range| {
+                let mut parts = range.split(';');
+                let tag = parts.next()?.trim();
+                let q = parts
+                    .find_map(|p| p.trim().strip_prefix("q="))
+                    .map_or(Some(1.0), |q| q.trim().parse().ok())?;
+                Some((tag, q))
+            })
+            .filter(|(_, q)| *q > 0.0)
+            .collect();
+        // Stable, so equal weights keep the client's order
+        ranges.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap());
+
+        ranges
+            .into_iter()
+            .find_map(|(tag, _)| Language::parse(tag))
+            .unwrap_or_default()
+    }
+
+    pub fn code(self) -> &'static str {
+        match self {
+            Language::En => "en",
+            Language::Th => "th",
+            Language::Id => "id",
+        }
+    }
+
+    fn labels(self) -> &'static dyn Labels {
+        match self {
+            Language::En => &English,
+            Language::Th => &Thai,
+            Language::Id => &Indonesian,
+        }
+    }
+
+    /// Label for a sky condition, in English if this language lacks one
+    pub fn condition(self, condition: Condition) -> &'static str {
+        self.labels()
+            .condition(condition)
+            .unwrap_or_else(|| english_condition(condition))
+    }
+
+    /// Label for a drying verdict, in English if this language lacks one
+    pub fn verdict(self, verdict: Verdict) -> &'static str {
+        self.labels()
+            .verdict(verdict)
+            .unwrap_or_else(|| english_verdict(verdict))
+    }
+}
+
+/// Sky summary shown for a window
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Condition {
+    Sunny,
+    PartlyCloudy,
+    Cloudy,
+    Rainy,
+}
+
+impl Condition {
+    pub fn from_weather(cloud: f64, rain_mm: f64) -> Self {
+        if rain_mm > 0.1 {
+            Condition::Rainy
+        } else if cloud > 80.0 {
+            Condition::Cloudy
+        } else if cloud < 30.0 {
+            Condition::Sunny
+        } else {
+            Condition::PartlyCloudy
+        }
+    }
+}
+
+/// A translation table. Returning `None` falls back to English, so a table
+/// only needs the labels it has been translated for.
+pub trait Labels: Sync {
+    fn condition(&self, condition: Condition) -> Option<&'static str>;
+    fn verdict(&self, verdict: Verdict) -> Option<&'static str>;
+}
+
+fn english_condition(condition: Condition) -> &'static str {
+    match condition {
+        Condition::Sunny => "Sunny",
+        Condition::PartlyCloudy => "Partly Cloudy",
+        Condition::Cloudy => "Cloudy",
+        Condition::Rainy => "Rainy",
+    }
+}
+
+fn english_verdict(verdict: Verdict) -> &'static str {
+    match verdict {
+        Verdict::Excellent => "Excellent drying conditions!",
+        Verdict::Good => "Good drying conditions",
+        Verdict::Fair => "Fair drying conditions",
+        Verdict::Poor => "Poor drying conditions",
+    }
+}
+
+struct English;
+
+impl Labels for English {
+    fn condition(&self, condition: Condition) -> Option<&'static str> {
+        Some(english_condition(condition))
+    }
+
+    fn verdict(&self, verdict: Verdict) -> Option<&'static str> {
+        Some(english_verdict(verdict))
+    }
+}
+
+struct Thai;
+
+impl Labels for Thai {
+    fn condition(&self, condition: Condition) -> Option<&'static str> {
+        Some(match condition {
+            Condition::Sunny => "แดดออก",
+            Condition::PartlyCloudy => "มีเมฆบางส่วน",
+            Condition::Cloudy => "มีเมฆมาก",
+            Condition::Rainy => "ฝนตก",
+        })
+    }
+
+    fn verdict(&self, verdict: Verdict) -> Option<&'static str> {
+        Some(match verdict {
+            Verdict::Excellent => "สภาพอากาศเหมาะมากสำหรับตากผ้า!",
+            Verdict::Good => "สภาพอากาศดีสำหรับตากผ้า",
+            Verdict::Fair => "สภาพอากาศพอใช้สำหรับตากผ้า",
+            Verdict::Poor => "สภาพอากาศไม่เหมาะสำหรับตากผ้า",
+        })
+    }
+}
+
+struct Indonesian;
+
+impl Labels for Indonesian {
+    fn condition(&self, condition: Condition) -> Option<&'static str> {
+        Some(match condition {
+            Condition::Sunny => "Cerah",
+            Condition::PartlyCloudy => "Cerah berawan",
+            Condition::Cloudy => "Berawan",
+            Condition::Rainy => "Hujan",
+        })
+    }
+
+    fn verdict(&self, verdict: Verdict) -> Option<&'static str> {
+        Some(match verdict {
+            Verdict::Excellent => "Kondisi menjemur sangat baik!",
+            Verdict::Good => "Kondisi menjemur baik",
+            Verdict::Fair => "Kondisi menjemur cukup",
+            Verdict::Poor => "Kondisi menjemur buruk",
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_negotiate_prefers_lang_then_accept_language() {
+        assert_eq!(Language::negotiate(Some("th"), Some("id")), Language::Th);
+        assert_eq!(Language::negotiate(Some("fr"), Some("th-TH,en;q=0.8")), Language::Th);
+        assert_eq!(Language::negotiate(None, Some("fr;q=1.0, id;q=0.5, th;q=0.9")), Language::Th);
+        assert_eq!(Language::negotiate(None, Some("fr, de")), Language::En);
+        assert_eq!(Language::negotiate(None, None), Language::En);
+    }
+
+    #[test]
+    fn test_labels_are_localized() {
+        assert_eq!(Language::Th.condition(Condition::Rainy), "ฝนตก");
+        assert_eq!(Language::En.verdict(Verdict::Good), "Good drying conditions");
+        assert_eq!(Language::Id.condition(Condition::Sunny), "Cerah");
+    }
+}
@@ -0,0 +1,17 @@
+pub mod ai;
+pub mod backtest;
+pub mod cli;
+pub mod config;
+pub mod database;
+pub mod error;
+pub mod forecast;
+pub mod i18n;
+pub mod middleware;
+pub mod nearby;
+pub mod notifications;
+pub mod routes;
+pub mod scoring;
+pub mod solar;
+pub mod tips;
+pub mod utils;
+pub mod warnings;
@@ -1,69 +1,169 @@
-use axum::Router;
+use clap::{Parser, Subcommand};
 use tower_http::cors::CorsLayer;
-use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
+use tracing_subscriber::{fmt::writer::BoxMakeWriter, layer::SubscriberExt, util::SubscriberInitExt};
 use std::sync::Arc;
-use sqlx::SqlitePool;
+use std::time::Duration;
 
-mod ai;
-mod config;
-mod database;
-mod forecast;
-mod routes;
-mod scoring;
+use laundry_optimizer_server::{
+    ai::{AiClient, AiProvider, CachedAiClient},
+    cli::{check_config, score_location, train_weights},
+    config::Config,
+    database::{spawn_retention_cleanup, Database},
+    forecast::openweather::OpenWeatherClient,
+    routes::{create_router, AppState},
+};
+
+#[derive(Parser)]
+#[command(name = "laundry-optimizer", version, about = "Laundry drying window optimizer")]
+struct Cli {
+    /// Runs the HTTP server when omitted
+    #[command(subcommand)]
+    command: Option<Command>,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Print the best drying window for a location as JSON and exit
+    Score {
+        #[arg(long, allow_hyphen_values = true)]
+        lat: f64,
+        #[arg(long, allow_hyphen_values = true)]
+        lon: f64,
+        /// Window length in hours (1-12, default 3)
+        #[arg(long)]
+        window_hours: Option<u32>,
+    },
+    /// Validate the environment and print a pass/fail report; exits non-zero on failure
+    CheckConfig {
+        /// Also make one call to the weather and AI providers
+        #[arg(long)]
+        ping: bool,
+    },
+    /// Fit global weights to all stored feedback and print them as JSON; meant for a nightly cron job
+    TrainWeights {
+        /// Averaged minibatch steps over the whole feedback set
+        #[arg(long, default_value_t = 50)]
+        epochs: usize,
+    },
+}
 
-use ai::AiClient;
-use config::Config;
-use database::Database;
-use forecast::openweather::OpenWeatherClient;
-use routes::{create_router, AppState};
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let cli = Cli::parse();
+
     // Load environment variables from .env file
     dotenv::dotenv().ok();
 
+    // Subcommands print JSON on stdout, so their logs go to stderr
+    let writer = match cli.command {
+        Some(_) => BoxMakeWriter::new(std::io::stderr),
+        None => BoxMakeWriter::new(std::io::stdout),
+    };
     tracing_subscriber::registry()
         .with(
             tracing_subscriber::EnvFilter::try_from_default_env()
                 .unwrap_or_else(|_| "laundry_optimizer_server=debug,tower_http=debug".into()),
         )
-        .with(tracing_subscriber::fmt::layer())
+        .with(tracing_subscriber::fmt::layer().with_writer(writer))
         .init();
 
-    // Load configuration
-    let config = Config::from_env()?;
-    
-    // Initialize database
+    match cli.command {
+        Some(Command::CheckConfig { ping }) => {
+            let report = check_config(|name| std::env::var(name).ok(), ping).await;
+            println!("{}", report);
+            std::process::exit(report.exit_code());
+        }
+        Some(Command::Score { lat, lon, window_hours }) => {
+            let config = Config::from_env()?;
+            let weather_client = OpenWeatherClient::new(config.clone());
+            let report = score_location(&config, &weather_client, lat, lon, window_hours)
+                .await
+                .map_err(|e| e.message)?;
+            println!("{}", serde_json::to_string_pretty(&report)?);
+            Ok(())
+        }
+        Some(Command::TrainWeights { epochs }) => {
+            let database = open_database().await?;
+            let report = train_weights(&database, epochs).await?;
+            println!("{}", serde_json::to_string_pretty(&report)?);
+            Ok(())
+        }
+        None => serve(Config::from_env()?).await,
+    }
+}
+
+/// Connect to `DATABASE_URL` and make sure the tables exist
+async fn open_database() -> Result<Database, Box<dyn std::error::Error>> {
     let database_url = std::env::var("DATABASE_URL")
         .unwrap_or_else(|_| "sqlite:./laundry_optimizer.db".to_string());
     let pool = sqlx::SqlitePool::connect(&database_url).await?;
-    let database = Arc::new(Database::new(pool));
-    
-    // Initialize database tables
+    let database = Database::new(pool);
     database.init_tables().await?;
+    Ok(database)
+}
+
+async fn serve(config: Config) -> Result<(), Box<dyn std::error::Error>> {
+    // Initialize database
+    let database = Arc::new(open_database().await?);
     
     // Initialize weather client
     let weather_client = Arc::new(OpenWeatherClient::new(config.clone()));
     
-    // Initialize AI client
-    let ai_client = Arc::new(AiClient::new(config.clone()));
+    // Initialize AI client, cached unless AI_CACHE_TTL_SECS=0
+    let ai_client: Arc<dyn AiProvider> = Arc::new(AiClient::new(config.clone()));
+    let ai_client: Arc<dyn AiProvider> = if config.ai_cache_ttl_secs > 0 {
+        Arc::new(CachedAiClient::new(ai_client, Duration::from_secs(config.ai_cache_ttl_secs)))
+    } else {
+        ai_client
+    };
+    
+    let retention = config.retention_policy();
+    if retention.feedback.is_some() || retention.served_windows.is_some() {
+        spawn_retention_cleanup(database.clone(), retention);
+    }
     
+    let drain = Duration::from_secs(config.shutdown_drain_secs);
     let config = Arc::new(config);
     
     // Create application state
-    let state = AppState {
-        config,
-        database,
-        weather_client,
-        ai_client,
-    };
+    let state = AppState::new(config, database, weather_client, ai_client);
 
-    let app = create_router(state)
+    let app = create_router(state.clone())
         .layer(CorsLayer::permissive());
 
     let listener = tokio::net::TcpListener::bind("0.0.0.0:8080").await?;
     tracing::info!("Server starting on http://0.0.0.0:8080");
     
-    axum::serve(listener, app).await?;
+    axum::serve(listener, app)
+        .with_graceful_shutdown(shutdown_signal(state, drain))
+        .await?;
     
     Ok(())
-}
\ No newline at end of file
+}
+
+/// Resolve once the server should stop accepting connections: after Ctrl-C
+/// or SIGTERM, `/readyz` fails for `drain` so load balancers move traffic
+/// away first. In-flight requests then finish before the process exits.
+async fn shutdown_signal(state: AppState, drain: Duration) {
+    let ctrl_c = async {
+        tokio::signal::ctrl_c().await.expect("Ctrl-C handler can be installed");
+    };
+    #[cfg(unix)]
+    let terminate = async {
+        tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate())
+            .expect("SIGTERM handler can be installed")
+            .recv()
+            .await;
+    };
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+
+    tokio::select! {
+        _ = ctrl_c => {}
+        _ = terminate => {}
+    }
+
+    tracing::info!("Shutting down: draining for {:?}", drain);
+    state.begin_draining();
+    tokio::time::sleep(drain).await;
+}
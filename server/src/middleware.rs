@@ -0,0 +1,205 @@
+use axum::{
+    extract::{MatchedPath, Request, State},
+    http::{header, HeaderName, HeaderValue, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use std::sync::Arc;
+use std::time::Instant;
+use tokio::sync::Semaphore;
+use uuid::Uuid;
+
+use crate::error::AppError;
+
+/// Seconds clients are asked to wait before retrying a rejected request
+const RETRY_AFTER_SECS: &str = "1";
+
+/// Global cap on requests being handled at once
+#[derive(Clone)]
+pub struct ConcurrencyLimit {
+    semaphore: Arc<Semaphore>,
+}
+
+impl ConcurrencyLimit {
+    pub fn new(max_concurrent: usize) -> Self {
+        Self {
+            semaphore: Arc::new(Semaphore::new(max_concurrent)),
+        }
+    }
+}
+
+/// Reject with `503` and `Retry-After` instead of queueing when saturated,
+/// so a spike can't fan out into unbounded upstream calls.
+pub async fn limit_concurrency(
+    State(limit): State<ConcurrencyLimit>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let Ok(_permit) = limit.semaphore.try_acquire() else {
+        tracing::warn!("Rejecting {} {}: too many concurrent requests", request.method(), request.uri().path());
+        let mut response = AppError::new(
+            StatusCode::SERVICE_UNAVAILABLE,
+            "overloaded",
+            "Too many concurrent requests, please retry shortly",
+        )
+        .into_response();
+        response
+            .headers_mut()
+            .insert(header::RETRY_AFTER, HeaderValue::from_static(RETRY_AFTER_SECS));
+        return response;
+    };
+
+    next.run(request).await
+}
+
+pub static API_KEY_HEADER: HeaderName = HeaderName::from_static("x-api-key");
+
+/// The key admin requests must present, if admin endpoints are enabled
+#[derive(Clone)]
+pub struct AdminKey(Option<Arc<str>>);
+
+impl AdminKey {
+    pub fn new(key: Option<&str>) -> Self {
+        Self(key.map(Arc::from))
+    }
+}
+
+/// Admin endpoints answer `404` unless a key is configured, and `401`
+/// unless the request's `X-API-Key` matches it
+pub async fn require_api_key(State(AdminKey(key)): State<AdminKey>, request: Request, next: Next) -> Response {
+    let Some(key) = key else {
+        return StatusCode::NOT_FOUND.into_response();
+    };
+    let presented = request.headers().get(&API_KEY_HEADER).map(HeaderValue::as_bytes);
+    if !presented.is_some_and(|presented| constant_time_eq(presented, key.as_bytes())) {
+        tracing::warn!("Rejecting {} {}: missing or wrong API key", request.method(), request.uri().path());
+        return AppError::new(StatusCode::UNAUTHORIZED, "unauthorized", "A valid X-API-Key header is required")
+            .into_response();
+    }
+
+    next.run(request).await
+}
+
+/// Compare without stopping at the first difference, so timing doesn't leak the key
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0, |diff, (x, y)| diff | (x ^ y)) == 0
+}
+
+pub static REQUEST_ID_HEADER: HeaderName = HeaderName::from_static("x-request-id");
+
+/// Query parameters whose values never reach the logs
+const SENSITIVE_PARAMS: [&str; 6] = ["appid", "api_key", "key", "token", "password", "secret"];
+
+/// Replace the values of sensitive query parameters with `REDACTED`
+pub fn redact_query(query: &str) -> String {
+    query
+        .split('&')
+        .map(|pair| match pair.split_once('=') {
+            Some((name, _)) if SENSITIVE_PARAMS.contains(&name.to_lowercase().as_str()) => {
+                format!("{}=REDACTED", name)
+            }
+            _ => pair.to_string(),
+        })
+        .collect::<Vec<_>>()
+        .join("&")
+}
+
+/// Structured access log: one info line per request with method, matched
+/// route, status and latency. Reuses the caller's `x-request-id` or assigns
+/// one, and echoes it on the response.
+pub async fn access_log(request: Request, next: Next) -> Response {
+    let started = Instant::now();
+    let request_id = request
+        .headers()
+        .get(&REQUEST_ID_HEADER)
+        .and_then(|v| v.to_str().ok())
+        .map(str::to_string)
+        .unwrap_or_else(|| Uuid::new_v4().to_string());
+    let method = request.method().clone();
+    let route = request
+        .extensions()
+        .get::<MatchedPath>()
+        .map(|path| path.as_str().to_string())
+        .unwrap_or_else(|| request.uri().path().to_string());
+    let query = request.uri().query().map(redact_query).unwrap_or_default();
+
+    let mut response = next.run(request).await;
+
+    tracing::info!(
+        request_id = %request_id,
+        method = %method,
+        route = %route,
+        query = %query,
+        status = response.status().as_u16(),
+        elapsed_ms = started.elapsed().as_millis() as u64,
+        "request completed"
+    );
+
+    if let Ok(value) = HeaderValue::from_str(&request_id) {
+        response.headers_mut().insert(REQUEST_ID_HEADER.clone(), value);
+    }
+    response
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{body::Body, routing::get, Router};
+    use std::io::Write;
+    use std::sync::Mutex;
+    use tower::ServiceExt;
+
+    /// Writer that appends every log line to a shared buffer
+    #[derive(Clone, Default)]
+    struct Captured(Arc<Mutex<Vec<u8>>>);
+
+    impl Write for Captured {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.lock().unwrap().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_redact_query() {
+        assert_eq!(redact_query("lat=1&lon=2"), "lat=1&lon=2");
+        assert_eq!(redact_query("q=Bangkok&appid=abc123&Token=x"), "q=Bangkok&appid=REDACTED&Token=REDACTED");
+    }
+
+    #[tokio::test]
+    async fn test_access_log_emits_line_for_request() {
+        let captured = Captured::default();
+        let writer = captured.clone();
+        let subscriber = tracing_subscriber::fmt()
+            .with_writer(move || writer.clone())
+            .with_ansi(false)
+            .finish();
+        let _guard = tracing::subscriber::set_default(subscriber);
+
+        let app = Router::new()
+            .route("/items/:id", get(|| async { "ok" }))
+            .layer(axum::middleware::from_fn(access_log));
+        let request = Request::builder()
+            .uri("/items/42?appid=secret-key&lat=1")
+            .header(&REQUEST_ID_HEADER, "req-123")
+            .body(Body::empty())
+            .unwrap();
+
+        let response = app.oneshot(request).await.unwrap();
+        assert_eq!(response.headers()[&REQUEST_ID_HEADER], "req-123");
+
+        let logs = String::from_utf8(captured.0.lock().unwrap().clone()).unwrap();
+        assert!(logs.contains("request completed"));
+        assert!(logs.contains("request_id=req-123"));
+        assert!(logs.contains("method=GET"));
+        assert!(logs.contains("route=/items/:id"));
+        assert!(logs.contains("status=200"));
+        assert!(logs.contains("elapsed_ms="));
+        assert!(logs.contains("appid=REDACTED"));
+        assert!(!logs.contains("secret-key"));
+    }
+}
@@ -0,0 +1,127 @@
+use crate::backtest::feedback_label;
+use crate::database::{DataStore, DatabaseError};
+use serde::{Deserialize, Serialize};
+
+/// Search radius for borrowing feedback from nearby users
+pub const NEARBY_RADIUS_KM: f64 = 10.0;
+
+/// Most recent nearby feedback records considered, however dense the area
+const NEARBY_FEEDBACK_LIMIT: i64 = 500;
+
+/// Pseudo-count given to the forecast score when blending in nearby outcomes,
+/// so a handful of reports nudges confidence rather than overriding it.
+const PRIOR_SAMPLES: f64 = 5.0;
+
+/// Aggregated outcomes from other users near a location. Only counts are
+/// exposed; individual records never leave this module.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NearbyFeedback {
+    pub samples: usize,
+    pub satisfied: usize,
+    pub satisfaction_rate: f64,
+}
+
+/// Collect labelled feedback left by users whose saved location is within
+/// `radius_km`, the latest [`NEARBY_FEEDBACK_LIMIT`] records at most. Returns
+/// `None` when there is nothing to learn from.
+pub async fn nearby_feedback(
+    store: &dyn DataStore,
+    lat: f64,
+    lon: f64,
+    radius_km: f64,
+) -> Result<Option<NearbyFeedback>, DatabaseError> {
+    let labels: Vec<bool> = store
+        .find_feedback_near(lat, lon, radius_km, NEARBY_FEEDBACK_LIMIT)
+        .await?
+        .iter()
+        .filter_map(feedback_label)
+        .collect();
+
+    if labels.is_empty() {
+        return Ok(None);
+    }
+
+    let satisfied = labels.iter().filter(|&&label| label).count();
+    Ok(Some(NearbyFeedback {
+        samples: labels.len(),
+        satisfied,
+        satisfaction_rate: satisfied as f64 / labels.len() as f64,
+    }))
+}
+
+/// Blend the forecast-based confidence with the nearby satisfaction rate,
+/// weighting the local outcomes by how many there are.
+pub fn blend_confidence(base: f64, nearby: Option<&NearbyFeedback>) -> f64 {
+    let base = base.clamp(0.0, 1.0);
+    match nearby {
+        Some(nearby) => {
+            let weight = nearby.samples as f64 / (nearby.samples as f64 + PRIOR_SAMPLES);
+            base * (1.0 - weight) + nearby.satisfaction_rate * weight
+        }
+        None => base,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::{CreateFeedback, CreateUserPreferences, MockDatabase};
+
+    fn located_at(lat: f64, lon: f64) -> CreateUserPreferences {
+        CreateUserPreferences {
+            preferred_drying_hours: None,
+            min_temperature: None,
+            max_humidity: None,
+            avoid_rain_probability: None,
+            location_lat: Some(lat),
+            location_lon: Some(lon),
+            location_name: None,
+            timezone: None,
+            notification_preferences: None,
+            scoring_model: None,
+        }
+    }
+
+    fn rated(user_id: uuid::Uuid, rating: i32) -> CreateFeedback {
+        CreateFeedback {
+            user_id: Some(user_id),
+            window_id: format!("window_{}_3", uuid::Uuid::new_v4().as_u128() % 1_000_000_000),
+            feedback_text: "still damp".to_string(),
+            satisfaction_rating: Some(rating),
+            drying_result: None,
+            weather_temp_c: None,
+            weather_humidity: None,
+            weather_wind_ms: None,
+            weather_rain_mm: None,
+            predicted_score: None,
+            actual_outcome: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_nearby_negative_feedback_lowers_confidence() {
+        let store = MockDatabase::new();
+        let neighbour = store.create_user_preferences(located_at(13.76, 100.5)).await.unwrap();
+        let far_away = store.create_user_preferences(located_at(18.79, 98.98)).await.unwrap();
+        for _ in 0..3 {
+            store.create_feedback(rated(neighbour.user_id, 1)).await.unwrap();
+        }
+        store.create_feedback(rated(far_away.user_id, 5)).await.unwrap();
+
+        let nearby = nearby_feedback(&store, 13.75, 100.5, NEARBY_RADIUS_KM)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(nearby.samples, 3);
+        assert_eq!(nearby.satisfied, 0);
+
+        let fresh = blend_confidence(0.8, None);
+        let blended = blend_confidence(0.8, Some(&nearby));
+        assert_eq!(fresh, 0.8);
+        assert!(blended < fresh);
+        assert!((blended - 0.8 * 5.0 / 8.0).abs() < 1e-9);
+
+        // Nobody nearby: nothing to blend
+        assert!(nearby_feedback(&store, -33.9, 151.2, NEARBY_RADIUS_KM).await.unwrap().is_none());
+    }
+}
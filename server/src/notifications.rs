@@ -0,0 +1,178 @@
+use chrono::{DateTime, Duration, NaiveDateTime, NaiveTime, TimeZone, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::database::UserPreferences;
+use crate::utils::parse_timezone;
+
+/// The keys of a user's `notification_preferences` the server reads; anything
+/// else in there belongs to the client and is left alone
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NotificationSettings {
+    /// Local time (`HH:MM`) from which alerts are held back
+    #[serde(default)]
+    pub quiet_hours_start: Option<NaiveTime>,
+    /// Local time at which held-back alerts may go out again
+    #[serde(default)]
+    pub quiet_hours_end: Option<NaiveTime>,
+}
+
+/// What to do with an alert that is due now
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Delivery {
+    Now,
+    /// Hold it until the end of quiet hours
+    Defer(DateTime<Utc>),
+    /// Drop it: quiet hours outlast the thing it was about
+    Suppress,
+}
+
+impl NotificationSettings {
+    /// Settings from stored `notification_preferences`; quiet hours that are
+    /// missing or malformed are treated as unset
+    pub fn from_preferences(value: Option<&serde_json::Value>) -> Self {
+        value
+            .and_then(|value| serde_json::from_value(value.clone()).ok())
+            .unwrap_or_default()
+    }
+
+    /// Settings from `notification_preferences` about to be saved: quiet
+    /// hours must be valid `HH:MM` times, given together. Anything that isn't
+    /// an object is the client's business.
+    pub fn validate(value: &serde_json::Value) -> Result<Self, String> {
+        if !value.is_object() {
+            return Ok(Self::default());
+        }
+        let settings: Self = serde_json::from_value(value.clone())
+            .map_err(|e| format!("quiet hours must be HH:MM local times: {}", e))?;
+        if settings.quiet_hours_start.is_some() != settings.quiet_hours_end.is_some() {
+            return Err("quiet_hours_start and quiet_hours_end must be set together".to_string());
+        }
+        Ok(settings)
+    }
+
+    /// Whether local `time` falls in quiet hours. A span whose start is later
+    /// than its end runs past midnight; equal ends mean no quiet hours.
+    pub fn is_quiet(&self, time: NaiveTime) -> bool {
+        match (self.quiet_hours_start, self.quiet_hours_end) {
+            (Some(start), Some(end)) if start < end => start <= time && time < end,
+            (Some(start), Some(end)) if start > end => time >= start || time < end,
+            _ => false,
+        }
+    }
+
+    /// How to deliver an alert due at `due` for a user in `tz`, which stops
+    /// mattering at `relevant_until` (typically the start of its window).
+    /// Alerts held by quiet hours go out when they end, if still relevant.
+    pub fn schedule<Tz: TimeZone>(&self, due: DateTime<Utc>, relevant_until: DateTime<Utc>, tz: &Tz) -> Delivery {
+        let local = due.with_timezone(tz).naive_local();
+        let Some(end) = self.quiet_hours_end.filter(|_| self.is_quiet(local.time())) else {
+            return Delivery::Now;
+        };
+
+        // Quiet hours end today, or tomorrow when they run past midnight
+        let mut resume = local.date().and_time(end);
+        if resume <= local {
+            resume += Duration::days(1);
+        }
+        match to_utc(resume, tz) {
+            Some(resume) if resume < relevant_until => Delivery::Defer(resume),
+            _ => Delivery::Suppress,
+        }
+    }
+}
+
+/// [`NotificationSettings::schedule`] with the user's own settings and
+/// timezone (UTC when unset or unknown)
+pub fn schedule_for_user(prefs: &UserPreferences, due: DateTime<Utc>, relevant_until: DateTime<Utc>) -> Delivery {
+    let settings = NotificationSettings::from_preferences(prefs.notification_preferences.as_ref());
+    match prefs.timezone.as_deref().map(parse_timezone) {
+        Some(Ok(tz)) => settings.schedule(due, relevant_until, &tz),
+        _ => settings.schedule(due, relevant_until, &Utc),
+    }
+}
+
+/// A local time in `tz` as UTC, moving past a daylight-saving gap
+fn to_utc<Tz: TimeZone>(local: NaiveDateTime, tz: &Tz) -> Option<DateTime<Utc>> {
+    tz.from_local_datetime(&local)
+        .earliest()
+        .or_else(|| tz.from_local_datetime(&(local + Duration::hours(1))).earliest())
+        .map(|t| t.with_timezone(&Utc))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn bangkok_user(notification_preferences: serde_json::Value) -> UserPreferences {
+        UserPreferences {
+            user_id: uuid::Uuid::new_v4(),
+            preferred_drying_hours: None,
+            min_temperature: None,
+            max_humidity: None,
+            avoid_rain_probability: None,
+            location_lat: None,
+            location_lon: None,
+            location_name: None,
+            timezone: Some("Asia/Bangkok".to_string()),
+            notification_preferences: Some(notification_preferences),
+            scoring_model: None,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        }
+    }
+
+    fn utc(s: &str) -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339(s).unwrap().with_timezone(&Utc)
+    }
+
+    #[test]
+    fn test_alert_in_quiet_hours_is_deferred_or_suppressed() {
+        let user = bangkok_user(json!({ "quiet_hours_start": "22:00", "quiet_hours_end": "07:00", "email": true }));
+        // 03:00 in Bangkok
+        let due = utc("2024-06-01T20:00:00Z");
+
+        // Still relevant at 07:00 local: held until then
+        assert_eq!(
+            schedule_for_user(&user, due, utc("2024-06-02T05:00:00Z")),
+            Delivery::Defer(utc("2024-06-02T00:00:00Z"))
+        );
+        // Over by 05:00 local: never sent
+        assert_eq!(schedule_for_user(&user, due, utc("2024-06-01T22:00:00Z")), Delivery::Suppress);
+        // 12:00 and 21:59 local are outside quiet hours
+        assert_eq!(schedule_for_user(&user, utc("2024-06-01T05:00:00Z"), utc("2024-06-02T00:00:00Z")), Delivery::Now);
+        assert_eq!(schedule_for_user(&user, utc("2024-06-01T14:59:00Z"), utc("2024-06-02T00:00:00Z")), Delivery::Now);
+        // 23:00 local waits for 07:00 the next morning
+        assert_eq!(
+            schedule_for_user(&user, utc("2024-06-01T16:00:00Z"), utc("2024-06-02T05:00:00Z")),
+            Delivery::Defer(utc("2024-06-02T00:00:00Z"))
+        );
+    }
+
+    #[test]
+    fn test_quiet_hours_within_one_day_and_unset() {
+        let time = |h| NaiveTime::from_hms_opt(h, 0, 0).unwrap();
+        let nap = NotificationSettings {
+            quiet_hours_start: Some(time(13)),
+            quiet_hours_end: Some(time(15)),
+        };
+        assert!(nap.is_quiet(time(14)));
+        assert!(!nap.is_quiet(time(15)) && !nap.is_quiet(time(3)));
+
+        let unset = NotificationSettings::from_preferences(Some(&json!({ "quiet_hours_start": "not a time" })));
+        assert_eq!(unset, NotificationSettings::default());
+        assert!(!unset.is_quiet(time(3)));
+    }
+
+    #[test]
+    fn test_validate_quiet_hours() {
+        let settings = NotificationSettings::validate(&json!({ "quiet_hours_start": "22:00", "quiet_hours_end": "07:30", "email": true }));
+        assert_eq!(settings.unwrap().quiet_hours_end, NaiveTime::from_hms_opt(7, 30, 0));
+        assert!(NotificationSettings::validate(&json!({ "email": true })).is_ok());
+        assert!(NotificationSettings::validate(&json!(null)).is_ok());
+
+        assert!(NotificationSettings::validate(&json!({ "quiet_hours_start": "25:00", "quiet_hours_end": "07:00" })).is_err());
+        assert!(NotificationSettings::validate(&json!({ "quiet_hours_start": 22, "quiet_hours_end": 7 })).is_err());
+        assert!(NotificationSettings::validate(&json!({ "quiet_hours_start": "22:00" })).is_err());
+    }
+}
@@ -1,34 +1,147 @@
 use axum::{
-    extract::{Path, Query, State},
-    http::StatusCode,
-    response::Json,
-    routing::{get, post},
+    extract::{DefaultBodyLimit, Path, Query, State},
+    http::{header, HeaderMap, StatusCode},
+    middleware,
+    response::{IntoResponse, Json, Response},
+    routing::{delete, get, patch, post, put},
     Router,
 };
+use moka::future::Cache;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::fmt::Write;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
+use std::time::Duration;
 use uuid::Uuid;
 
 use crate::{
-    ai::{AiClient, FeedbackAnalysis},
+    ai::{ActualOutcome, AiError, AiProvider, AnalysisConfidence, DryingResult, FeedbackAnalysis, ModelCircuit},
+    backtest::{run_backtest, BacktestReport},
     config::Config,
-    database::{CreateFeedback, CreateUserPreferences, Database, FeedbackRecord, UserPreferences},
+    i18n::{Condition, Language},
+    database::{
+        CreateFeedback, CreateUserPreferences, DataStore, DatabaseError, FeedbackRecord, PatchUserPreferences,
+        SaveUserLocation,
+        ServedWindow, UserLocation, UserPreferences,
+    },
+    error::{validate_range, AppError},
+    middleware::{access_log, limit_concurrency, require_api_key, AdminKey, ConcurrencyLimit},
     forecast::{
-        merge::{group_into_windows, merge_weather_data, WindowData},
-        openweather::OpenWeatherClient,
-        types::{GeocodeResponse, HourlyData},
+        merge::{group_into_windows, group_into_windows_with_gaps, merge_weather_data_with, smooth_hourly, WindowData},
+        types::{DataSource, GeocodeResponse, HourlyData, TempRange},
+        mock::{MockScenario, MockWeatherClient},
+        init_cache, CachedForecastData, ForecastCache, WeatherProvider,
+    },
+    nearby::{blend_confidence, nearby_feedback, NearbyFeedback, NEARBY_RADIUS_KM},
+    notifications::NotificationSettings,
+    solar::{compass_point, sun_position, sun_times, SunTimes},
+    scoring::{
+        calculate_drying_score_with, factor_effects, model_contributions, normalize_features_with, score_windows_with,
+        weight_schema, DryingScore, DryingWeights, Fabric, PatchDryingWeights, ScoreContributions, ScoreThresholds, ScoringConfig,
+        ScoringModel, Verdict, WeatherFeatures, WeightSpec, SGD_LEARNING_RATE, SGD_REGULARIZATION,
     },
-    scoring::{calculate_drying_score, DryingScore, WeatherFeatures},
+    tips::{heuristic_explanation, heuristic_tips, TipsEngine},
+    utils::{format_duration, round_to_decimals, split_tips, OutputPrecision},
+    warnings::{push_unique, Warning, WarningCode, LOW_CONFIDENCE, LOW_CONFIDENCE_SPREAD},
 };
 
+/// Longest drying window, in hours, that can be requested
+pub const MAX_WINDOW_HOURS: u32 = 12;
+
+/// How long a geocoded place name is reused
+const PLACE_CACHE_TTL: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// Most windows `/drying-windows` will return
+pub const MAX_WINDOWS: u32 = 20;
+
+/// Most tips `/recommendations` will return
+pub const MAX_TIPS: u32 = 10;
+
+/// How long a location's last forecast is kept for `/forecast/changes`
+const FORECAST_SNAPSHOT_TTL: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// How long `/readyz` reuses its last weather provider check, so frequent
+/// probes don't each call upstream
+const WEATHER_READINESS_TTL: Duration = Duration::from_secs(30);
+
+/// Age past which a forecast is reported as stale; upstream models refresh
+/// about hourly
+const FORECAST_STALE_AFTER_MINUTES: i64 = 60;
+
+/// Recently built `/recommendations` responses. Concurrent identical requests
+/// wait on one computation (and one AI call) instead of each making their own.
+pub type RecommendationCache = Cache<String, RecommendationResponse>;
+
 // Shared application state
 #[derive(Clone)]
 pub struct AppState {
     pub config: Arc<Config>,
-    pub database: Arc<Database>,
-    pub weather_client: Arc<OpenWeatherClient>,
-    pub ai_client: Arc<AiClient>,
+    pub database: Arc<dyn DataStore>,
+    pub weather_client: Arc<dyn WeatherProvider>,
+    pub ai_client: Arc<dyn AiProvider>,
+    pub recommendations: RecommendationCache,
+    /// Place names already resolved for `q`, keyed by the normalized name
+    pub places: Cache<String, LocationInfo>,
+    /// The last forecast fetched for each ~1 km location
+    pub forecast_snapshots: Cache<String, ForecastSnapshot>,
+    /// Fetched forecasts reused for `WEATHER_CACHE_TTL_SECS`, per ~1 km location
+    pub forecasts: ForecastCache,
+    /// The weather provider's last readiness result, as an error message if it failed
+    pub weather_readiness: Cache<(), Result<(), String>>,
+    /// Set once shutdown begins, so `/readyz` sends load balancers elsewhere
+    pub draining: Arc<AtomicBool>,
+}
+
+/// Merged hours as fetched at one moment, to diff a later fetch against
+#[derive(Debug, Clone)]
+pub struct ForecastSnapshot {
+    pub fetched_at: chrono::DateTime<chrono::Utc>,
+    pub hours: Arc<Vec<HourlyData>>,
+}
+
+impl AppState {
+    pub fn new(
+        config: Arc<Config>,
+        database: Arc<dyn DataStore>,
+        weather_client: Arc<dyn WeatherProvider>,
+        ai_client: Arc<dyn AiProvider>,
+    ) -> Self {
+        let recommendations = Cache::builder()
+            .max_capacity(10_000)
+            .time_to_live(Duration::from_secs(config.recommendation_cache_ttl_secs))
+            .build();
+        let places = Cache::builder()
+            .max_capacity(10_000)
+            .time_to_live(PLACE_CACHE_TTL)
+            .build();
+        let forecast_snapshots = Cache::builder()
+            .max_capacity(10_000)
+            .time_to_live(FORECAST_SNAPSHOT_TTL)
+            .build();
+        let forecasts = init_cache(Duration::from_secs(config.weather_cache_ttl_secs));
+        let weather_readiness = Cache::builder()
+            .max_capacity(1)
+            .time_to_live(WEATHER_READINESS_TTL)
+            .build();
+
+        Self {
+            config,
+            database,
+            weather_client,
+            ai_client,
+            recommendations,
+            places,
+            forecast_snapshots,
+            forecasts,
+            weather_readiness,
+            draining: Arc::new(AtomicBool::new(false)),
+        }
+    }
+
+    /// Start failing readiness ahead of shutdown; requests are still served
+    pub fn begin_draining(&self) {
+        self.draining.store(true, Ordering::SeqCst);
+    }
 }
 
 // Request/Response types
@@ -42,25 +155,142 @@ pub struct GeocodeQuery {
 
 #[derive(Debug, Deserialize)]
 pub struct ForecastQuery {
+    /// Place name to geocode when `lat`/`lon` are not given
+    pub q: Option<String>,
+    pub lat: Option<f64>,
+    pub lon: Option<f64>,
+    pub hours: Option<u32>,
+    /// Refetch the forecast if the cached one is older than this many seconds
+    pub max_age_secs: Option<u32>,
+    /// Serve this scripted mock weather instead of the provider's (needs `DEBUG_ENDPOINTS`)
+    pub scenario: Option<MockScenario>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ForecastChangesQuery {
+    /// Place name to geocode when `lat`/`lon` are not given
+    pub q: Option<String>,
+    pub lat: Option<f64>,
+    pub lon: Option<f64>,
+    /// Refetch the forecast if the cached one is older than this many seconds
+    pub max_age_secs: Option<u32>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ForecastCsvQuery {
     pub lat: f64,
     pub lon: f64,
     pub hours: Option<u32>,
+    /// Append each hour's drying score as a final column
+    #[serde(default)]
+    pub with_scores: bool,
 }
 
 #[derive(Debug, Deserialize)]
 pub struct DryingWindowsQuery {
-    pub lat: f64,
-    pub lon: f64,
+    /// Place name to geocode when `lat`/`lon` are not given
+    pub q: Option<String>,
+    pub lat: Option<f64>,
+    pub lon: Option<f64>,
     pub window_hours: Option<u32>,
     pub max_windows: Option<u32>,
+    pub scoring_model: Option<ScoringModel>,
+    /// Fabric of the load; heat-sensitive fabrics avoid scorching windows
+    pub fabric: Option<Fabric>,
+    /// Rainy hours a window may contain (default `RAIN_GAP_TOLERANCE_HOURS`)
+    pub rain_gap_hours: Option<u32>,
+    /// Language for labels; overrides `Accept-Language`
+    pub lang: Option<String>,
+    /// Include each score's full feature breakdown, not just its headline
+    #[serde(default)]
+    pub verbose: bool,
+    pub user_id: Option<Uuid>,
+    /// Also score each window with `user_id`'s personal weights
+    #[serde(default)]
+    pub compare_weights: bool,
+    /// Refetch the forecast if the cached one is older than this many seconds
+    pub max_age_secs: Option<u32>,
+    /// Serve this scripted mock weather instead of the provider's (needs `DEBUG_ENDPOINTS`)
+    pub scenario: Option<MockScenario>,
 }
 
 #[derive(Debug, Deserialize)]
 pub struct RecommendationQuery {
-    pub lat: f64,
-    pub lon: f64,
+    /// Place name to geocode when `lat`/`lon` are not given
+    pub q: Option<String>,
+    pub lat: Option<f64>,
+    pub lon: Option<f64>,
     pub user_id: Option<Uuid>,
+    /// One of `user_id`'s saved locations, used instead of `lat`/`lon` or `q`
+    pub location_label: Option<String>,
+    pub window_hours: Option<u32>,
+    /// Windows to return (default 3); AI output still covers only the best one
+    pub max_windows: Option<u32>,
+    /// Hours the load needs to dry; shorter windows are never recommended
+    pub min_dry_hours: Option<u32>,
+    pub scoring_model: Option<ScoringModel>,
+    /// Tips to return (default 3), from the AI or the fallback list alike
+    pub max_tips: Option<u32>,
+    /// Fabric of the load; heat-sensitive fabrics avoid scorching windows
+    pub fabric: Option<Fabric>,
+    /// Rainy hours a window may contain (default `RAIN_GAP_TOLERANCE_HOURS`)
+    pub rain_gap_hours: Option<u32>,
+    /// Language for labels; overrides `Accept-Language`
+    pub lang: Option<String>,
+    /// Include each score's full feature breakdown, not just its headline
+    #[serde(default)]
+    pub verbose: bool,
+    /// Include an hourly score series over the horizon for charting
+    #[serde(default)]
+    pub sparkline: bool,
+    /// Refetch the forecast if the cached one is older than this many seconds
+    pub max_age_secs: Option<u32>,
+    /// Serve this scripted mock weather instead of the provider's (needs `DEBUG_ENDPOINTS`)
+    pub scenario: Option<MockScenario>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct BestDayQuery {
+    /// Place name to geocode when `lat`/`lon` are not given
+    pub q: Option<String>,
+    pub lat: Option<f64>,
+    pub lon: Option<f64>,
     pub window_hours: Option<u32>,
+    /// Include the window score's full feature breakdown
+    #[serde(default)]
+    pub verbose: bool,
+    /// Refetch the forecast if the cached one is older than this many seconds
+    pub max_age_secs: Option<u32>,
+    /// Serve this scripted mock weather instead of the provider's (needs `DEBUG_ENDPOINTS`)
+    pub scenario: Option<MockScenario>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SunQuery {
+    /// Place name to geocode when `lat`/`lon` are not given
+    pub q: Option<String>,
+    pub lat: Option<f64>,
+    pub lon: Option<f64>,
+    /// Local date (`APP_TIMEZONE`); defaults to today
+    pub date: Option<chrono::NaiveDate>,
+}
+
+/// A measured drying outcome, e.g. from a moisture sensor
+#[derive(Debug, Deserialize)]
+pub struct OutcomeRequest {
+    pub user_id: Uuid,
+    pub window_id: String,
+    pub dry: bool,
+    pub minutes_to_dry: Option<u32>,
+    /// Where the laundry was hung; defaults to the user's saved location
+    pub lat: Option<f64>,
+    pub lon: Option<f64>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct PreferencesQuery {
+    /// Create and return empty preferences for an unknown user instead of 404
+    pub create_if_missing: Option<bool>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -69,13 +299,16 @@ pub struct FeedbackRequest {
     pub window_id: String,
     pub feedback_text: String,
     pub satisfaction_rating: Option<i32>,
-    pub drying_result: Option<String>,
+    pub drying_result: Option<DryingResult>,
     pub weather_conditions: Option<WeatherConditions>,
     pub predicted_score: Option<f64>,
-    pub actual_outcome: Option<String>,
+    pub actual_outcome: Option<ActualOutcome>,
+    /// Where the window was served; defaults to the user's saved location
+    pub lat: Option<f64>,
+    pub lon: Option<f64>,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Clone, Default, Deserialize)]
 pub struct WeatherConditions {
     pub temp_c: Option<f64>,
     pub humidity: Option<f64>,
@@ -83,6 +316,44 @@ pub struct WeatherConditions {
     pub rain_mm: Option<f64>,
 }
 
+impl WeatherConditions {
+    /// Whether every condition is known, so no default stands in for one
+    pub fn is_complete(&self) -> bool {
+        [self.temp_c, self.humidity, self.wind_ms, self.rain_mm].iter().all(Option::is_some)
+    }
+}
+
+/// The conditions stored with a feedback record
+impl From<&FeedbackRecord> for WeatherConditions {
+    fn from(record: &FeedbackRecord) -> Self {
+        Self {
+            temp_c: record.weather_temp_c,
+            humidity: record.weather_humidity,
+            wind_ms: record.weather_wind_ms,
+            rain_mm: record.weather_rain_mm,
+        }
+    }
+}
+
+/// Weather to analyze feedback against, with neutral defaults for anything
+/// the user didn't report
+impl From<&WeatherConditions> for WeatherFeatures {
+    fn from(conditions: &WeatherConditions) -> Self {
+        let rain_mm = conditions.rain_mm.unwrap_or(0.0);
+        Self {
+            temp_c: conditions.temp_c.unwrap_or(20.0),
+            rh: conditions.humidity.unwrap_or(50.0),
+            wind_ms: conditions.wind_ms.unwrap_or(2.0),
+            cloud: 0.5, // Cloud cover isn't reported
+            rain_p: if rain_mm > 0.0 { 0.8 } else { 0.0 },
+            rain_mm,
+            gust_ms: None,
+            is_daylight: None,
+            wind_deg: None,
+        }
+    }
+}
+
 #[derive(Debug, Deserialize)]
 pub struct ExplainRequest {
     pub window_data: WindowData,
@@ -90,6 +361,26 @@ pub struct ExplainRequest {
     pub user_preferences: Option<UserPreferences>,
 }
 
+#[derive(Debug, Deserialize)]
+pub struct WhatIfRequest {
+    pub weather: WeatherFeatures,
+    pub weights: DryingWeights,
+    pub scoring_model: Option<ScoringModel>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SetWeightsRequest {
+    pub user_id: Uuid,
+    pub weights: DryingWeights,
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct BacktestRequest {
+    pub weights: Option<DryingWeights>,
+    pub threshold: Option<f64>,
+    pub limit: Option<i64>,
+}
+
 #[derive(Debug, Deserialize)]
 pub struct AiRecommendationQuery {
     pub lat: f64,
@@ -101,16 +392,125 @@ pub struct HealthResponse {
     pub status: String,
     pub timestamp: chrono::DateTime<chrono::Utc>,
     pub version: String,
+    /// Circuit state of each configured AI model, in the order they are tried
+    pub ai_models: Vec<ModelCircuit>,
 }
 
 #[derive(Debug, Serialize)]
-pub struct ForecastResponse {
+pub struct LivenessResponse {
+    pub status: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ReadinessResponse {
+    /// `ready`, `not_ready` when a check failed, or `draining` on shutdown
+    pub status: String,
+    pub checks: Vec<ReadinessCheck>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ReadinessCheck {
+    pub name: String,
+    pub ok: bool,
+    /// What went wrong, for a failed check
+    pub error: Option<String>,
+}
+
+impl ReadinessCheck {
+    fn new<E: std::fmt::Display>(name: &str, result: Result<(), E>) -> Self {
+        Self {
+            name: name.to_string(),
+            ok: result.is_ok(),
+            error: result.err().map(|e| e.to_string()),
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct VersionResponse {
+    pub version: String,
+    pub git_sha: String,
+    pub build_timestamp: Option<chrono::DateTime<chrono::Utc>>,
+    pub rust_version: String,
+}
+
+/// One forecast hour as returned by the API, rounded to the configured precision
+#[derive(Debug, Serialize)]
+pub struct HourlyDataDto {
+    pub ts: chrono::DateTime<chrono::FixedOffset>,
+    pub temp_c: f64,
+    pub rh: f64,
+    pub wind_ms: f64,
+    pub cloud: f64,
+    pub rain_p: f64,
+    pub rain_mm: f64,
+    pub wind_gust_ms: Option<f64>,
+    pub wind_deg: Option<f64>,
+    pub source: DataSource,
+}
+
+impl HourlyDataDto {
+    pub fn new(hour: &HourlyData, precision: &OutputPrecision) -> Self {
+        Self {
+            ts: hour.ts,
+            temp_c: round_to_decimals(hour.temp_c, precision.temperature),
+            rh: round_to_decimals(hour.rh, precision.humidity),
+            wind_ms: round_to_decimals(hour.wind_ms, precision.wind),
+            cloud: round_to_decimals(hour.cloud, precision.probability),
+            rain_p: round_to_decimals(hour.rain_p, precision.probability),
+            rain_mm: round_to_decimals(hour.rain_mm, precision.rain),
+            wind_gust_ms: hour.wind_gust_ms.map(|g| round_to_decimals(g, precision.wind)),
+            wind_deg: hour.wind_deg.map(|d| d.round()),
+            source: hour.source,
+        }
+    }
+}
+
+/// How one hour scores, as compared between two forecasts
+#[derive(Debug, Clone, Serialize)]
+pub struct HourOutlook {
+    pub score: f64,
+    pub verdict: Verdict,
+    pub veto_reason: Option<String>,
+}
+
+/// An hour whose verdict, or whether it is vetoed, differs between forecasts
+#[derive(Debug, Serialize)]
+pub struct HourChange {
+    pub ts: chrono::DateTime<chrono::FixedOffset>,
+    pub before: HourOutlook,
+    pub after: HourOutlook,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ForecastChangesResponse {
     pub location: LocationInfo,
-    pub hourly_data: Vec<HourlyData>,
+    /// When the forecast being compared against was fetched; null if none was
+    pub previous_fetched_at: Option<chrono::DateTime<chrono::Utc>>,
+    pub changes: Vec<HourChange>,
     pub generated_at: chrono::DateTime<chrono::Utc>,
 }
 
 #[derive(Debug, Serialize)]
+pub struct ForecastResponse {
+    pub location: LocationInfo,
+    pub hourly_data: Vec<HourlyDataDto>,
+    pub source: ForecastSource,
+    pub generated_at: chrono::DateTime<chrono::Utc>,
+}
+
+/// Which provider a response's weather came from, and how old it is
+#[derive(Debug, Clone, Serialize)]
+pub struct ForecastSource {
+    pub provider: String,
+    pub fetched_at: chrono::DateTime<chrono::Utc>,
+    /// Served from an earlier fetch rather than fetched for this request
+    pub cache_hit: bool,
+    /// Older than an hour, so likely behind the upstream forecast
+    pub stale: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
 pub struct LocationInfo {
     pub lat: f64,
     pub lon: f64,
@@ -122,10 +522,11 @@ pub struct LocationInfo {
 pub struct DryingWindowsResponse {
     pub location: LocationInfo,
     pub windows: Vec<DryingWindow>,
+    pub source: ForecastSource,
     pub generated_at: chrono::DateTime<chrono::Utc>,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Clone)]
 pub struct DryingWindow {
     pub id: String,
     pub start_time: chrono::DateTime<chrono::Utc>,
@@ -134,32 +535,300 @@ pub struct DryingWindow {
     pub score: DryingScore,
     pub weather_summary: WeatherSummary,
     pub recommendation: String,
+    /// Risks that didn't rule the window out, e.g. a tolerated shower
+    pub warnings: Vec<Warning>,
+    /// Upsides worth knowing, e.g. strong sun for whites
+    pub notes: Vec<String>,
+    /// Worst and best case score over `temp_range`, for windows forecast
+    /// only as daily summaries
+    pub score_range: Option<ScoreRange>,
+    /// The score under default and personal weights, when asked to compare
+    pub weight_comparison: Option<WeightComparison>,
+    pub condition: Condition,
+    pub verdict: Verdict,
+    /// Serialize only the [`CompactScore`] rather than the full breakdown
+    pub compact: bool,
 }
 
+/// The headline of a [`DryingScore`], without the feature breakdown
 #[derive(Debug, Serialize)]
+pub struct CompactScore<'a> {
+    pub score: f64,
+    pub unsafe_window: bool,
+    pub veto_reason: Option<&'a str>,
+}
+
+impl<'a> From<&'a DryingScore> for CompactScore<'a> {
+    fn from(score: &'a DryingScore) -> Self {
+        Self {
+            score: score.score,
+            unsafe_window: score.unsafe_window,
+            veto_reason: score.veto_reason.as_deref(),
+        }
+    }
+}
+
+impl Serialize for DryingWindow {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        use serde::ser::SerializeStruct;
+
+        // `condition`, `verdict` and `compact` only shape the output
+        let mut window = serializer.serialize_struct("DryingWindow", 11)?;
+        window.serialize_field("id", &self.id)?;
+        window.serialize_field("start_time", &self.start_time)?;
+        window.serialize_field("end_time", &self.end_time)?;
+        window.serialize_field("duration_hours", &self.duration_hours)?;
+        if self.compact {
+            window.serialize_field("score", &CompactScore::from(&self.score))?;
+        } else {
+            window.serialize_field("score", &self.score)?;
+        }
+        window.serialize_field("weather_summary", &self.weather_summary)?;
+        window.serialize_field("recommendation", &self.recommendation)?;
+        window.serialize_field("warnings", &self.warnings)?;
+        window.serialize_field("notes", &self.notes)?;
+        window.serialize_field("score_range", &self.score_range)?;
+        window.serialize_field("weight_comparison", &self.weight_comparison)?;
+        window.end()
+    }
+}
+
+/// A window scored with the default weights and with a user's own. Users
+/// who haven't trained any weights score the same under both.
+#[derive(Debug, Clone, Copy, Serialize)]
+pub struct WeightComparison {
+    pub default_score: f64,
+    pub personal_score: f64,
+    /// `personal_score - default_score`
+    pub delta: f64,
+}
+
+#[derive(Debug, Clone, Serialize)]
 pub struct WeatherSummary {
     pub avg_temp_c: f64,
     pub avg_humidity: f64,
     pub avg_wind_ms: f64,
+    pub avg_wind_deg: Option<f64>,
+    /// Speed of the mean wind vector behind `avg_wind_deg`; well below
+    /// `avg_wind_ms` when the wind keeps changing direction
+    pub avg_wind_resultant_ms: Option<f64>,
+    /// Coolest and warmest hour, to show how much the window swings
+    pub min_temp_c: f64,
+    pub max_temp_c: f64,
+    pub min_humidity: f64,
+    pub max_humidity: f64,
     pub total_rain_mm: f64,
+    /// Peak UV index, when the forecast has one
+    pub uv_index: Option<f64>,
+    /// The day's low and high, when the window comes from a daily forecast
+    pub temp_range: Option<TempRange>,
     pub conditions: String,
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct ScoreRange {
+    pub worst: f64,
+    pub best: f64,
+}
+
+impl WeatherSummary {
+    fn round(&mut self, precision: &OutputPrecision) {
+        self.avg_temp_c = round_to_decimals(self.avg_temp_c, precision.temperature);
+        self.avg_humidity = round_to_decimals(self.avg_humidity, precision.humidity);
+        self.min_temp_c = round_to_decimals(self.min_temp_c, precision.temperature);
+        self.max_temp_c = round_to_decimals(self.max_temp_c, precision.temperature);
+        self.min_humidity = round_to_decimals(self.min_humidity, precision.humidity);
+        self.max_humidity = round_to_decimals(self.max_humidity, precision.humidity);
+        self.avg_wind_ms = round_to_decimals(self.avg_wind_ms, precision.wind);
+        self.avg_wind_deg = self.avg_wind_deg.map(|d| d.round());
+        self.avg_wind_resultant_ms = self.avg_wind_resultant_ms.map(|ms| round_to_decimals(ms, precision.wind));
+        self.uv_index = self.uv_index.map(|uv| round_to_decimals(uv, 1));
+        self.temp_range = self.temp_range.map(|range| TempRange {
+            min_c: round_to_decimals(range.min_c, precision.temperature),
+            max_c: round_to_decimals(range.max_c, precision.temperature),
+        });
+        self.total_rain_mm = round_to_decimals(self.total_rain_mm, precision.rain);
+    }
+}
+
+impl DryingWindow {
+    /// Round for the response; only call once scoring and ranking are done
+    pub fn round(&mut self, precision: &OutputPrecision) {
+        round_score(&mut self.score, precision);
+        self.weather_summary.round(precision);
+        self.score_range = self.score_range.map(|range| ScoreRange {
+            worst: round_to_decimals(range.worst, precision.score),
+            best: round_to_decimals(range.best, precision.score),
+        });
+        self.weight_comparison = self.weight_comparison.map(|comparison| WeightComparison {
+            default_score: round_to_decimals(comparison.default_score, precision.score),
+            personal_score: round_to_decimals(comparison.personal_score, precision.score),
+            delta: round_to_decimals(comparison.delta, precision.score),
+        });
+    }
+    
+    /// Serialize the full score breakdown, or only its headline
+    pub fn set_verbose(&mut self, verbose: bool) {
+        self.compact = !verbose;
+    }
+    
+    /// Relabel the conditions and verdict; windows are built in English
+    pub fn localize(&mut self, language: Language) {
+        self.weather_summary.conditions = language.condition(self.condition).to_string();
+        self.recommendation = language.verdict(self.verdict).to_string();
+    }
+}
+
+/// Language for a request's labels, from `lang` or `Accept-Language`
+fn request_language(lang: Option<&str>, headers: &HeaderMap) -> Language {
+    let accept_language = headers
+        .get(header::ACCEPT_LANGUAGE)
+        .and_then(|value| value.to_str().ok());
+    Language::negotiate(lang, accept_language)
+}
+
+fn round_score(score: &mut DryingScore, precision: &OutputPrecision) {
+    let round = |v: f64| round_to_decimals(v, precision.score);
+    score.score = round(score.score);
+    score.vpd_kpa = round(score.vpd_kpa);
+
+    let features = &mut score.features;
+    for value in [
+        &mut features.f_temp,
+        &mut features.f_hum,
+        &mut features.f_wind,
+        &mut features.f_cloud,
+        &mut features.f_rain,
+        &mut features.f_vpd,
+    ] {
+        *value = round(*value);
+    }
+
+    let raw = &mut score.raw;
+    raw.temp_c = round_to_decimals(raw.temp_c, precision.temperature);
+    raw.rh = round_to_decimals(raw.rh, precision.humidity);
+    raw.wind_ms = round_to_decimals(raw.wind_ms, precision.wind);
+    raw.cloud = round_to_decimals(raw.cloud, precision.probability);
+    raw.rain_p = round_to_decimals(raw.rain_p, precision.probability);
+    raw.rain_mm = round_to_decimals(raw.rain_mm, precision.rain);
+    raw.gust_ms = raw.gust_ms.map(|g| round_to_decimals(g, precision.wind));
+    raw.wind_deg = raw.wind_deg.map(|d| d.round());
+}
+
 #[derive(Debug, Serialize)]
 pub struct AiRecommendationResponse {
     pub recommendation: String,
     pub generated_at: chrono::DateTime<chrono::Utc>,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Clone, Serialize)]
+pub struct NextGoodWindow {
+    pub window_id: String,
+    pub start_time: chrono::DateTime<chrono::Utc>,
+    pub score: f64,
+    pub starts_in_hours: u32,
+    pub countdown: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
 pub struct RecommendationResponse {
     pub location: LocationInfo,
     pub best_windows: Vec<DryingWindow>,
+    pub next_good_window: Option<NextGoodWindow>,
+    pub message: Option<String>,
+    pub urgency: Urgency,
+    pub confidence: f64,
+    pub nearby_feedback: Option<NearbyFeedback>,
     pub ai_explanation: Option<String>,
     pub tips: Vec<String>,
+    /// The best window's warnings, plus any about the recommendation overall
+    pub warnings: Vec<Warning>,
+    /// Scores over the horizon when `sparkline=true` was asked for
+    pub sparkline: Option<Vec<SparklinePoint>>,
+    pub source: ForecastSource,
+    pub generated_at: chrono::DateTime<chrono::Utc>,
+}
+
+/// One point of an hourly score chart; downsampled points average their hours
+#[derive(Debug, Clone, Serialize)]
+pub struct SparklinePoint {
+    pub ts: chrono::DateTime<chrono::FixedOffset>,
+    pub score: f64,
+}
+
+/// The single best laundry day in the coming week. `date`, `best_window` and
+/// `reason` are all null, with a `message` instead, when no day has a window
+/// worth using.
+#[derive(Debug, Serialize)]
+pub struct BestDayResponse {
+    pub location: LocationInfo,
+    /// Local date (`APP_TIMEZONE`) the best window starts on
+    pub date: Option<chrono::NaiveDate>,
+    pub best_window: Option<DryingWindow>,
+    pub reason: Option<String>,
+    pub message: Option<String>,
     pub generated_at: chrono::DateTime<chrono::Utc>,
 }
 
+/// Where the sun is through one day, for orienting a fixed rack. `sunrise`
+/// and `sunset` are null during polar day or night.
+#[derive(Debug, Serialize)]
+pub struct SunResponse {
+    pub location: LocationInfo,
+    pub date: chrono::NaiveDate,
+    pub sunrise: Option<chrono::DateTime<chrono::FixedOffset>>,
+    pub solar_noon: chrono::DateTime<chrono::FixedOffset>,
+    pub sunset: Option<chrono::DateTime<chrono::FixedOffset>>,
+    pub source: SunTimesSource,
+    /// Each whole hour the sun is up
+    pub path: Vec<SunPathPoint>,
+}
+
+/// Where sunrise and sunset came from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SunTimesSource {
+    /// Today's times from the 3-hour forecast's city data
+    Forecast,
+    /// A solar-position calculation
+    Calculated,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SunPathPoint {
+    pub ts: chrono::DateTime<chrono::FixedOffset>,
+    pub azimuth_deg: f64,
+    pub elevation_deg: f64,
+    /// Compass side of the house the sun is on, e.g. `SE`
+    pub facing: &'static str,
+}
+
+/// How soon to act on the forecast
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Urgency {
+    /// The upcoming window is the best and conditions get worse after it
+    ActNow,
+    /// No strong reason to hurry or to hold off
+    Flexible,
+    /// A clearly better window comes later
+    Wait,
+}
+
+#[derive(Debug, Serialize)]
+pub struct OutcomeResponse {
+    pub id: Uuid,
+    /// Score the user's weights gave the window before this outcome
+    pub predicted_score: f64,
+    pub weights: DryingWeights,
+    /// Updates applied to the user's weights, including this one
+    pub training_step: u64,
+    /// Learning rate this update used
+    pub learning_rate: f64,
+    /// Running log-loss estimate over the user's recent outcomes
+    pub loss: Option<f64>,
+}
+
 #[derive(Debug, Serialize)]
 pub struct FeedbackResponse {
     pub id: Uuid,
@@ -167,6 +836,49 @@ pub struct FeedbackResponse {
     pub message: String,
 }
 
+#[derive(Debug, Deserialize)]
+pub struct ReanalyzeQuery {
+    /// Store the fresh analysis over the record's current one
+    #[serde(default)]
+    pub persist: bool,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct DisagreementsQuery {
+    pub limit: Option<u32>,
+}
+
+/// Feedback where the user's `drying_result` and the AI's reading differ
+#[derive(Debug, Serialize)]
+pub struct FeedbackDisagreement {
+    pub feedback_id: Uuid,
+    pub user_id: Option<Uuid>,
+    pub window_id: String,
+    pub feedback_text: String,
+    pub user_drying_result: DryingResult,
+    pub ai_drying_result: DryingResult,
+    pub ai_confidence: AnalysisConfidence,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct DisagreementsResponse {
+    pub disagreements: Vec<FeedbackDisagreement>,
+    pub count: usize,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ReanalyzeResponse {
+    pub id: Uuid,
+    pub analysis: FeedbackAnalysis,
+    /// Some stored weather was missing and neutral defaults stood in, so the
+    /// analysis confidence was lowered
+    pub weather_defaults_used: bool,
+    /// The analysis replaced the one stored with the record
+    pub persisted: bool,
+    pub analyzed_at: chrono::DateTime<chrono::Utc>,
+}
+
 #[derive(Debug, Serialize)]
 pub struct ExplainResponse {
     pub explanation: String,
@@ -174,12 +886,117 @@ pub struct ExplainResponse {
     pub tips: Vec<String>,
 }
 
+#[derive(Debug, Serialize)]
+pub struct DebugHour {
+    #[serde(flatten)]
+    pub hour: HourlyData,
+    pub synthetic: bool,
+}
+
+#[derive(Debug, Serialize)]
+pub struct DebugMergedResponse {
+    pub location: LocationInfo,
+    pub hours: Vec<DebugHour>,
+    pub generated_at: chrono::DateTime<chrono::Utc>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct PurgeCacheQuery {
+    /// Purge only this location (with `lon`); omit both to purge everything
+    pub lat: Option<f64>,
+    pub lon: Option<f64>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct PurgeCacheResponse {
+    pub forecasts_purged: usize,
+    /// Cached `/recommendations` built from the purged forecasts
+    pub recommendations_purged: usize,
+}
+
+#[derive(Debug, Serialize)]
+pub struct WhatIfResponse {
+    pub score: DryingScore,
+    pub contributions: ScoreContributions,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ModelSchemaResponse {
+    pub weights: Vec<WeightSpec>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SetWeightsResponse {
+    pub user_id: Uuid,
+    pub weights: DryingWeights,
+    /// Training updates so far; manual tuning doesn't reset it
+    pub training_step: u64,
+}
+
 // Route handlers
-pub async fn health() -> Json<HealthResponse> {
+/// Liveness: the process is up and serving. Says nothing about dependencies,
+/// so a database outage doesn't get the pod restarted.
+pub async fn livez() -> Json<LivenessResponse> {
+    Json(LivenessResponse {
+        status: "alive".to_string(),
+    })
+}
+
+/// Readiness: `503` while draining for shutdown or when the database (or,
+/// with `READINESS_CHECK_WEATHER`, the weather provider) doesn't answer
+pub async fn readyz(State(state): State<AppState>) -> (StatusCode, Json<ReadinessResponse>) {
+    if state.draining.load(Ordering::SeqCst) {
+        let response = ReadinessResponse {
+            status: "draining".to_string(),
+            checks: Vec::new(),
+        };
+        return (StatusCode::SERVICE_UNAVAILABLE, Json(response));
+    }
+
+    let mut checks = vec![ReadinessCheck::new("database", state.database.health_check().await)];
+    if state.config.readiness_check_weather {
+        let weather = state
+            .weather_readiness
+            .get_with((), async {
+                state.weather_client.get_forecast3h(0.0, 0.0).await.map(|_| ()).map_err(|e| e.to_string())
+            })
+            .await;
+        checks.push(ReadinessCheck::new("weather", weather));
+    }
+
+    let ready = checks.iter().all(|check| check.ok);
+    for check in checks.iter().filter(|check| !check.ok) {
+        tracing::warn!("Not ready: {} check failed: {}", check.name, check.error.as_deref().unwrap_or_default());
+    }
+    let response = ReadinessResponse {
+        status: if ready { "ready" } else { "not_ready" }.to_string(),
+        checks,
+    };
+    let status = if ready { StatusCode::OK } else { StatusCode::SERVICE_UNAVAILABLE };
+    (status, Json(response))
+}
+
+pub async fn health(State(state): State<AppState>) -> Json<HealthResponse> {
     Json(HealthResponse {
         status: "healthy".to_string(),
         timestamp: chrono::Utc::now(),
         version: env!("CARGO_PKG_VERSION").to_string(),
+        ai_models: state.ai_client.circuits(),
+    })
+}
+
+/// Build metadata captured by `build.rs`, for matching a deployment to its source
+pub async fn version() -> Json<VersionResponse> {
+    let build_timestamp = env!("BUILD_TIMESTAMP")
+        .parse()
+        .ok()
+        .and_then(|secs| chrono::DateTime::from_timestamp(secs, 0));
+
+    Json(VersionResponse {
+        version: env!("CARGO_PKG_VERSION").to_string(),
+        git_sha: env!("BUILD_GIT_SHA").to_string(),
+        build_timestamp,
+        rust_version: env!("BUILD_RUST_VERSION").to_string(),
     })
 }
 
@@ -213,310 +1030,1695 @@ pub async fn geocode(
     }
 }
 
-pub async fn get_forecast(
-    State(state): State<AppState>,
-    Query(params): Query<ForecastQuery>,
-) -> Result<Json<ForecastResponse>, StatusCode> {
-    let hours = params.hours.unwrap_or(48).min(168); // Max 7 days
-    
-    // Fetch weather data
-    let onecall_result = state.weather_client.get_onecall(params.lat, params.lon).await;
-    let forecast3h_result = state.weather_client.get_forecast3h(params.lat, params.lon).await;
-    
-    let onecall = onecall_result.ok();
-    let forecast3h = forecast3h_result.ok();
-    
-    if onecall.is_none() && forecast3h.is_none() {
-        tracing::error!("Failed to fetch any weather data");
-        return Err(StatusCode::INTERNAL_SERVER_ERROR);
+/// Where a forecast request is for. Explicit coordinates win; otherwise `q`
+/// is geocoded (cached) and the first match is used.
+async fn resolve_location(
+    state: &AppState,
+    q: Option<&str>,
+    lat: Option<f64>,
+    lon: Option<f64>,
+) -> Result<LocationInfo, AppError> {
+    if let (Some(lat), Some(lon)) = (lat, lon) {
+        return Ok(LocationInfo {
+            lat,
+            lon,
+            name: None,
+            country: None,
+        });
+    }
+
+    let Some(query) = q.map(str::trim).filter(|q| !q.is_empty()) else {
+        return Err(AppError::bad_request("missing_location", "Provide lat and lon, or a place name in q"));
+    };
+
+    state
+        .places
+        .try_get_with(query.to_lowercase(), async {
+            let results = state.weather_client.geocode_direct(query).await.map_err(|e| {
+                tracing::error!("Geocoding {:?} failed: {}", query, e);
+                AppError::from(StatusCode::BAD_GATEWAY)
+            })?;
+            results
+                .into_iter()
+                .next()
+                .map(|place| LocationInfo {
+                    lat: place.lat,
+                    lon: place.lon,
+                    name: Some(place.name),
+                    country: Some(place.country),
+                })
+                .ok_or_else(|| {
+                    AppError::new(StatusCode::NOT_FOUND, "place_not_found", format!("No place found for {:?}", query))
+                })
+        })
+        .await
+        .map_err(|e| (*e).clone())
+}
+
+/// The user's location saved under `label`
+async fn resolve_saved_location(
+    state: &AppState,
+    user_id: Option<Uuid>,
+    label: &str,
+) -> Result<LocationInfo, AppError> {
+    let user_id = user_id
+        .ok_or_else(|| AppError::bad_request("invalid_parameter", "location_label requires a user_id"))?;
+    let saved = state
+        .database
+        .get_user_location(user_id, label.trim())
+        .await
+        .map_err(|e| {
+            tracing::error!("Failed to load location {:?} for {}: {}", label, user_id, e);
+            AppError::from(StatusCode::INTERNAL_SERVER_ERROR)
+        })?
+        .ok_or_else(|| {
+            AppError::new(StatusCode::NOT_FOUND, "location_not_found", format!("No saved location {:?}", label))
+        })?;
+
+    Ok(LocationInfo {
+        lat: saved.lat,
+        lon: saved.lon,
+        name: Some(saved.label),
+        country: None,
+    })
+}
+
+pub async fn get_forecast(
+    State(state): State<AppState>,
+    Query(params): Query<ForecastQuery>,
+) -> Result<Json<ForecastResponse>, AppError> {
+    let hours = validate_range("hours", params.hours, 48, state.config.max_forecast_hours)?;
+    let max_age = validate_max_age(params.max_age_secs)?;
+    let scenario = validate_scenario(&state.config, params.scenario)?;
+    let location = resolve_location(&state, params.q.as_deref(), params.lat, params.lon).await?;
+    let (hourly_data, source) = forecast_hours(&state, location.lat, location.lon, hours, max_age, scenario).await?;
+    let hourly_data = hourly_data
+        .iter()
+        .map(|hour| HourlyDataDto::new(hour, &state.config.output_precision))
+        .collect();
+        
+    Ok(Json(ForecastResponse {
+        location,
+        hourly_data,
+        source,
+        generated_at: chrono::Utc::now(),
+    }))
+}
+
+/// Fetch OneCall and the 3-hour forecast together and merge them into hours
+/// in the app timezone. Either source is enough; only both failing is an error.
+pub async fn fetch_merged_hourly(
+    config: &Config,
+    weather_client: &dyn WeatherProvider,
+    lat: f64,
+    lon: f64,
+) -> Result<Vec<HourlyData>, AppError> {
+    fetch_weather(config, weather_client, lat, lon).await.map(|fetched| fetched.merged_hours)
+}
+
+/// Merged hours for the location, reusing a fetch of the same ~1 km spot
+/// from the forecast cache unless it is older than `max_age`, with where
+/// they came from. A `scenario` replaces the provider with scripted weather.
+async fn cached_forecast(
+    state: &AppState,
+    lat: f64,
+    lon: f64,
+    max_age: Option<chrono::Duration>,
+    scenario: Option<MockScenario>,
+) -> Result<(Vec<HourlyData>, ForecastSource), AppError> {
+    let scripted;
+    let (client, key): (&dyn WeatherProvider, _) = match scenario {
+        Some(scenario) => {
+            scripted = MockWeatherClient::new().with_scenario(scenario);
+            (&scripted, format!("{:.2},{:.2}|{:?}", lat, lon, scenario))
+        }
+        None => (state.weather_client.as_ref(), format!("{:.2},{:.2}", lat, lon)),
+    };
+    evict_older_than(&state.forecasts, &key, max_age, |fetched| fetched.cached_at).await;
+    let entry = state
+        .forecasts
+        .entry(key)
+        .or_try_insert_with(fetch_weather(&state.config, client, lat, lon))
+        .await
+        .map_err(|e| (*e).clone())?;
+    let cache_hit = !entry.is_fresh();
+    let fetched = entry.into_value();
+    
+    let source = ForecastSource {
+        provider: client.name().to_string(),
+        fetched_at: fetched.cached_at,
+        cache_hit,
+        stale: chrono::Utc::now() - fetched.cached_at > chrono::Duration::minutes(FORECAST_STALE_AFTER_MINUTES),
+    };
+    Ok((fetched.merged_hours, source))
+}
+
+/// Drop `key` from `cache` if what it holds was fetched longer than
+/// `max_age` ago, so the next lookup fetches afresh
+async fn evict_older_than<V: Clone + Send + Sync + 'static>(
+    cache: &Cache<String, V>,
+    key: &str,
+    max_age: Option<chrono::Duration>,
+    fetched_at: impl Fn(&V) -> chrono::DateTime<chrono::Utc>,
+) {
+    let Some(max_age) = max_age else { return };
+    if let Some(value) = cache.get(key).await {
+        if chrono::Utc::now() - fetched_at(&value) >= max_age {
+            cache.invalidate(key).await;
+        }
+    }
+}
+
+/// [`fetch_merged_hourly`], keeping the raw responses alongside the merge
+async fn fetch_weather(
+    config: &Config,
+    weather_client: &dyn WeatherProvider,
+    lat: f64,
+    lon: f64,
+) -> Result<CachedForecastData, AppError> {
+    // Independent requests, so overlap them
+    let (onecall, forecast3h) = tokio::join!(
+        weather_client.get_onecall(lat, lon),
+        weather_client.get_forecast3h(lat, lon),
+    );
+    
+    if let (Err(onecall_error), Err(forecast3h_error)) = (&onecall, &forecast3h) {
+        tracing::error!(
+            "Failed to fetch any weather data (onecall: {}; forecast3h: {})",
+            onecall_error,
+            forecast3h_error
+        );
+        return Err(AppError::new(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "weather_unavailable",
+            "Weather data is unavailable, please retry shortly",
+        ));
     }
     
-    let merged_data = merge_weather_data(
+    let (onecall, forecast3h) = (onecall.ok(), forecast3h.ok());
+    let merged_hours = merge_weather_data_with(
         onecall.as_ref(),
         forecast3h.as_ref(),
-        7 * 3600 // Default timezone offset
+        config.timezone_offset_secs(),
+        config.forecast3h_mode,
     );
+    Ok(CachedForecastData {
+        onecall,
+        forecast3h,
+        merged_hours,
+        cached_at: chrono::Utc::now(),
+    })
+}
+
+/// The merged forecast, trimmed to the first `hours` hours
+async fn forecast_hours(
+    state: &AppState,
+    lat: f64,
+    lon: f64,
+    hours: u32,
+    max_age: Option<chrono::Duration>,
+    scenario: Option<MockScenario>,
+) -> Result<(Vec<HourlyData>, ForecastSource), AppError> {
+    let (merged_data, source) = cached_forecast(state, lat, lon, max_age, scenario).await?;
+    // Scripted hours aren't the location's forecast
+    if scenario.is_none() {
+        snapshot_forecast(state, lat, lon, &merged_data, &source).await;
+    }
     
-    let hourly_data: Vec<HourlyData> = merged_data
+    Ok((merged_data.into_iter().take(hours as usize).collect(), source))
+}
+
+/// Remember freshly fetched `hours` as the location's latest forecast,
+/// returning the one it replaces. Cached hours were remembered when they
+/// were fetched, so the latest is returned untouched.
+async fn snapshot_forecast(
+    state: &AppState,
+    lat: f64,
+    lon: f64,
+    hours: &[HourlyData],
+    source: &ForecastSource,
+) -> Option<ForecastSnapshot> {
+    let key = format!("{:.2},{:.2}", lat, lon);
+    let previous = state.forecast_snapshots.get(&key).await;
+    if source.cache_hit {
+        return previous;
+    }
+    let snapshot = ForecastSnapshot {
+        fetched_at: source.fetched_at,
+        hours: Arc::new(hours.to_vec()),
+    };
+    state.forecast_snapshots.insert(key, snapshot).await;
+    previous
+}
+
+/// What changed since the location's forecast was last fetched, by
+/// `/forecast` or here. The first fetch has nothing to compare against, and
+/// a cached forecast nothing new.
+pub async fn get_forecast_changes(
+    State(state): State<AppState>,
+    Query(params): Query<ForecastChangesQuery>,
+) -> Result<Json<ForecastChangesResponse>, AppError> {
+    let max_age = validate_max_age(params.max_age_secs)?;
+    let location = resolve_location(&state, params.q.as_deref(), params.lat, params.lon).await?;
+    let (hourly_data, source) = cached_forecast(&state, location.lat, location.lon, max_age, None).await?;
+    let previous = snapshot_forecast(&state, location.lat, location.lon, &hourly_data, &source).await;
+    
+    let changes = previous
+        .as_ref()
+        .map(|previous| {
+            diff_forecasts(
+                &previous.hours,
+                &hourly_data,
+                &state.config.scoring_config(None),
+                &state.config.score_thresholds,
+            )
+        })
+        .unwrap_or_default()
         .into_iter()
-        .take(hours as usize)
+        .map(|mut change| {
+            let score_decimals = state.config.output_precision.score;
+            change.before.score = round_to_decimals(change.before.score, score_decimals);
+            change.after.score = round_to_decimals(change.after.score, score_decimals);
+            change
+        })
         .collect();
-        
-    Ok(Json(ForecastResponse {
-        location: LocationInfo {
-            lat: params.lat,
-            lon: params.lon,
-            name: None,
-            country: None,
-        },
-        hourly_data,
+    
+    Ok(Json(ForecastChangesResponse {
+        location,
+        previous_fetched_at: previous.map(|previous| previous.fetched_at),
+        changes,
         generated_at: chrono::Utc::now(),
     }))
 }
 
-pub async fn get_drying_windows(
+/// Hours forecast in both `before` and `after` whose verdict flipped or that
+/// became (or stopped being) vetoed
+pub fn diff_forecasts(
+    before: &[HourlyData],
+    after: &[HourlyData],
+    scoring_config: &ScoringConfig,
+    thresholds: &ScoreThresholds,
+) -> Vec<HourChange> {
+    let outlook = |hour: &HourlyData| {
+        let score = calculate_drying_score_with(&WeatherFeatures::from(hour), &Default::default(), scoring_config);
+        HourOutlook {
+            score: score.score,
+            verdict: thresholds.grade(score.score),
+            veto_reason: score.veto_reason,
+        }
+    };
+    
+    after
+        .iter()
+        .filter_map(|hour| {
+            let earlier = before.iter().find(|earlier| earlier.ts == hour.ts)?;
+            let (before, after) = (outlook(earlier), outlook(hour));
+            let flipped = before.verdict != after.verdict || before.veto_reason.is_some() != after.veto_reason.is_some();
+            flipped.then_some(HourChange { ts: hour.ts, before, after })
+        })
+        .collect()
+}
+
+/// The hourly forecast as a CSV download
+pub async fn get_forecast_csv(
     State(state): State<AppState>,
-    Query(params): Query<DryingWindowsQuery>,
-) -> Result<Json<DryingWindowsResponse>, StatusCode> {
-    let window_hours = params.window_hours.unwrap_or(3).min(12);
-    let max_windows = params.max_windows.unwrap_or(10).min(20);
+    Query(params): Query<ForecastCsvQuery>,
+) -> Result<Response, AppError> {
+    let hours = validate_range("hours", params.hours, 48, state.config.max_forecast_hours)?;
+    let (hourly_data, _) = forecast_hours(&state, params.lat, params.lon, hours, None, None).await?;
+    let scoring_config = state.config.scoring_config(None);
     
-    // Get weather data
-    let onecall_result = state.weather_client.get_onecall(params.lat, params.lon).await;
-    let forecast3h_result = state.weather_client.get_forecast3h(params.lat, params.lon).await;
+    let mut csv = String::from("ts,temp_c,rh,wind_ms,cloud,rain_p,rain_mm");
+    if params.with_scores {
+        csv.push_str(",score");
+    }
+    csv.push('\n');
     
-    let onecall = onecall_result.ok();
-    let forecast3h = forecast3h_result.ok();
+    for hour in &hourly_data {
+        let _ = write!(
+            csv,
+            "{},{},{},{},{},{},{}",
+            hour.ts.to_rfc3339(),
+            hour.temp_c,
+            hour.rh,
+            hour.wind_ms,
+            hour.cloud,
+            hour.rain_p,
+            hour.rain_mm
+        );
+        if params.with_scores {
+            let score = calculate_drying_score_with(&WeatherFeatures::from(hour), &Default::default(), &scoring_config);
+            let _ = write!(csv, ",{:.3}", score.score);
+        }
+        csv.push('\n');
+    }
+    
+    Ok((
+        [
+            (header::CONTENT_TYPE, "text/csv; charset=utf-8"),
+            (header::CONTENT_DISPOSITION, "attachment; filename=\"forecast.csv\""),
+        ],
+        csv,
+    )
+        .into_response())
+}
+
+pub async fn get_drying_windows(
+    State(state): State<AppState>,
+    Query(params): Query<DryingWindowsQuery>,
+    headers: HeaderMap,
+) -> Result<Json<DryingWindowsResponse>, AppError> {
+    let window_hours = validate_range("window_hours", params.window_hours, 3, MAX_WINDOW_HOURS)?;
+    let max_windows = validate_range("max_windows", params.max_windows, 10, MAX_WINDOWS)?;
+    let rain_gap_hours = validate_rain_gap_hours(&state.config, params.rain_gap_hours)?;
+    let max_age = validate_max_age(params.max_age_secs)?;
+    let scenario = validate_scenario(&state.config, params.scenario)?;
+    let compare_user = params
+        .compare_weights
+        .then(|| {
+            params.user_id.ok_or_else(|| {
+                AppError::bad_request("invalid_parameter", "compare_weights requires a user_id")
+            })
+        })
+        .transpose()?;
+    let language = request_language(params.lang.as_deref(), &headers);
+    let location = resolve_location(&state, params.q.as_deref(), params.lat, params.lon).await?;
     
-    if onecall.is_none() && forecast3h.is_none() {
-        tracing::error!("Failed to fetch any weather data");
-        return Err(StatusCode::INTERNAL_SERVER_ERROR);
+    let mut scoring_config = ScoringConfig {
+        fabric: params.fabric,
+        ..state.config.scoring_config(params.scoring_model)
+    };
+    scoring_config.rain.gap_tolerance_hours = rain_gap_hours;
+    let (hourly_data, source) = cached_forecast(&state, location.lat, location.lon, max_age, scenario).await?;
+    let mut drying_windows = score_drying_windows(&state.config, &hourly_data, window_hours, &DryingWeights::default(), &scoring_config);
+    drying_windows.truncate(max_windows as usize);
+    if let Some(user_id) = compare_user {
+        let personal = state
+            .database
+            .get_user_weights(user_id)
+            .await
+            .map_err(|e| {
+                tracing::error!("Failed to load weights for {}: {}", user_id, e);
+                AppError::from(StatusCode::INTERNAL_SERVER_ERROR)
+            })?
+            .map(|trained| trained.weights)
+            .unwrap_or_default();
+        for window in &mut drying_windows {
+            let personal_score = calculate_drying_score_with(&window.score.raw, &personal, &scoring_config).score;
+            window.weight_comparison = Some(WeightComparison {
+                default_score: window.score.score,
+                personal_score,
+                delta: personal_score - window.score.score,
+            });
+        }
+    }
+    for window in &mut drying_windows {
+        window.round(&state.config.output_precision);
+        window.localize(language);
+        window.set_verbose(params.verbose);
     }
     
-    let hourly_data = merge_weather_data(
-        onecall.as_ref(),
-        forecast3h.as_ref(),
-        7 * 3600 // Default timezone offset
+    Ok(Json(DryingWindowsResponse {
+        location,
+        windows: drying_windows,
+        source,
+        generated_at: chrono::Utc::now(),
+    }))
+}
+
+/// Fetch, merge and score every window in the forecast horizon, best first
+pub async fn build_drying_windows(
+    config: &Config,
+    weather_client: &dyn WeatherProvider,
+    lat: f64,
+    lon: f64,
+    window_hours: u32,
+    scoring_config: &ScoringConfig,
+) -> Result<Vec<DryingWindow>, AppError> {
+    let hourly_data = fetch_merged_hourly(config, weather_client, lat, lon).await?;
+    Ok(score_drying_windows(config, &hourly_data, window_hours, &DryingWeights::default(), scoring_config))
+}
+
+/// Score every window of already merged hours, best first
+pub fn score_drying_windows(
+    config: &Config,
+    hourly_data: &[HourlyData],
+    window_hours: u32,
+    weights: &DryingWeights,
+    scoring_config: &ScoringConfig,
+) -> Vec<DryingWindow> {
+    // Group into windows averaged over smoothed hours
+    let windows = group_into_windows_with_gaps(
+        hourly_data,
+        window_hours as i32,
+        &scoring_config.rain,
+        scoring_config.rain.gap_tolerance_hours,
+        config.smoothing_window,
     );
     
-    // Group into windows
-    let windows = group_into_windows(&hourly_data, window_hours as i32);
-    
     // Calculate scores and create response
+    let scores = score_windows_with(&windows, weights, scoring_config);
     let mut drying_windows: Vec<DryingWindow> = windows
         .into_iter()
-        .map(|window| {
-            let features = WeatherFeatures {
-                temp_c: window.weather.temp_c,
-                rh: window.weather.rh,
-                wind_ms: window.weather.wind_ms,
-                cloud: window.weather.cloud,
-                rain_p: 0.0, // Rain probability placeholder
-                rain_mm: window.weather.rain_mm,
-            };
-            
-            let score = calculate_drying_score(&features, &Default::default());
-            
-            let conditions = if window.weather.rain_mm > 0.1 {
-                "Rainy".to_string()
-            } else if window.weather.cloud > 80.0 {
-                "Cloudy".to_string()
-            } else if window.weather.cloud < 30.0 {
-                "Sunny".to_string()
-            } else {
-                "Partly Cloudy".to_string()
-            };
+        .zip(scores)
+        .map(|(window, score)| {
+            let score_range = window
+                .temp_range
+                .and_then(|range| temperature_score_range(&window.weather, &score, range, scoring_config));
             
-            let recommendation = if score.score > 0.8 {
-                "Excellent drying conditions!".to_string()
-            } else if score.score > 0.6 {
-                "Good drying conditions".to_string()
-            } else if score.score > 0.4 {
-                "Fair drying conditions".to_string()
-            } else {
-                "Poor drying conditions".to_string()
-            };
+            let total_rain_mm = window.weather.rain_mm + window.rain_gap_mm;
+            let condition = Condition::from_weather(window.weather.cloud, total_rain_mm);
+            let verdict = config.score_thresholds.grade(score.score);
+            let uv_advice = uv_advice(window.uv_index, scoring_config.fabric);
             
             DryingWindow {
                 id: format!("window_{}_{}", window.start_time.timestamp(), window_hours),
                 start_time: window.start_time.into(),
                 end_time: window.end_time.into(),
-                duration_hours: window_hours,
+                duration_hours: (window.end_time - window.start_time).num_hours() as u32,
                 score,
+                // Temperature and humidity as forecast, so the average sits
+                // within the min and max beside it
                 weather_summary: WeatherSummary {
-                    avg_temp_c: window.weather.temp_c,
-                    avg_humidity: window.weather.rh,
+                    avg_temp_c: window.temp_stats.mean,
+                    avg_humidity: window.humidity_stats.mean,
                     avg_wind_ms: window.weather.wind_ms,
-                    total_rain_mm: window.weather.rain_mm,
-                    conditions,
+                    avg_wind_deg: window.weather.wind_deg,
+                    avg_wind_resultant_ms: window.wind_resultant_ms,
+                    min_temp_c: window.temp_stats.min,
+                    max_temp_c: window.temp_stats.max,
+                    min_humidity: window.humidity_stats.min,
+                    max_humidity: window.humidity_stats.max,
+                    total_rain_mm,
+                    uv_index: window.uv_index,
+                    temp_range: window.temp_range,
+                    conditions: Language::En.condition(condition).to_string(),
                 },
-                recommendation,
+                recommendation: Language::En.verdict(verdict).to_string(),
+                warnings: window_warnings(&window, uv_advice.as_ref(), score_range),
+                notes: uv_advice.as_ref().and_then(UvAdvice::note).into_iter().collect(),
+                score_range,
+                weight_comparison: None,
+                condition,
+                verdict,
+                compact: false,
             }
         })
         .collect();
     
-    // Sort by score (best first) and limit
+    // Sort by score (best first)
     drying_windows.sort_by(|a, b| b.score.score.partial_cmp(&a.score.score).unwrap());
-    drying_windows.truncate(max_windows as usize);
     
-    Ok(Json(DryingWindowsResponse {
-        location: LocationInfo {
-            lat: params.lat,
-            lon: params.lon,
-            name: None,
-            country: None,
-        },
-        windows: drying_windows,
-        generated_at: chrono::Utc::now(),
+    drying_windows
+}
+
+/// Scores at either end of `range`, widened to include the point score.
+/// `None` for vetoed windows, whose score doesn't depend on temperature.
+fn temperature_score_range(
+    features: &WeatherFeatures,
+    score: &DryingScore,
+    range: TempRange,
+    scoring_config: &ScoringConfig,
+) -> Option<ScoreRange> {
+    if score.unsafe_window {
+        return None;
+    }
+    let at = |temp_c: f64| {
+        let weather = WeatherFeatures {
+            temp_c,
+            ..features.clone()
+        };
+        // A vetoed end (e.g. freezing at the day's low) counts as no drying
+        calculate_drying_score_with(&weather, &Default::default(), scoring_config).score.max(0.0)
+    };
+    let ends = [at(range.min_c), at(range.max_c), score.score];
+    
+    Some(ScoreRange {
+        worst: ends.iter().copied().fold(f64::INFINITY, f64::min),
+        best: ends.iter().copied().fold(f64::NEG_INFINITY, f64::max),
+    })
+}
+
+/// The forecast's own warnings plus those that come from scoring: strong sun
+/// for the load, and a score that could swing widely
+fn window_warnings(window: &WindowData, uv_advice: Option<&UvAdvice>, score_range: Option<ScoreRange>) -> Vec<Warning> {
+    let mut warnings = window.warnings();
+    warnings.extend(uv_advice.and_then(UvAdvice::warning));
+    if let Some(range) = score_range.filter(|r| r.best - r.worst >= LOW_CONFIDENCE_SPREAD) {
+        warnings.push(Warning::new(
+            WarningCode::LowConfidence,
+            format!(
+                "Far-ahead forecast: the score could be anywhere from {:.2} to {:.2}",
+                range.worst, range.best
+            ),
+        ));
+    }
+    warnings
+}
+
+/// UV index from which sun noticeably bleaches fabric
+const HIGH_UV_INDEX: f64 = 6.0;
+
+/// What strong sun means for the load on the line
+#[derive(Debug, Clone, PartialEq)]
+enum UvAdvice {
+    /// Bleaches and freshens whites and towels
+    Bonus(String),
+    /// Fades colours and weakens delicate fibres
+    Fade(String),
+}
+
+impl UvAdvice {
+    fn note(&self) -> Option<String> {
+        match self {
+            UvAdvice::Bonus(note) => Some(note.clone()),
+            UvAdvice::Fade(_) => None,
+        }
+    }
+
+    fn warning(&self) -> Option<Warning> {
+        match self {
+            UvAdvice::Fade(warning) => Some(Warning::new(WarningCode::HighUv, warning.clone())),
+            UvAdvice::Bonus(_) => None,
+        }
+    }
+}
+
+/// Advice for a high-UV window, only when the caller named the fabric
+fn uv_advice(uv_index: Option<f64>, fabric: Option<Fabric>) -> Option<UvAdvice> {
+    let uv = uv_index.filter(|uv| *uv >= HIGH_UV_INDEX)?;
+    Some(match fabric? {
+        Fabric::Whites | Fabric::Cotton => UvAdvice::Bonus(format!(
+            "High UV ({:.0}) helps brighten and freshen whites and towels",
+            uv
+        )),
+        Fabric::Colors | Fabric::Delicates | Fabric::Synthetic => UvAdvice::Fade(format!(
+            "High UV ({:.0}) fades bright colours and weakens delicate fibres - hang inside out or in the shade",
+            uv
+        )),
+    })
+}
+
+/// Rainy hours a window may contain for this request, defaulting to the
+/// deployment's setting
+fn validate_rain_gap_hours(config: &Config, requested: Option<u32>) -> Result<u32, AppError> {
+    match requested {
+        None => Ok(config.rain.gap_tolerance_hours),
+        Some(hours) if hours <= MAX_WINDOW_HOURS => Ok(hours),
+        Some(hours) => Err(AppError::bad_request(
+            "invalid_parameter",
+            format!("rain_gap_hours must be between 0 and {} (got {})", MAX_WINDOW_HOURS, hours),
+        )),
+    }
+}
+
+/// Longest `max_age_secs` accepted: a day-old forecast is stale for any purpose
+const MAX_FORECAST_AGE_SECS: u32 = 24 * 60 * 60;
+
+/// How fresh the caller needs the forecast, if they said
+fn validate_max_age(requested: Option<u32>) -> Result<Option<chrono::Duration>, AppError> {
+    match requested {
+        Some(secs) if secs > MAX_FORECAST_AGE_SECS => Err(AppError::bad_request(
+            "invalid_parameter",
+            format!("max_age_secs must be between 0 and {} (got {})", MAX_FORECAST_AGE_SECS, secs),
+        )),
+        secs => Ok(secs.map(|secs| chrono::Duration::seconds(secs.into()))),
+    }
+}
+
+/// Scripted weather is for demos and tests, never what real users are shown
+fn validate_scenario(config: &Config, requested: Option<MockScenario>) -> Result<Option<MockScenario>, AppError> {
+    if requested.is_some() && !config.debug_endpoints {
+        return Err(AppError::bad_request(
+            "invalid_parameter",
+            "scenario is only available with DEBUG_ENDPOINTS=true",
+        ));
+    }
+    Ok(requested)
+}
+
+/// Windows after the upcoming one averaged to judge where conditions are heading
+const URGENCY_LOOKAHEAD: usize = 3;
+/// Score gap that makes a later window "much better", or conditions "worse"
+const URGENCY_MARGIN: f64 = 0.15;
+
+/// Compare the upcoming window with the best one and with the rolling average
+/// of the few windows that follow it
+pub fn assess_urgency(
+    windows: &[DryingWindow],
+    thresholds: &ScoreThresholds,
+    now: chrono::DateTime<chrono::Utc>,
+) -> Urgency {
+    let mut upcoming: Vec<&DryingWindow> = windows.iter().filter(|w| w.end_time > now).collect();
+    upcoming.sort_by_key(|w| w.start_time);
+    // Vetoed windows carry -1.0; for trends they're simply as bad as it gets
+    let score = |w: &DryingWindow| w.score.score.max(0.0);
+    
+    let Some(current) = upcoming.first() else {
+        return Urgency::Flexible;
+    };
+    let best = upcoming
+        .iter()
+        .map(|w| score(w))
+        .fold(f64::NEG_INFINITY, f64::max);
+    if best - score(current) >= URGENCY_MARGIN {
+        return Urgency::Wait;
+    }
+    
+    let later: Vec<f64> = upcoming[1..].iter().take(URGENCY_LOOKAHEAD).map(|w| score(w)).collect();
+    let degrading = !later.is_empty()
+        && later.iter().sum::<f64>() / later.len() as f64 <= score(current) - URGENCY_MARGIN;
+    if degrading && !current.score.unsafe_window && thresholds.is_good(current.score.score) {
+        Urgency::ActNow
+    } else {
+        Urgency::Flexible
+    }
+}
+
+/// Soonest window the thresholds rate as good, with a countdown from `now`
+pub fn find_next_good_window(
+    windows: &[DryingWindow],
+    thresholds: &ScoreThresholds,
+    now: chrono::DateTime<chrono::Utc>,
+) -> Option<NextGoodWindow> {
+    windows
+        .iter()
+        .filter(|w| !w.score.unsafe_window && thresholds.is_good(w.score.score) && w.end_time > now)
+        .min_by_key(|w| w.start_time)
+        .map(|w| {
+            let minutes = (w.start_time - now).num_minutes().max(0);
+            let starts_in_hours = ((minutes + 59) / 60) as u32;
+            let countdown = if starts_in_hours == 0 {
+                "now".to_string()
+            } else {
+                format!("in {}", format_duration(starts_in_hours))
+            };
+            
+            NextGoodWindow {
+                window_id: w.id.clone(),
+                start_time: w.start_time,
+                score: w.score.score,
+                starts_in_hours,
+                countdown,
+            }
+        })
+}
+
+/// How far ahead `/best-day` looks
+const BEST_DAY_HORIZON_DAYS: i64 = 7;
+
+/// The best usable window in the next week, at least fair and not vetoed.
+/// Equal scores go to the earlier window, so ties break toward the earlier day.
+pub fn find_best_day_window<'a>(
+    windows: &'a [DryingWindow],
+    thresholds: &ScoreThresholds,
+    now: chrono::DateTime<chrono::Utc>,
+) -> Option<&'a DryingWindow> {
+    let horizon = now + chrono::Duration::days(BEST_DAY_HORIZON_DAYS);
+    let mut candidates: Vec<&DryingWindow> = windows
+        .iter()
+        .filter(|w| !w.score.unsafe_window && w.score.score > thresholds.fair)
+        .filter(|w| w.end_time > now && w.start_time < horizon)
+        .collect();
+    candidates.sort_by_key(|w| w.start_time);
+    candidates
+        .into_iter()
+        .reduce(|best, w| if w.score.score > best.score.score { w } else { best })
+}
+
+/// One line on why the window's day wins the week
+fn best_day_reason(window: &DryingWindow, start: chrono::DateTime<chrono::FixedOffset>) -> String {
+    let summary = &window.weather_summary;
+    format!(
+        "{} has the week's best drying window from {}: {} ({:.2}), {:.0}°C, {:.0}% humidity and {:.1} m/s wind",
+        start.format("%A"),
+        start.format("%H:%M"),
+        window.recommendation.to_lowercase(),
+        window.score.score,
+        summary.avg_temp_c,
+        summary.avg_humidity,
+        summary.avg_wind_ms
+    )
+}
+
+pub async fn get_best_day(
+    State(state): State<AppState>,
+    Query(params): Query<BestDayQuery>,
+) -> Result<Json<BestDayResponse>, AppError> {
+    let window_hours = validate_range("window_hours", params.window_hours, 3, MAX_WINDOW_HOURS)?;
+    let max_age = validate_max_age(params.max_age_secs)?;
+    let scenario = validate_scenario(&state.config, params.scenario)?;
+    let location = resolve_location(&state, params.q.as_deref(), params.lat, params.lon).await?;
+    
+    let (hourly_data, _) = cached_forecast(&state, location.lat, location.lon, max_age, scenario).await?;
+    let windows = score_drying_windows(
+        &state.config,
+        &hourly_data,
+        window_hours,
+        &DryingWeights::default(),
+        &state.config.scoring_config(None),
+    );
+    
+    let now = chrono::Utc::now();
+    let offset = chrono::FixedOffset::east_opt(state.config.timezone_offset_secs())
+        .unwrap_or(chrono::FixedOffset::east_opt(0).unwrap());
+    let best = find_best_day_window(&windows, &state.config.score_thresholds, now).cloned();
+    
+    let (date, best_window, reason, message) = match best {
+        Some(mut window) => {
+            let start = window.start_time.with_timezone(&offset);
+            let reason = best_day_reason(&window, start);
+            window.round(&state.config.output_precision);
+            window.set_verbose(params.verbose);
+            (Some(start.date_naive()), Some(window), Some(reason), None)
+        }
+        None => (
+            None,
+            None,
+            None,
+            Some("No day in the next week has a usable drying window - rain or poor conditions throughout".to_string()),
+        ),
+    };
+    
+    Ok(Json(BestDayResponse {
+        location,
+        date,
+        best_window,
+        reason,
+        message,
+        generated_at: now,
+    }))
+}
+
+/// Sunrise, sunset, solar noon and the sun's bearing through the day. Today's
+/// sunrise and sunset come from the forecast when it has them.
+pub async fn get_sun(
+    State(state): State<AppState>,
+    Query(params): Query<SunQuery>,
+) -> Result<Json<SunResponse>, AppError> {
+    let location = resolve_location(&state, params.q.as_deref(), params.lat, params.lon).await?;
+    let offset = chrono::FixedOffset::east_opt(state.config.timezone_offset_secs())
+        .unwrap_or(chrono::FixedOffset::east_opt(0).unwrap());
+    let today = chrono::Utc::now().with_timezone(&offset).date_naive();
+    let date = params.date.unwrap_or(today);
+    
+    let calculated = sun_times(location.lat, location.lon, date);
+    let forecast = if date == today {
+        forecast_sun_times(state.weather_client.as_ref(), &location, offset, date).await
+    } else {
+        None
+    };
+    let (times, source) = match forecast {
+        Some(times) => (times, SunTimesSource::Forecast),
+        None => (calculated, SunTimesSource::Calculated),
+    };
+    
+    let local_midnight = date
+        .and_hms_opt(0, 0, 0)
+        .expect("midnight exists")
+        .and_local_timezone(offset)
+        .single()
+        .expect("fixed offsets are unambiguous");
+    let path = (0..24)
+        .map(|hour| local_midnight + chrono::Duration::hours(hour))
+        .filter_map(|ts| {
+            let position = sun_position(location.lat, location.lon, ts.with_timezone(&chrono::Utc));
+            (position.elevation_deg > 0.0).then(|| SunPathPoint {
+                ts,
+                azimuth_deg: position.azimuth_deg.round(),
+                elevation_deg: round_to_decimals(position.elevation_deg, 1),
+                facing: compass_point(position.azimuth_deg),
+            })
+        })
+        .collect();
+    
+    Ok(Json(SunResponse {
+        location,
+        date,
+        sunrise: times.sunrise.map(|t| t.with_timezone(&offset)),
+        solar_noon: times.solar_noon.with_timezone(&offset),
+        sunset: times.sunset.map(|t| t.with_timezone(&offset)),
+        source,
+        path,
     }))
 }
 
+/// Sunrise and sunset from the 3-hour forecast's city data, when it has them
+/// for `date`; solar noon falls halfway between
+async fn forecast_sun_times(
+    weather_client: &dyn WeatherProvider,
+    location: &LocationInfo,
+    offset: chrono::FixedOffset,
+    date: chrono::NaiveDate,
+) -> Option<SunTimes> {
+    let city = weather_client
+        .get_forecast3h(location.lat, location.lon)
+        .await
+        .map_err(|e| tracing::warn!("No forecast sun times, calculating instead: {}", e))
+        .ok()?
+        .city;
+    let sunrise = chrono::DateTime::from_timestamp(city.sunrise, 0)?;
+    let sunset = chrono::DateTime::from_timestamp(city.sunset, 0)?;
+    if sunrise >= sunset || sunrise.with_timezone(&offset).date_naive() != date {
+        return None;
+    }
+    
+    Some(SunTimes {
+        sunrise: Some(sunrise),
+        solar_noon: sunrise + (sunset - sunrise) / 2,
+        sunset: Some(sunset),
+    })
+}
+
 pub async fn get_recommendations(
     State(state): State<AppState>,
     Query(params): Query<RecommendationQuery>,
-) -> Result<Json<RecommendationResponse>, StatusCode> {
-    let window_hours = params.window_hours.unwrap_or(3);
+    headers: HeaderMap,
+) -> Result<Json<RecommendationResponse>, AppError> {
+    let window_hours = validate_range("window_hours", params.window_hours, 3, MAX_WINDOW_HOURS)?;
+    let max_windows = validate_range("max_windows", params.max_windows, 3, MAX_WINDOWS)?;
+    let min_dry_hours = params
+        .min_dry_hours
+        .map(|hours| validate_range("min_dry_hours", Some(hours), 1, MAX_WINDOW_HOURS))
+        .transpose()?;
+    let max_tips = validate_range("max_tips", params.max_tips, 3, MAX_TIPS)?;
+    let rain_gap_hours = validate_rain_gap_hours(&state.config, params.rain_gap_hours)?;
+    let max_age = validate_max_age(params.max_age_secs)?;
+    let scenario = validate_scenario(&state.config, params.scenario)?;
+    let location = match params.location_label.as_deref() {
+        Some(label) => resolve_saved_location(&state, params.user_id, label).await?,
+        None => resolve_location(&state, params.q.as_deref(), params.lat, params.lon).await?,
+    };
+    
+    // Recommendations are scored with the user's trained weights. Until
+    // those load they get the defaults, which mustn't be cached as theirs.
+    let (weights, cacheable) = match user_weights(&state, params.user_id).await {
+        Ok(weights) => (weights, true),
+        Err(_) => (None, false),
+    };
+    // Users unhappy with recent results get a stricter reading until they
+    // recover, which must not be served from the cache once they have
+    let caution = conservative_adjustment(&state, params.user_id).await;
+    // Rounded to ~1 km so neighbours asking at the same moment share one
+    // result; retrained weights start a fresh one
+    let key = format!(
+        "{:.2},{:.2}|{}|{}|{:?}|{}|{:?}|{:?}|{}|{:?}|{:?}|{}|{:?}|{}",
+        location.lat,
+        location.lon,
+        window_hours,
+        max_windows,
+        min_dry_hours,
+        max_tips,
+        params.scoring_model,
+        params.fabric,
+        rain_gap_hours,
+        params.user_id,
+        weights.as_ref().map(DryingWeights::fingerprint),
+        params.sparkline,
+        scenario,
+        caution
+    );
+    let settings = RecommendationSettings {
+        window_hours,
+        max_windows,
+        min_dry_hours,
+        max_tips,
+        rain_gap_hours,
+        max_age,
+        scenario,
+        caution,
+        weights: weights.unwrap_or_default(),
+    };
+    let (mut response, cache_hit) = if cacheable {
+        // A cached result is only as fresh as the forecast it was built from
+        evict_older_than(&state.recommendations, &key, max_age, |response| response.source.fetched_at).await;
+        let entry = state
+            .recommendations
+            .entry(key)
+            .or_try_insert_with(build_recommendations(&state, &params, &location, settings))
+            .await
+            .map_err(|e| (*e).clone())?;
+        let cache_hit = !entry.is_fresh();
+        (entry.into_value(), cache_hit)
+    } else {
+        (build_recommendations(&state, &params, &location, settings).await?, false)
+    };
+    response.location = location;
+    response.source.cache_hit |= cache_hit;
+    // Shared across languages in the cache, so localized per request
+    let language = request_language(params.lang.as_deref(), &headers);
+    for window in &mut response.best_windows {
+        window.localize(language);
+        window.set_verbose(params.verbose);
+    }
     
+    Ok(Json(response))
+}
+
+/// The user's trained weights, if they have any
+async fn user_weights(state: &AppState, user_id: Option<Uuid>) -> Result<Option<DryingWeights>, DatabaseError> {
+    let Some(user_id) = user_id else {
+        return Ok(None);
+    };
+    let trained = state.database.get_user_weights(user_id).await.map_err(|e| {
+        tracing::warn!("Failed to load weights for {}: {}", user_id, e);
+        e
+    })?;
+    Ok(trained.map(|trained| trained.weights))
+}
+
+/// `/recommendations` parameters once validated, with the user's caution
+struct RecommendationSettings {
+    window_hours: u32,
+    max_windows: u32,
+    min_dry_hours: Option<u32>,
+    max_tips: u32,
+    rain_gap_hours: u32,
+    max_age: Option<chrono::Duration>,
+    scenario: Option<MockScenario>,
+    caution: f64,
+    /// The user's trained weights, or the defaults
+    weights: DryingWeights,
+}
+
+/// Everything behind `/recommendations` once the parameters are validated
+async fn build_recommendations(
+    state: &AppState,
+    params: &RecommendationQuery,
+    location: &LocationInfo,
+    settings: RecommendationSettings,
+) -> Result<RecommendationResponse, AppError> {
+    let RecommendationSettings {
+        window_hours,
+        max_windows,
+        min_dry_hours,
+        max_tips,
+        rain_gap_hours,
+        max_age,
+        scenario,
+        caution,
+        weights,
+    } = settings;
     // Get user preferences if user_id provided
-    let _user_prefs = if let Some(user_id) = params.user_id {
+    let user_prefs = if let Some(user_id) = params.user_id {
         state.database.get_user_preferences(user_id).await.ok()
     } else {
         None
     };
     
-    // Get drying windows (reuse the logic)
-    let windows_query = DryingWindowsQuery {
-        lat: params.lat,
-        lon: params.lon,
-        window_hours: Some(window_hours),
-        max_windows: Some(3), // Top 3 for recommendations
+    // An explicit query parameter wins over the user's saved model
+    let scoring_model = params
+        .scoring_model
+        .or_else(|| user_prefs.as_ref().and_then(|prefs| prefs.scoring_model));
+    let mut scoring_config = ScoringConfig {
+        fabric: params.fabric,
+        ..state.config.scoring_config(scoring_model)
     };
+    scoring_config.rain.gap_tolerance_hours = rain_gap_hours;
+    scoring_config.rain = scoring_config.rain.stricter(caution);
+    let thresholds = state.config.score_thresholds.raised(caution);
     
-    let windows_response = get_drying_windows(State(state.clone()), Query(windows_query)).await?;
-    let windows_data = windows_response.0;
+    // Windows must cover the whole drying time, so widen them if needed
+    let window_hours = window_hours.max(min_dry_hours.unwrap_or(0));
     
-    // Generate AI explanation for the best window
-    let ai_explanation = if let Some(best_window) = windows_data.windows.first() {
-        let weather_features = WeatherFeatures {
-            temp_c: best_window.weather_summary.avg_temp_c,
-            rh: best_window.weather_summary.avg_humidity,
-            wind_ms: best_window.weather_summary.avg_wind_ms,
-            cloud: 50.0, // Default cloud coverage
-            rain_p: if best_window.weather_summary.total_rain_mm > 0.0 { 0.8 } else { 0.0 },
-            rain_mm: best_window.weather_summary.total_rain_mm,
-        };
-        
-        let window_data = vec![(
-            best_window.start_time.to_string(),
-            best_window.score.clone(),
-            weather_features,
-        )];
-        
-        state.ai_client
-            .explain_recommendation(
-                &window_data,
-                None,
+    // Score the whole horizon so the next good window isn't cut off by the top 3
+    let (hourly_data, source) = cached_forecast(state, location.lat, location.lon, max_age, scenario).await?;
+    let mut windows = score_drying_windows(&state.config, &hourly_data, window_hours, &weights, &scoring_config);
+    if let Some(min_dry_hours) = min_dry_hours {
+        // Drop the truncated window at the end of the horizon
+        windows.retain(|w| w.duration_hours >= min_dry_hours);
+    }
+    let sparkline = params
+        .sparkline
+        .then(|| score_sparkline(&state.config, &hourly_data, &weights, &scoring_config));
+    
+    let now = chrono::Utc::now();
+    let next_good_window = find_next_good_window(&windows, &thresholds, now);
+    let urgency = assess_urgency(&windows, &thresholds, now);
+    let message = match (&next_good_window, min_dry_hours) {
+        (Some(_), _) if urgency == Urgency::ActNow => {
+            Some("Dry now - conditions get worse later.".to_string())
+        }
+        (Some(_), _) => None,
+        (None, Some(hours)) => Some(format!(
+            "No good drying window of at least {} in the forecast horizon. Consider indoor drying.",
+            format_duration(hours)
+        )),
+        (None, None) => Some("No good drying window in the forecast horizon. Consider indoor drying.".to_string()),
+    };
+    
+    windows.truncate(max_windows as usize);
+    
+    // Users without feedback of their own borrow outcomes from nearby users
+    let nearby = if state.config.nearby_feedback
+        && is_cold_start(state.database.as_ref(), params.user_id).await
+    {
+        nearby_feedback(state.database.as_ref(), location.lat, location.lon, NEARBY_RADIUS_KM)
+            .await
+            .unwrap_or_else(|e| {
+                tracing::warn!("Failed to load nearby feedback: {}", e);
+                None
+            })
+    } else {
+        None
+    };
+    let confidence = blend_confidence(
+        windows.first().map(|w| w.score.score).unwrap_or(0.0),
+        nearby.as_ref(),
+    );
+    
+    // Explain and advise on the best window only, to bound AI cost
+    let best_weather = windows.first().map(|best_window| best_window.score.raw.clone());
+    
+    let (ai_explanation, mut tips) = match (windows.first().zip(best_weather), state.config.tips_engine) {
+        (Some((best_window, weather_features)), TipsEngine::Heuristic) => (
+            Some(heuristic_explanation(&weather_features, &best_window.score)),
+            heuristic_tips(&weather_features),
+        ),
+        (Some((best_window, weather_features)), TipsEngine::Ai) => {
+            let window_data = vec![(
+                best_window.start_time.to_string(),
+                best_window.score.clone(),
+                weather_features.clone(),
+            )];
+            
+            let ai_explanation = state.ai_client
+                .explain_recommendation(
+                    &window_data,
+                    None,
+                )
+                .await
+                .ok();
+            
+            let text = state.ai_client
+                .generate_drying_tips(&weather_features, &best_window.score)
+                .await
+                .unwrap_or_else(|_| "Check weather conditions before hanging clothes. Avoid drying during rain or high humidity. Wind helps with faster drying.".to_string());
+            (ai_explanation, split_tips(&text))
+        }
+        (None, _) => (
+            None,
+            vec![
+                "Check weather conditions before hanging clothes".to_string(),
+                "Avoid drying during rain or high humidity".to_string(),
+                "Wind helps with faster drying".to_string(),
+            ],
+        ),
+    };
+    tips.truncate(max_tips as usize);
+    
+    let precision = &state.config.output_precision;
+    for window in &mut windows {
+        window.round(precision);
+    }
+    let next_good_window = next_good_window.map(|next| NextGoodWindow {
+        score: round_to_decimals(next.score, precision.score),
+        ..next
+    });
+    let sparkline = sparkline.map(|points| {
+        points
+            .into_iter()
+            .map(|point| SparklinePoint {
+                score: round_to_decimals(point.score, precision.score),
+                ..point
+            })
+            .collect()
+    });
+    
+    if state.config.persist_windows {
+        persist_served_windows(state.database.as_ref(), location, &windows).await;
+    }
+    
+    let mut warnings = windows.first().map(|best| best.warnings.clone()).unwrap_or_default();
+    if !windows.is_empty() && confidence < LOW_CONFIDENCE {
+        push_unique(
+            &mut warnings,
+            Warning::new(
+                WarningCode::LowConfidence,
+                format!("Low confidence ({:.2}) in this recommendation", confidence),
+            ),
+        );
+    }
+    
+    Ok(RecommendationResponse {
+        location: location.clone(),
+        best_windows: windows,
+        next_good_window,
+        message,
+        urgency,
+        confidence: round_to_decimals(confidence, precision.score),
+        nearby_feedback: nearby,
+        ai_explanation,
+        tips,
+        warnings,
+        sparkline,
+        source,
+        generated_at: chrono::Utc::now(),
+    })
+}
+
+/// Score every hour on its own, smoothed as for [`score_drying_windows`], and
+/// average consecutive hours down to at most the configured number of points.
+/// A vetoed hour plots as no drying rather than dragging its neighbours below
+/// zero.
+pub fn score_sparkline(
+    config: &Config,
+    hourly_data: &[HourlyData],
+    weights: &DryingWeights,
+    scoring_config: &ScoringConfig,
+) -> Vec<SparklinePoint> {
+    let hourly: Vec<SparklinePoint> = smooth_hourly(hourly_data, config.smoothing_window)
+        .iter()
+        .map(|hour| SparklinePoint {
+            ts: hour.ts,
+            score: calculate_drying_score_with(&WeatherFeatures::from(hour), weights, scoring_config).score.max(0.0),
+        })
+        .collect();
+    
+    let bucket = hourly.len().div_ceil(config.sparkline_max_points.max(1)).max(1);
+    hourly
+        .chunks(bucket)
+        .map(|chunk| SparklinePoint {
+            ts: chunk[0].ts,
+            score: chunk.iter().map(|point| point.score).sum::<f64>() / chunk.len() as f64,
+        })
+        .collect()
+}
+
+/// Keep a snapshot of each window as served. Failures are only logged: a
+/// recommendation is still worth returning without its feedback linkage.
+async fn persist_served_windows(store: &dyn DataStore, location: &LocationInfo, windows: &[DryingWindow]) {
+    let served_at = chrono::Utc::now();
+    let snapshots: Vec<ServedWindow> = windows
+        .iter()
+        .map(|window| ServedWindow {
+            window_id: window.id.clone(),
+            lat: served_coordinate(location.lat),
+            lon: served_coordinate(location.lon),
+            start_time: window.start_time,
+            end_time: window.end_time,
+            score: sqlx::types::Json(window.score.clone()),
+            served_at,
+        })
+        .collect();
+    if let Err(e) = store.save_served_windows(&snapshots).await {
+        tracing::warn!("Failed to persist served windows: {}", e);
+    }
+}
+
+/// Served windows are keyed by location to ~1 km, as window ids only
+/// encode the start time and length
+fn served_coordinate(degrees: f64) -> f64 {
+    round_to_decimals(degrees, 2)
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ServedWindowQuery {
+    pub lat: f64,
+    pub lon: f64,
+}
+
+/// A window exactly as it was served, for linking feedback to its prediction
+pub async fn get_served_window(
+    State(state): State<AppState>,
+    Path(window_id): Path<String>,
+    Query(params): Query<ServedWindowQuery>,
+) -> Result<Json<ServedWindow>, AppError> {
+    state
+        .database
+        .get_served_window(&window_id, served_coordinate(params.lat), served_coordinate(params.lon))
+        .await
+        .map_err(|e| {
+            tracing::error!("Failed to load served window {}: {}", window_id, e);
+            AppError::from(StatusCode::INTERNAL_SERVER_ERROR)
+        })?
+        .map(Json)
+        .ok_or_else(|| {
+            AppError::new(
+                StatusCode::NOT_FOUND,
+                "window_not_found",
+                format!("No served window with id {}", window_id),
             )
+        })
+}
+
+/// True when the user (if any) hasn't left feedback of their own yet
+async fn is_cold_start(store: &dyn DataStore, user_id: Option<Uuid>) -> bool {
+    match user_id {
+        Some(user_id) => store
+            .get_user_feedback(user_id, Some(1))
             .await
-            .ok()
+            .map(|feedback| feedback.is_empty())
+            .unwrap_or(true),
+        None => true,
+    }
+}
+
+/// Satisfaction EMA (1-5) below which a user's recommendations turn conservative
+const LOW_SATISFACTION_EMA: f64 = 3.0;
+
+/// How much stricter to be with the user (if any): the configured
+/// adjustment while their recent satisfaction is low, zero otherwise
+async fn conservative_adjustment(state: &AppState, user_id: Option<Uuid>) -> f64 {
+    let by = state.config.conservative_adjustment;
+    let Some(user_id) = user_id.filter(|_| by > 0.0) else {
+        return 0.0;
+    };
+    match state.database.get_user_satisfaction_ema(user_id).await {
+        Ok(Some(ema)) if ema < LOW_SATISFACTION_EMA => by,
+        Ok(_) => 0.0,
+        Err(e) => {
+            tracing::warn!("Failed to load satisfaction for {}: {}", user_id, e);
+            0.0
+        }
+    }
+}
+
+/// Where the feedback's window was served: the request's coordinates, or
+/// the user's saved location
+async fn feedback_location(state: &AppState, request: &FeedbackRequest) -> Option<(f64, f64)> {
+    if let (Some(lat), Some(lon)) = (request.lat, request.lon) {
+        return Some((lat, lon));
+    }
+    let prefs = state.database.get_user_preferences(request.user_id?).await.ok()?;
+    prefs.location_lat.zip(prefs.location_lon)
+}
+
+pub async fn submit_feedback(
+    State(state): State<AppState>,
+    Json(request): Json<FeedbackRequest>,
+) -> Result<Json<FeedbackResponse>, AppError> {
+    check_feedback_allowed(&state, &request).await?;
+    
+    // Anything the user didn't report comes from the window as it was served
+    let location = if state.config.persist_windows {
+        feedback_location(&state, &request).await
     } else {
         None
     };
+    let served = match location {
+        Some((lat, lon)) => state
+            .database
+            .get_served_window(&request.window_id, served_coordinate(lat), served_coordinate(lon))
+            .await
+            .unwrap_or_else(|e| {
+                tracing::warn!("Failed to load served window {}: {}", request.window_id, e);
+                None
+            }),
+        None => None,
+    };
+    let served_weather = served.as_ref().map(|window| &window.score.raw);
+    let weather = request.weather_conditions.as_ref();
+    let conditions = WeatherConditions {
+        temp_c: weather.and_then(|w| w.temp_c).or(served_weather.map(|w| w.temp_c)),
+        humidity: weather.and_then(|w| w.humidity).or(served_weather.map(|w| w.rh)),
+        wind_ms: weather.and_then(|w| w.wind_ms).or(served_weather.map(|w| w.wind_ms)),
+        rain_mm: weather.and_then(|w| w.rain_mm).or(served_weather.map(|w| w.rain_mm)),
+    };
     
-    // Generate general tips
-    let tips = if let Some(best_window) = windows_data.windows.first() {
-        let weather_features = WeatherFeatures {
-            temp_c: best_window.weather_summary.avg_temp_c,
-            rh: best_window.weather_summary.avg_humidity,
-            wind_ms: best_window.weather_summary.avg_wind_ms,
-            cloud: 50.0, // Default cloud coverage
-            rain_p: if best_window.weather_summary.total_rain_mm > 0.0 { 0.8 } else { 0.0 },
-            rain_mm: best_window.weather_summary.total_rain_mm,
-        };
-        
-        state.ai_client
-            .generate_drying_tips(&weather_features, &best_window.score)
+    // Create feedback record
+    let create_feedback = CreateFeedback {
+        user_id: request.user_id,
+        window_id: request.window_id.clone(),
+        feedback_text: request.feedback_text.clone(),
+        satisfaction_rating: request.satisfaction_rating,
+        drying_result: request.drying_result,
+        weather_temp_c: conditions.temp_c,
+        weather_humidity: conditions.humidity,
+        weather_wind_ms: conditions.wind_ms,
+        weather_rain_mm: conditions.rain_mm,
+        predicted_score: request
+            .predicted_score
+            .or(served.as_ref().map(|window| window.score.score)),
+        actual_outcome: request.actual_outcome,
+    };
+    
+    match state.database.create_feedback(create_feedback).await {
+        Ok(feedback_record) => {
+            // Analyze feedback with AI
+            let weather_features = WeatherFeatures::from(&conditions);
+            
+            let analysis = state.ai_client
+                .analyze_feedback(&request.feedback_text, &weather_features)
+                .await
+                .ok();
+            if let Some(analysis) = &analysis {
+                if let Err(e) = state.database.save_feedback_analysis(feedback_record.id, analysis).await {
+                    tracing::warn!("Failed to store analysis of feedback {}: {}", feedback_record.id, e);
+                }
+            }
+            
+            Ok(Json(FeedbackResponse {
+                id: feedback_record.id,
+                analysis,
+                message: "Feedback submitted successfully".to_string(),
+            }))
+        }
+        // Lost a race with a concurrent post for the same window
+        Err(DatabaseError::AlreadyExists) => Err(duplicate_feedback(&request.window_id)),
+        Err(e) => {
+            tracing::error!("Failed to save feedback: {}", e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR.into())
+        }
+    }
+}
+
+fn duplicate_feedback(window_id: &str) -> AppError {
+    AppError::new(
+        StatusCode::CONFLICT,
+        "duplicate_feedback",
+        format!("Feedback already covers window {}", window_id),
+    )
+}
+
+/// Guard the open feedback endpoint against floods: text must be long enough
+/// to mean something, and a user gets one submission per window and a daily
+/// allowance. Anonymous feedback shares that allowance per window.
+async fn check_feedback_allowed(state: &AppState, request: &FeedbackRequest) -> Result<(), AppError> {
+    let min_length = state.config.feedback_min_text_length;
+    if request.feedback_text.trim().chars().count() < min_length {
+        return Err(AppError::bad_request(
+            "feedback_too_short",
+            format!("feedback_text must be at least {} characters", min_length),
+        ));
+    }
+    
+    let internal = |e: DatabaseError| {
+        tracing::error!("Failed to check earlier feedback: {}", e);
+        AppError::from(StatusCode::INTERNAL_SERVER_ERROR)
+    };
+    let limit = state.config.feedback_daily_limit;
+    let limit_reached = || {
+        AppError::new(
+            StatusCode::TOO_MANY_REQUESTS,
+            "feedback_limit_reached",
+            format!("At most {} feedback submissions per day", limit),
+        )
+    };
+    let since = chrono::Utc::now() - chrono::Duration::days(1);
+    
+    let Some(user_id) = request.user_id else {
+        let recent = state
+            .database
+            .count_anonymous_feedback_since(&request.window_id, since)
             .await
-            .unwrap_or_else(|_| "Check weather conditions before hanging clothes. Avoid drying during rain or high humidity. Wind helps with faster drying.".to_string())
-            .split(". ")
-            .map(|s| s.to_string())
-            .collect::<Vec<String>>()
+            .map_err(internal)?;
+        if recent >= i64::from(limit) {
+            return Err(limit_reached());
+        }
+        return Ok(());
+    };
+    
+    if let Some(existing) = state
+        .database
+        .find_user_window_feedback(user_id, &request.window_id)
+        .await
+        .map_err(internal)?
+    {
+        return Err(AppError::new(
+            StatusCode::CONFLICT,
+            "duplicate_feedback",
+            format!("Feedback {} already covers window {}", existing.id, request.window_id),
+        ));
+    }
+    
+    let recent = state
+        .database
+        .count_user_feedback_since(user_id, since)
+        .await
+        .map_err(internal)?;
+    if recent >= i64::from(limit) {
+        return Err(limit_reached());
+    }
+    
+    Ok(())
+}
+
+/// Feedback flagged for review because the AI read the outcome differently
+/// from the user. The user's value is the one used for training.
+pub async fn get_feedback_disagreements(
+    State(state): State<AppState>,
+    Query(params): Query<DisagreementsQuery>,
+) -> Result<Json<DisagreementsResponse>, AppError> {
+    let limit = validate_range("limit", params.limit, 50, 100)?;
+    let records = state.database.get_feedback_disagreements(Some(limit.into())).await.map_err(|e| {
+        tracing::error!("Failed to load feedback disagreements: {}", e);
+        AppError::from(StatusCode::INTERNAL_SERVER_ERROR)
+    })?;
+    
+    let disagreements: Vec<FeedbackDisagreement> = records
+        .into_iter()
+        .filter_map(|record| {
+            let user_drying_result = record.drying_result?;
+            let analysis = record.analysis?.0;
+            Some(FeedbackDisagreement {
+                feedback_id: record.id,
+                user_id: record.user_id,
+                window_id: record.window_id,
+                feedback_text: record.feedback_text,
+                user_drying_result,
+                ai_drying_result: analysis.drying_result,
+                ai_confidence: analysis.confidence,
+                created_at: record.created_at,
+            })
+        })
+        .collect();
+    
+    Ok(Json(DisagreementsResponse {
+        count: disagreements.len(),
+        disagreements,
+    }))
+}
+
+/// Re-run AI analysis on stored feedback, e.g. after the model improved
+pub async fn reanalyze_feedback(
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+    Query(params): Query<ReanalyzeQuery>,
+) -> Result<Json<ReanalyzeResponse>, AppError> {
+    let record = state
+        .database
+        .get_feedback(id)
+        .await
+        .map_err(|e| {
+            tracing::error!("Failed to load feedback {}: {}", id, e);
+            AppError::from(StatusCode::INTERNAL_SERVER_ERROR)
+        })?
+        .ok_or_else(|| AppError::new(StatusCode::NOT_FOUND, "feedback_not_found", format!("No feedback with id {}", id)))?;
+    
+    let stored = WeatherConditions::from(&record);
+    let weather_defaults_used = !stored.is_complete();
+    let weather = WeatherFeatures::from(&stored);
+    
+    let mut analysis = state
+        .ai_client
+        .analyze_feedback(&record.feedback_text, &weather)
+        .await
+        .map_err(|e| {
+            tracing::error!("Reanalysis of feedback {} failed: {}", id, e);
+            match e {
+                AiError::RateLimited => AppError::from(StatusCode::TOO_MANY_REQUESTS),
+                _ => AppError::from(StatusCode::INTERNAL_SERVER_ERROR),
+            }
+        })?;
+    if weather_defaults_used {
+        analysis.confidence = AnalysisConfidence::Low;
+    }
+    if params.persist {
+        state.database.save_feedback_analysis(id, &analysis).await.map_err(|e| {
+            tracing::error!("Failed to store reanalysis of feedback {}: {}", id, e);
+            AppError::from(StatusCode::INTERNAL_SERVER_ERROR)
+        })?;
+    }
+    
+    Ok(Json(ReanalyzeResponse {
+        id,
+        analysis,
+        weather_defaults_used,
+        persisted: params.persist,
+        analyzed_at: chrono::Utc::now(),
+    }))
+}
+
+/// Start time and length encoded in a window id (`window_{start}_{hours}`)
+fn parse_window_id(window_id: &str) -> Option<(i64, u32)> {
+    let (start, hours) = window_id.strip_prefix("window_")?.split_once('_')?;
+    Some((start.parse().ok()?, hours.parse().ok()?))
+}
+
+/// Record a measured outcome and train the user's weights on it. The
+/// window's weather is re-resolved from the forecast, so the window must
+/// still (at least partly) be inside it.
+pub async fn submit_outcome(
+    State(state): State<AppState>,
+    Json(request): Json<OutcomeRequest>,
+) -> Result<Json<OutcomeResponse>, AppError> {
+    let (start_ts, hours) = parse_window_id(&request.window_id).ok_or_else(|| {
+        AppError::bad_request("invalid_window_id", format!("Unrecognized window id: {}", request.window_id))
+    })?;
+    
+    let prefs = require_user(&state, request.user_id).await?;
+    let (Some(lat), Some(lon)) = (request.lat.or(prefs.location_lat), request.lon.or(prefs.location_lon)) else {
+        return Err(AppError::bad_request(
+            "missing_location",
+            "lat/lon are required when the user has no saved location",
+        ));
+    };
+    
+    // Outcomes arrive once the window is over, so train on the weather it
+    // was served with; the forecast only has it while it is still ahead
+    let served = if state.config.persist_windows {
+        state
+            .database
+            .get_served_window(&request.window_id, served_coordinate(lat), served_coordinate(lon))
+            .await
+            .unwrap_or_else(|e| {
+                tracing::warn!("Failed to load served window {}: {}", request.window_id, e);
+                None
+            })
     } else {
-        vec![
-            "Check weather conditions before hanging clothes".to_string(),
-            "Avoid drying during rain or high humidity".to_string(),
-            "Wind helps with faster drying".to_string(),
-        ]
+        None
+    };
+    let weather = match served {
+        Some(window) => window.score.0.raw,
+        None => forecast_window_weather(&state, &request.window_id, lat, lon, start_ts, hours).await?,
+    };
+    
+    let scoring_config = state.config.scoring_config(prefs.scoring_model);
+    let mut trained = state
+        .database
+        .get_user_weights(request.user_id)
+        .await
+        .map_err(|e| {
+            tracing::error!("Failed to load weights: {}", e);
+            AppError::from(StatusCode::INTERNAL_SERVER_ERROR)
+        })?
+        .unwrap_or_default();
+    let score = calculate_drying_score_with(&weather, &trained.weights, &scoring_config);
+    
+    let label = if request.dry { 1.0 } else { 0.0 };
+    let learning_rate = trained.update(
+        &score.features,
+        label,
+        SGD_LEARNING_RATE,
+        state.config.sgd_lr_decay,
+        SGD_REGULARIZATION,
+    );
+    
+    let outcome = if request.dry { ActualOutcome::Dry } else { ActualOutcome::NotDry };
+    let feedback_text = match (request.dry, request.minutes_to_dry) {
+        (true, Some(minutes)) => format!("Sensor outcome: dry after {} minutes", minutes),
+        (true, None) => "Sensor outcome: dry".to_string(),
+        (false, _) => "Sensor outcome: not dry".to_string(),
+    };
+    let record = CreateFeedback {
+        user_id: Some(request.user_id),
+        window_id: request.window_id.clone(),
+        feedback_text,
+        satisfaction_rating: None,
+        drying_result: Some(if request.dry { DryingResult::CompletelyDry } else { DryingResult::NotDry }),
+        weather_temp_c: Some(weather.temp_c),
+        weather_humidity: Some(weather.rh),
+        weather_wind_ms: Some(weather.wind_ms),
+        weather_rain_mm: Some(weather.rain_mm),
+        predicted_score: Some(score.score),
+        actual_outcome: Some(outcome),
     };
     
-    Ok(Json(RecommendationResponse {
-        location: windows_data.location,
-        best_windows: windows_data.windows,
-        ai_explanation,
-        tips,
-        generated_at: chrono::Utc::now(),
+    let saved = async {
+        let record = state.database.create_feedback(record).await?;
+        state.database.save_user_weights(request.user_id, &trained).await?;
+        Ok::<_, crate::database::DatabaseError>(record)
+    }
+    .await
+    .map_err(|e| match e {
+        DatabaseError::AlreadyExists => duplicate_feedback(&request.window_id),
+        e => {
+            tracing::error!("Failed to save outcome: {}", e);
+            AppError::from(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    })?;
+    
+    Ok(Json(OutcomeResponse {
+        id: saved.id,
+        predicted_score: score.score,
+        weights: trained.weights,
+        training_step: trained.step,
+        learning_rate,
+        loss: trained.loss,
     }))
 }
 
-pub async fn submit_feedback(
-    State(state): State<AppState>,
-    Json(request): Json<FeedbackRequest>,
-) -> Result<Json<FeedbackResponse>, StatusCode> {
-    // Create feedback record
-    let create_feedback = CreateFeedback {
-        user_id: request.user_id,
-        window_id: request.window_id,
-        feedback_text: request.feedback_text.clone(),
-        satisfaction_rating: request.satisfaction_rating,
-        drying_result: request.drying_result,
-        weather_temp_c: request.weather_conditions.as_ref().and_then(|w| w.temp_c),
-        weather_humidity: request.weather_conditions.as_ref().and_then(|w| w.humidity),
-        weather_wind_ms: request.weather_conditions.as_ref().and_then(|w| w.wind_ms),
-        weather_rain_mm: request.weather_conditions.as_ref().and_then(|w| w.rain_mm),
-        predicted_score: request.predicted_score,
-        actual_outcome: request.actual_outcome,
-    };
-    
-    match state.database.create_feedback(create_feedback).await {
-        Ok(feedback_record) => {
-            // Analyze feedback with AI
-            let weather_features = if let Some(weather) = &request.weather_conditions {
-                WeatherFeatures {
-                    temp_c: weather.temp_c.unwrap_or(20.0),
-                    rh: weather.humidity.unwrap_or(50.0),
-                    wind_ms: weather.wind_ms.unwrap_or(2.0),
-                    cloud: 50.0, // Default cloud coverage
-                    rain_p: if weather.rain_mm.unwrap_or(0.0) > 0.0 { 0.8 } else { 0.0 },
-                    rain_mm: weather.rain_mm.unwrap_or(0.0),
-                }
-            } else {
-                WeatherFeatures {
-                    temp_c: 20.0,
-                    rh: 50.0,
-                    wind_ms: 2.0,
-                    cloud: 50.0,
-                    rain_p: 0.0,
-                    rain_mm: 0.0,
-                }
-            };
-            
-            let analysis = state.ai_client
-                .analyze_feedback(&request.feedback_text, &weather_features)
-                .await
-                .ok();
-            
-            Ok(Json(FeedbackResponse {
-                id: feedback_record.id,
-                analysis,
-                message: "Feedback submitted successfully".to_string(),
-            }))
-        }
-        Err(e) => {
-            tracing::error!("Failed to save feedback: {}", e);
-            Err(StatusCode::INTERNAL_SERVER_ERROR)
-        }
+/// A window's weather averaged over the same smoothed hours it was scored
+/// on, for a window that is still in the forecast
+async fn forecast_window_weather(
+    state: &AppState,
+    window_id: &str,
+    lat: f64,
+    lon: f64,
+    start_ts: i64,
+    hours: u32,
+) -> Result<WeatherFeatures, AppError> {
+    let (hourly_data, _) = forecast_hours(state, lat, lon, 168, None, None).await?;
+    let window_hours: Vec<HourlyData> = smooth_hourly(&hourly_data, state.config.smoothing_window)
+        .into_iter()
+        .filter(|h| (start_ts..start_ts + hours as i64 * 3600).contains(&h.ts.timestamp()))
+        .collect();
+    if window_hours.is_empty() {
+        return Err(AppError::new(
+            StatusCode::UNPROCESSABLE_ENTITY,
+            "window_unavailable",
+            format!("Weather for {} is no longer in the forecast", window_id),
+        ));
     }
+    Ok(group_into_windows(&window_hours, window_hours.len() as i32)[0].weather.clone())
 }
 
 pub async fn get_user_preferences(
     State(state): State<AppState>,
     Path(user_id): Path<Uuid>,
+    Query(query): Query<PreferencesQuery>,
 ) -> Result<Json<UserPreferences>, StatusCode> {
     match state.database.get_user_preferences(user_id).await {
         Ok(prefs) => Ok(Json(prefs)),
+        Err(DatabaseError::UserNotFound) if query.create_if_missing.unwrap_or(false) => {
+            // First load for this id: save empty preferences instead of 404ing
+            match state
+                .database
+                .create_user_preferences_with_id(user_id, CreateUserPreferences::default())
+                .await
+            {
+                Ok(prefs) => Ok(Json(prefs)),
+                Err(e) => {
+                    tracing::error!("Failed to create default preferences for {}: {}", user_id, e);
+                    Err(StatusCode::INTERNAL_SERVER_ERROR)
+                }
+            }
+        }
         Err(_) => Err(StatusCode::NOT_FOUND),
     }
 }
 
+/// Reject notification preferences whose quiet hours could never be applied
+fn validate_notification_preferences(value: Option<&serde_json::Value>) -> Result<(), AppError> {
+    match value {
+        Some(value) => NotificationSettings::validate(value)
+            .map(drop)
+            .map_err(|message| AppError::bad_request("invalid_quiet_hours", message)),
+        None => Ok(()),
+    }
+}
+
 pub async fn create_user_preferences(
     State(state): State<AppState>,
     Json(request): Json<CreateUserPreferences>,
-) -> Result<Json<UserPreferences>, StatusCode> {
+) -> Result<Json<UserPreferences>, AppError> {
+    validate_notification_preferences(request.notification_preferences.as_ref())?;
     match state.database.create_user_preferences(request).await {
         Ok(prefs) => Ok(Json(prefs)),
         Err(e) => {
             tracing::error!("Failed to create user preferences: {}", e);
-            Err(StatusCode::INTERNAL_SERVER_ERROR)
+            Err(StatusCode::INTERNAL_SERVER_ERROR.into())
         }
     }
 }
@@ -525,15 +2727,33 @@ pub async fn update_user_preferences(
     State(state): State<AppState>,
     Path(user_id): Path<Uuid>,
     Json(request): Json<CreateUserPreferences>,
-) -> Result<Json<UserPreferences>, StatusCode> {
+) -> Result<Json<UserPreferences>, AppError> {
+    validate_notification_preferences(request.notification_preferences.as_ref())?;
     match state.database.update_user_preferences(user_id, request).await {
         Ok(prefs) => Ok(Json(prefs)),
-        Err(_) => Err(StatusCode::NOT_FOUND),
+        Err(_) => Err(StatusCode::NOT_FOUND.into()),
+    }
+}
+
+/// Partial update where an explicit `null` clears a field
+pub async fn patch_user_preferences(
+    State(state): State<AppState>,
+    Path(user_id): Path<Uuid>,
+    Json(request): Json<PatchUserPreferences>,
+) -> Result<Json<UserPreferences>, AppError> {
+    validate_notification_preferences(request.notification_preferences.as_ref().and_then(Option::as_ref))?;
+    match state.database.patch_user_preferences(user_id, request).await {
+        Ok(prefs) => Ok(Json(prefs)),
+        Err(DatabaseError::UserNotFound) => Err(StatusCode::NOT_FOUND.into()),
+        Err(e) => {
+            tracing::error!("Failed to patch user preferences: {}", e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR.into())
+        }
     }
 }
 
 async fn generate_recommendation_with_retry(
-    ai_client: &AiClient,
+    ai_client: &dyn AiProvider,
     weather_features: &WeatherFeatures,
     max_retries: u32,
 ) -> Result<String, crate::ai::AiError> {
@@ -557,56 +2777,41 @@ async fn generate_recommendation_with_retry(
 pub async fn get_ai_recommendation(
     State(state): State<AppState>,
     Query(query): Query<AiRecommendationQuery>,
-) -> Result<Json<AiRecommendationResponse>, StatusCode> {
-    // Fetch weather data
-    let onecall_result = state.weather_client.get_onecall(query.lat, query.lon).await;
-    let forecast3h_result = state.weather_client.get_forecast3h(query.lat, query.lon).await;
-    
-    let onecall = onecall_result.ok();
-    let forecast3h = forecast3h_result.ok();
-    
-    if onecall.is_none() && forecast3h.is_none() {
-        tracing::error!("Failed to fetch any weather data for AI recommendation");
-        return Err(StatusCode::INTERNAL_SERVER_ERROR);
-    }
-    
-    let merged_data = merge_weather_data(
-        onecall.as_ref(),
-        forecast3h.as_ref(),
-        7 * 3600 // Default timezone offset
-    );
+) -> Result<Json<AiRecommendationResponse>, AppError> {
+    let merged_data = fetch_merged_hourly(&state.config, state.weather_client.as_ref(), query.lat, query.lon).await?;
     
     // Get current weather from the first hour of merged data
     let current_weather = match merged_data.first() {
         Some(hourly) => hourly,
         None => {
             tracing::error!("No current weather data available");
-            return Err(StatusCode::INTERNAL_SERVER_ERROR);
+            return Err(StatusCode::INTERNAL_SERVER_ERROR.into());
         }
     };
-    let weather_features = WeatherFeatures {
-        temp_c: current_weather.temp_c,
-        rh: current_weather.rh,
-        wind_ms: current_weather.wind_ms,
-        cloud: current_weather.cloud,
-        rain_p: current_weather.rain_p,
-        rain_mm: current_weather.rain_mm,
-    };
+    let weather_features = WeatherFeatures::from(current_weather);
 
     // Generate AI recommendation with retry logic
-    match generate_recommendation_with_retry(&state.ai_client, &weather_features, 3).await {
+    match generate_recommendation_with_retry(state.ai_client.as_ref(), &weather_features, 3).await {
         Ok(recommendation) => Ok(Json(AiRecommendationResponse {
             recommendation,
             generated_at: chrono::Utc::now(),
         })),
+        // Every model is cooling down, so answer without waiting on any of them
+        Err(crate::ai::AiError::CircuitOpen(model)) => {
+            tracing::warn!("AI model {} is cooling down, serving heuristic recommendation", model);
+            Ok(Json(AiRecommendationResponse {
+                recommendation: heuristic_tips(&weather_features).join(" "),
+                generated_at: chrono::Utc::now(),
+            }))
+        }
         Err(e) => {
             tracing::error!("AI recommendation failed after retries: {}", e);
             match e {
                 crate::ai::AiError::RateLimited => {
-                    Err(StatusCode::TOO_MANY_REQUESTS)
+                    Err(StatusCode::TOO_MANY_REQUESTS.into())
                 }
                 _ => {
-                    Err(StatusCode::INTERNAL_SERVER_ERROR)
+                    Err(StatusCode::INTERNAL_SERVER_ERROR.into())
                 }
             }
         }
@@ -617,15 +2822,9 @@ pub async fn explain_recommendation(
     State(state): State<AppState>,
     Json(request): Json<ExplainRequest>,
 ) -> Result<Json<ExplainResponse>, StatusCode> {
-    let weather_features = WeatherFeatures {
-        temp_c: request.window_data.weather.temp_c,
-        rh: request.window_data.weather.rh,
-        wind_ms: request.window_data.weather.wind_ms,
-        cloud: request.window_data.weather.cloud,
-        rain_p: request.window_data.weather.rain_p,
-        rain_mm: request.window_data.weather.rain_mm,
-    };
+    let weather_features = WeatherFeatures::from(&request.window_data);
     
+    let weather = weather_features.clone();
     let window_data = vec![(
         request.window_data.start_time.to_string(),
         request.score.clone(),
@@ -642,19 +2841,20 @@ pub async fn explain_recommendation(
         user_prefs.as_deref(),
     ).await {
         Ok(explanation) => {
-            let factors = vec![
-                format!("Temperature: {:.1}°C", request.window_data.weather.temp_c),
-                format!("Humidity: {:.1}%", request.window_data.weather.rh),
-                format!("Wind: {:.1} m/s", request.window_data.weather.wind_ms),
-                format!("Rain: {:.1} mm", request.window_data.weather.rain_mm),
-                format!("Drying Score: {:.2}", request.score.score),
-            ];
+            // Name the strongest drivers from the actual score breakdown
+            let scoring_config = state.config.scoring_config(None);
+            let (features, _) = normalize_features_with(&weather, &scoring_config.normalization);
+            let mut factors: Vec<String> = factor_effects(&weather, &features, &DryingWeights::default(), &scoring_config)
+                .into_iter()
+                .take(3)
+                .map(|(name, effect)| {
+                    let verdict = if effect > 0.0 { "helps" } else { "slows" };
+                    format!("{} {} drying ({:+.2})", name, verdict, effect)
+                })
+                .collect();
+            factors.push(format!("Drying Score: {:.2}", request.score.score));
             
-            let tips = vec![
-                "Hang clothes in well-ventilated areas".to_string(),
-                "Avoid direct sunlight for delicate fabrics".to_string(),
-                "Shake out clothes before hanging".to_string(),
-            ];
+            let tips = heuristic_tips(&weather);
             
             Ok(Json(ExplainResponse {
                 explanation,
@@ -669,19 +2869,744 @@ pub async fn explain_recommendation(
     }
 }
 
+// Preview a score under hypothetical weights; nothing is persisted
+pub async fn what_if_score(
+    State(state): State<AppState>,
+    Json(request): Json<WhatIfRequest>,
+) -> Json<WhatIfResponse> {
+    let scoring_config = state.config.scoring_config(request.scoring_model);
+    let score = calculate_drying_score_with(&request.weather, &request.weights, &scoring_config);
+    let contributions = model_contributions(
+        &request.weather,
+        &score.features,
+        &request.weights,
+        &scoring_config,
+    );
+    
+    Json(WhatIfResponse {
+        score,
+        contributions,
+    })
+}
+
+// What each linear-model weight means, for rendering tuning controls
+pub async fn model_schema(State(state): State<AppState>) -> Json<ModelSchemaResponse> {
+    Json(ModelSchemaResponse {
+        weights: weight_schema(&state.config.normalization),
+    })
+}
+
+/// Save hand-tuned weights for a user. Later outcomes keep training from them.
+pub async fn set_user_weights(
+    State(state): State<AppState>,
+    Json(request): Json<SetWeightsRequest>,
+) -> Result<Json<SetWeightsResponse>, AppError> {
+    let weights = request.weights;
+    tune_user_weights(&state, request.user_id, |_| weights).await.map(Json)
+}
+
+/// Tune some or all of a user's weights; the rest keep their current values
+pub async fn patch_user_weights(
+    State(state): State<AppState>,
+    Path(user_id): Path<Uuid>,
+    Json(patch): Json<PatchDryingWeights>,
+) -> Result<Json<SetWeightsResponse>, AppError> {
+    tune_user_weights(&state, user_id, |current| patch.apply(current)).await.map(Json)
+}
+
+/// Replace a user's weights with `tune(current)` once it's within bounds,
+/// keeping their training step
+async fn tune_user_weights(
+    state: &AppState,
+    user_id: Uuid,
+    tune: impl FnOnce(&DryingWeights) -> DryingWeights,
+) -> Result<SetWeightsResponse, AppError> {
+    state.database.get_user_preferences(user_id).await.map_err(|_| {
+        AppError::new(StatusCode::NOT_FOUND, "user_not_found", format!("No user {}", user_id))
+    })?;
+    
+    let mut trained = state
+        .database
+        .get_user_weights(user_id)
+        .await
+        .map_err(|e| {
+            tracing::error!("Failed to load weights: {}", e);
+            AppError::from(StatusCode::INTERNAL_SERVER_ERROR)
+        })?
+        .unwrap_or_default();
+    trained.weights = tune(&trained.weights);
+    
+    let invalid = trained.weights.out_of_bounds();
+    if !invalid.is_empty() {
+        return Err(AppError::bad_request(
+            "invalid_weights",
+            format!("Weights out of bounds (see /model/schema): {}", invalid.join(", ")),
+        ));
+    }
+    
+    state.database.save_user_weights(user_id, &trained).await.map_err(|e| {
+        tracing::error!("Failed to save weights: {}", e);
+        AppError::from(StatusCode::INTERNAL_SERVER_ERROR)
+    })?;
+    
+    Ok(SetWeightsResponse {
+        user_id,
+        weights: trained.weights,
+        training_step: trained.step,
+    })
+}
+
+/// Longest label a saved location may have, in characters
+const MAX_LOCATION_LABEL_CHARS: usize = 64;
+
+/// The user's preferences, or `404` when there is no such user
+async fn require_user(state: &AppState, user_id: Uuid) -> Result<UserPreferences, AppError> {
+    match state.database.get_user_preferences(user_id).await {
+        Ok(prefs) => Ok(prefs),
+        Err(DatabaseError::UserNotFound) => Err(AppError::new(
+            StatusCode::NOT_FOUND,
+            "user_not_found",
+            format!("No user {}", user_id),
+        )),
+        Err(e) => {
+            tracing::error!("Failed to load user {}: {}", user_id, e);
+            Err(AppError::from(StatusCode::INTERNAL_SERVER_ERROR))
+        }
+    }
+}
+
+/// The user's saved locations, by label
+pub async fn get_user_locations(
+    State(state): State<AppState>,
+    Path(user_id): Path<Uuid>,
+) -> Result<Json<Vec<UserLocation>>, AppError> {
+    require_user(&state, user_id).await?;
+    
+    let locations = state.database.get_user_locations(user_id).await.map_err(|e| {
+        tracing::error!("Failed to load locations for {}: {}", user_id, e);
+        AppError::from(StatusCode::INTERNAL_SERVER_ERROR)
+    })?;
+    Ok(Json(locations))
+}
+
+/// Save a location under `label`, replacing the one already there
+pub async fn save_user_location(
+    State(state): State<AppState>,
+    Path((user_id, label)): Path<(Uuid, String)>,
+    Json(request): Json<SaveUserLocation>,
+) -> Result<Json<UserLocation>, AppError> {
+    let label = label.trim();
+    if label.is_empty() || label.chars().count() > MAX_LOCATION_LABEL_CHARS {
+        return Err(AppError::bad_request(
+            "invalid_parameter",
+            format!("label must be 1 to {} characters", MAX_LOCATION_LABEL_CHARS),
+        ));
+    }
+    if !(-90.0..=90.0).contains(&request.lat) || !(-180.0..=180.0).contains(&request.lon) {
+        return Err(AppError::bad_request(
+            "invalid_parameter",
+            format!("lat must be within ±90 and lon within ±180 (got {}, {})", request.lat, request.lon),
+        ));
+    }
+    require_user(&state, user_id).await?;
+    
+    let saved = state.database.save_user_location(user_id, label, request).await.map_err(|e| {
+        tracing::error!("Failed to save location {:?} for {}: {}", label, user_id, e);
+        AppError::from(StatusCode::INTERNAL_SERVER_ERROR)
+    })?;
+    Ok(Json(saved))
+}
+
+pub async fn delete_user_location(
+    State(state): State<AppState>,
+    Path((user_id, label)): Path<(Uuid, String)>,
+) -> Result<StatusCode, AppError> {
+    require_user(&state, user_id).await?;
+    
+    match state.database.delete_user_location(user_id, label.trim()).await {
+        Ok(true) => Ok(StatusCode::NO_CONTENT),
+        Ok(false) => Err(AppError::new(
+            StatusCode::NOT_FOUND,
+            "location_not_found",
+            format!("No saved location {:?}", label),
+        )),
+        Err(e) => {
+            tracing::error!("Failed to delete location {:?} for {}: {}", label, user_id, e);
+            Err(AppError::from(StatusCode::INTERNAL_SERVER_ERROR))
+        }
+    }
+}
+
+// Replay stored feedback through the current scoring
+pub async fn backtest_model(
+    State(state): State<AppState>,
+    Json(request): Json<BacktestRequest>,
+) -> Result<Json<BacktestReport>, StatusCode> {
+    let weights = request.weights.unwrap_or_default();
+    let threshold = request.threshold.unwrap_or(0.5);
+    
+    match state.database.get_feedback_with_weather(request.limit).await {
+        Ok(records) => Ok(Json(run_backtest(&records, &weights, threshold))),
+        Err(e) => {
+            tracing::error!("Failed to load feedback for backtest: {}", e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+// Raw merged hourly series, for reproducing recommendation issues
+pub async fn debug_merged(
+    State(state): State<AppState>,
+    Query(params): Query<AiRecommendationQuery>,
+) -> Result<Json<DebugMergedResponse>, AppError> {
+    if !state.config.debug_endpoints {
+        return Err(StatusCode::NOT_FOUND.into());
+    }
+    
+    let hours = fetch_merged_hourly(&state.config, state.weather_client.as_ref(), params.lat, params.lon)
+        .await?
+        .into_iter()
+        .map(|hour| DebugHour {
+            synthetic: hour.source.is_synthetic(),
+            hour,
+        })
+        .collect();
+    
+    Ok(Json(DebugMergedResponse {
+        location: LocationInfo {
+            lat: params.lat,
+            lon: params.lon,
+            name: None,
+            country: None,
+        },
+        hours,
+        generated_at: chrono::Utc::now(),
+    }))
+}
+
+/// Drop cached forecasts, and the recommendations built from them, for one
+/// location or all of them, so bad upstream data needn't wait out the TTL
+pub async fn purge_cache(
+    State(state): State<AppState>,
+    Query(params): Query<PurgeCacheQuery>,
+) -> Result<Json<PurgeCacheResponse>, AppError> {
+    let location = match (params.lat, params.lon) {
+        (Some(lat), Some(lon)) => Some(format!("{:.2},{:.2}", lat, lon)),
+        (None, None) => None,
+        _ => return Err(AppError::bad_request("invalid_parameter", "Provide both lat and lon, or neither")),
+    };
+    // Recommendation keys start with the location, then `|` and the parameters
+    let matches = |key: &str| match &location {
+        Some(location) => key.split('|').next() == Some(location.as_str()),
+        None => true,
+    };
+    
+    let response = PurgeCacheResponse {
+        forecasts_purged: purge_matching(&state.forecasts, matches).await,
+        recommendations_purged: purge_matching(&state.recommendations, matches).await,
+    };
+    tracing::info!(
+        "Purged {} forecasts and {} recommendations ({})",
+        response.forecasts_purged,
+        response.recommendations_purged,
+        location.as_deref().unwrap_or("all locations")
+    );
+    Ok(Json(response))
+}
+
+/// Invalidate every entry whose key `matches`, returning how many there were
+async fn purge_matching<V: Clone + Send + Sync + 'static>(cache: &Cache<String, V>, matches: impl Fn(&str) -> bool) -> usize {
+    let keys: Vec<_> = cache.iter().map(|(key, _)| key).filter(|key| matches(key)).collect();
+    for key in &keys {
+        cache.invalidate(key.as_str()).await;
+    }
+    keys.len()
+}
+
 // Create the router
 pub fn create_router(state: AppState) -> Router {
-    Router::new()
-        .route("/health", get(health))
+    let limit = ConcurrencyLimit::new(state.config.max_concurrent_requests);
+    let admin_key = AdminKey::new(state.config.admin_api_key.as_deref());
+    
+    let admin = Router::new()
+        .route("/admin/cache/purge", post(purge_cache))
+        .route_layer(middleware::from_fn_with_state(admin_key, require_api_key));
+
+    let api = Router::new()
         .route("/geocode", get(geocode))
         .route("/forecast", get(get_forecast))
+        .route("/forecast/changes", get(get_forecast_changes))
+        .route("/sun", get(get_sun))
+        .route("/forecast.csv", get(get_forecast_csv))
         .route("/drying-windows", get(get_drying_windows))
         .route("/recommendations", get(get_recommendations))
+        .route("/best-day", get(get_best_day))
+        .route("/windows/:window_id", get(get_served_window))
         .route("/ai-recommendation", get(get_ai_recommendation))
         .route("/feedback", post(submit_feedback))
+        .route("/feedback/disagreements", get(get_feedback_disagreements))
+        .route("/feedback/:id/reanalyze", post(reanalyze_feedback))
+        .route("/outcomes", post(submit_outcome))
         .route("/preferences/:user_id", get(get_user_preferences))
         .route("/preferences/:user_id", post(update_user_preferences))
+        .route("/preferences/:user_id", patch(patch_user_preferences))
+        .route("/preferences/:user_id/weights", post(patch_user_weights))
+        .route("/preferences/:user_id/locations", get(get_user_locations))
+        .route("/preferences/:user_id/locations/:label", put(save_user_location))
+        .route("/preferences/:user_id/locations/:label", delete(delete_user_location))
         .route("/preferences", post(create_user_preferences))
         .route("/explain", post(explain_recommendation))
+        .route("/score/what-if", post(what_if_score))
+        .route("/model/backtest", post(backtest_model))
+        .route("/model/schema", get(model_schema))
+        .route("/model/weights", post(set_user_weights))
+        .route("/debug/merged", get(debug_merged))
+        .merge(admin)
+        .route_layer(middleware::from_fn_with_state(limit, limit_concurrency))
+        // Oversized JSON bodies are rejected with 413 before they're parsed
+        .layer(DefaultBodyLimit::max(state.config.max_body_bytes));
+
+    // Health checks stay outside the concurrency limit
+    Router::new()
+        .route("/health", get(health))
+        .route("/livez", get(livez))
+        .route("/readyz", get(readyz))
+        .route("/version", get(version))
+        .merge(api)
+        .layer(middleware::from_fn(access_log))
         .with_state(state)
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        ai::AiClient,
+        database::Database,
+        forecast::{
+            mock::MockWeatherClient,
+            openweather::{OpenWeatherClient, OpenWeatherError},
+            types::{Forecast3hResponse, GeocodeResponse, OneCallResponse},
+        },
+    };
+    
+    /// Weather provider whose every call fails
+    struct DownWeather;
+    
+    #[async_trait::async_trait]
+    impl WeatherProvider for DownWeather {
+        async fn get_onecall(&self, _lat: f64, _lon: f64) -> Result<OneCallResponse, OpenWeatherError> {
+            Err(OpenWeatherError::ApiError("down".to_string()))
+        }
+        
+        async fn get_forecast3h(&self, _lat: f64, _lon: f64) -> Result<Forecast3hResponse, OpenWeatherError> {
+            Err(OpenWeatherError::ApiError("down".to_string()))
+        }
+        
+        async fn geocode_direct(&self, _query: &str) -> Result<Vec<GeocodeResponse>, OpenWeatherError> {
+            Err(OpenWeatherError::ApiError("down".to_string()))
+        }
+        
+        async fn geocode_reverse(&self, _lat: f64, _lon: f64) -> Result<Vec<GeocodeResponse>, OpenWeatherError> {
+            Err(OpenWeatherError::ApiError("down".to_string()))
+        }
+    }
+    
+    #[tokio::test]
+    async fn test_fetch_merged_hourly_fails_only_when_both_sources_fail() {
+        let config = Config::for_tests();
+        
+        let hours = fetch_merged_hourly(&config, &MockWeatherClient::new(), 13.75, 100.5).await.unwrap();
+        assert!(!hours.is_empty());
+        
+        let err = fetch_merged_hourly(&config, &DownWeather, 13.75, 100.5).await.unwrap_err();
+        assert_eq!(err.status, StatusCode::INTERNAL_SERVER_ERROR);
+        assert_eq!(err.code, "weather_unavailable");
+    }
+    
+    #[tokio::test]
+    async fn test_debug_merged_reports_weather_unavailable() {
+        let config = Config {
+            debug_endpoints: true,
+            ..Config::for_tests()
+        };
+        let mut state = test_state(config).await;
+        state.weather_client = Arc::new(DownWeather);
+        let query = || Query(AiRecommendationQuery { lat: 13.75, lon: 100.5 });
+        
+        let err = debug_merged(State(state.clone()), query()).await.unwrap_err();
+        assert_eq!(err.code, "weather_unavailable");
+        
+        state.weather_client = Arc::new(MockWeatherClient::new());
+        assert!(!debug_merged(State(state), query()).await.unwrap().hours.is_empty());
+    }
+    
+    #[test]
+    fn test_sparkline_scores_the_hours_windows_are_scored_on() {
+        let config = Config {
+            sparkline_max_points: 1000,
+            ..Config::for_tests()
+        };
+        let scoring_config = config.scoring_config(None);
+        let now = chrono::Utc::now().with_timezone(&chrono::FixedOffset::east_opt(7 * 3600).unwrap());
+        // Temperature and humidity jumping around from hour to hour
+        let hours: Vec<HourlyData> = (0..12)
+            .map(|i| HourlyData {
+                ts: now + chrono::Duration::hours(i),
+                temp_c: if i % 2 == 0 { 32.0 } else { 20.0 },
+                rh: if i % 2 == 0 { 40.0 } else { 80.0 },
+                wind_ms: 3.0,
+                cloud: 0.2,
+                rain_p: 0.0,
+                rain_mm: 0.0,
+                wind_gust_ms: None,
+                source: crate::forecast::types::DataSource::OneCall,
+                wind_deg: None,
+                uv_index: None,
+                temp_range: None,
+            })
+            .collect();
+        
+        let points = score_sparkline(&config, &hours, &DryingWeights::default(), &scoring_config);
+        let windows = score_drying_windows(&config, &hours, 1, &DryingWeights::default(), &scoring_config);
+        assert_eq!(points.len(), windows.len());
+        for window in &windows {
+            let point = points.iter().find(|p| p.ts == window.start_time).unwrap();
+            assert!((point.score - window.score.score).abs() < 1e-9, "{} vs {}", point.score, window.score.score);
+        }
+    }
+    
+    #[tokio::test]
+    async fn test_daily_synthesized_windows_report_wider_ranges() {
+        let config = Config::for_tests();
+        let mut windows = build_drying_windows(
+            &config,
+            &MockWeatherClient::new(),
+            13.75,
+            100.5,
+            3,
+            &config.scoring_config(None),
+        )
+        .await
+        .unwrap();
+        windows.sort_by_key(|w| w.start_time);
+        let width = |w: &DryingWindow| w.score_range.map_or(0.0, |r| r.best - r.worst);
+        
+        // Hourly forecasts carry no band
+        let near = &windows[0];
+        assert!(near.weather_summary.temp_range.is_none());
+        assert_eq!(width(near), 0.0);
+        
+        // The sixth day is past the 3-hourly forecast, so synthesized from daily
+        let day6 = windows
+            .iter()
+            .find(|w| w.start_time >= near.start_time + chrono::Duration::days(5) && !w.score.unsafe_window)
+            .expect("a safe day-6 window");
+        let temp_range = day6.weather_summary.temp_range.expect("day-6 window has a band");
+        assert!(temp_range.min_c < day6.weather_summary.avg_temp_c && day6.weather_summary.avg_temp_c < temp_range.max_c);
+        let range = day6.score_range.unwrap();
+        assert!(range.worst <= day6.score.score && day6.score.score <= range.best);
+        assert!(width(day6) > width(near));
+    }
+    
+    async fn test_state(config: Config) -> AppState {
+        // A single connection keeps every query on the same in-memory database
+        let pool = sqlx::sqlite::SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+        let database = Database::new(pool);
+        database.init_tables().await.unwrap();
+        
+        AppState::new(
+            Arc::new(config.clone()),
+            Arc::new(database),
+            Arc::new(OpenWeatherClient::new(config.clone())),
+            Arc::new(AiClient::new(config)),
+        )
+    }
+    
+    #[test]
+    fn test_reported_conditions_convert_with_neutral_defaults() {
+        let unreported = WeatherFeatures::from(&WeatherConditions::default());
+        assert_eq!((unreported.temp_c, unreported.rh, unreported.wind_ms), (20.0, 50.0, 2.0));
+        assert_eq!(unreported.cloud, 0.5);
+        assert_eq!((unreported.rain_p, unreported.rain_mm), (0.0, 0.0));
+        
+        let rainy = WeatherFeatures::from(&WeatherConditions {
+            temp_c: Some(24.0),
+            rain_mm: Some(1.5),
+            ..Default::default()
+        });
+        assert_eq!(rainy.temp_c, 24.0);
+        assert_eq!((rainy.rain_p, rainy.rain_mm), (0.8, 1.5));
+    }
+    
+    fn sample_weather() -> WeatherFeatures {
+        WeatherFeatures {
+            temp_c: 27.0,
+            rh: 55.0,
+            wind_ms: 3.0,
+            cloud: 0.2,
+            rain_p: 0.1,
+            rain_mm: 0.0,
+            gust_ms: None,
+            is_daylight: None,
+            wind_deg: None,
+        }
+    }
+    
+    #[tokio::test]
+    async fn test_what_if_default_vs_tweaked_weights() {
+        let state = test_state(Config::for_tests()).await;
+        
+        let default = what_if_score(State(state.clone()), Json(WhatIfRequest {
+            weather: sample_weather(),
+            weights: DryingWeights::default(),
+            scoring_model: None,
+        }))
+        .await
+        .0;
+        
+        let tweaked_weights = DryingWeights {
+            w3: 0.45, // Lean much harder on wind
+            ..DryingWeights::default()
+        };
+        let tweaked = what_if_score(State(state.clone()), Json(WhatIfRequest {
+            weather: sample_weather(),
+            weights: tweaked_weights,
+            scoring_model: None,
+        }))
+        .await
+        .0;
+        
+        assert!(tweaked.score.score > default.score.score);
+        assert!(tweaked.contributions.wind > default.contributions.wind);
+        assert_eq!(tweaked.contributions.temp, default.contributions.temp);
+        assert!((tweaked.contributions.total() - tweaked.score.score).abs() < 1e-9);
+    }
+    
+    #[tokio::test]
+    async fn test_debug_merged_404_when_disabled() {
+        let state = test_state(Config::for_tests()).await;
+        
+        let result = debug_merged(
+            State(state),
+            Query(AiRecommendationQuery { lat: 13.75, lon: 100.5 }),
+        )
+        .await;
+        
+        assert_eq!(result.unwrap_err().status, StatusCode::NOT_FOUND);
+    }
+    
+    fn window_at(
+        now: chrono::DateTime<chrono::Utc>,
+        offset_hours: i64,
+        score_value: f64,
+    ) -> DryingWindow {
+        let mut score = calculate_drying_score_with(
+            &sample_weather(),
+            &DryingWeights::default(),
+            &Default::default(),
+        );
+        score.score = score_value;
+        let start_time = now + chrono::Duration::hours(offset_hours);
+        
+        DryingWindow {
+            id: format!("window_{}_3", start_time.timestamp()),
+            start_time,
+            end_time: start_time + chrono::Duration::hours(3),
+            duration_hours: 3,
+            score,
+            weather_summary: WeatherSummary {
+                avg_temp_c: 27.0,
+                avg_humidity: 55.0,
+                avg_wind_ms: 3.0,
+                avg_wind_deg: None,
+                avg_wind_resultant_ms: None,
+                min_temp_c: 27.0,
+                max_temp_c: 27.0,
+                min_humidity: 55.0,
+                max_humidity: 55.0,
+                total_rain_mm: 0.0,
+                uv_index: None,
+                temp_range: None,
+                conditions: "Sunny".to_string(),
+            },
+            recommendation: String::new(),
+            warnings: Vec::new(),
+            notes: Vec::new(),
+            score_range: None,
+            weight_comparison: None,
+            condition: Condition::Sunny,
+            verdict: Verdict::Good,
+            compact: false,
+        }
+    }
+    
+    #[test]
+    fn test_gusty_low_confidence_window_carries_both_warnings() {
+        let start = chrono::Utc::now().with_timezone(&chrono::FixedOffset::east_opt(7 * 3600).unwrap());
+        let hours: Vec<HourlyData> = (0..3)
+            .map(|offset| HourlyData {
+                ts: start + chrono::Duration::hours(offset),
+                temp_c: 24.0,
+                rh: 55.0,
+                wind_ms: 4.0,
+                cloud: 0.3,
+                rain_p: 0.0,
+                rain_mm: 0.0,
+                wind_gust_ms: Some(14.0),
+                wind_deg: None,
+                uv_index: None,
+                temp_range: Some(TempRange { min_c: 12.0, max_c: 36.0 }),
+                source: DataSource::DailySynthesized,
+            })
+            .collect();
+        let window = &group_into_windows(&hours, 3)[0];
+        let config = ScoringConfig::default();
+        let score = calculate_drying_score_with(&window.weather, &Default::default(), &config);
+        let range = temperature_score_range(&window.weather, &score, window.temp_range.unwrap(), &config);
+        
+        let warnings = window_warnings(window, None, range);
+        let codes: Vec<WarningCode> = warnings.iter().map(|w| w.code).collect();
+        assert!(codes.contains(&WarningCode::Gusty));
+        assert!(codes.contains(&WarningCode::LowConfidence));
+        assert!(codes.contains(&WarningCode::SyntheticData));
+        assert!(!codes.contains(&WarningCode::RainGap));
+        
+        // A calm, hour-by-hour forecast raises none
+        let steady: Vec<HourlyData> = hours
+            .iter()
+            .map(|h| HourlyData {
+                wind_gust_ms: Some(5.0),
+                temp_range: None,
+                source: DataSource::OneCall,
+                ..h.clone()
+            })
+            .collect();
+        assert!(window_warnings(&group_into_windows(&steady, 3)[0], None, None).is_empty());
+    }
+    
+    #[test]
+    fn test_high_uv_fades_colored_delicates_but_helps_whites() {
+        let fade = uv_advice(Some(8.2), Some(Fabric::Delicates)).unwrap();
+        let warning = fade.warning().unwrap();
+        assert_eq!(warning.code, WarningCode::HighUv);
+        assert!(warning.message.contains("fades bright colours"));
+        assert_eq!(fade.note(), None);
+        assert!(uv_advice(Some(8.2), Some(Fabric::Colors)).unwrap().warning().is_some());
+        
+        let bonus = uv_advice(Some(8.2), Some(Fabric::Whites)).unwrap();
+        assert!(bonus.note().unwrap().contains("whites"));
+        assert_eq!(bonus.warning(), None);
+        
+        // Gated on the fabric, and on the UV actually being high
+        assert_eq!(uv_advice(Some(8.2), None), None);
+        assert_eq!(uv_advice(Some(3.0), Some(Fabric::Delicates)), None);
+        assert_eq!(uv_advice(None, Some(Fabric::Whites)), None);
+    }
+    
+    #[test]
+    fn test_best_day_picks_the_clearly_best_day() {
+        let now = chrono::Utc::now();
+        // A synthesized week of 3-hour windows, with day 4 clearly best
+        let mut windows: Vec<DryingWindow> = (0..7 * 8)
+            .map(|i| {
+                let score = if i / 8 == 4 { 0.5 + 0.05 * (i % 8) as f64 } else { 0.45 };
+                window_at(now, i * 3, score)
+            })
+            .collect();
+        windows.sort_by(|a, b| b.score.score.partial_cmp(&a.score.score).unwrap());
+        
+        let best = find_best_day_window(&windows, &ScoreThresholds::default(), now).unwrap();
+        assert_eq!(best.start_time, now + chrono::Duration::hours((4 * 8 + 7) * 3));
+    }
+    
+    #[test]
+    fn test_best_day_ties_break_toward_the_earlier_day() {
+        let now = chrono::Utc::now();
+        // Best-first order would list the later window first
+        let windows = vec![window_at(now, 50, 0.7), window_at(now, 26, 0.7), window_at(now, 2, 0.5)];
+        
+        let best = find_best_day_window(&windows, &ScoreThresholds::default(), now).unwrap();
+        assert_eq!(best.start_time, now + chrono::Duration::hours(26));
+    }
+    
+    #[test]
+    fn test_best_day_is_none_for_a_rainy_week() {
+        let now = chrono::Utc::now();
+        let mut windows: Vec<DryingWindow> = (0..7 * 8).map(|i| window_at(now, i * 3, -1.0)).collect();
+        for window in &mut windows {
+            window.score.unsafe_window = true;
+        }
+        // A poor window isn't worth recommending either
+        windows.push(window_at(now, 10, 0.3));
+        
+        assert!(find_best_day_window(&windows, &ScoreThresholds::default(), now).is_none());
+    }
+    
+    #[test]
+    fn test_next_good_window_later_today() {
+        let now = chrono::Utc::now();
+        // Sorted best-first, as build_drying_windows returns them
+        let windows = vec![
+            window_at(now, 9, 0.85),
+            window_at(now, 4, 0.7),
+            window_at(now, 0, 0.3),
+        ];
+        
+        let next = find_next_good_window(&windows, &ScoreThresholds::default(), now).unwrap();
+        assert_eq!(next.window_id, windows[1].id);
+        assert_eq!(next.starts_in_hours, 4);
+        assert_eq!(next.countdown, "in 4 hours");
+    }
+    
+    #[test]
+    fn test_next_good_window_now_and_none() {
+        let now = chrono::Utc::now();
+        
+        let windows = vec![window_at(now, 0, 0.9), window_at(now, 3, 0.65)];
+        let next = find_next_good_window(&windows, &ScoreThresholds::default(), now).unwrap();
+        assert_eq!(next.starts_in_hours, 0);
+        assert_eq!(next.countdown, "now");
+        
+        let poor = vec![window_at(now, 0, 0.4), window_at(now, 3, 0.2)];
+        assert!(find_next_good_window(&poor, &ScoreThresholds::default(), now).is_none());
+    }
+    
+    #[test]
+    fn test_urgency_act_now_when_forecast_degrades() {
+        let now = chrono::Utc::now();
+        let thresholds = ScoreThresholds::default();
+        let windows = vec![
+            window_at(now, 0, 0.8),
+            window_at(now, 3, 0.6),
+            window_at(now, 6, 0.4),
+            window_at(now, 9, -1.0),
+        ];
+        
+        assert_eq!(assess_urgency(&windows, &thresholds, now), Urgency::ActNow);
+        
+        // Holding steady is no reason to hurry
+        let steady = vec![window_at(now, 0, 0.8), window_at(now, 3, 0.75), window_at(now, 6, 0.78)];
+        assert_eq!(assess_urgency(&steady, &thresholds, now), Urgency::Flexible);
+    }
+    
+    #[test]
+    fn test_urgency_wait_when_forecast_improves() {
+        let now = chrono::Utc::now();
+        let thresholds = ScoreThresholds::default();
+        // Sorted best-first, as build_drying_windows returns them
+        let windows = vec![
+            window_at(now, 9, 0.9),
+            window_at(now, 6, 0.7),
+            window_at(now, 3, 0.55),
+            window_at(now, 0, 0.5),
+        ];
+        
+        assert_eq!(assess_urgency(&windows, &thresholds, now), Urgency::Wait);
+        assert_eq!(assess_urgency(&[], &thresholds, now), Urgency::Flexible);
+    }
+}
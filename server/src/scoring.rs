@@ -1,5 +1,8 @@
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+
+use crate::forecast::merge::WindowData;
+use crate::i18n::Language;
+use crate::utils::round_to_decimals;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct WeatherFeatures {
@@ -9,6 +12,47 @@ pub struct WeatherFeatures {
     pub cloud: f64,
     pub rain_p: f64,
     pub rain_mm: f64,
+    #[serde(default)]
+    pub gust_ms: Option<f64>,
+    #[serde(default)]
+    pub is_daylight: Option<bool>,
+    /// Direction the wind blows from, in degrees
+    #[serde(default)]
+    pub wind_deg: Option<f64>,
+}
+
+/// Round `value` to the nearest multiple of `step`
+fn bucket(value: f64, step: f64) -> f64 {
+    round_to_decimals((value / step).round() * step, 2)
+}
+
+impl WeatherFeatures {
+    /// Stable key for caching and dedup. Values are bucketed (temperature to
+    /// 0.5°C, humidity to 5%, wind and gusts to 0.5 m/s, cloud to 10%, rain
+    /// probability to 5%, rain to 0.1 mm) so near-identical conditions share
+    /// one key. Wind direction doesn't affect the score and is left out.
+    pub fn cache_key(&self) -> String {
+        let gust = self
+            .gust_ms
+            .map(|g| bucket(g, 0.5).to_string())
+            .unwrap_or_else(|| "-".to_string());
+        let daylight = match self.is_daylight {
+            Some(true) => "day",
+            Some(false) => "night",
+            None => "-",
+        };
+        format!(
+            "t{}:h{}:w{}:c{}:p{}:r{}:g{}:{}",
+            bucket(self.temp_c, 0.5),
+            bucket(self.rh, 5.0),
+            bucket(self.wind_ms, 0.5),
+            bucket(self.cloud, 0.1),
+            bucket(self.rain_p, 0.05),
+            bucket(self.rain_mm, 0.1),
+            gust,
+            daylight
+        )
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -21,7 +65,7 @@ pub struct NormalizedFeatures {
     pub f_vpd: f64,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct DryingWeights {
     pub w0: f64,
     pub w1: f64,
@@ -32,6 +76,34 @@ pub struct DryingWeights {
     pub w6: f64,
 }
 
+/// Some of the weights, for tuning a few without restating the rest
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct PatchDryingWeights {
+    pub w0: Option<f64>,
+    pub w1: Option<f64>,
+    pub w2: Option<f64>,
+    pub w3: Option<f64>,
+    pub w4: Option<f64>,
+    pub w5: Option<f64>,
+    pub w6: Option<f64>,
+}
+
+impl PatchDryingWeights {
+    /// `weights` with the given values replaced
+    pub fn apply(&self, weights: &DryingWeights) -> DryingWeights {
+        DryingWeights {
+            w0: self.w0.unwrap_or(weights.w0),
+            w1: self.w1.unwrap_or(weights.w1),
+            w2: self.w2.unwrap_or(weights.w2),
+            w3: self.w3.unwrap_or(weights.w3),
+            w4: self.w4.unwrap_or(weights.w4),
+            w5: self.w5.unwrap_or(weights.w5),
+            w6: self.w6.unwrap_or(weights.w6),
+        }
+    }
+}
+
 impl Default for DryingWeights {
     fn default() -> Self {
         Self {
@@ -46,15 +118,453 @@ impl Default for DryingWeights {
     }
 }
 
+/// What one linear-model weight multiplies and the range training keeps it in
+#[derive(Debug, Clone, Serialize)]
+pub struct WeightSpec {
+    pub name: &'static str,
+    /// Normalized feature the weight multiplies; `None` for the bias
+    pub feature: Option<&'static str>,
+    pub meaning: String,
+    pub default: f64,
+    pub min: f64,
+    pub max: f64,
+}
+
+/// Name, feature and `(min, max)` of each weight in `w0..w6` order. Training
+/// clamps to these bounds and hand-tuned weights are checked against them.
+const WEIGHT_BOUNDS: [(&str, Option<&str>, f64, f64); 7] = [
+    ("w0", None, -0.5, 0.5),
+    ("w1", Some("f_temp"), 0.0, 0.5),
+    ("w2", Some("f_hum"), 0.0, 0.5),
+    ("w3", Some("f_wind"), 0.0, 0.5),
+    ("w4", Some("f_cloud"), 0.0, 0.3),
+    ("w5", Some("f_rain"), 0.0, 0.3),
+    ("w6", Some("f_vpd"), 0.0, 0.5),
+];
+
+/// The weights in `w0..w6` order, with their defaults and bounds, and what
+/// their features mean under `normalization`
+pub fn weight_schema(normalization: &NormalizationConfig) -> Vec<WeightSpec> {
+    let n = normalization;
+    let meanings = [
+        "Bias added to every score".to_string(),
+        format!(
+            "Warmth: 0 at {}°C or below, 1 at {}°C or above",
+            n.temp_baseline_c,
+            n.temp_baseline_c + n.temp_range_c
+        ),
+        "Dry air: 1 at 0% relative humidity, 0 at 100%".to_string(),
+        format!("Breeze: 0 when calm, 1 at {} m/s or more", n.wind_scale_ms),
+        "Clear sky: 1 minus the cloud cover fraction".to_string(),
+        "Dry forecast: 1 minus the rain probability".to_string(),
+        format!("Vapour pressure deficit: 0 at 0 kPa, 1 at {} kPa or more", n.vpd_scale_kpa),
+    ];
+
+    WEIGHT_BOUNDS
+        .into_iter()
+        .zip(meanings)
+        .zip(DryingWeights::default().as_array())
+        .map(|(((name, feature, min, max), meaning), default)| WeightSpec {
+            name,
+            feature,
+            meaning,
+            default,
+            min,
+            max,
+        })
+        .collect()
+}
+
+/// Which formula turns normalized features into a score.
+///
+/// - `linear`: `w0 + Σ wᵢ·fᵢ` over all features, minus the soft penalties.
+/// - `vpd`: `0.75·f_vpd + 0.25·f_wind`, where by default
+///   `f_vpd = min(vpd_kpa / 2.5, 1)` and `f_wind = min(wind_ms / 6, 1)`. Meant for arid climates where the
+///   temperature and humidity terms double-count what VPD already measures.
+///
+/// The rain veto and rain penalty apply to both models.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, sqlx::Type)]
+#[serde(rename_all = "lowercase")]
+#[sqlx(rename_all = "lowercase")]
+pub enum ScoringModel {
+    #[default]
+    Linear,
+    Vpd,
+}
+
+/// Optional scoring terms that can be switched on per deployment
+/// (`FEATURES=gust,daylight`) before they become part of the default model.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ScoringFeatures {
+    /// Penalize gusts strong enough to blow laundry off the line
+    pub gust: bool,
+    /// Penalize windows that fall mostly outside daylight hours
+    pub daylight: bool,
+    /// Penalize clear nights close to the dew point, when dew re-wets laundry
+    #[serde(default)]
+    pub dew: bool,
+}
+
+impl ScoringFeatures {
+    /// Parse a comma-separated flag list, returning any unrecognized names
+    pub fn parse(flags: &str) -> (Self, Vec<String>) {
+        let mut features = Self::default();
+        let mut unknown = Vec::new();
+
+        for flag in flags.split(',').map(|f| f.trim().to_lowercase()) {
+            match flag.as_str() {
+                "" => {}
+                "gust" => features.gust = true,
+                "daylight" => features.daylight = true,
+                "dew" => features.dew = true,
+                _ => unknown.push(flag),
+            }
+        }
+
+        (features, unknown)
+    }
+}
+
+/// Rain handling, split by what the forecast actually says: a likely shower
+/// of unknown size only costs score, while forecast accumulation vetoes.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct RainThresholds {
+    /// Precipitation probability above which the soft rain penalty applies
+    pub penalty_probability: f64,
+    /// Forecast rain (mm) above which the window is unsafe
+    pub veto_mm: f64,
+    /// Hours over `veto_mm` a window may contain and still be scored on its
+    /// dry hours, for someone home to bring the laundry in
+    #[serde(default)]
+    pub gap_tolerance_hours: u32,
+}
+
+impl Default for RainThresholds {
+    fn default() -> Self {
+        Self {
+            penalty_probability: 0.50,
+            veto_mm: 0.2,
+            gap_tolerance_hours: 0,
+        }
+    }
+}
+
+impl RainThresholds {
+    /// Why the window is unsafe, if it is
+    pub fn veto_reason(&self, weather: &WeatherFeatures) -> Option<String> {
+        (weather.rain_mm > self.veto_mm).then(|| {
+            format!(
+                "Expected rain {:.1} mm exceeds the {:.1} mm threshold",
+                weather.rain_mm, self.veto_mm
+            )
+        })
+    }
+
+    /// These thresholds tightened by `by`: the penalty probability lowered by
+    /// it and the rain allowance shrunk by that fraction
+    pub fn stricter(&self, by: f64) -> Self {
+        Self {
+            penalty_probability: (self.penalty_probability - by).max(0.0),
+            veto_mm: self.veto_mm * (1.0 - by).max(0.0),
+            ..*self
+        }
+    }
+}
+
+/// Air near freezing, with wind chill this low, freezes wet laundry stiff
+/// rather than drying it
+const FREEZE_RISK_MAX_TEMP_C: f64 = 2.0;
+const FREEZE_RISK_WIND_CHILL_C: f64 = -5.0;
+
+/// Why a cold, windy window would freeze the laundry, if it would
+pub fn freeze_veto_reason(weather: &WeatherFeatures) -> Option<String> {
+    let wind_chill = calculate_wind_chill_c(weather.temp_c, weather.wind_ms);
+    (weather.temp_c <= FREEZE_RISK_MAX_TEMP_C && wind_chill <= FREEZE_RISK_WIND_CHILL_C).then(|| {
+        format!(
+            "Risk of freezing: wind chill {:.0}°C at {:.0}°C air temperature",
+            wind_chill, weather.temp_c
+        )
+    })
+}
+
+/// Score lost when rain is likely but not forecast to accumulate
+const RAIN_PENALTY: f64 = -0.25;
+
+/// What's on the line. Fabrics differ in how much harsh sun they tolerate,
+/// which scales the high-temperature penalty.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Fabric {
+    Whites,
+    Cotton,
+    Synthetic,
+    Colors,
+    Delicates,
+}
+
+impl Fabric {
+    /// Multiplier on the heat penalty
+    pub fn heat_sensitivity(self) -> f64 {
+        match self {
+            Fabric::Whites | Fabric::Cotton => 0.5,
+            Fabric::Synthetic => 1.0,
+            Fabric::Colors => 1.5,
+            Fabric::Delicates => 2.0,
+        }
+    }
+}
+
+/// Soft penalty for scorching windows, where sun fades colours and damages
+/// delicates faster than the extra heat helps drying. Only applies when the
+/// caller names the fabric, so unspecified loads score as before.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct HeatPenalty {
+    /// Air temperature (°C) above which the penalty applies
+    pub threshold_c: f64,
+    /// Score lost by a fabric of sensitivity 1.0
+    pub penalty: f64,
+}
+
+impl Default for HeatPenalty {
+    fn default() -> Self {
+        Self {
+            threshold_c: 35.0,
+            penalty: -0.10,
+        }
+    }
+}
+
+/// Score cut-offs behind the verdicts shown to users. Every endpoint that
+/// labels a window (or looks for a "good" one) reads these.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct ScoreThresholds {
+    pub excellent: f64,
+    pub good: f64,
+    pub fair: f64,
+}
+
+impl Default for ScoreThresholds {
+    fn default() -> Self {
+        Self {
+            excellent: 0.8,
+            good: 0.6,
+            fair: 0.4,
+        }
+    }
+}
+
+impl ScoreThresholds {
+    /// Parse `excellent,good,fair`; the values must be strictly descending
+    pub fn parse(value: &str) -> Option<Self> {
+        let values: Vec<f64> = value
+            .split(',')
+            .map(|v| v.trim().parse().ok())
+            .collect::<Option<_>>()?;
+        match values[..] {
+            [excellent, good, fair] if excellent > good && good > fair => Some(Self { excellent, good, fair }),
+            _ => None,
+        }
+    }
+
+    pub fn is_good(&self, score: f64) -> bool {
+        score > self.good
+    }
+
+    pub fn grade(&self, score: f64) -> Verdict {
+        if score > self.excellent {
+            Verdict::Excellent
+        } else if score > self.good {
+            Verdict::Good
+        } else if score > self.fair {
+            Verdict::Fair
+        } else {
+            Verdict::Poor
+        }
+    }
+
+    /// Every threshold raised by `by`, for a more demanding reading of
+    /// scores, though never past a perfect 1.0
+    pub fn raised(&self, by: f64) -> Self {
+        Self {
+            excellent: (self.excellent + by).min(1.0),
+            good: (self.good + by).min(1.0),
+            fair: (self.fair + by).min(1.0),
+        }
+    }
+
+    /// The verdict in English; see [`Language::verdict`] for other languages
+    pub fn verdict(&self, score: f64) -> &'static str {
+        Language::En.verdict(self.grade(score))
+    }
+}
+
+/// Band a score falls in, as judged by [`ScoreThresholds`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Verdict {
+    Excellent,
+    Good,
+    Fair,
+    Poor,
+}
+
+/// Constants that map raw weather onto the 0-1 feature scale
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct NormalizationConfig {
+    /// Temperature (°C) at which `f_temp` starts rising above 0
+    pub temp_baseline_c: f64,
+    /// Degrees above the baseline at which `f_temp` reaches 1
+    pub temp_range_c: f64,
+    /// Exponent in `f_hum = 1 - (rh/100)^exp`; higher is more forgiving of humidity
+    pub humidity_exponent: f64,
+    /// Wind speed (m/s) at which `f_wind` saturates
+    pub wind_scale_ms: f64,
+    /// VPD (kPa) at which `f_vpd` saturates
+    pub vpd_scale_kpa: f64,
+}
+
+impl Default for NormalizationConfig {
+    fn default() -> Self {
+        Self {
+            temp_baseline_c: 15.0,
+            temp_range_c: 15.0,
+            humidity_exponent: 0.7,
+            wind_scale_ms: 6.0,
+            vpd_scale_kpa: 2.5,
+        }
+    }
+}
+
+impl NormalizationConfig {
+    /// Parse `name=value` overrides (e.g. `humidity_exponent=0.9`) on top of
+    /// the defaults, returning any entries that couldn't be applied. Every
+    /// value must be positive.
+    pub fn parse(value: &str) -> (Self, Vec<String>) {
+        let mut config = Self::default();
+        let mut invalid = Vec::new();
+
+        for entry in value.split(',').map(str::trim).filter(|e| !e.is_empty()) {
+            let parsed = entry.split_once('=').and_then(|(name, value)| {
+                let value = value.trim().parse::<f64>().ok().filter(|v| *v > 0.0 && v.is_finite())?;
+                Some((name.trim(), value))
+            });
+            let Some((name, value)) = parsed else {
+                invalid.push(entry.to_string());
+                continue;
+            };
+            match name {
+                "temp_baseline_c" => config.temp_baseline_c = value,
+                "temp_range_c" => config.temp_range_c = value,
+                "humidity_exponent" => config.humidity_exponent = value,
+                "wind_scale_ms" => config.wind_scale_ms = value,
+                "vpd_scale_kpa" => config.vpd_scale_kpa = value,
+                _ => invalid.push(entry.to_string()),
+            }
+        }
+
+        (config, invalid)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScoringConfig {
+    pub model: ScoringModel,
+    pub features: ScoringFeatures,
+    pub rain: RainThresholds,
+    pub normalization: NormalizationConfig,
+    #[serde(default)]
+    pub heat: HeatPenalty,
+    /// Fabric of the load being scored, if the caller said
+    #[serde(default)]
+    pub fabric: Option<Fabric>,
+    /// Keep non-vetoed scores within 0-1; -1.0 stays reserved for vetoes
+    #[serde(default = "default_clamp_scores")]
+    pub clamp_scores: bool,
+    /// Score lost by a clear night near the dew point, with the `dew` feature on
+    #[serde(default = "default_dew_penalty")]
+    pub dew_penalty: f64,
+}
+
+fn default_clamp_scores() -> bool {
+    true
+}
+
+fn default_dew_penalty() -> f64 {
+    DEW_PENALTY
+}
+
+impl Default for ScoringConfig {
+    fn default() -> Self {
+        Self {
+            model: ScoringModel::default(),
+            features: ScoringFeatures::default(),
+            rain: RainThresholds::default(),
+            normalization: NormalizationConfig::default(),
+            heat: HeatPenalty::default(),
+            fabric: None,
+            clamp_scores: default_clamp_scores(),
+            dew_penalty: DEW_PENALTY,
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct DryingScore {
     pub score: f64,
     pub unsafe_window: bool,
+    /// Why an unsafe window was vetoed; `score` stays at -1.0 for compatibility
+    #[serde(default)]
+    pub veto_reason: Option<String>,
     pub features: NormalizedFeatures,
     pub raw: WeatherFeatures,
     pub vpd_kpa: f64,
 }
 
+/// Per-term breakdown of a linear drying score (`weight * feature` for each
+/// term, plus the soft penalties applied afterwards).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScoreContributions {
+    pub bias: f64,
+    pub temp: f64,
+    pub humidity: f64,
+    pub wind: f64,
+    pub cloud: f64,
+    pub rain: f64,
+    pub vpd: f64,
+    pub cold_penalty: f64,
+    pub calm_penalty: f64,
+    pub gust_penalty: f64,
+    pub night_penalty: f64,
+    #[serde(default)]
+    pub dew_penalty: f64,
+    #[serde(default)]
+    pub heat_penalty: f64,
+    pub rain_penalty: f64,
+    /// Correction that brings the total back within 0-1, when clamping is on
+    #[serde(default)]
+    pub clamp: f64,
+}
+
+impl ScoreContributions {
+    pub fn total(&self) -> f64 {
+        self.bias
+            + self.temp
+            + self.humidity
+            + self.wind
+            + self.cloud
+            + self.rain
+            + self.vpd
+            + self.cold_penalty
+            + self.calm_penalty
+            + self.gust_penalty
+            + self.night_penalty
+            + self.dew_penalty
+            + self.heat_penalty
+            + self.rain_penalty
+            + self.clamp
+    }
+}
+
 pub fn clamp(value: f64, min: f64, max: f64) -> f64 {
     value.max(min).min(max)
 }
@@ -65,63 +575,357 @@ pub fn calculate_vpd_kpa(temp_c: f64, rh: f64) -> f64 {
     (es - e).max(0.0)
 }
 
+/// Wind chill in °C (the Environment Canada / NWS formula). Defined only
+/// for air at or below 10°C with wind above 4.8 km/h; otherwise the air
+/// temperature is returned unchanged.
+pub fn calculate_wind_chill_c(temp_c: f64, wind_ms: f64) -> f64 {
+    let wind_kmh = wind_ms * 3.6;
+    if temp_c > 10.0 || wind_kmh <= 4.8 {
+        return temp_c;
+    }
+    let v = wind_kmh.powf(0.16);
+    13.12 + 0.6215 * temp_c - 11.37 * v + 0.3965 * temp_c * v
+}
+
+/// Dew point in °C (Magnus approximation)
+pub fn calculate_dew_point_c(temp_c: f64, rh: f64) -> f64 {
+    let gamma = (rh.clamp(1.0, 100.0) / 100.0).ln() + (17.27 * temp_c) / (temp_c + 237.3);
+    237.3 * gamma / (17.27 - gamma)
+}
+
 pub fn normalize_features(weather: &WeatherFeatures) -> (NormalizedFeatures, f64) {
-    let vpd_kpa = calculate_vpd_kpa(weather.temp_c, weather.rh);
-    
-    let features = NormalizedFeatures {
-        f_temp: clamp((weather.temp_c - 15.0) / 15.0, 0.0, 1.0),
-        f_hum: 1.0 - (weather.rh / 100.0).powf(0.7),
-        f_wind: clamp(weather.wind_ms / 6.0, 0.0, 1.0),
-        f_cloud: 1.0 - clamp(weather.cloud, 0.0, 1.0),
-        f_rain: 1.0 - clamp(weather.rain_p, 0.0, 1.0),
-        f_vpd: clamp(vpd_kpa / 2.5, 0.0, 1.0),
+    normalize_features_with(weather, &NormalizationConfig::default())
+}
+
+pub fn normalize_features_with(
+    weather: &WeatherFeatures,
+    config: &NormalizationConfig,
+) -> (NormalizedFeatures, f64) {
+    Normalizer::new(config).normalize(weather)
+}
+
+/// A [`NormalizationConfig`] with its scales inverted, so normalizing is
+/// multiplication. Worked out once per batch by [`score_windows_with`].
+#[derive(Debug, Clone, Copy)]
+struct Normalizer {
+    temp_baseline_c: f64,
+    per_temp_range_c: f64,
+    humidity_exponent: f64,
+    per_wind_scale_ms: f64,
+    per_vpd_scale_kpa: f64,
+}
+
+impl Normalizer {
+    fn new(config: &NormalizationConfig) -> Self {
+        Self {
+            temp_baseline_c: config.temp_baseline_c,
+            per_temp_range_c: config.temp_range_c.recip(),
+            humidity_exponent: config.humidity_exponent,
+            per_wind_scale_ms: config.wind_scale_ms.recip(),
+            per_vpd_scale_kpa: config.vpd_scale_kpa.recip(),
+        }
+    }
+
+    fn normalize(&self, weather: &WeatherFeatures) -> (NormalizedFeatures, f64) {
+        let vpd_kpa = calculate_vpd_kpa(weather.temp_c, weather.rh);
+        
+        let features = NormalizedFeatures {
+            f_temp: clamp((weather.temp_c - self.temp_baseline_c) * self.per_temp_range_c, 0.0, 1.0),
+            f_hum: 1.0 - (weather.rh / 100.0).powf(self.humidity_exponent),
+            f_wind: clamp(weather.wind_ms * self.per_wind_scale_ms, 0.0, 1.0),
+            f_cloud: 1.0 - clamp(weather.cloud, 0.0, 1.0),
+            f_rain: 1.0 - clamp(weather.rain_p, 0.0, 1.0),
+            f_vpd: clamp(vpd_kpa * self.per_vpd_scale_kpa, 0.0, 1.0),
+        };
+        
+        (features, vpd_kpa)
+    }
+}
+
+pub fn score_contributions(
+    weather: &WeatherFeatures,
+    features: &NormalizedFeatures,
+    weights: &DryingWeights,
+) -> ScoreContributions {
+    ScoreContributions {
+        bias: weights.w0,
+        temp: weights.w1 * features.f_temp,
+        humidity: weights.w2 * features.f_hum,
+        wind: weights.w3 * features.f_wind,
+        cloud: weights.w4 * features.f_cloud,
+        rain: weights.w5 * features.f_rain,
+        vpd: weights.w6 * features.f_vpd,
+        // Soft penalties
+        cold_penalty: if weather.temp_c < 18.0 { -0.15 } else { 0.0 },
+        calm_penalty: if weather.wind_ms < 1.0 { -0.10 } else { 0.0 },
+        gust_penalty: 0.0,
+        night_penalty: 0.0,
+        dew_penalty: 0.0,
+        heat_penalty: 0.0,
+        rain_penalty: 0.0,
+        clamp: 0.0,
+    }
+}
+
+/// Fixed weights of the VPD model
+const VPD_MODEL_VPD_WEIGHT: f64 = 0.75;
+const VPD_MODEL_WIND_WEIGHT: f64 = 0.25;
+
+pub fn vpd_contributions(features: &NormalizedFeatures) -> ScoreContributions {
+    ScoreContributions {
+        bias: 0.0,
+        temp: 0.0,
+        humidity: 0.0,
+        wind: VPD_MODEL_WIND_WEIGHT * features.f_wind,
+        cloud: 0.0,
+        rain: 0.0,
+        vpd: VPD_MODEL_VPD_WEIGHT * features.f_vpd,
+        cold_penalty: 0.0,
+        calm_penalty: 0.0,
+        gust_penalty: 0.0,
+        night_penalty: 0.0,
+        dew_penalty: 0.0,
+        heat_penalty: 0.0,
+        rain_penalty: 0.0,
+        clamp: 0.0,
+    }
+}
+
+/// Cloud cover below which a night sky counts as clear enough to radiate heat
+const DEW_CLEAR_SKY_CLOUD: f64 = 0.3;
+/// Temperature within this many °C of the dew point risks dew forming
+const DEW_POINT_SPREAD_C: f64 = 3.0;
+/// Default score lost to dew risk
+pub const DEW_PENALTY: f64 = -0.15;
+
+/// Clear nights radiate heat away, so laundry cools below the air and
+/// collects dew once the air is near its dew point, even at moderate humidity.
+fn dew_risk(weather: &WeatherFeatures) -> bool {
+    weather.is_daylight == Some(false)
+        && weather.cloud < DEW_CLEAR_SKY_CLOUD
+        && weather.temp_c - calculate_dew_point_c(weather.temp_c, weather.rh) < DEW_POINT_SPREAD_C
+}
+
+/// Apply the optional, flag-gated terms on top of a model's contributions
+fn apply_feature_terms(
+    contributions: &mut ScoreContributions,
+    weather: &WeatherFeatures,
+    features: &ScoringFeatures,
+    dew_penalty: f64,
+) {
+    if features.gust && weather.gust_ms.is_some_and(|gust| gust > 12.0) {
+        contributions.gust_penalty = -0.15;
+    }
+    if features.daylight && weather.is_daylight == Some(false) {
+        contributions.night_penalty = -0.10;
+    }
+    if features.dew && dew_risk(weather) {
+        contributions.dew_penalty = dew_penalty;
+    }
+}
+
+pub fn model_contributions(
+    weather: &WeatherFeatures,
+    features: &NormalizedFeatures,
+    weights: &DryingWeights,
+    config: &ScoringConfig,
+) -> ScoreContributions {
+    let mut contributions = match config.model {
+        ScoringModel::Linear => score_contributions(weather, features, weights),
+        ScoringModel::Vpd => vpd_contributions(features),
     };
-    
-    (features, vpd_kpa)
+    apply_feature_terms(&mut contributions, weather, &config.features, config.dew_penalty);
+    if let Some(fabric) = config.fabric.filter(|_| weather.temp_c > config.heat.threshold_c) {
+        contributions.heat_penalty = config.heat.penalty * fabric.heat_sensitivity();
+    }
+    if weather.rain_p > config.rain.penalty_probability {
+        contributions.rain_penalty = RAIN_PENALTY;
+    }
+    if config.clamp_scores {
+        let total = contributions.total();
+        contributions.clamp = clamp(total, 0.0, 1.0) - total;
+    }
+    contributions
+}
+
+/// Weather factors ranked by how much they moved the score, strongest first.
+/// Each effect is the factor's contribution relative to a middling reading
+/// (normalized feature of 0.5) with its soft penalties folded in, so positive
+/// effects helped drying and negative ones held it back.
+pub fn factor_effects(
+    weather: &WeatherFeatures,
+    features: &NormalizedFeatures,
+    weights: &DryingWeights,
+    config: &ScoringConfig,
+) -> Vec<(&'static str, f64)> {
+    let c = model_contributions(weather, features, weights, config);
+    let [temp, humidity, wind, cloud, rain, vpd] = match config.model {
+        ScoringModel::Linear => [weights.w1, weights.w2, weights.w3, weights.w4, weights.w5, weights.w6],
+        ScoringModel::Vpd => [0.0, 0.0, VPD_MODEL_WIND_WEIGHT, 0.0, 0.0, VPD_MODEL_VPD_WEIGHT],
+    };
+
+    let mut effects = vec![
+        ("Temperature", c.temp - 0.5 * temp + c.cold_penalty + c.heat_penalty),
+        ("Humidity", c.humidity - 0.5 * humidity),
+        ("Wind", c.wind - 0.5 * wind + c.calm_penalty + c.gust_penalty),
+        ("Cloud cover", c.cloud - 0.5 * cloud),
+        ("Rain chance", c.rain - 0.5 * rain + c.rain_penalty),
+        ("Vapour pressure deficit", c.vpd - 0.5 * vpd),
+        ("Daylight", c.night_penalty),
+        ("Dew risk", c.dew_penalty),
+    ];
+    effects.retain(|(_, effect)| effect.abs() > 1e-9);
+    effects.sort_by(|a, b| b.1.abs().partial_cmp(&a.1.abs()).unwrap());
+    effects
 }
 
 pub fn calculate_drying_score(weather: &WeatherFeatures, weights: &DryingWeights) -> DryingScore {
-    let (features, vpd_kpa) = normalize_features(weather);
-    
-    // Hard veto check
-    let unsafe_window = weather.rain_p > 0.50 || weather.rain_mm > 0.2;
+    calculate_drying_score_with(weather, weights, &ScoringConfig::default())
+}
+
+pub fn calculate_drying_score_with(
+    weather: &WeatherFeatures,
+    weights: &DryingWeights,
+    config: &ScoringConfig,
+) -> DryingScore {
+    score_normalized(weather, weights, config, &Normalizer::new(&config.normalization))
+}
+
+/// [`calculate_drying_score_with`] with `config.normalization` already prepared
+fn score_normalized(
+    weather: &WeatherFeatures,
+    weights: &DryingWeights,
+    config: &ScoringConfig,
+    normalizer: &Normalizer,
+) -> DryingScore {
+    let (features, vpd_kpa) = normalizer.normalize(weather);
     
-    if unsafe_window {
+    // Hard veto: forecast accumulation or freezing wind chill rules a window out
+    if let Some(reason) = config.rain.veto_reason(weather).or_else(|| freeze_veto_reason(weather)) {
         return DryingScore {
             score: -1.0,
             unsafe_window: true,
+            veto_reason: Some(reason),
             features,
             raw: weather.clone(),
             vpd_kpa,
         };
     }
     
-    // Linear score calculation
-    let mut score = weights.w0 
-        + weights.w1 * features.f_temp
-        + weights.w2 * features.f_hum
-        + weights.w3 * features.f_wind
-        + weights.w4 * features.f_cloud
-        + weights.w5 * features.f_rain
-        + weights.w6 * features.f_vpd;
-    
-    // Soft penalties
-    if weather.temp_c < 18.0 {
-        score -= 0.15;
-    }
-    if weather.wind_ms < 1.0 {
-        score -= 0.10;
-    }
+    let score = model_contributions(weather, &features, weights, config).total();
     
     DryingScore {
         score,
         unsafe_window: false,
+        veto_reason: None,
         features,
         raw: weather.clone(),
         vpd_kpa,
     }
 }
 
+/// Score every window, as [`calculate_drying_score`] would each
+pub fn score_windows(windows: &[WindowData], weights: &DryingWeights) -> Vec<DryingScore> {
+    score_windows_with(windows, weights, &ScoringConfig::default())
+}
+
+/// [`score_windows`] under `config`, preparing its normalization once for
+/// the batch instead of once per window
+pub fn score_windows_with(windows: &[WindowData], weights: &DryingWeights, config: &ScoringConfig) -> Vec<DryingScore> {
+    let normalizer = Normalizer::new(&config.normalization);
+    windows
+        .iter()
+        .map(|window| score_normalized(&window.weather, weights, config, &normalizer))
+        .collect()
+}
+
+/// Default step size for online weight updates
+pub const SGD_LEARNING_RATE: f64 = 0.05;
+/// Default L2 regularization for online weight updates
+pub const SGD_REGULARIZATION: f64 = 1e-4;
+
+impl DryingWeights {
+    fn as_array(&self) -> [f64; 7] {
+        [self.w0, self.w1, self.w2, self.w3, self.w4, self.w5, self.w6]
+    }
+
+    /// Hash of the exact weights, which changes whenever any of them does
+    pub fn fingerprint(&self) -> u64 {
+        use std::hash::{Hash, Hasher};
+
+        let mut hasher = std::collections::hash_map::DefaultHasher::new();
+        for w in self.as_array() {
+            w.to_bits().hash(&mut hasher);
+        }
+        hasher.finish()
+    }
+
+    /// Each weight outside the bounds in [`weight_schema`] (the same ones SGD
+    /// clamps to), described as e.g. `w4=0.9 (allowed 0 to 0.3)`
+    pub fn out_of_bounds(&self) -> Vec<String> {
+        WEIGHT_BOUNDS
+            .iter()
+            .zip(self.as_array())
+            .filter(|((_, _, min, max), w)| !(*min..=*max).contains(w))
+            .map(|((name, _, min, max), w)| format!("{}={} (allowed {} to {})", name, w, min, max))
+            .collect()
+    }
+
+    /// Apply `w := w - η·(g + 2λw)` and bound each weight to its range
+    fn step(&mut self, gradient: &[f64; 7], learning_rate: f64, regularization: f64) {
+        let w = self.as_array();
+        let updated: Vec<f64> = w
+            .iter()
+            .zip(gradient)
+            .map(|(wi, gi)| wi - learning_rate * (gi + 2.0 * regularization * wi))
+            .collect();
+
+        // Bound weights to their documented ranges
+        let bounded: Vec<f64> = WEIGHT_BOUNDS
+            .iter()
+            .zip(updated)
+            .map(|((_, _, min, max), w)| clamp(w, *min, *max))
+            .collect();
+        self.w0 = bounded[0];
+        self.w1 = bounded[1];
+        self.w2 = bounded[2];
+        self.w3 = bounded[3];
+        self.w4 = bounded[4];
+        self.w5 = bounded[5];
+        self.w6 = bounded[6];
+    }
+}
+
+/// x = [1, f_temp, f_hum, f_wind, f_cloud, f_rain, f_vpd]
+fn feature_vector(features: &NormalizedFeatures) -> [f64; 7] {
+    [
+        1.0,
+        features.f_temp,
+        features.f_hum,
+        features.f_wind,
+        features.f_cloud,
+        features.f_rain,
+        features.f_vpd,
+    ]
+}
+
+/// Logistic prediction p = σ(w·x) that the window dries
+fn predicted_probability(weights: &DryingWeights, features: &NormalizedFeatures) -> f64 {
+    let z: f64 = weights
+        .as_array()
+        .iter()
+        .zip(feature_vector(features))
+        .map(|(wi, xi)| wi * xi)
+        .sum();
+    1.0 / (1.0 + (-z).exp())
+}
+
+/// Log-loss gradient `(p - y)·x` for one labelled sample
+fn logistic_gradient(weights: &DryingWeights, features: &NormalizedFeatures, label: f64) -> [f64; 7] {
+    let error = predicted_probability(weights, features) - label;
+    feature_vector(features).map(|xi| error * xi)
+}
+
 // Online learning update using logistic regression with SGD
 pub fn update_weights_sgd(
     weights: &mut DryingWeights,
@@ -130,53 +934,82 @@ pub fn update_weights_sgd(
     learning_rate: f64, // η ≈ 0.05
     regularization: f64, // λ ≈ 1e-4
 ) {
-    // Create feature vector x = [1, f_temp, f_hum, f_wind, f_cloud, f_rain, f_vpd]
-    let x = vec![
-        1.0,
-        features.f_temp,
-        features.f_hum,
-        features.f_wind,
-        features.f_cloud,
-        features.f_rain,
-        features.f_vpd,
-    ];
-    
-    // Current weights vector
-    let w = vec![
-        weights.w0,
-        weights.w1,
-        weights.w2,
-        weights.w3,
-        weights.w4,
-        weights.w5,
-        weights.w6,
-    ];
-    
-    // Calculate z = w·x
-    let z: f64 = w.iter().zip(x.iter()).map(|(wi, xi)| wi * xi).sum();
-    
-    // Calculate sigmoid p = σ(z) = 1/(1+e^-z)
-    let p = 1.0 / (1.0 + (-z).exp());
-    
-    // Update weights: w := w - η * (p - y) * x + 2λw
-    let error = p - feedback;
-    
-    weights.w0 -= learning_rate * (error * x[0] + 2.0 * regularization * w[0]);
-    weights.w1 -= learning_rate * (error * x[1] + 2.0 * regularization * w[1]);
-    weights.w2 -= learning_rate * (error * x[2] + 2.0 * regularization * w[2]);
-    weights.w3 -= learning_rate * (error * x[3] + 2.0 * regularization * w[3]);
-    weights.w4 -= learning_rate * (error * x[4] + 2.0 * regularization * w[4]);
-    weights.w5 -= learning_rate * (error * x[5] + 2.0 * regularization * w[5]);
-    weights.w6 -= learning_rate * (error * x[6] + 2.0 * regularization * w[6]);
-    
-    // Bound weights to reasonable ranges
-    weights.w0 = clamp(weights.w0, -0.5, 0.5);
-    weights.w1 = clamp(weights.w1, 0.0, 0.5);
-    weights.w2 = clamp(weights.w2, 0.0, 0.5);
-    weights.w3 = clamp(weights.w3, 0.0, 0.5);
-    weights.w4 = clamp(weights.w4, 0.0, 0.3);
-    weights.w5 = clamp(weights.w5, 0.0, 0.3);
-    weights.w6 = clamp(weights.w6, 0.0, 0.5);
+    let gradient = logistic_gradient(weights, features, feedback);
+    weights.step(&gradient, learning_rate, regularization);
+}
+
+/// Default decay for the learning rate `η / (1 + decay·step)`; 0 keeps it fixed
+pub const SGD_LR_DECAY: f64 = 0.01;
+/// Weight of the newest sample in the running loss estimate
+const LOSS_SMOOTHING: f64 = 0.1;
+
+/// Learned weights plus the training progress needed to decay the learning
+/// rate. The weights are flattened so sets saved without a step counter
+/// still load (as step 0).
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct TrainedWeights {
+    #[serde(flatten)]
+    pub weights: DryingWeights,
+    /// Number of updates applied so far
+    #[serde(default)]
+    pub step: u64,
+    /// Running average of the log-loss each outcome had before its update
+    #[serde(default)]
+    pub loss: Option<f64>,
+}
+
+impl TrainedWeights {
+    /// Learning rate for the next update
+    pub fn learning_rate(&self, base: f64, decay: f64) -> f64 {
+        base / (1.0 + decay * self.step as f64)
+    }
+
+    /// Apply one decayed SGD step for a labelled outcome and return the
+    /// learning rate that was used.
+    pub fn update(
+        &mut self,
+        features: &NormalizedFeatures,
+        label: f64,
+        learning_rate: f64,
+        decay: f64,
+        regularization: f64,
+    ) -> f64 {
+        let p = predicted_probability(&self.weights, features).clamp(1e-9, 1.0 - 1e-9);
+        let loss = -(label * p.ln() + (1.0 - label) * (1.0 - p).ln());
+        self.loss = Some(match self.loss {
+            Some(previous) => previous + LOSS_SMOOTHING * (loss - previous),
+            None => loss,
+        });
+
+        let effective = self.learning_rate(learning_rate, decay);
+        update_weights_sgd(&mut self.weights, features, label, effective, regularization);
+        self.step += 1;
+        effective
+    }
+}
+
+/// Batch variant of `update_weights_sgd`: average the gradient over all
+/// `(features, label)` samples and take a single step, so one noisy outcome
+/// can't yank the weights around. No-op for an empty batch.
+pub fn update_weights_minibatch(
+    weights: &mut DryingWeights,
+    samples: &[(NormalizedFeatures, f64)],
+    learning_rate: f64,
+    regularization: f64,
+) {
+    if samples.is_empty() {
+        return;
+    }
+
+    let mut gradient = [0.0; 7];
+    for (features, label) in samples {
+        let sample = logistic_gradient(weights, features, *label);
+        for (total, gi) in gradient.iter_mut().zip(sample) {
+            *total += gi;
+        }
+    }
+    let batch = samples.len() as f64;
+    weights.step(&gradient.map(|g| g / batch), learning_rate, regularization);
 }
 
 #[cfg(test)]
@@ -199,6 +1032,9 @@ mod tests {
             cloud: 0.3,
             rain_p: 0.1,
             rain_mm: 0.0,
+            gust_ms: None,
+            is_daylight: None,
+            wind_deg: None,
         };
         
         let (features, _) = normalize_features(&weather);
@@ -218,17 +1054,390 @@ mod tests {
             rh: 60.0,
             wind_ms: 3.0,
             cloud: 0.3,
-            rain_p: 0.6, // > 0.50, should trigger veto
+            rain_p: 0.9,
+            rain_mm: 5.0, // > 0.2mm, should trigger veto
+            gust_ms: None,
+            is_daylight: None,
+            wind_deg: None,
+        };
+        
+        let weights = DryingWeights::default();
+        let score = calculate_drying_score(&weather, &weights);
+        
+        assert!(score.unsafe_window);
+        assert_eq!(score.score, -1.0);
+    }
+    
+    #[test]
+    fn test_likely_trace_rain_is_penalized_not_vetoed() {
+        let drizzle = WeatherFeatures {
+            temp_c: 25.0,
+            rh: 60.0,
+            wind_ms: 3.0,
+            cloud: 0.3,
+            rain_p: 0.6,
+            rain_mm: 0.1,
+            gust_ms: None,
+            is_daylight: None,
+            wind_deg: None,
+        };
+        let dry = WeatherFeatures {
+            rain_p: 0.4,
+            ..drizzle.clone()
+        };
+        let weights = DryingWeights::default();
+        
+        let drizzle_score = calculate_drying_score(&drizzle, &weights);
+        let dry_score = calculate_drying_score(&dry, &weights);
+        assert!(!drizzle_score.unsafe_window);
+        assert!(drizzle_score.score > 0.0);
+        // Penalty on top of the smaller f_rain term
+        let f_rain_delta = weights.w5 * 0.2;
+        assert!((dry_score.score - drizzle_score.score - f_rain_delta - 0.25).abs() < 1e-9);
+        
+        // Thresholds are configurable
+        let strict = ScoringConfig {
+            rain: RainThresholds {
+                veto_mm: 0.05,
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        assert!(calculate_drying_score_with(&drizzle, &weights, &strict).unsafe_window);
+    }
+    
+    #[test]
+    fn test_contributions_sum_to_score() {
+        let weather = WeatherFeatures {
+            temp_c: 16.0,
+            rh: 70.0,
+            wind_ms: 0.5,
+            cloud: 0.4,
+            rain_p: 0.2,
             rain_mm: 0.0,
+            gust_ms: None,
+            is_daylight: None,
+            wind_deg: None,
         };
         
         let weights = DryingWeights::default();
         let score = calculate_drying_score(&weather, &weights);
+        let contributions = score_contributions(&weather, &score.features, &weights);
+        
+        assert!((contributions.total() - score.score).abs() < 1e-9);
+        assert_eq!(contributions.cold_penalty, -0.15);
+        assert_eq!(contributions.calm_penalty, -0.10);
+    }
+    
+    #[test]
+    fn test_vpd_model_vs_linear_for_high_vpd_window() {
+        // Hot desert afternoon: very dry air, light breeze
+        let weather = WeatherFeatures {
+            temp_c: 38.0,
+            rh: 12.0,
+            wind_ms: 1.5,
+            cloud: 0.0,
+            rain_p: 0.0,
+            rain_mm: 0.0,
+            gust_ms: None,
+            is_daylight: None,
+            wind_deg: None,
+        };
+        
+        let weights = DryingWeights::default();
+        let linear = calculate_drying_score(&weather, &weights);
+        let vpd = calculate_drying_score_with(
+            &weather,
+            &weights,
+            &ScoringConfig {
+                model: ScoringModel::Vpd,
+                ..Default::default()
+            },
+        );
+        
+        // f_vpd saturates, so the VPD model is driven by VPD and wind only
+        assert_eq!(vpd.features.f_vpd, 1.0);
+        assert!((vpd.score - (0.75 + 0.25 * 1.5 / 6.0)).abs() < 1e-9);
+        // The linear model stacks temp, humidity and VPD on top of each other
+        assert!(linear.score > vpd.score);
+    }
+    
+    #[test]
+    fn test_vpd_model_keeps_rain_veto() {
+        let weather = WeatherFeatures {
+            temp_c: 35.0,
+            rh: 20.0,
+            wind_ms: 3.0,
+            cloud: 0.5,
+            rain_p: 0.9,
+            rain_mm: 2.0,
+            gust_ms: None,
+            is_daylight: None,
+            wind_deg: None,
+        };
+        
+        let score = calculate_drying_score_with(
+            &weather,
+            &DryingWeights::default(),
+            &ScoringConfig {
+                model: ScoringModel::Vpd,
+                ..Default::default()
+            },
+        );
         
         assert!(score.unsafe_window);
         assert_eq!(score.score, -1.0);
     }
     
+    #[test]
+    fn test_gust_flag_toggles_gust_penalty() {
+        let weather = WeatherFeatures {
+            temp_c: 26.0,
+            rh: 55.0,
+            wind_ms: 6.0,
+            cloud: 0.3,
+            rain_p: 0.1,
+            rain_mm: 0.0,
+            gust_ms: Some(16.0),
+            is_daylight: Some(true),
+            wind_deg: None,
+        };
+        let weights = DryingWeights::default();
+        
+        let off = calculate_drying_score_with(&weather, &weights, &ScoringConfig::default());
+        let on = calculate_drying_score_with(
+            &weather,
+            &weights,
+            &ScoringConfig {
+                features: ScoringFeatures {
+                    gust: true,
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
+        );
+        
+        assert_eq!(off.score, calculate_drying_score(&weather, &weights).score);
+        assert!((off.score - on.score - 0.15).abs() < 1e-9);
+    }
+    
+    #[test]
+    fn test_dew_flag_penalizes_clear_night_near_dew_point() {
+        let clear_night = WeatherFeatures {
+            temp_c: 14.0,
+            rh: 85.0,
+            wind_ms: 1.5,
+            cloud: 0.1,
+            rain_p: 0.0,
+            rain_mm: 0.0,
+            gust_ms: None,
+            is_daylight: Some(false),
+            wind_deg: None,
+        };
+        let cloudy_night = WeatherFeatures {
+            cloud: 0.9,
+            ..clear_night.clone()
+        };
+        let weights = DryingWeights::default();
+        let config = ScoringConfig {
+            features: ScoringFeatures {
+                dew: true,
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let (features, _) = normalize_features(&clear_night);
+        
+        assert!((calculate_dew_point_c(14.0, 85.0) - 11.6).abs() < 0.1);
+        let clear = model_contributions(&clear_night, &features, &weights, &config);
+        let cloudy = model_contributions(&cloudy_night, &features, &weights, &config);
+        assert_eq!(clear.dew_penalty, DEW_PENALTY);
+        assert_eq!(cloudy.dew_penalty, 0.0);
+        
+        let off = model_contributions(&clear_night, &features, &weights, &ScoringConfig::default());
+        assert_eq!(off.dew_penalty, 0.0);
+        
+        // The penalty is configurable
+        let harsher = ScoringConfig {
+            dew_penalty: -0.3,
+            ..config.clone()
+        };
+        assert_eq!(model_contributions(&clear_night, &features, &weights, &harsher).dew_penalty, -0.3);
+        
+        // A dry clear night is far from its dew point
+        let dry_night = WeatherFeatures {
+            rh: 40.0,
+            ..clear_night
+        };
+        assert_eq!(model_contributions(&dry_night, &features, &weights, &config).dew_penalty, 0.0);
+    }
+
+    #[test]
+    fn test_freezing_wind_chill_vetoes_window() {
+        let freezing = WeatherFeatures {
+            temp_c: 0.0,
+            rh: 70.0,
+            wind_ms: 8.0,
+            cloud: 0.2,
+            rain_p: 0.0,
+            rain_mm: 0.0,
+            gust_ms: None,
+            is_daylight: Some(true),
+            wind_deg: None,
+        };
+        assert!((calculate_wind_chill_c(0.0, 8.0) - -6.3).abs() < 0.1);
+        
+        let score = calculate_drying_score(&freezing, &DryingWeights::default());
+        assert!(score.unsafe_window);
+        assert!(score.veto_reason.unwrap().starts_with("Risk of freezing"));
+        
+        // Still air at the same temperature, or wind on a mild day, is fine
+        let calm = WeatherFeatures {
+            wind_ms: 1.0,
+            ..freezing.clone()
+        };
+        assert!(!calculate_drying_score(&calm, &DryingWeights::default()).unsafe_window);
+        let mild = WeatherFeatures {
+            temp_c: 8.0,
+            ..freezing
+        };
+        assert!(!calculate_drying_score(&mild, &DryingWeights::default()).unsafe_window);
+    }
+    
+    #[test]
+    fn test_scores_are_clamped_to_unit_range() {
+        let ideal = WeatherFeatures {
+            temp_c: 35.0,
+            rh: 10.0,
+            wind_ms: 8.0,
+            cloud: 0.0,
+            rain_p: 0.0,
+            rain_mm: 0.0,
+            gust_ms: None,
+            is_daylight: Some(true),
+            wind_deg: None,
+        };
+        // Weights summing well above 1
+        let heavy = DryingWeights {
+            w0: 0.5,
+            w1: 0.5,
+            w2: 0.5,
+            w3: 0.5,
+            w4: 0.3,
+            w5: 0.3,
+            w6: 0.5,
+        };
+        let score = calculate_drying_score(&ideal, &heavy);
+        assert_eq!(score.score, 1.0);
+        let (features, _) = normalize_features(&ideal);
+        let contributions = model_contributions(&ideal, &features, &heavy, &ScoringConfig::default());
+        assert!(contributions.clamp < 0.0);
+        assert_eq!(contributions.total(), score.score);
+
+        // Cold and still with no positive weight would go negative
+        let chilly = WeatherFeatures {
+            temp_c: 10.0,
+            wind_ms: 0.5,
+            ..ideal.clone()
+        };
+        let flat = DryingWeights {
+            w0: 0.0,
+            w1: 0.0,
+            w2: 0.0,
+            w3: 0.0,
+            w4: 0.0,
+            w5: 0.0,
+            w6: 0.0,
+        };
+        assert_eq!(calculate_drying_score(&chilly, &flat).score, 0.0);
+
+        let unclamped = ScoringConfig {
+            clamp_scores: false,
+            ..Default::default()
+        };
+        assert!(calculate_drying_score_with(&ideal, &heavy, &unclamped).score > 1.0);
+        assert!(calculate_drying_score_with(&chilly, &flat, &unclamped).score < 0.0);
+        // Vetoes keep their sentinel
+        let wet = WeatherFeatures {
+            rain_mm: 2.0,
+            ..ideal
+        };
+        assert_eq!(calculate_drying_score(&wet, &heavy).score, -1.0);
+    }
+
+    #[test]
+    fn test_heat_penalty_above_threshold_scales_with_fabric() {
+        let warm = WeatherFeatures {
+            temp_c: 28.0,
+            rh: 50.0,
+            wind_ms: 3.0,
+            cloud: 0.1,
+            rain_p: 0.0,
+            rain_mm: 0.0,
+            gust_ms: None,
+            is_daylight: Some(true),
+            wind_deg: None,
+        };
+        let scorching = WeatherFeatures {
+            temp_c: 40.0,
+            ..warm.clone()
+        };
+        let weights = DryingWeights::default();
+        let contributions = |weather: &WeatherFeatures, config: &ScoringConfig| {
+            let (features, _) = normalize_features(weather);
+            model_contributions(weather, &features, &weights, config)
+        };
+        let synthetic = ScoringConfig {
+            fabric: Some(Fabric::Synthetic),
+            ..Default::default()
+        };
+        
+        assert_eq!(contributions(&warm, &synthetic).heat_penalty, 0.0);
+        let hot = contributions(&scorching, &synthetic);
+        assert_eq!(hot.heat_penalty, HeatPenalty::default().penalty);
+        // No fabric, no penalty
+        assert_eq!(contributions(&scorching, &ScoringConfig::default()).heat_penalty, 0.0);
+        
+        // The threshold is configurable
+        let raised = ScoringConfig {
+            heat: HeatPenalty {
+                threshold_c: 45.0,
+                ..Default::default()
+            },
+            ..synthetic.clone()
+        };
+        assert_eq!(contributions(&scorching, &raised).heat_penalty, 0.0);
+        
+        // Delicates are hit harder than cotton
+        let delicates = ScoringConfig {
+            fabric: Some(Fabric::Delicates),
+            ..Default::default()
+        };
+        let cotton = ScoringConfig {
+            fabric: Some(Fabric::Cotton),
+            ..Default::default()
+        };
+        assert!(contributions(&scorching, &delicates).heat_penalty < contributions(&scorching, &cotton).heat_penalty);
+        
+        // A 40°C window scores below a 28°C one for a delicate load
+        let warm_score = calculate_drying_score_with(&warm, &weights, &delicates).score;
+        let hot_score = calculate_drying_score_with(&scorching, &weights, &delicates).score;
+        assert!(hot_score < warm_score);
+    }
+    
+    #[test]
+    fn test_parse_scoring_features() {
+        let (features, unknown) = ScoringFeatures::parse("gust, Daylight,pressure,");
+        
+        assert!(features.gust);
+        assert!(features.daylight);
+        assert!(!features.dew);
+        assert_eq!(unknown, vec!["pressure".to_string()]);
+        
+        let (features, _) = ScoringFeatures::parse("dew");
+        assert!(features.dew);
+    }
+    
     #[test]
     fn test_sgd_update() {
         let mut weights = DryingWeights::default();
@@ -251,4 +1460,242 @@ mod tests {
         // Weights should be within bounds
         assert!(weights.w1 >= 0.0 && weights.w1 <= 0.5);
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_minibatch_converges_more_smoothly_than_single_steps() {
+        let uniform = |f: f64| NormalizedFeatures {
+            f_temp: f,
+            f_hum: f,
+            f_wind: f,
+            f_cloud: f,
+            f_rain: f,
+            f_vpd: f,
+        };
+        // Consistent data: good conditions dry, poor conditions don't
+        let samples = vec![(uniform(0.9), 1.0), (uniform(0.1), 0.0)];
+
+        // Count how often w0 changes direction over the trajectory
+        let reversals = |trajectory: &[f64]| {
+            let deltas: Vec<f64> = trajectory.windows(2).map(|w| w[1] - w[0]).collect();
+            deltas.windows(2).filter(|d| d[0] * d[1] < 0.0).count()
+        };
+
+        let mut single = DryingWeights::default();
+        let mut single_path = vec![single.w0];
+        for _ in 0..20 {
+            for (features, label) in &samples {
+                update_weights_sgd(&mut single, features, *label, 0.05, 1e-4);
+                single_path.push(single.w0);
+            }
+        }
+
+        let mut batched = DryingWeights::default();
+        let mut batched_path = vec![batched.w0];
+        for _ in 0..20 {
+            update_weights_minibatch(&mut batched, &samples, 0.05, 1e-4);
+            batched_path.push(batched.w0);
+        }
+
+        assert!(reversals(&single_path) > 0);
+        assert_eq!(reversals(&batched_path), 0);
+
+        // An empty batch leaves the weights alone
+        let before = batched.clone();
+        update_weights_minibatch(&mut batched, &[], 0.05, 1e-4);
+        assert_eq!(batched, before);
+    }
+
+    #[test]
+    fn test_learning_rate_decays_across_updates() {
+        let features = NormalizedFeatures {
+            f_temp: 0.5,
+            f_hum: 0.6,
+            f_wind: 0.4,
+            f_cloud: 0.7,
+            f_rain: 0.9,
+            f_vpd: 0.5,
+        };
+        let mut trained = TrainedWeights::default();
+
+        let rates: Vec<f64> = (0..5)
+            .map(|_| trained.update(&features, 1.0, SGD_LEARNING_RATE, SGD_LR_DECAY, SGD_REGULARIZATION))
+            .collect();
+
+        assert_eq!(rates[0], SGD_LEARNING_RATE);
+        assert!(rates.windows(2).all(|pair| pair[1] < pair[0]));
+        assert_eq!(trained.step, 5);
+        assert!(trained.loss.unwrap() > 0.0);
+
+        // No decay keeps the rate fixed
+        assert_eq!(trained.learning_rate(SGD_LEARNING_RATE, 0.0), SGD_LEARNING_RATE);
+
+        // Weights saved before the step counter existed still load
+        let legacy: TrainedWeights = serde_json::to_value(DryingWeights::default())
+            .and_then(serde_json::from_value)
+            .unwrap();
+        assert_eq!(legacy, TrainedWeights::default());
+    }
+
+    fn weather(temp_c: f64, rh: f64) -> WeatherFeatures {
+        WeatherFeatures {
+            temp_c,
+            rh,
+            wind_ms: 3.0,
+            cloud: 0.3,
+            rain_p: 0.1,
+            rain_mm: 0.0,
+            gust_ms: None,
+            is_daylight: Some(true),
+            wind_deg: None,
+        }
+    }
+
+    #[test]
+    fn test_cache_key_same_bucket() {
+        let key = weather(28.0, 60.0).cache_key();
+        assert_eq!(key, weather(28.1, 61.5).cache_key());
+        assert_eq!(key, weather(27.9, 58.0).cache_key());
+
+        let mut shifted = weather(28.0, 60.0);
+        shifted.wind_deg = Some(270.0);
+        assert_eq!(key, shifted.cache_key());
+    }
+
+    #[test]
+    fn test_cache_key_across_buckets() {
+        let key = weather(28.0, 60.0).cache_key();
+        assert_ne!(key, weather(28.5, 60.0).cache_key());
+        assert_ne!(key, weather(28.0, 65.0).cache_key());
+
+        let mut rainy = weather(28.0, 60.0);
+        rainy.rain_mm = 0.5;
+        assert_ne!(key, rainy.cache_key());
+    }
+
+    #[test]
+    fn test_low_wind_is_a_negative_factor() {
+        let mut calm = weather(30.0, 25.0);
+        calm.wind_ms = 0.3;
+        let (features, _) = normalize_features(&calm);
+
+        let effects = factor_effects(&calm, &features, &DryingWeights::default(), &ScoringConfig::default());
+
+        let wind = effects.iter().find(|(name, _)| *name == "Wind").unwrap();
+        assert!(wind.1 < 0.0);
+        // Wind is the biggest drag on an otherwise warm, dry afternoon
+        let worst = effects.iter().min_by(|a, b| a.1.partial_cmp(&b.1).unwrap()).unwrap();
+        assert_eq!(worst.0, "Wind");
+        assert!(effects.iter().any(|(name, effect)| *name == "Humidity" && *effect > 0.0));
+    }
+
+    #[test]
+    fn test_good_threshold_changes_verdict() {
+        let defaults = ScoreThresholds::default();
+        assert_eq!(defaults.verdict(0.65), "Good drying conditions");
+        assert_eq!(defaults.verdict(0.85), "Excellent drying conditions!");
+
+        let stricter = ScoreThresholds::parse("0.8, 0.7, 0.4").unwrap();
+        assert_eq!(stricter.verdict(0.65), "Fair drying conditions");
+        assert!(!stricter.is_good(0.65));
+
+        assert!(ScoreThresholds::parse("0.4,0.6,0.8").is_none());
+        assert!(ScoreThresholds::parse("0.8,0.6").is_none());
+
+        // Raised thresholds stop at 1.0, where lower bands still work
+        let raised = defaults.raised(0.3);
+        assert_eq!(raised.excellent, 1.0);
+        assert_eq!(raised.grade(0.95), Verdict::Good);
+        assert_eq!(defaults.raised(0.7).good, 1.0);
+    }
+
+    #[test]
+    fn test_veto_reasons() {
+        let weights = DryingWeights::default();
+
+        let mut downpour = weather(25.0, 60.0);
+        downpour.rain_mm = 5.0;
+        let score = calculate_drying_score(&downpour, &weights);
+        assert_eq!(score.score, -1.0);
+        assert_eq!(score.veto_reason.as_deref(), Some("Expected rain 5.0 mm exceeds the 0.2 mm threshold"));
+
+        // Probability alone only costs score, however high
+        let mut likely = weather(25.0, 60.0);
+        likely.rain_p = 0.95;
+        let score = calculate_drying_score(&likely, &weights);
+        assert!(!score.unsafe_window);
+        assert!(score.veto_reason.is_none());
+
+        let dry = calculate_drying_score(&weather(25.0, 60.0), &weights);
+        assert!(!dry.unsafe_window);
+        assert!(dry.veto_reason.is_none());
+    }
+
+    #[test]
+    fn test_humidity_exponent_changes_f_hum() {
+        let humid = weather(28.0, 70.0);
+        let (default, _) = normalize_features(&humid);
+        assert!((default.f_hum - (1.0 - 0.7_f64.powf(0.7))).abs() < 1e-12);
+
+        let (forgiving, invalid) = NormalizationConfig::parse("humidity_exponent=1.5");
+        assert!(invalid.is_empty());
+        let (tuned, _) = normalize_features_with(&humid, &forgiving);
+        assert!((tuned.f_hum - (1.0 - 0.7_f64.powf(1.5))).abs() < 1e-12);
+        assert!(tuned.f_hum > default.f_hum);
+        // Other features are untouched
+        assert_eq!(tuned.f_wind, default.f_wind);
+
+        let (_, invalid) = NormalizationConfig::parse("humidity_exponent=0,wind=3");
+        assert_eq!(invalid, vec!["humidity_exponent=0", "wind=3"]);
+    }
+
+    /// A week of hourly windows, as the sliding-window mode produces
+    fn week_of_windows() -> Vec<WindowData> {
+        let start = chrono::Utc::now().with_timezone(&chrono::FixedOffset::east_opt(7 * 3600).unwrap());
+        let hourly = crate::forecast::mock::generate_mock_hourly_data(168, start);
+        crate::forecast::merge::group_into_windows(&hourly, 1)
+    }
+
+    #[test]
+    fn test_score_windows_matches_per_window_scoring() {
+        let windows = week_of_windows();
+        assert_eq!(windows.len(), 168);
+        let weights = DryingWeights::default();
+
+        let batch = score_windows(&windows, &weights);
+        assert_eq!(batch.len(), windows.len());
+        for (window, score) in windows.iter().zip(&batch) {
+            let single = calculate_drying_score(&WeatherFeatures::from(window), &weights);
+            assert_eq!(score.score, single.score);
+            assert_eq!(score.veto_reason, single.veto_reason);
+            assert_eq!(score.vpd_kpa, single.vpd_kpa);
+        }
+    }
+
+    const BENCH_ROUNDS: u32 = 2_000;
+
+    /// Baseline for [`bench_score_windows`]: time both with
+    /// `cargo test --release -- --ignored --exact <name>`
+    #[test]
+    #[ignore = "benchmark"]
+    fn bench_per_window_loop() {
+        let windows = week_of_windows();
+        let (weights, config) = (DryingWeights::default(), ScoringConfig::default());
+        for _ in 0..BENCH_ROUNDS {
+            let scores: Vec<DryingScore> = windows
+                .iter()
+                .map(|window| calculate_drying_score_with(&window.weather, &weights, &config))
+                .collect();
+            std::hint::black_box(scores);
+        }
+    }
+
+    #[test]
+    #[ignore = "benchmark"]
+    fn bench_score_windows() {
+        let windows = week_of_windows();
+        let (weights, config) = (DryingWeights::default(), ScoringConfig::default());
+        for _ in 0..BENCH_ROUNDS {
+            std::hint::black_box(score_windows_with(&windows, &weights, &config));
+        }
+    }
+}
@@ -0,0 +1,150 @@
+use chrono::{DateTime, Duration, NaiveDate, Utc};
+use serde::Serialize;
+
+/// Zenith angle (degrees) of the sun's centre at apparent sunrise and sunset,
+/// allowing for refraction and the size of the disc
+const SUNRISE_ZENITH_DEG: f64 = 90.833;
+
+/// Where the sun is in the sky
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct SunPosition {
+    /// Compass bearing of the sun, clockwise from north
+    pub azimuth_deg: f64,
+    /// Height above the horizon; negative once the sun has set
+    pub elevation_deg: f64,
+}
+
+/// Sunrise, solar noon and sunset on one day. `sunrise` and `sunset` are
+/// `None` during polar day or night.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SunTimes {
+    pub sunrise: Option<DateTime<Utc>>,
+    pub solar_noon: DateTime<Utc>,
+    pub sunset: Option<DateTime<Utc>>,
+}
+
+/// Declination (radians) and equation of time (minutes) at `t`, after the
+/// NOAA solar calculator's approximations
+fn declination_and_equation_of_time(t: DateTime<Utc>) -> (f64, f64) {
+    let julian_day = t.timestamp() as f64 / 86_400.0 + 2440587.5;
+    let century = (julian_day - 2451545.0) / 36525.0;
+
+    let mean_longitude = (280.46646 + century * (36000.76983 + century * 0.0003032)).rem_euclid(360.0);
+    let mean_anomaly = 357.52911 + century * (35999.05029 - 0.0001537 * century);
+    let eccentricity = 0.016708634 - century * (0.000042037 + 0.0000001267 * century);
+    let m = mean_anomaly.to_radians();
+    let centre = m.sin() * (1.914602 - century * (0.004817 + 0.000014 * century))
+        + (2.0 * m).sin() * (0.019993 - 0.000101 * century)
+        + (3.0 * m).sin() * 0.000289;
+    let omega = (125.04 - 1934.136 * century).to_radians();
+    let apparent_longitude = (mean_longitude + centre - 0.00569 - 0.00478 * omega.sin()).to_radians();
+    let mean_obliquity =
+        23.0 + (26.0 + (21.448 - century * (46.815 + century * (0.00059 - century * 0.001813))) / 60.0) / 60.0;
+    let obliquity = (mean_obliquity + 0.00256 * omega.cos()).to_radians();
+
+    let declination = (obliquity.sin() * apparent_longitude.sin()).asin();
+
+    let y = (obliquity / 2.0).tan().powi(2);
+    let l0 = mean_longitude.to_radians();
+    let equation_of_time = 4.0
+        * (y * (2.0 * l0).sin() - 2.0 * eccentricity * m.sin()
+            + 4.0 * eccentricity * y * m.sin() * (2.0 * l0).cos()
+            - 0.5 * y * y * (4.0 * l0).sin()
+            - 1.25 * eccentricity * eccentricity * (2.0 * m).sin())
+        .to_degrees();
+
+    (declination, equation_of_time)
+}
+
+/// The sun's position seen from `lat`/`lon` at `t`
+pub fn sun_position(lat: f64, lon: f64, t: DateTime<Utc>) -> SunPosition {
+    let (declination, equation_of_time) = declination_and_equation_of_time(t);
+    let minutes_utc = t.timestamp().rem_euclid(86_400) as f64 / 60.0;
+    let true_solar_minutes = minutes_utc + equation_of_time + 4.0 * lon;
+    let hour_angle = (true_solar_minutes / 4.0 - 180.0).to_radians();
+    let lat = lat.to_radians();
+
+    let cos_zenith = lat.sin() * declination.sin() + lat.cos() * declination.cos() * hour_angle.cos();
+    let zenith = cos_zenith.clamp(-1.0, 1.0).acos();
+    let azimuth = hour_angle
+        .sin()
+        .atan2(hour_angle.cos() * lat.sin() - declination.tan() * lat.cos())
+        .to_degrees();
+
+    SunPosition {
+        azimuth_deg: (azimuth + 180.0).rem_euclid(360.0),
+        elevation_deg: 90.0 - zenith.to_degrees(),
+    }
+}
+
+/// Sunrise, solar noon and sunset at `lat`/`lon` on `date` (UTC calendar day,
+/// which matches the local day away from the date line)
+pub fn sun_times(lat: f64, lon: f64, date: NaiveDate) -> SunTimes {
+    let midnight = date.and_hms_opt(0, 0, 0).expect("midnight exists").and_utc();
+    // Declination and equation of time barely move in a day; take them near noon
+    let approx_noon = midnight + Duration::minutes((720.0 - 4.0 * lon) as i64);
+    let (declination, equation_of_time) = declination_and_equation_of_time(approx_noon);
+
+    let at_minutes = |minutes: f64| midnight + Duration::seconds((minutes * 60.0).round() as i64);
+    let noon_minutes = 720.0 - 4.0 * lon - equation_of_time;
+
+    let lat = lat.to_radians();
+    let cos_hour_angle = SUNRISE_ZENITH_DEG.to_radians().cos() / (lat.cos() * declination.cos())
+        - lat.tan() * declination.tan();
+    let half_day_minutes = (-1.0..=1.0)
+        .contains(&cos_hour_angle)
+        .then(|| 4.0 * cos_hour_angle.acos().to_degrees());
+
+    SunTimes {
+        sunrise: half_day_minutes.map(|half| at_minutes(noon_minutes - half)),
+        solar_noon: at_minutes(noon_minutes),
+        sunset: half_day_minutes.map(|half| at_minutes(noon_minutes + half)),
+    }
+}
+
+/// Eight-point compass name for a bearing, e.g. `SW` for 225°
+pub fn compass_point(bearing_deg: f64) -> &'static str {
+    const POINTS: [&str; 8] = ["N", "NE", "E", "SE", "S", "SW", "W", "NW"];
+    POINTS[((bearing_deg.rem_euclid(360.0) + 22.5) / 45.0) as usize % 8]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Timelike;
+
+    #[test]
+    fn test_bangkok_sun_times_and_noon_position() {
+        let (lat, lon) = (13.75, 100.5);
+        let times = sun_times(lat, lon, NaiveDate::from_ymd_opt(2024, 6, 21).unwrap());
+        let local = |t: DateTime<Utc>| t + Duration::hours(7);
+
+        // Published times for the June solstice: 05:51, 12:20 and 18:49
+        let minutes = |t: DateTime<Utc>| {
+            let t = local(t);
+            t.hour() * 60 + t.minute()
+        };
+        assert!(minutes(times.sunrise.unwrap()).abs_diff(5 * 60 + 51) <= 3);
+        assert!(minutes(times.solar_noon).abs_diff(12 * 60 + 20) <= 3);
+        assert!(minutes(times.sunset.unwrap()).abs_diff(18 * 60 + 49) <= 3);
+
+        // North of Bangkok at midsummer noon, nearly overhead
+        let at_noon = sun_position(lat, lon, times.solar_noon);
+        assert!(at_noon.elevation_deg > 80.0);
+        assert_eq!(compass_point(at_noon.azimuth_deg), "N");
+        // Morning sun in the east, afternoon sun in the west, both north of the line
+        let morning = sun_position(lat, lon, times.solar_noon - Duration::hours(4));
+        let afternoon = sun_position(lat, lon, times.solar_noon + Duration::hours(4));
+        assert!((0.0..90.0).contains(&morning.azimuth_deg));
+        assert!((270.0..360.0).contains(&afternoon.azimuth_deg));
+        assert_eq!(compass_point(225.0), "SW");
+    }
+
+    #[test]
+    fn test_polar_night_has_no_sunrise() {
+        let times = sun_times(78.2, 15.6, NaiveDate::from_ymd_opt(2024, 12, 21).unwrap());
+
+        assert!(times.sunrise.is_none() && times.sunset.is_none());
+        assert!(sun_position(78.2, 15.6, times.solar_noon).elevation_deg < 0.0);
+    }
+}
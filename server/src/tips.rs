@@ -0,0 +1,234 @@
+use crate::scoring::{DryingScore, WeatherFeatures};
+use serde::{Deserialize, Serialize};
+
+/// Where recommendation tips and explanations come from
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TipsEngine {
+    /// Ask the AI provider, falling back to generic advice on failure
+    #[default]
+    Ai,
+    /// The rules below; no AI calls and no cost
+    Heuristic,
+}
+
+impl TipsEngine {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "ai" => Some(TipsEngine::Ai),
+            "heuristic" => Some(TipsEngine::Heuristic),
+            _ => None,
+        }
+    }
+}
+
+/// One piece of advice and when it applies. Rules are checked in order, so
+/// the most pressing advice (rain, gusts) comes first and survives `max_tips`.
+struct Rule {
+    applies: fn(&WeatherFeatures) -> bool,
+    tip: &'static str,
+}
+
+const RULES: &[Rule] = &[
+    // Rain
+    Rule {
+        applies: |w| w.rain_p > 0.6 || w.rain_mm > 0.2,
+        tip: "Rain is likely - dry indoors or under cover, or wait for a drier window.",
+    },
+    Rule {
+        applies: |w| w.rain_p > 0.3 && w.rain_p <= 0.6,
+        tip: "Keep an eye on the sky and be ready to bring clothes in if rain starts.",
+    },
+    // Gusts
+    Rule {
+        applies: |w| w.gust_ms.is_some_and(|g| g > 12.0),
+        tip: "Strong gusts are expected - use extra pegs and keep light items indoors.",
+    },
+    Rule {
+        applies: |w| w.gust_ms.is_some_and(|g| g > 8.0 && g <= 12.0),
+        tip: "It will be gusty at times - peg items at both ends so they don't blow off the line.",
+    },
+    // Daylight
+    Rule {
+        applies: |w| w.is_daylight == Some(false),
+        tip: "Drying after dark is slow and dew can settle - hang clothes under cover or bring them in overnight.",
+    },
+    // Wind
+    Rule {
+        applies: |w| w.wind_ms > 3.0,
+        tip: "Take advantage of the strong wind by hanging clothes in open areas.",
+    },
+    Rule {
+        applies: |w| w.wind_ms < 1.0,
+        tip: "With low wind, space clothes well apart for better air circulation.",
+    },
+    // Humidity
+    Rule {
+        applies: |w| w.rh > 80.0,
+        tip: "High humidity may slow drying - consider using a covered but ventilated area.",
+    },
+    Rule {
+        applies: |w| w.rh < 40.0,
+        tip: "The air is dry, so even thick items like towels and jeans should dry well.",
+    },
+    // Temperature
+    Rule {
+        applies: |w| w.temp_c > 32.0,
+        tip: "Strong heat can fade colours - turn dark clothes inside out or hang them in the shade.",
+    },
+    Rule {
+        applies: |w| w.temp_c < 15.0,
+        tip: "Cool air slows drying - allow extra time and hang thick items where they catch the sun.",
+    },
+    // Sky
+    Rule {
+        applies: |w| w.cloud > 0.7,
+        tip: "With little sun, rely on airflow - turn thick items halfway through.",
+    },
+    Rule {
+        applies: |w| w.cloud < 0.3 && w.is_daylight != Some(false) && w.rain_p <= 0.3,
+        tip: "Clear skies - hang whites in direct sun to help them brighten.",
+    },
+];
+
+const FALLBACK_TIP: &str = "Conditions look good for drying - hang clothes evenly spaced for best results.";
+
+/// Rule-based drying tips for the given conditions, used wherever an AI
+/// reply isn't wanted or available
+pub fn heuristic_tips(weather: &WeatherFeatures) -> Vec<String> {
+    let tips: Vec<String> = RULES
+        .iter()
+        .filter(|rule| (rule.applies)(weather))
+        .map(|rule| rule.tip.to_string())
+        .collect();
+
+    if tips.is_empty() {
+        vec![FALLBACK_TIP.to_string()]
+    } else {
+        tips
+    }
+}
+
+/// A short rule-based explanation of a window's score
+pub fn heuristic_explanation(weather: &WeatherFeatures, score: &DryingScore) -> String {
+    if score.unsafe_window {
+        let reason = score.veto_reason.as_deref().unwrap_or("Rain is expected");
+        return format!("{} - this window isn't safe for outdoor drying.", reason);
+    }
+
+    let mut helps = Vec::new();
+    let mut slows = Vec::new();
+    if weather.temp_c >= 28.0 {
+        helps.push("warm air");
+    } else if weather.temp_c < 18.0 {
+        slows.push("cool air");
+    }
+    if weather.rh < 60.0 {
+        helps.push("low humidity");
+    } else if weather.rh > 80.0 {
+        slows.push("high humidity");
+    }
+    if weather.wind_ms >= 3.0 {
+        helps.push("a good breeze");
+    } else if weather.wind_ms < 1.0 {
+        slows.push("still air");
+    }
+    if weather.rain_p > 0.3 {
+        slows.push("a chance of rain");
+    }
+
+    let mut explanation = format!(
+        "Drying score {:.2} at {:.0}°C, {:.0}% humidity and {:.1} m/s wind.",
+        score.score, weather.temp_c, weather.rh, weather.wind_ms
+    );
+    if !helps.is_empty() {
+        explanation.push_str(&format!(" Helped by {}.", helps.join(", ")));
+    }
+    if !slows.is_empty() {
+        explanation.push_str(&format!(" Held back by {}.", slows.join(", ")));
+    }
+    explanation
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scoring::{calculate_drying_score, DryingWeights};
+
+    fn weather(temp_c: f64, rh: f64, wind_ms: f64, rain_p: f64) -> WeatherFeatures {
+        WeatherFeatures {
+            temp_c,
+            rh,
+            wind_ms,
+            cloud: 0.5,
+            rain_p,
+            rain_mm: 0.0,
+            gust_ms: None,
+            is_daylight: Some(true),
+            wind_deg: None,
+        }
+    }
+
+    #[test]
+    fn test_mild_conditions_fall_back_to_general_tip() {
+        assert_eq!(heuristic_tips(&weather(25.0, 60.0, 2.0, 0.0)), vec![FALLBACK_TIP.to_string()]);
+    }
+
+    #[test]
+    fn test_hot_dry_windy_day() {
+        let tips = heuristic_tips(&WeatherFeatures {
+            cloud: 0.1,
+            ..weather(35.0, 30.0, 4.0, 0.0)
+        });
+        assert!(tips.iter().any(|t| t.contains("strong wind")));
+        assert!(tips.iter().any(|t| t.contains("dry")));
+        assert!(tips.iter().any(|t| t.contains("fade colours")));
+        assert!(tips.iter().any(|t| t.contains("Clear skies")));
+        assert!(!tips.iter().any(|t| t.contains("rain")));
+    }
+
+    #[test]
+    fn test_rain_and_gusts_come_first() {
+        let tips = heuristic_tips(&WeatherFeatures {
+            gust_ms: Some(14.0),
+            ..weather(22.0, 85.0, 5.0, 0.7)
+        });
+        assert!(tips[0].starts_with("Rain is likely"));
+        assert!(tips[1].starts_with("Strong gusts"));
+        assert!(tips.iter().any(|t| t.contains("High humidity")));
+
+        // A possible shower gets the milder warning, never both
+        let showery = heuristic_tips(&weather(22.0, 60.0, 2.0, 0.4));
+        assert_eq!(showery[0], "Keep an eye on the sky and be ready to bring clothes in if rain starts.");
+        assert!(!showery.iter().any(|t| t.starts_with("Rain is likely")));
+    }
+
+    #[test]
+    fn test_cold_still_night() {
+        let tips = heuristic_tips(&WeatherFeatures {
+            is_daylight: Some(false),
+            cloud: 0.1,
+            ..weather(10.0, 70.0, 0.5, 0.0)
+        });
+        assert!(tips.iter().any(|t| t.contains("after dark")));
+        assert!(tips.iter().any(|t| t.contains("Cool air")));
+        assert!(tips.iter().any(|t| t.contains("low wind")));
+        assert!(!tips.iter().any(|t| t.contains("Clear skies")));
+    }
+
+    #[test]
+    fn test_explanation_names_drivers() {
+        let conditions = weather(30.0, 45.0, 4.0, 0.0);
+        let score = calculate_drying_score(&conditions, &DryingWeights::default());
+        let explanation = heuristic_explanation(&conditions, &score);
+        assert!(explanation.contains("warm air, low humidity, a good breeze"));
+        assert!(!explanation.contains("Held back"));
+
+        let wet = WeatherFeatures {
+            rain_mm: 2.0,
+            ..conditions
+        };
+        let score = calculate_drying_score(&wet, &DryingWeights::default());
+        assert!(heuristic_explanation(&wet, &score).contains("isn't safe"));
+    }
+}
@@ -1,7 +1,6 @@
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
-use uuid::Uuid;
 
 /// Generate a unique window ID based on location and time
 pub fn generate_window_id(lat: f64, lon: f64, start_time: DateTime<Utc>, duration_hours: u32) -> String {
@@ -16,10 +15,10 @@ pub fn generate_window_id(lat: f64, lon: f64, start_time: DateTime<Utc>, duratio
 
 /// Validate latitude and longitude coordinates
 pub fn validate_coordinates(lat: f64, lon: f64) -> Result<(), String> {
-    if lat < -90.0 || lat > 90.0 {
+    if !(-90.0..=90.0).contains(&lat) {
         return Err(format!("Invalid latitude: {}. Must be between -90 and 90", lat));
     }
-    if lon < -180.0 || lon > 180.0 {
+    if !(-180.0..=180.0).contains(&lon) {
         return Err(format!("Invalid longitude: {}. Must be between -180 and 180", lon));
     }
     Ok(())
@@ -128,7 +127,7 @@ pub fn moving_average(values: &[f64], window_size: usize) -> Vec<f64> {
     let mut result = Vec::new();
     
     for i in 0..values.len() {
-        let start = if i >= window_size - 1 { i - window_size + 1 } else { 0 };
+        let start = (i + 1).saturating_sub(window_size);
         let end = i + 1;
         let window = &values[start..end];
         let avg = window.iter().sum::<f64>() / window.len() as f64;
@@ -162,6 +161,61 @@ pub fn round_to_decimals(value: f64, decimals: u32) -> f64 {
     (value * multiplier).round() / multiplier
 }
 
+/// Decimal places numbers are rounded to in API responses
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OutputPrecision {
+    pub temperature: u32,
+    pub humidity: u32,
+    pub wind: u32,
+    pub rain: u32,
+    pub probability: u32,
+    pub score: u32,
+}
+
+impl Default for OutputPrecision {
+    fn default() -> Self {
+        Self {
+            temperature: 1,
+            humidity: 1,
+            wind: 1,
+            rain: 1,
+            probability: 2,
+            score: 2,
+        }
+    }
+}
+
+impl OutputPrecision {
+    /// Parse `name=decimals` overrides (e.g. `temperature=2,score=3`) on top
+    /// of the defaults, returning any entries that couldn't be applied
+    pub fn parse(value: &str) -> (Self, Vec<String>) {
+        let mut precision = Self::default();
+        let mut invalid = Vec::new();
+
+        for entry in value.split(',').map(str::trim).filter(|e| !e.is_empty()) {
+            let parsed = entry.split_once('=').and_then(|(name, decimals)| {
+                let decimals = decimals.trim().parse::<u32>().ok().filter(|&d| d <= 6)?;
+                Some((name.trim(), decimals))
+            });
+            let Some((name, decimals)) = parsed else {
+                invalid.push(entry.to_string());
+                continue;
+            };
+            match name {
+                "temperature" => precision.temperature = decimals,
+                "humidity" => precision.humidity = decimals,
+                "wind" => precision.wind = decimals,
+                "rain" => precision.rain = decimals,
+                "probability" => precision.probability = decimals,
+                "score" => precision.score = decimals,
+                _ => invalid.push(entry.to_string()),
+            }
+        }
+
+        (precision, invalid)
+    }
+}
+
 /// Generate a random jitter value for retry delays
 pub fn jitter(base_ms: u64, max_jitter_ms: u64) -> u64 {
     use rand::Rng;
@@ -193,9 +247,65 @@ pub fn sanitize_string(input: &str, max_length: usize) -> String {
         .to_string()
 }
 
+/// Abbreviations whose trailing period doesn't end a sentence
+const ABBREVIATIONS: [&str; 8] = ["e.g.", "i.e.", "approx.", "incl.", "esp.", "vs.", "min.", "max."];
+
+/// Split free text into sentences for display as separate tips, each kept
+/// with its closing punctuation. A period only ends a sentence when followed
+/// by whitespace and not after an abbreviation or a list number, so "2.5 m/s",
+/// "e.g. towels" and "1. Hang early" stay whole; line breaks also end one.
+/// Leading list markers are stripped and fragments with no words dropped.
+pub fn split_tips(text: &str) -> Vec<String> {
+    let mut tips = Vec::new();
+    let mut current = String::new();
+    let mut chars = text.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        if c != '\n' {
+            current.push(c);
+        }
+        let ends_sentence = matches!(c, '.' | '!' | '?')
+            && chars.peek().is_none_or(|next| next.is_whitespace())
+            && !(c == '.' && continues_sentence(&current));
+        if c == '\n' || ends_sentence {
+            push_tip(&mut tips, &mut current);
+        }
+    }
+    push_tip(&mut tips, &mut current);
+
+    tips
+}
+
+/// Whether the period just pushed onto `current` belongs to its last word
+fn continues_sentence(current: &str) -> bool {
+    let word = current.split_whitespace().last().unwrap_or_default().to_lowercase();
+    let number = word.trim_end_matches('.');
+    let list_marker = !number.is_empty() && number.chars().all(|c| c.is_ascii_digit()) && current.trim() == word;
+
+    list_marker || ABBREVIATIONS.contains(&word.trim_start_matches(['(', '"', '\''].as_ref()))
+}
+
+fn push_tip(tips: &mut Vec<String>, current: &mut String) {
+    let tip = current.trim().trim_start_matches(['-', '*', '•']).trim_start();
+    let tip = match tip.split_once(". ") {
+        Some((number, rest)) if !number.is_empty() && number.chars().all(|c| c.is_ascii_digit()) => rest.trim_start(),
+        _ => tip,
+    };
+    if tip.chars().any(char::is_alphanumeric) {
+        tips.push(tip.to_string());
+    }
+    current.clear();
+}
+
 /// Validate email format (basic validation)
 pub fn is_valid_email(email: &str) -> bool {
-    email.contains('@') && email.contains('.') && email.len() > 5 && email.len() < 255
+    if email.len() <= 5 || email.len() >= 255 {
+        return false;
+    }
+    match email.split_once('@') {
+        Some((local, domain)) => !local.is_empty() && domain.contains('.'),
+        None => false,
+    }
 }
 
 /// Generate a cache key for weather data
@@ -236,7 +346,7 @@ impl RateLimiter {
         let cutoff = now - self.window_duration;
         
         // Clean old requests
-        let requests = self.requests.entry(key.to_string()).or_insert_with(Vec::new);
+        let requests = self.requests.entry(key.to_string()).or_default();
         requests.retain(|&timestamp| timestamp > cutoff);
         
         // Check if under limit
@@ -311,6 +421,50 @@ impl<T> SuccessResponse<T> {
 mod tests {
     use super::*;
     
+    #[test]
+    fn test_output_precision_parse() {
+        let (precision, invalid) = OutputPrecision::parse("temperature=2, score=3");
+        assert_eq!(precision.temperature, 2);
+        assert_eq!(precision.score, 3);
+        assert_eq!(precision.wind, 1);
+        assert!(invalid.is_empty());
+
+        let (_, invalid) = OutputPrecision::parse("pressure=1,wind=x,rain=9");
+        assert_eq!(invalid, vec!["pressure=1", "wind=x", "rain=9"]);
+    }
+
+    #[test]
+    fn test_split_tips_keeps_decimals_whole() {
+        let tips = split_tips("Wind is around 2.5 m/s, so hang early. . Bring clothes in by 17.30!\nTurn them once.");
+        assert_eq!(
+            tips,
+            vec![
+                "Wind is around 2.5 m/s, so hang early.",
+                "Bring clothes in by 17.30!",
+                "Turn them once.",
+            ]
+        );
+        assert!(split_tips(" . ").is_empty());
+    }
+
+    #[test]
+    fn test_split_tips_keeps_abbreviations_and_lists_whole() {
+        let tips = split_tips(
+            "Dry heavy items first, e.g. towels and jeans. Wind is 2.5 m/s (approx. 9 km/h), i.e. a light breeze. Done.",
+        );
+        assert_eq!(
+            tips,
+            vec![
+                "Dry heavy items first, e.g. towels and jeans.",
+                "Wind is 2.5 m/s (approx. 9 km/h), i.e. a light breeze.",
+                "Done.",
+            ]
+        );
+
+        let tips = split_tips("1. Hang early.\n2. Turn items at noon.\n- Bring them in by 5pm.");
+        assert_eq!(tips, vec!["Hang early.", "Turn items at noon.", "Bring them in by 5pm."]);
+    }
+
     #[test]
     fn test_validate_coordinates() {
         assert!(validate_coordinates(0.0, 0.0).is_ok());
@@ -366,8 +520,8 @@ mod tests {
     #[test]
     fn test_sanitize_string() {
         assert_eq!(sanitize_string("Hello, World!", 20), "Hello, World!");
-        assert_eq!(sanitize_string("<script>alert('xss')</script>", 20), "scriptalert'xss'script");
-        assert_eq!(sanitize_string("Very long string that exceeds limit", 10), "Very long ");
+        assert_eq!(sanitize_string("<script>alert('xss')</script>", 30), "scriptalert('xss')script");
+        assert_eq!(sanitize_string("Very long string that exceeds limit", 10), "Very long");
     }
     
     #[test]
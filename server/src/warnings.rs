@@ -0,0 +1,47 @@
+use serde::{Deserialize, Serialize};
+
+/// Gust speed (m/s) from which pegs and light items need attention
+pub const GUSTY_WIND_MS: f64 = 8.0;
+/// Spread between a window's worst and best case score that makes it a guess
+pub const LOW_CONFIDENCE_SPREAD: f64 = 0.2;
+/// Recommendation confidence below which the advice is flagged as uncertain
+pub const LOW_CONFIDENCE: f64 = 0.4;
+
+/// Kinds of soft concern; clients key badges off these
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WarningCode {
+    /// Showers the window was scored around
+    RainGap,
+    /// Gusts strong enough to blow items off the line
+    Gusty,
+    /// Strong sun that fades the load
+    HighUv,
+    /// Hours estimated rather than forecast directly
+    SyntheticData,
+    /// The score could be well off either way
+    LowConfidence,
+}
+
+/// A concern that didn't rule a window out
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Warning {
+    pub code: WarningCode,
+    pub message: String,
+}
+
+impl Warning {
+    pub fn new(code: WarningCode, message: impl Into<String>) -> Self {
+        Self {
+            code,
+            message: message.into(),
+        }
+    }
+}
+
+/// Append `warning` unless one with the same code is already there
+pub fn push_unique(warnings: &mut Vec<Warning>, warning: Warning) {
+    if !warnings.iter().any(|w| w.code == warning.code) {
+        warnings.push(warning);
+    }
+}
@@ -0,0 +1,48 @@
+mod common;
+
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+use std::time::Duration;
+
+use common::FakeAi;
+use laundry_optimizer_server::{
+    ai::{AiProvider, CachedAiClient},
+    scoring::{calculate_drying_score, DryingScore, WeatherFeatures},
+};
+
+fn window(temp_c: f64) -> (String, DryingScore, WeatherFeatures) {
+    let weather = WeatherFeatures {
+        temp_c,
+        rh: 55.0,
+        wind_ms: 3.0,
+        cloud: 0.2,
+        rain_p: 0.1,
+        rain_mm: 0.0,
+        gust_ms: None,
+        is_daylight: None,
+        wind_deg: None,
+    };
+    let score = calculate_drying_score(&weather, &Default::default());
+    ("2026-10-16 09:00".to_string(), score, weather)
+}
+
+#[tokio::test]
+async fn cached_ai_serves_repeat_requests_from_cache() {
+    let fake = Arc::new(FakeAi::new());
+    let cached = CachedAiClient::new(fake.clone(), Duration::from_secs(3600));
+
+    let first = cached.explain_recommendation(&[window(28.0)], None).await.unwrap();
+    let (_, score, weather) = window(28.0);
+    cached.generate_drying_tips(&weather, &score).await.unwrap();
+    assert_eq!(fake.calls.load(Ordering::SeqCst), 2);
+
+    // Identical requests: zero further AI calls
+    let second = cached.explain_recommendation(&[window(28.0)], None).await.unwrap();
+    cached.generate_drying_tips(&weather, &score).await.unwrap();
+    assert_eq!(first, second);
+    assert_eq!(fake.calls.load(Ordering::SeqCst), 2);
+
+    // Different conditions miss the cache
+    cached.explain_recommendation(&[window(34.0)], None).await.unwrap();
+    assert_eq!(fake.calls.load(Ordering::SeqCst), 3);
+}
@@ -0,0 +1,309 @@
+//! Shared harness for HTTP-level tests: a router wired to the mock weather
+//! client, the mock AI client and an in-memory SQLite database, plus
+//! configurable fakes for tests that need the weather or the AI to misbehave.
+
+// Each test binary uses its own subset of the harness
+#![allow(dead_code)]
+
+use async_trait::async_trait;
+use axum::{
+    body::Body,
+    http::{header, Method, Request, StatusCode},
+    response::Response,
+    Router,
+};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tower::ServiceExt;
+
+use laundry_optimizer_server::{
+    ai::{AiError, AiProvider, FeedbackAnalysis, MockAiClient},
+    config::Config,
+    database::{DataStore, Database, MockDatabase},
+    forecast::{
+        mock::MockWeatherClient,
+        openweather::OpenWeatherError,
+        types::{Forecast3hResponse, GeocodeResponse, OneCallHourly, OneCallResponse},
+        WeatherProvider,
+    },
+    routes::{create_router, AppState},
+    scoring::{DryingScore, WeatherFeatures},
+};
+
+/// Configuration built from a fixed set of variables, with `overrides` on top
+pub fn test_config(overrides: &[(&str, &str)]) -> Config {
+    let mut vars: HashMap<String, String> = [
+        ("OPENROUTER_API_KEY", "test"),
+        ("OPENWEATHER_API_KEY", "test"),
+        ("OPENROUTER_BASE_URL", "http://127.0.0.1:9/chat/completions"),
+        ("OPENWEATHER_BASE_URL", "http://127.0.0.1:9"),
+    ]
+    .iter()
+    .map(|(k, v)| (k.to_string(), v.to_string()))
+    .collect();
+    for (k, v) in overrides {
+        vars.insert(k.to_string(), v.to_string());
+    }
+
+    Config::from_lookup(|name| vars.get(name).cloned()).expect("test config is valid")
+}
+
+pub async fn sqlite_store() -> Arc<dyn DataStore> {
+    // A single connection keeps every query on the same in-memory database
+    let pool = sqlx::sqlite::SqlitePoolOptions::new()
+        .max_connections(1)
+        .connect("sqlite::memory:")
+        .await
+        .unwrap();
+    let database = Database::new(pool);
+    database.init_tables().await.unwrap();
+
+    Arc::new(database)
+}
+
+pub fn test_state(config: Config, database: Arc<dyn DataStore>) -> AppState {
+    AppState::new(
+        Arc::new(config),
+        database,
+        Arc::new(MockWeatherClient::new()),
+        Arc::new(MockAiClient::new()),
+    )
+}
+
+/// Router backed by an in-memory SQLite database
+pub async fn test_app() -> Router {
+    create_router(test_state(test_config(&[]), sqlite_store().await))
+}
+
+/// Router backed by `MockDatabase`, for tests that don't need SQL semantics
+pub fn mock_app() -> Router {
+    create_router(test_state(test_config(&[]), Arc::new(MockDatabase::new())))
+}
+
+/// Send a GET through the router and return the raw response, headers included
+pub async fn raw_get(app: &Router, uri: &str) -> Response {
+    let request = Request::builder().uri(uri).body(Body::empty()).unwrap();
+    app.clone().oneshot(request).await.unwrap()
+}
+
+/// Read a response body as text
+pub async fn body_text(response: Response) -> String {
+    let bytes = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    String::from_utf8(bytes.to_vec()).unwrap()
+}
+
+/// Send one request through the router and decode the JSON body (or `Null`)
+pub async fn send(app: &Router, method: Method, uri: &str, body: Option<Value>) -> (StatusCode, Value) {
+    let request = Request::builder().method(method).uri(uri);
+    let request = match body {
+        Some(json) => request
+            .header(header::CONTENT_TYPE, "application/json")
+            .body(Body::from(json.to_string())),
+        None => request.body(Body::empty()),
+    }
+    .unwrap();
+
+    dispatch(app, request).await
+}
+
+/// Send a bodyless request with extra `headers`, decoding it like [`send`]
+pub async fn send_with_headers(app: &Router, method: Method, uri: &str, headers: &[(&str, &str)]) -> (StatusCode, Value) {
+    let mut request = Request::builder().method(method).uri(uri);
+    for (name, value) in headers {
+        request = request.header(*name, *value);
+    }
+
+    dispatch(app, request.body(Body::empty()).unwrap()).await
+}
+
+async fn dispatch(app: &Router, request: Request<Body>) -> (StatusCode, Value) {
+    let response = app.clone().oneshot(request).await.unwrap();
+    let status = response.status();
+    let bytes = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    let json = serde_json::from_slice(&bytes).unwrap_or(Value::Null);
+
+    (status, json)
+}
+
+pub async fn get(app: &Router, uri: &str) -> (StatusCode, Value) {
+    send(app, Method::GET, uri, None).await
+}
+
+pub async fn post(app: &Router, uri: &str, body: Value) -> (StatusCode, Value) {
+    send(app, Method::POST, uri, Some(body)).await
+}
+
+type HourScript = Box<dyn Fn(usize, &mut OneCallHourly) + Send + Sync>;
+
+/// Mock weather with optional latency and scripted hourly conditions
+#[derive(Default)]
+pub struct FakeWeather {
+    /// Forecast calls made
+    pub calls: AtomicUsize,
+    /// Most forecast calls seen in flight at once
+    pub max_in_flight: AtomicUsize,
+    in_flight: AtomicUsize,
+    inner: MockWeatherClient,
+    delay: Duration,
+    script: Option<HourScript>,
+    rainy_days: bool,
+    forecast3h_down: bool,
+}
+
+impl FakeWeather {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Wait `delay` before answering each forecast call
+    pub fn delayed(mut self, delay: Duration) -> Self {
+        self.delay = delay;
+        self
+    }
+
+    /// Rewrite every onecall hour (by index) on each fetch
+    pub fn hourly(mut self, script: impl Fn(usize, &mut OneCallHourly) + Send + Sync + 'static) -> Self {
+        self.script = Some(Box::new(script));
+        self
+    }
+
+    /// Make every day beyond the hourly range wet
+    pub fn rainy_days(mut self) -> Self {
+        self.rainy_days = true;
+        self
+    }
+
+    /// Fail every 3-hour forecast call
+    pub fn without_forecast3h(mut self) -> Self {
+        self.forecast3h_down = true;
+        self
+    }
+
+    /// Count a forecast call in flight for `delay`
+    async fn wait(&self) {
+        self.calls.fetch_add(1, Ordering::SeqCst);
+        let now = self.in_flight.fetch_add(1, Ordering::SeqCst) + 1;
+        self.max_in_flight.fetch_max(now, Ordering::SeqCst);
+        tokio::time::sleep(self.delay).await;
+        self.in_flight.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+#[async_trait]
+impl WeatherProvider for FakeWeather {
+    async fn get_onecall(&self, lat: f64, lon: f64) -> Result<OneCallResponse, OpenWeatherError> {
+        self.wait().await;
+        let mut onecall = self.inner.get_onecall(lat, lon).await?;
+        if let Some(script) = &self.script {
+            for (i, hour) in onecall.hourly.iter_mut().enumerate() {
+                script(i, hour);
+            }
+        }
+        if self.rainy_days {
+            for day in &mut onecall.daily {
+                day.rain = Some(40.0);
+            }
+        }
+        Ok(onecall)
+    }
+
+    async fn get_forecast3h(&self, lat: f64, lon: f64) -> Result<Forecast3hResponse, OpenWeatherError> {
+        self.wait().await;
+        if self.forecast3h_down {
+            return Err(OpenWeatherError::ApiError("unavailable".to_string()));
+        }
+        self.inner.get_forecast3h(lat, lon).await
+    }
+
+    async fn geocode_direct(&self, query: &str) -> Result<Vec<GeocodeResponse>, OpenWeatherError> {
+        self.inner.geocode_direct(query).await
+    }
+
+    async fn geocode_reverse(&self, lat: f64, lon: f64) -> Result<Vec<GeocodeResponse>, OpenWeatherError> {
+        self.inner.geocode_reverse(lat, lon).await
+    }
+}
+
+/// Mock AI that counts and records what it is asked, and can be made to
+/// reply with fixed tips, answer slowly or fail as if every model were
+/// cooling down
+#[derive(Default)]
+pub struct FakeAi {
+    /// Requests of any kind that reached the provider
+    pub calls: AtomicUsize,
+    pub explanations: AtomicUsize,
+    /// Weather passed to each explanation and tips request
+    pub weather: Mutex<Vec<WeatherFeatures>>,
+    delay: Duration,
+    tips: Option<&'static str>,
+    tripped: bool,
+}
+
+impl FakeAi {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Wait `delay` before answering each explanation
+    pub fn delayed(mut self, delay: Duration) -> Self {
+        self.delay = delay;
+        self
+    }
+
+    /// Reply to every tips request with `tips`
+    pub fn with_tips(mut self, tips: &'static str) -> Self {
+        self.tips = Some(tips);
+        self
+    }
+
+    /// Fail every request with an open circuit
+    pub fn tripped(mut self) -> Self {
+        self.tripped = true;
+        self
+    }
+
+    fn ask(&self) -> Result<(), AiError> {
+        self.calls.fetch_add(1, Ordering::SeqCst);
+        if self.tripped {
+            return Err(AiError::CircuitOpen("primary".to_string()));
+        }
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl AiProvider for FakeAi {
+    async fn explain_recommendation(
+        &self,
+        window_data: &[(String, DryingScore, WeatherFeatures)],
+        user_preferences: Option<&str>,
+    ) -> Result<String, AiError> {
+        self.ask()?;
+        self.explanations.fetch_add(1, Ordering::SeqCst);
+        self.weather.lock().unwrap().extend(window_data.iter().map(|(_, _, w)| w.clone()));
+        tokio::time::sleep(self.delay).await;
+        MockAiClient::new().explain_recommendation(window_data, user_preferences).await
+    }
+
+    async fn generate_drying_tips(&self, weather: &WeatherFeatures, score: &DryingScore) -> Result<String, AiError> {
+        self.ask()?;
+        self.weather.lock().unwrap().push(weather.clone());
+        match self.tips {
+            Some(tips) => Ok(tips.to_string()),
+            None => MockAiClient::new().generate_drying_tips(weather, score).await,
+        }
+    }
+
+    async fn analyze_feedback(&self, text: &str, weather: &WeatherFeatures) -> Result<FeedbackAnalysis, AiError> {
+        self.ask()?;
+        MockAiClient::new().analyze_feedback(text, weather).await
+    }
+
+    async fn generate_laundry_recommendation(&self, weather: &WeatherFeatures) -> Result<String, AiError> {
+        self.ask()?;
+        MockAiClient::new().generate_laundry_recommendation(weather).await
+    }
+}
@@ -0,0 +1,1553 @@
+mod common;
+
+use axum::http::{header, Method, StatusCode};
+use serde_json::json;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use common::{
+    get, mock_app, post, send, send_with_headers, sqlite_store, test_app, test_config, test_state, FakeAi, FakeWeather,
+};
+use laundry_optimizer_server::{
+    ai::ActualOutcome,
+    database::{CreateFeedback, FeedbackRecord, MockDatabase, ServedWindow},
+    forecast::types::Precipitation,
+    routes::create_router,
+    scoring::{DryingWeights, TrainedWeights},
+};
+
+/// Warm, dry two-hour spells broken up by an hour of rain, with rainy days
+/// beyond the hourly range and no 3-hour forecast
+fn shower_weather() -> FakeWeather {
+    FakeWeather::new()
+        .hourly(|i, hour| {
+            hour.temp = 31.0;
+            hour.humidity = 35.0;
+            hour.wind_speed = 4.0;
+            hour.clouds = 10.0;
+            hour.pop = if i % 3 == 2 { 0.8 } else { 0.0 };
+            hour.rain = (i % 3 == 2).then(|| Precipitation::ByPeriod([("1h".to_string(), 2.0)].into()));
+        })
+        .rainy_days()
+        .without_forecast3h()
+}
+
+const BANGKOK: &str = "lat=13.75&lon=100.5";
+
+#[tokio::test]
+async fn version_reports_build_info() {
+    let app = mock_app();
+
+    let (status, body) = get(&app, "/version").await;
+
+    assert_eq!(status, StatusCode::OK);
+    assert!(!body["version"].as_str().unwrap().is_empty());
+    assert!(!body["git_sha"].as_str().unwrap().is_empty());
+    assert!(body["rust_version"].as_str().unwrap().starts_with("rustc"));
+    assert!(body["build_timestamp"].is_string());
+}
+
+#[tokio::test]
+async fn forecast_returns_requested_hours() {
+    let app = test_app().await;
+
+    let (status, body) = get(&app, &format!("/forecast?{}&hours=24", BANGKOK)).await;
+
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["hourly_data"].as_array().unwrap().len(), 24);
+    assert_eq!(body["location"]["lat"], 13.75);
+}
+
+#[tokio::test]
+async fn forecast_changes_flag_hours_that_turned_rainy() {
+    // Fixed, hour-aligned weather (so two fetches line up) that turns from
+    // warm and dry to steady rain once `rainy` is set
+    let rainy = Arc::new(AtomicBool::new(false));
+    let turning = rainy.clone();
+    let weather = FakeWeather::new()
+        .hourly(move |_, hour| {
+            let rainy = turning.load(Ordering::SeqCst);
+            hour.dt -= hour.dt % 3600;
+            hour.temp = 30.0;
+            hour.humidity = 40.0;
+            hour.wind_speed = 4.0;
+            hour.clouds = 10.0;
+            hour.pop = if rainy { 1.0 } else { 0.0 };
+            hour.rain = rainy.then(|| Precipitation::ByPeriod([("1h".to_string(), 3.0)].into()));
+        })
+        .rainy_days()
+        .without_forecast3h();
+    let weather = Arc::new(weather);
+    let mut state = test_state(test_config(&[]), Arc::new(MockDatabase::new()));
+    state.weather_client = weather.clone();
+    let app = create_router(state);
+
+    // Nothing to compare the first fetch against
+    let (status, body) = get(&app, &format!("/forecast/changes?{}", BANGKOK)).await;
+    assert_eq!(status, StatusCode::OK);
+    assert!(body["previous_fetched_at"].is_null());
+    assert!(body["changes"].as_array().unwrap().is_empty());
+
+    // A cached forecast has nothing new
+    rainy.store(true, Ordering::SeqCst);
+    let calls = weather.calls.load(Ordering::SeqCst);
+    let (status, body) = get(&app, &format!("/forecast/changes?{}", BANGKOK)).await;
+    assert_eq!(status, StatusCode::OK);
+    assert!(body["changes"].as_array().unwrap().is_empty());
+    assert_eq!(weather.calls.load(Ordering::SeqCst), calls);
+
+    // Compared against when the earlier forecast was fetched
+    let (_, forecast) = get(&app, &format!("/forecast?{}", BANGKOK)).await;
+    assert_eq!(body["previous_fetched_at"], forecast["source"]["fetched_at"]);
+
+    let (status, body) = get(&app, &format!("/forecast/changes?{}&max_age_secs=0", BANGKOK)).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["previous_fetched_at"], forecast["source"]["fetched_at"]);
+    let changes = body["changes"].as_array().unwrap();
+    assert!(!changes.is_empty());
+    for change in changes {
+        assert!(change["before"]["veto_reason"].is_null());
+        assert!(change["after"]["veto_reason"].is_string());
+        assert_eq!(change["after"]["verdict"], "poor");
+    }
+
+    // The rainy forecast is now the baseline
+    let (_, body) = get(&app, &format!("/forecast/changes?{}&max_age_secs=0", BANGKOK)).await;
+    assert!(body["changes"].as_array().unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn sun_rises_before_solar_noon_before_setting() {
+    let app = mock_app();
+
+    for query in [BANGKOK.to_string(), format!("{}&date=2024-12-21", BANGKOK)] {
+        let (status, body) = get(&app, &format!("/sun?{}", query)).await;
+        assert_eq!(status, StatusCode::OK);
+
+        let time = |field: &str| chrono::DateTime::parse_from_rfc3339(body[field].as_str().unwrap()).unwrap();
+        assert!(time("sunrise") < time("solar_noon"));
+        assert!(time("solar_noon") < time("sunset"));
+        let path = body["path"].as_array().unwrap();
+        assert!(!path.is_empty());
+        assert!(path.iter().all(|point| point["elevation_deg"].as_f64().unwrap() > 0.0));
+    }
+
+    let (_, body) = get(&app, &format!("/sun?{}&date=2024-12-21", BANGKOK)).await;
+    assert_eq!(body["source"], "calculated");
+    assert_eq!(body["date"], "2024-12-21");
+    // Winter sun over Bangkok is due south at midday
+    let midday = body["path"].as_array().unwrap().iter().find(|p| p["ts"].as_str().unwrap().contains("T12:00")).unwrap();
+    assert_eq!(midday["facing"], "S");
+}
+
+#[tokio::test]
+async fn place_name_is_geocoded_for_forecast_endpoints() {
+    let app = mock_app();
+
+    let (status, body) = get(&app, "/recommendations?q=Chiang%20Mai").await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["location"]["name"], "Chiang Mai");
+    assert_eq!(body["location"]["country"], "TH");
+    assert_eq!(body["location"]["lat"], 18.7883);
+    assert_eq!(body["location"]["lon"], 98.9853);
+    assert!(!body["best_windows"].as_array().unwrap().is_empty());
+
+    let (status, body) = get(&app, "/forecast?q=chiang%20mai&hours=6").await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["location"]["name"], "Chiang Mai");
+    assert_eq!(body["hourly_data"].as_array().unwrap().len(), 6);
+
+    // Explicit coordinates win over the place name
+    let (status, body) = get(&app, &format!("/drying-windows?q=Chiang%20Mai&{}", BANGKOK)).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["location"]["lat"], 13.75);
+    assert!(body["location"]["name"].is_null());
+
+    let (status, body) = get(&app, "/drying-windows?q=Atlantis").await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    assert_eq!(body["code"], "place_not_found");
+
+    let (status, _) = get(&app, "/forecast").await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn forecast_calls_are_fetched_concurrently() {
+    for uri in ["/forecast", "/drying-windows", "/ai-recommendation"] {
+        // Every request has to fetch, rather than reuse an earlier forecast
+        let weather = Arc::new(FakeWeather::new().delayed(Duration::from_millis(50)));
+        let mut state = test_state(test_config(&[("WEATHER_CACHE_TTL_SECS", "0")]), Arc::new(MockDatabase::new()));
+        state.weather_client = weather.clone();
+        let app = create_router(state);
+
+        let (status, _) = get(&app, &format!("{}?{}", uri, BANGKOK)).await;
+
+        assert_eq!(status, StatusCode::OK, "{}", uri);
+        // Onecall and the 3-hour forecast were waiting at the same time
+        assert_eq!(weather.max_in_flight.load(Ordering::SeqCst), 2, "{}", uri);
+    }
+}
+
+#[tokio::test]
+async fn forecast_csv_has_header_and_one_row_per_hour() {
+    let app = test_app().await;
+
+    let response = common::raw_get(&app, &format!("/forecast.csv?{}&hours=12", BANGKOK)).await;
+
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.headers()[header::CONTENT_TYPE], "text/csv; charset=utf-8");
+    assert!(response.headers()[header::CONTENT_DISPOSITION]
+        .to_str()
+        .unwrap()
+        .contains("forecast.csv"));
+    let body = common::body_text(response).await;
+    let lines: Vec<&str> = body.lines().collect();
+    assert_eq!(lines[0], "ts,temp_c,rh,wind_ms,cloud,rain_p,rain_mm");
+    assert_eq!(lines.len(), 12 + 1);
+
+    let response = common::raw_get(&app, &format!("/forecast.csv?{}&hours=6&with_scores=true", BANGKOK)).await;
+    let body = common::body_text(response).await;
+    assert!(body.lines().next().unwrap().ends_with(",score"));
+    assert!(body.lines().skip(1).all(|line| line.split(',').count() == 8));
+}
+
+#[tokio::test]
+async fn drying_windows_are_sorted_best_first() {
+    let app = test_app().await;
+
+    let (status, body) = get(&app, &format!("/drying-windows?{}&max_windows=5", BANGKOK)).await;
+
+    assert_eq!(status, StatusCode::OK);
+    let windows = body["windows"].as_array().unwrap();
+    assert_eq!(windows.len(), 5);
+    let scores: Vec<f64> = windows
+        .iter()
+        .map(|w| w["score"]["score"].as_f64().unwrap())
+        .collect();
+    assert!(scores.windows(2).all(|pair| pair[0] >= pair[1]));
+}
+
+/// Digits after the decimal point in a JSON number
+fn decimals(value: &serde_json::Value) -> usize {
+    value.to_string().split_once('.').map_or(0, |(_, fraction)| fraction.trim_end_matches('0').len())
+}
+
+#[tokio::test]
+async fn drying_windows_are_rounded_for_output() {
+    let app = test_app().await;
+
+    let (status, body) = get(&app, &format!("/drying-windows?{}&verbose=true", BANGKOK)).await;
+
+    assert_eq!(status, StatusCode::OK);
+    for window in body["windows"].as_array().unwrap() {
+        let summary = &window["weather_summary"];
+        assert!(decimals(&summary["avg_temp_c"]) <= 1, "{}", summary);
+        assert!(decimals(&summary["avg_humidity"]) <= 1, "{}", summary);
+        assert!(decimals(&summary["avg_wind_ms"]) <= 1, "{}", summary);
+        assert!(decimals(&window["score"]["score"]) <= 2, "{}", window["score"]);
+        assert!(decimals(&window["score"]["raw"]["rain_p"]) <= 2, "{}", window["score"]);
+    }
+
+    // Precision is configurable
+    let state = test_state(test_config(&[("OUTPUT_PRECISION", "temperature=0")]), sqlite_store().await);
+    let (_, body) = get(&create_router(state), &format!("/drying-windows?{}", BANGKOK)).await;
+    assert!(body["windows"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .all(|w| decimals(&w["weather_summary"]["avg_temp_c"]) == 0));
+}
+
+#[tokio::test]
+async fn window_scores_are_compact_unless_verbose() {
+    let app = test_app().await;
+
+    let (_, compact) = get(&app, &format!("/drying-windows?{}&max_windows=10", BANGKOK)).await;
+    let (_, verbose) = get(&app, &format!("/drying-windows?{}&max_windows=10&verbose=true", BANGKOK)).await;
+
+    let score = &compact["windows"][0]["score"];
+    assert!(score["score"].is_number());
+    assert!(score["unsafe_window"].is_boolean());
+    assert!(score.get("features").is_none() && score.get("raw").is_none() && score.get("vpd_kpa").is_none());
+    assert!(verbose["windows"][0]["score"]["features"].is_object());
+    let scores_len = |body: &serde_json::Value| -> usize {
+        body["windows"].as_array().unwrap().iter().map(|w| w["score"].to_string().len()).sum()
+    };
+    let (compact_len, verbose_len) = (scores_len(&compact), scores_len(&verbose));
+    // At least a third smaller
+    assert!(compact_len * 3 < verbose_len * 2, "{} vs {} bytes", compact_len, verbose_len);
+
+    // Recommendations list windows the same way
+    let (_, body) = get(&app, &format!("/recommendations?{}", BANGKOK)).await;
+    assert!(body["best_windows"][0]["score"].get("raw").is_none());
+    let (_, body) = get(&app, &format!("/recommendations?{}&verbose=true", BANGKOK)).await;
+    assert!(body["best_windows"][0]["score"]["raw"].is_object());
+}
+
+#[tokio::test]
+async fn lang_th_returns_thai_labels() {
+    let app = test_app().await;
+    let thai = ["แดดออก", "มีเมฆบางส่วน", "มีเมฆมาก", "ฝนตก"];
+
+    let (status, body) = get(&app, &format!("/drying-windows?{}&lang=th", BANGKOK)).await;
+
+    assert_eq!(status, StatusCode::OK);
+    for window in body["windows"].as_array().unwrap() {
+        let conditions = window["weather_summary"]["conditions"].as_str().unwrap();
+        assert!(thai.contains(&conditions), "{}", conditions);
+        assert!(window["recommendation"].as_str().unwrap().contains("ตากผ้า"));
+    }
+
+    // Recommendations are cached once and labelled per request
+    let (_, english) = get(&app, &format!("/recommendations?{}", BANGKOK)).await;
+    let (_, localized) = get(&app, &format!("/recommendations?{}&lang=th", BANGKOK)).await;
+    let conditions = |body: &serde_json::Value| body["best_windows"][0]["weather_summary"]["conditions"].clone();
+    assert!(!thai.contains(&conditions(&english).as_str().unwrap()));
+    assert!(thai.contains(&conditions(&localized).as_str().unwrap()));
+}
+
+#[tokio::test]
+async fn recommendations_include_top_windows_and_tips() {
+    let app = test_app().await;
+
+    let (status, body) = get(&app, &format!("/recommendations?{}", BANGKOK)).await;
+
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["best_windows"].as_array().unwrap().len(), 3);
+    assert!(body["ai_explanation"].is_string());
+    assert!(!body["tips"].as_array().unwrap().is_empty());
+    // Either a countdown or an explanation of why there isn't one
+    assert!(body["next_good_window"].is_object() || body["message"].is_string());
+}
+
+#[tokio::test]
+async fn sparkline_is_downsampled_to_the_configured_cap() {
+    let app = create_router(test_state(test_config(&[("SPARKLINE_MAX_POINTS", "10")]), sqlite_store().await));
+
+    let (status, body) = get(&app, &format!("/recommendations?{}", BANGKOK)).await;
+    assert_eq!(status, StatusCode::OK);
+    assert!(body["sparkline"].is_null());
+
+    let (status, body) = get(&app, &format!("/recommendations?{}&sparkline=true", BANGKOK)).await;
+    assert_eq!(status, StatusCode::OK);
+    let points = body["sparkline"].as_array().unwrap();
+    assert!(!points.is_empty() && points.len() <= 10, "{} points", points.len());
+    for point in points {
+        assert!(point["ts"].is_string());
+        assert!((0.0..=1.0).contains(&point["score"].as_f64().unwrap()));
+    }
+}
+
+#[tokio::test]
+async fn best_day_returns_one_window_with_a_reason() {
+    let app = create_router(test_state(test_config(&[("DEBUG_ENDPOINTS", "true")]), sqlite_store().await));
+
+    let (status, body) = get(&app, &format!("/best-day?{}&scenario=perfect", BANGKOK)).await;
+    assert_eq!(status, StatusCode::OK);
+    let date = body["date"].as_str().unwrap();
+    assert!(chrono::NaiveDate::parse_from_str(date, "%Y-%m-%d").is_ok());
+    assert!(body["best_window"]["score"]["score"].as_f64().unwrap() > 0.6);
+    assert!(body["reason"].as_str().unwrap().contains("best drying window"));
+    assert!(body["message"].is_null());
+
+    // A week of rain has no best day
+    let (status, body) = get(&app, &format!("/best-day?{}&scenario=rainy", BANGKOK)).await;
+    assert_eq!(status, StatusCode::OK);
+    assert!(body["date"].is_null() && body["best_window"].is_null() && body["reason"].is_null());
+    assert!(body["message"].is_string());
+}
+
+#[tokio::test]
+async fn max_tips_caps_split_ai_tips() {
+    let mut state = test_state(test_config(&[]), Arc::new(MockDatabase::new()));
+    state.ai_client = Arc::new(FakeAi::new().with_tips(
+        "Hang early while the wind is 2.5 m/s. . Space items apart. Turn them at noon. Bring them in by 5pm.",
+    ));
+    let app = create_router(state);
+
+    let (status, body) = get(&app, &format!("/recommendations?{}", BANGKOK)).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(
+        body["tips"],
+        json!(["Hang early while the wind is 2.5 m/s.", "Space items apart.", "Turn them at noon."])
+    );
+
+    let (_, body) = get(&app, &format!("/recommendations?{}&max_tips=1", BANGKOK)).await;
+    assert_eq!(body["tips"], json!(["Hang early while the wind is 2.5 m/s."]));
+
+    let (status, body) = get(&app, &format!("/recommendations?{}&max_tips=0", BANGKOK)).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(body["code"], "invalid_parameter");
+}
+
+#[tokio::test]
+async fn tripped_ai_circuit_serves_heuristic_recommendation() {
+    let mut state = test_state(test_config(&[]), Arc::new(MockDatabase::new()));
+    state.ai_client = Arc::new(FakeAi::new().tripped());
+    let app = create_router(state);
+
+    let (status, body) = get(&app, &format!("/ai-recommendation?{}", BANGKOK)).await;
+
+    assert_eq!(status, StatusCode::OK);
+    assert!(!body["recommendation"].as_str().unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn ai_tips_are_split_into_complete_sentences() {
+    let mut state = test_state(test_config(&[]), Arc::new(MockDatabase::new()));
+    state.ai_client = Arc::new(FakeAi::new().with_tips(
+        "Hang bulky items, e.g. towels, in the sun. The wind is about 2.5 m/s, so space clothes apart.",
+    ));
+    let app = create_router(state);
+
+    let (status, body) = get(&app, &format!("/recommendations?{}", BANGKOK)).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(
+        body["tips"],
+        json!([
+            "Hang bulky items, e.g. towels, in the sun.",
+            "The wind is about 2.5 m/s, so space clothes apart."
+        ])
+    );
+}
+
+#[tokio::test]
+async fn recommendations_can_return_more_windows_with_one_explanation() {
+    let ai = Arc::new(FakeAi::new());
+    let mut state = test_state(test_config(&[]), sqlite_store().await);
+    state.ai_client = ai.clone();
+    let app = create_router(state);
+
+    let (status, body) = get(&app, &format!("/recommendations?{}&max_windows=10", BANGKOK)).await;
+
+    assert_eq!(status, StatusCode::OK);
+    let windows = body["best_windows"].as_array().unwrap();
+    assert!(windows.len() > 3 && windows.len() <= 10);
+    assert!(body["ai_explanation"].is_string());
+    assert_eq!(ai.explanations.load(Ordering::SeqCst), 1);
+
+    let (status, _) = get(&app, &format!("/recommendations?{}&max_windows=21", BANGKOK)).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn concurrent_identical_recommendations_share_one_ai_call() {
+    let ai = Arc::new(FakeAi::new().delayed(Duration::from_millis(50)));
+    // No caching beyond the requests in flight
+    let mut state = test_state(test_config(&[("RECOMMENDATION_CACHE_TTL_SECS", "0")]), sqlite_store().await);
+    state.ai_client = ai.clone();
+    let app = create_router(state);
+
+    let uri = format!("/recommendations?{}", BANGKOK);
+    let mut requests = tokio::task::JoinSet::new();
+    for _ in 0..10 {
+        let (app, uri) = (app.clone(), uri.clone());
+        requests.spawn(async move { get(&app, &uri).await });
+    }
+    let mut responses = Vec::new();
+    while let Some(response) = requests.join_next().await {
+        responses.push(response.unwrap());
+    }
+
+    assert!(responses.iter().all(|(status, _)| *status == StatusCode::OK));
+    assert!(responses.iter().all(|(_, body)| body["ai_explanation"] == responses[0].1["ai_explanation"]));
+    assert_eq!(ai.explanations.load(Ordering::SeqCst), 1);
+
+    // Once the flight has landed the next request computes afresh
+    let (status, _) = get(&app, &uri).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(ai.explanations.load(Ordering::SeqCst), 2);
+}
+
+#[tokio::test]
+async fn heuristic_tips_engine_never_calls_the_ai() {
+    let ai = Arc::new(FakeAi::new());
+    let mut state = test_state(test_config(&[("TIPS_ENGINE", "heuristic")]), sqlite_store().await);
+    state.ai_client = ai.clone();
+    let app = create_router(state);
+
+    let (status, body) = get(&app, &format!("/recommendations?{}", BANGKOK)).await;
+
+    assert_eq!(status, StatusCode::OK);
+    assert!(body["ai_explanation"].as_str().unwrap().starts_with("Drying score"));
+    assert!(!body["tips"].as_array().unwrap().is_empty());
+    assert_eq!(ai.explanations.load(Ordering::SeqCst), 0);
+}
+
+#[tokio::test]
+async fn min_dry_hours_rules_out_short_windows() {
+    let mut state = test_state(test_config(&[("SMOOTHING_WINDOW", "1")]), sqlite_store().await);
+    state.weather_client = Arc::new(shower_weather());
+    let app = create_router(state);
+
+    // Two-hour windows fit between the showers
+    let (status, body) = get(&app, &format!("/recommendations?{}&window_hours=2", BANGKOK)).await;
+    assert_eq!(status, StatusCode::OK);
+    assert!(body["next_good_window"]["window_id"].is_string());
+
+    // Three hours of drying always catches a shower
+    let (status, body) = get(&app, &format!("/recommendations?{}&window_hours=2&min_dry_hours=3", BANGKOK)).await;
+    assert_eq!(status, StatusCode::OK);
+    assert!(body["next_good_window"].is_null());
+    assert!(body["message"].as_str().unwrap().contains("at least 3 hours"));
+    for window in body["best_windows"].as_array().unwrap() {
+        assert!(window["duration_hours"].as_u64().unwrap() >= 3);
+    }
+}
+
+#[tokio::test]
+async fn feedback_is_stored_and_analyzed() {
+    let app = test_app().await;
+
+    let (status, body) = post(
+        &app,
+        "/feedback",
+        json!({
+            "window_id": "window_0_3",
+            "feedback_text": "Great day, everything was completely dry",
+            "satisfaction_rating": 5,
+            "weather_conditions": { "temp_c": 30.0, "humidity": 50.0, "wind_ms": 3.0, "rain_mm": 0.0 }
+        }),
+    )
+    .await;
+
+    assert_eq!(status, StatusCode::OK);
+    assert!(body["id"].is_string());
+    assert_eq!(body["analysis"]["satisfaction"], "Satisfied");
+}
+
+#[tokio::test]
+async fn oversized_bodies_are_rejected_with_413() {
+    let app = create_router(test_state(test_config(&[("MAX_BODY_BYTES", "1024")]), sqlite_store().await));
+    let feedback = |text: String| json!({ "window_id": "window_0_3", "feedback_text": text });
+
+    let (status, _) = post(&app, "/feedback", feedback("x".repeat(2000))).await;
+    assert_eq!(status, StatusCode::PAYLOAD_TOO_LARGE);
+    let (status, _) = post(&app, "/explain", json!({ "window_data": "x".repeat(2000) })).await;
+    assert_eq!(status, StatusCode::PAYLOAD_TOO_LARGE);
+
+    let (status, _) = post(&app, "/feedback", feedback("Dried well by the afternoon".to_string())).await;
+    assert_eq!(status, StatusCode::OK);
+}
+
+#[tokio::test]
+async fn feedback_analysis_is_stored_with_the_record() {
+    let store = sqlite_store().await;
+    let app = create_router(test_state(test_config(&[]), store.clone()));
+
+    let (_, user) = post(&app, "/preferences", json!({ "location_lat": 13.75 })).await;
+    let user_id: uuid::Uuid = user["user_id"].as_str().unwrap().parse().unwrap();
+    let (status, body) = post(
+        &app,
+        "/feedback",
+        json!({
+            "user_id": user_id,
+            "window_id": "window_0_3",
+            "feedback_text": "Great day, everything was completely dry",
+            "weather_conditions": { "temp_c": 30.0, "humidity": 50.0, "wind_ms": 3.0, "rain_mm": 0.0 }
+        }),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+
+    let stored = store.get_user_feedback(user_id, None).await.unwrap();
+    assert_eq!(stored.len(), 1);
+    let analysis = serde_json::to_value(stored[0].analysis.as_ref().expect("analysis stored")).unwrap();
+    assert_eq!(analysis, body["analysis"]);
+    assert_eq!(analysis["satisfaction"], "Satisfied");
+}
+
+#[tokio::test]
+async fn served_window_is_retrievable_and_linked_from_feedback() {
+    let store = sqlite_store().await;
+    let app = create_router(test_state(test_config(&[("PERSIST_WINDOWS", "true")]), store.clone()));
+
+    let (status, body) = get(&app, &format!("/recommendations?{}&verbose=true", BANGKOK)).await;
+    assert_eq!(status, StatusCode::OK);
+    let served = &body["best_windows"][0];
+    let window_id = served["id"].as_str().unwrap();
+
+    let (status, window) = get(&app, &format!("/windows/{}?{}", window_id, BANGKOK)).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(window["window_id"], window_id);
+    assert_eq!(window["start_time"], served["start_time"]);
+    assert_eq!(window["score"]["score"], served["score"]["score"]);
+
+    // The same id served later somewhere else doesn't replace this one
+    let mut elsewhere: ServedWindow = serde_json::from_value(window.clone()).unwrap();
+    elsewhere.lat = 18.79;
+    elsewhere.lon = 98.98;
+    elsewhere.score.score = 0.1;
+    elsewhere.score.raw.temp_c = 12.0;
+    elsewhere.served_at = chrono::Utc::now() + chrono::Duration::minutes(1);
+    store.save_served_windows(&[elsewhere]).await.unwrap();
+    let (_, other) = get(&app, &format!("/windows/{}?lat=18.79&lon=98.98", window_id)).await;
+    assert_eq!(other["score"]["score"], 0.1);
+
+    // Feedback without a predicted score picks up the snapshot served at the user's location
+    let (_, user) = post(&app, "/preferences", json!({ "location_lat": 13.75, "location_lon": 100.5 })).await;
+    let user_id: uuid::Uuid = user["user_id"].as_str().unwrap().parse().unwrap();
+    let (status, _) = post(
+        &app,
+        "/feedback",
+        json!({
+            "user_id": user_id,
+            "window_id": window_id,
+            "feedback_text": "Great day, everything was completely dry"
+        }),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+
+    let stored = store.get_user_feedback(user_id, None).await.unwrap();
+    assert_eq!(stored[0].predicted_score, served["score"]["score"].as_f64());
+    assert_eq!(stored[0].weather_temp_c, window["score"]["raw"]["temp_c"].as_f64());
+
+    let (status, body) = get(&app, &format!("/windows/window_0_3?{}", BANGKOK)).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    assert_eq!(body["code"], "window_not_found");
+}
+
+#[tokio::test]
+async fn model_weights_are_described_and_validated() {
+    let store = sqlite_store().await;
+    let app = create_router(test_state(test_config(&[]), store.clone()));
+
+    let (status, schema) = get(&app, "/model/schema").await;
+    assert_eq!(status, StatusCode::OK);
+    let weights = schema["weights"].as_array().unwrap();
+    assert_eq!(weights.len(), 7);
+    assert_eq!(weights[0]["name"], "w0");
+    assert!(weights[0]["feature"].is_null());
+    assert_eq!(weights[1]["feature"], "f_temp");
+    assert_eq!((weights[1]["default"].as_f64(), weights[1]["max"].as_f64()), (Some(0.25), Some(0.5)));
+    assert_eq!(weights[3]["meaning"], "Breeze: 0 when calm, 1 at 6 m/s or more");
+
+    // Meanings follow the configured normalization
+    let tuned_app = create_router(test_state(test_config(&[("NORMALIZATION", "wind_scale_ms=8")]), store.clone()));
+    let (_, schema) = get(&tuned_app, "/model/schema").await;
+    assert_eq!(schema["weights"][3]["meaning"], "Breeze: 0 when calm, 1 at 8 m/s or more");
+
+    let (_, user) = post(&app, "/preferences", json!({ "location_lat": 13.75 })).await;
+    let user_id: uuid::Uuid = user["user_id"].as_str().unwrap().parse().unwrap();
+    let tuned = json!({ "w0": 0.0, "w1": 0.3, "w2": 0.2, "w3": 0.2, "w4": 0.1, "w5": 0.1, "w6": 0.2 });
+
+    let mut too_big = tuned.clone();
+    too_big["w4"] = json!(0.9);
+    too_big["w0"] = json!(-1.0);
+    let (status, body) = post(&app, "/model/weights", json!({ "user_id": user_id, "weights": too_big })).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(body["code"], "invalid_weights");
+    let error = body["error"].as_str().unwrap();
+    assert!(error.contains("w0=-1 (allowed -0.5 to 0.5), w4=0.9 (allowed 0 to 0.3)"), "{}", error);
+    assert!(store.get_user_weights(user_id).await.unwrap().is_none());
+
+    let (status, body) = post(&app, "/model/weights", json!({ "user_id": user_id, "weights": tuned })).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["weights"], tuned);
+    assert_eq!(store.get_user_weights(user_id).await.unwrap().unwrap().weights.w1, 0.3);
+
+    let (status, _) =
+        post(&app, "/model/weights", json!({ "user_id": uuid::Uuid::new_v4(), "weights": tuned })).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn drying_windows_compare_default_and_personal_weights() {
+    let store = sqlite_store().await;
+    let app = create_router(test_state(test_config(&[]), store.clone()));
+    let (_, user) = post(&app, "/preferences", json!({ "location_lat": 13.75 })).await;
+    let user_id: uuid::Uuid = user["user_id"].as_str().unwrap().parse().unwrap();
+    let personal = TrainedWeights {
+        weights: DryingWeights {
+            w0: -0.3,
+            ..DryingWeights::default()
+        },
+        ..TrainedWeights::default()
+    };
+    store.save_user_weights(user_id, &personal).await.unwrap();
+
+    let (_, body) = get(&app, &format!("/drying-windows?{}&user_id={}", BANGKOK, user_id)).await;
+    assert!(body["windows"][0]["weight_comparison"].is_null());
+
+    // Rejected before any forecast is fetched
+    let weather = Arc::new(FakeWeather::new());
+    let mut state = test_state(test_config(&[]), store.clone());
+    state.weather_client = weather.clone();
+    let (status, body) =
+        get(&create_router(state), &format!("/drying-windows?{}&compare_weights=true", BANGKOK)).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(body["code"], "invalid_parameter");
+    assert_eq!(weather.max_in_flight.load(Ordering::SeqCst), 0);
+
+    let (status, body) =
+        get(&app, &format!("/drying-windows?{}&user_id={}&compare_weights=true", BANGKOK, user_id)).await;
+    assert_eq!(status, StatusCode::OK);
+    let windows = body["windows"].as_array().unwrap();
+    for window in windows {
+        assert_eq!(window["weight_comparison"]["default_score"], window["score"]["score"]);
+    }
+    // The negative bias pulls every dry window's score down
+    let dry = windows
+        .iter()
+        .map(|window| &window["weight_comparison"])
+        .find(|comparison| comparison["default_score"].as_f64().unwrap() > 0.1)
+        .expect("the mock forecast has a dry window");
+    assert!(dry["personal_score"].as_f64().unwrap() < dry["default_score"].as_f64().unwrap());
+    assert!(dry["delta"].as_f64().unwrap() < 0.0);
+}
+
+#[tokio::test]
+async fn partial_weight_tune_merges_and_rejects_out_of_bounds() {
+    let store = sqlite_store().await;
+    let app = create_router(test_state(test_config(&[]), store.clone()));
+    let (_, user) = post(&app, "/preferences", json!({ "location_lat": 13.75 })).await;
+    let user_id: uuid::Uuid = user["user_id"].as_str().unwrap().parse().unwrap();
+    let uri = format!("/preferences/{}/weights", user_id);
+
+    // Untouched weights keep their defaults
+    let (status, body) = post(&app, &uri, json!({ "w3": 0.4 })).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["weights"]["w3"], 0.4);
+    assert_eq!(body["weights"]["w1"], 0.25);
+
+    // Later tunes merge with the saved weights
+    let (status, body) = post(&app, &uri, json!({ "w1": 0.1 })).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!((body["weights"]["w1"].as_f64(), body["weights"]["w3"].as_f64()), (Some(0.1), Some(0.4)));
+
+    let (status, body) = post(&app, &uri, json!({ "w5": 0.35 })).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(body["code"], "invalid_weights");
+    assert!(body["error"].as_str().unwrap().contains("w5=0.35 (allowed 0 to 0.3)"));
+    let saved = store.get_user_weights(user_id).await.unwrap().unwrap().weights;
+    assert_eq!((saved.w1, saved.w3, saved.w5), (0.1, 0.4, 0.15));
+}
+
+#[tokio::test]
+async fn duplicate_feedback_for_a_window_is_rejected() {
+    for app in [test_app().await, mock_app()] {
+        let (_, user) = post(&app, "/preferences", json!({ "location_lat": 13.75 })).await;
+        let feedback = json!({
+            "user_id": user["user_id"],
+            "window_id": "window_0_3",
+            "feedback_text": "Great day, everything was completely dry"
+        });
+
+        let (status, first) = post(&app, "/feedback", feedback.clone()).await;
+        assert_eq!(status, StatusCode::OK);
+        let (status, body) = post(&app, "/feedback", feedback).await;
+        assert_eq!(status, StatusCode::CONFLICT);
+        assert_eq!(body["code"], "duplicate_feedback");
+        assert!(body["error"].as_str().unwrap().contains(first["id"].as_str().unwrap()));
+
+        let (status, body) = post(
+            &app,
+            "/feedback",
+            json!({ "user_id": user["user_id"], "window_id": "window_1_3", "feedback_text": "ok" }),
+        )
+        .await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(body["code"], "feedback_too_short");
+    }
+}
+
+#[tokio::test]
+async fn feedback_is_capped_per_user_per_day() {
+    let app = create_router(test_state(test_config(&[("FEEDBACK_DAILY_LIMIT", "2")]), sqlite_store().await));
+    let (_, user) = post(&app, "/preferences", json!({ "location_lat": 13.75 })).await;
+    let submit = |window: u32| {
+        json!({
+            "user_id": user["user_id"],
+            "window_id": format!("window_{}_3", window),
+            "feedback_text": "Dried by the afternoon"
+        })
+    };
+
+    for window in 0..2 {
+        let (status, _) = post(&app, "/feedback", submit(window)).await;
+        assert_eq!(status, StatusCode::OK);
+    }
+    let (status, body) = post(&app, "/feedback", submit(2)).await;
+    assert_eq!(status, StatusCode::TOO_MANY_REQUESTS);
+    assert_eq!(body["code"], "feedback_limit_reached");
+
+    // Other users have their own allowance
+    let (_, other) = post(&app, "/preferences", json!({ "location_lat": 13.75 })).await;
+    let (status, _) = post(
+        &app,
+        "/feedback",
+        json!({ "user_id": other["user_id"], "window_id": "window_2_3", "feedback_text": "Dried by the afternoon" }),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    // Anonymous feedback shares one allowance per window
+    let anonymous = json!({ "window_id": "window_5_3", "feedback_text": "Dried by the afternoon" });
+    for _ in 0..2 {
+        let (status, _) = post(&app, "/feedback", anonymous.clone()).await;
+        assert_eq!(status, StatusCode::OK);
+    }
+    let (status, body) = post(&app, "/feedback", anonymous).await;
+    assert_eq!(status, StatusCode::TOO_MANY_REQUESTS);
+    assert_eq!(body["code"], "feedback_limit_reached");
+}
+
+#[tokio::test]
+async fn concurrent_duplicate_feedback_stores_one_record() {
+    let store = sqlite_store().await;
+    let app = create_router(test_state(test_config(&[]), store.clone()));
+    let (_, user) = post(&app, "/preferences", json!({ "location_lat": 13.75 })).await;
+    let user_id: uuid::Uuid = user["user_id"].as_str().unwrap().parse().unwrap();
+    let feedback = json!({
+        "user_id": user_id,
+        "window_id": "window_0_3",
+        "feedback_text": "Great day, everything was completely dry"
+    });
+
+    let (first, second) = tokio::join!(post(&app, "/feedback", feedback.clone()), post(&app, "/feedback", feedback));
+    let mut statuses = [first.0, second.0];
+    statuses.sort();
+    assert_eq!(statuses, [StatusCode::OK, StatusCode::CONFLICT]);
+    assert_eq!(store.get_user_feedback(user_id, None).await.unwrap().len(), 1);
+}
+
+#[tokio::test]
+async fn ai_disagreeing_with_user_drying_result_is_flagged() {
+    for app in [test_app().await, mock_app()] {
+        // The user says mostly dry, the AI reads the text as not dry
+        let (_, disputed) = post(
+            &app,
+            "/feedback",
+            json!({
+                "window_id": "window_0_3",
+                "feedback_text": "Towels were still wet at dinner",
+                "drying_result": "mostly_dry"
+            }),
+        )
+        .await;
+        post(
+            &app,
+            "/feedback",
+            json!({
+                "window_id": "window_0_3",
+                "feedback_text": "Mostly dry, good enough",
+                "drying_result": "completely_dry"
+            }),
+        )
+        .await;
+
+        let (status, body) = get(&app, "/feedback/disagreements").await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["count"], 1);
+        let flagged = &body["disagreements"][0];
+        assert_eq!(flagged["feedback_id"], disputed["id"]);
+        assert_eq!(flagged["user_drying_result"], "mostly_dry");
+        assert_eq!(flagged["ai_drying_result"], "not_dry");
+        assert!(flagged["ai_confidence"].is_string());
+
+        for limit in ["0", "-1", "101"] {
+            let (status, _) = get(&app, &format!("/feedback/disagreements?limit={}", limit)).await;
+            assert_eq!(status, StatusCode::BAD_REQUEST, "limit={}", limit);
+        }
+    }
+}
+
+#[tokio::test]
+async fn stored_feedback_can_be_reanalyzed() {
+    let store = sqlite_store().await;
+    let app = create_router(test_state(test_config(&[]), store.clone()));
+
+    let (_, stored) = post(
+        &app,
+        "/feedback",
+        json!({
+            "window_id": "window_0_3",
+            "feedback_text": "Great day, everything was completely dry",
+            "weather_conditions": { "temp_c": 30.0, "humidity": 50.0, "wind_ms": 3.0, "rain_mm": 0.0 }
+        }),
+    )
+    .await;
+    let (status, body) = post(&app, &format!("/feedback/{}/reanalyze", stored["id"].as_str().unwrap()), json!({})).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["id"], stored["id"]);
+    assert_eq!(body["analysis"]["satisfaction"], "Satisfied");
+    assert_eq!(body["weather_defaults_used"], false);
+
+    // No weather stored: neutral defaults, flagged as low confidence
+    let (_, stored) = post(
+        &app,
+        "/feedback",
+        json!({ "window_id": "window_0_3", "feedback_text": "Still damp in the evening" }),
+    )
+    .await;
+    let (status, body) = post(&app, &format!("/feedback/{}/reanalyze", stored["id"].as_str().unwrap()), json!({})).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["weather_defaults_used"], true);
+    assert_eq!(body["analysis"]["confidence"], "Low");
+    assert_eq!(body["persisted"], false);
+
+    // Only persisted on request
+    let id: uuid::Uuid = stored["id"].as_str().unwrap().parse().unwrap();
+    let stored_confidence = |record: Option<FeedbackRecord>| {
+        serde_json::to_value(&record.unwrap().analysis.unwrap().confidence).unwrap()
+    };
+    assert_eq!(stored_confidence(store.get_feedback(id).await.unwrap()), "Medium");
+    let (status, body) = post(&app, &format!("/feedback/{}/reanalyze?persist=true", id), json!({})).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["persisted"], true);
+    assert_eq!(stored_confidence(store.get_feedback(id).await.unwrap()), "Low");
+
+    let (status, body) = post(&app, &format!("/feedback/{}/reanalyze", uuid::Uuid::new_v4()), json!({})).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    assert_eq!(body["code"], "feedback_not_found");
+}
+
+#[tokio::test]
+async fn sensor_outcome_updates_user_weights() {
+    let store = sqlite_store().await;
+    let app = create_router(test_state(test_config(&[]), store.clone()));
+
+    let (_, user) = post(&app, "/preferences", json!({ "location_lat": 13.75, "location_lon": 100.5 })).await;
+    let user_id: uuid::Uuid = user["user_id"].as_str().unwrap().parse().unwrap();
+    let (_, windows) = get(&app, &format!("/drying-windows?{}&max_windows=1", BANGKOK)).await;
+    let window_id = windows["windows"][0]["id"].as_str().unwrap();
+    assert!(store.get_user_weights(user_id).await.unwrap().is_none());
+
+    let (status, body) = post(
+        &app,
+        "/outcomes",
+        json!({ "user_id": user_id, "window_id": window_id, "dry": false }),
+    )
+    .await;
+
+    assert_eq!(status, StatusCode::OK, "{}", body);
+    let trained = store.get_user_weights(user_id).await.unwrap().unwrap();
+    assert_ne!(trained.weights, DryingWeights::default());
+    assert_eq!(body["weights"]["w1"], trained.weights.w1);
+    assert_eq!(trained.step, 1);
+    assert_eq!(body["training_step"], 1);
+    assert!(body["loss"].as_f64().unwrap() > 0.0);
+
+    let feedback = store.get_user_feedback(user_id, None).await.unwrap();
+    assert_eq!(feedback.len(), 1);
+    assert_eq!(feedback[0].actual_outcome, Some(ActualOutcome::NotDry));
+    assert!(feedback[0].weather_temp_c.is_some());
+
+    let (status, body) = post(
+        &app,
+        "/outcomes",
+        json!({ "user_id": user_id, "window_id": "window_1_3", "dry": true }),
+    )
+    .await;
+    assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+    assert_eq!(body["code"], "window_unavailable");
+}
+
+#[tokio::test]
+async fn sensor_outcome_for_an_ended_window_uses_the_served_weather() {
+    let store = sqlite_store().await;
+    let app = create_router(test_state(test_config(&[("PERSIST_WINDOWS", "true")]), store.clone()));
+
+    let (_, user) = post(&app, "/preferences", json!({ "location_lat": 13.75, "location_lon": 100.5 })).await;
+    let user_id: uuid::Uuid = user["user_id"].as_str().unwrap().parse().unwrap();
+    let (_, body) = get(&app, &format!("/recommendations?{}", BANGKOK)).await;
+    let window_id = body["best_windows"][0]["id"].as_str().unwrap();
+    let (_, window) = get(&app, &format!("/windows/{}?{}", window_id, BANGKOK)).await;
+
+    // The same window as served yesterday afternoon, long out of the forecast
+    let mut ended: ServedWindow = serde_json::from_value(window).unwrap();
+    ended.start_time = chrono::Utc::now() - chrono::Duration::hours(24);
+    ended.end_time = ended.start_time + chrono::Duration::hours(3);
+    ended.window_id = format!("window_{}_3", ended.start_time.timestamp());
+    ended.score.raw.temp_c = 21.5;
+    store.save_served_windows(&[ended.clone()]).await.unwrap();
+
+    let (status, body) = post(
+        &app,
+        "/outcomes",
+        json!({ "user_id": user_id, "window_id": ended.window_id, "dry": true }),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+    let feedback = store.get_user_feedback(user_id, None).await.unwrap();
+    assert_eq!(feedback[0].weather_temp_c, Some(21.5));
+    assert_eq!(feedback[0].weather_humidity, Some(ended.score.raw.rh));
+
+    let (status, body) = post(
+        &app,
+        "/outcomes",
+        json!({ "user_id": uuid::Uuid::new_v4(), "window_id": ended.window_id, "dry": true }),
+    )
+    .await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    assert_eq!(body["code"], "user_not_found");
+}
+
+#[tokio::test]
+async fn preferences_round_trip() {
+    let app = test_app().await;
+
+    let (status, created) = post(
+        &app,
+        "/preferences",
+        json!({ "min_temperature": 20.0, "location_name": "Bangkok", "scoring_model": "vpd" }),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    let user_id = created["user_id"].as_str().unwrap();
+
+    let (status, updated) = post(
+        &app,
+        &format!("/preferences/{}", user_id),
+        json!({ "max_humidity": 70.0 }),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(updated["max_humidity"], 70.0);
+
+    let (status, fetched) = get(&app, &format!("/preferences/{}", user_id)).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(fetched["location_name"], "Bangkok");
+    assert_eq!(fetched["min_temperature"], 20.0);
+    assert_eq!(fetched["scoring_model"], "vpd");
+
+    let (status, _) = get(&app, "/preferences/00000000-0000-0000-0000-000000000000").await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn missing_preferences_can_be_created_on_first_load() {
+    let app = test_app().await;
+    let uri = "/preferences/6f1c2a3e-0d4b-4c5a-9e8f-7a6b5c4d3e2f";
+
+    // Strict by default
+    let (status, _) = get(&app, uri).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    let (status, _) = get(&app, &format!("{}?create_if_missing=false", uri)).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+
+    let (status, created) = get(&app, &format!("{}?create_if_missing=true", uri)).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(created["user_id"], "6f1c2a3e-0d4b-4c5a-9e8f-7a6b5c4d3e2f");
+    assert!(created["location_lat"].is_null());
+
+    // The defaults were persisted
+    let (status, fetched) = get(&app, uri).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(fetched["created_at"], created["created_at"]);
+}
+
+#[tokio::test]
+async fn patch_clears_null_fields_and_leaves_absent_ones() {
+    for app in [test_app().await, mock_app()] {
+        let (_, created) = post(
+            &app,
+            "/preferences",
+            json!({ "avoid_rain_probability": 0.3, "max_humidity": 70.0, "location_name": "Bangkok" }),
+        )
+        .await;
+        let uri = format!("/preferences/{}", created["user_id"].as_str().unwrap());
+
+        let (status, patched) = send(
+            &app,
+            Method::PATCH,
+            &uri,
+            Some(json!({ "avoid_rain_probability": null, "max_humidity": 65.0 })),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        assert!(patched["avoid_rain_probability"].is_null());
+        assert_eq!(patched["max_humidity"], 65.0);
+        assert_eq!(patched["location_name"], "Bangkok");
+
+        let (_, fetched) = get(&app, &uri).await;
+        assert!(fetched["avoid_rain_probability"].is_null());
+        assert_eq!(fetched["location_name"], "Bangkok");
+
+        let (status, _) = send(
+            &app,
+            Method::PATCH,
+            "/preferences/00000000-0000-0000-0000-000000000000",
+            Some(json!({ "timezone": null })),
+        )
+        .await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+    }
+}
+
+#[tokio::test]
+async fn malformed_quiet_hours_are_rejected_on_save() {
+    let app = test_app().await;
+    let quiet = |start: &str, end: &str| json!({ "quiet_hours_start": start, "quiet_hours_end": end });
+
+    let (status, body) = post(&app, "/preferences", json!({ "notification_preferences": quiet("3am", "07:00") })).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(body["code"], "invalid_quiet_hours");
+
+    let (status, created) = post(&app, "/preferences", json!({ "notification_preferences": quiet("22:00", "07:00") })).await;
+    assert_eq!(status, StatusCode::OK);
+    let uri = format!("/preferences/{}", created["user_id"].as_str().unwrap());
+
+    let (status, _) = post(&app, &uri, json!({ "notification_preferences": { "quiet_hours_start": "22:00" } })).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    let (status, _) = send(&app, Method::PATCH, &uri, Some(json!({ "notification_preferences": quiet("22:00", "24:30") }))).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    // Nothing rejected was saved
+    let (_, fetched) = get(&app, &uri).await;
+    assert_eq!(fetched["notification_preferences"], quiet("22:00", "07:00"));
+}
+
+#[tokio::test]
+async fn preferences_work_against_the_mock_store() {
+    let app = mock_app();
+
+    let (status, created) = post(&app, "/preferences", json!({ "location_name": "Chiang Mai" })).await;
+    assert_eq!(status, StatusCode::OK);
+
+    let (status, updated) = post(
+        &app,
+        &format!("/preferences/{}", created["user_id"].as_str().unwrap()),
+        json!({ "preferred_drying_hours": 5 }),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(updated["location_name"], "Chiang Mai");
+    assert_eq!(updated["preferred_drying_hours"], 5);
+}
+
+#[tokio::test]
+async fn over_limit_and_zero_counts_are_rejected() {
+    let app = test_app().await;
+
+    for uri in [
+        format!("/forecast?{}&hours=500", BANGKOK),
+        format!("/forecast?{}&hours=0", BANGKOK),
+        format!("/drying-windows?{}&window_hours=13", BANGKOK),
+        format!("/drying-windows?{}&max_windows=0", BANGKOK),
+        format!("/recommendations?{}&window_hours=0", BANGKOK),
+    ] {
+        let (status, body) = get(&app, &uri).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST, "{}", uri);
+        assert_eq!(body["code"], "invalid_parameter");
+    }
+
+    let (_, body) = get(&app, &format!("/forecast?{}&hours=500", BANGKOK)).await;
+    assert_eq!(body["error"], "hours must be between 1 and 168 (got 500)");
+
+    // The limits themselves are accepted
+    let (status, body) = get(&app, &format!("/forecast?{}&hours=168", BANGKOK)).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["hourly_data"].as_array().unwrap().len(), 168);
+}
+
+#[tokio::test]
+async fn requests_over_the_concurrency_limit_get_503() {
+    let mut state = test_state(test_config(&[("MAX_CONCURRENT_REQUESTS", "2")]), sqlite_store().await);
+    state.weather_client = Arc::new(FakeWeather::new().delayed(Duration::from_millis(300)));
+    let app = create_router(state);
+
+    let in_flight: Vec<_> = (0..2)
+        .map(|_| {
+            let app = app.clone();
+            tokio::spawn(async move { get(&app, &format!("/forecast?{}", BANGKOK)).await })
+        })
+        .collect();
+    tokio::time::sleep(Duration::from_millis(100)).await;
+
+    let response = common::raw_get(&app, &format!("/forecast?{}", BANGKOK)).await;
+    assert_eq!(response.status(), StatusCode::SERVICE_UNAVAILABLE);
+    assert_eq!(response.headers()[header::RETRY_AFTER], "1");
+
+    // Health checks are exempt even while saturated
+    let (status, _) = get(&app, "/health").await;
+    assert_eq!(status, StatusCode::OK);
+
+    for request in in_flight {
+        assert_eq!(request.await.unwrap().0, StatusCode::OK);
+    }
+    let (status, _) = get(&app, &format!("/forecast?{}", BANGKOK)).await;
+    assert_eq!(status, StatusCode::OK);
+}
+
+#[tokio::test]
+async fn unhappy_users_get_more_conservative_recommendations() {
+    let store = sqlite_store().await;
+    let mut state = test_state(test_config(&[("CONSERVATIVE_ADJUSTMENT", "0.3")]), store.clone());
+    state.weather_client = Arc::new(
+        // Warm, dry hours whose first day carries a 45% chance of rain that
+        // never falls
+        FakeWeather::new()
+            .hourly(|i, hour| {
+                hour.temp = 31.0;
+                hour.humidity = 35.0;
+                hour.wind_speed = 4.0;
+                hour.clouds = 10.0;
+                hour.pop = if i < 24 { 0.45 } else { 0.0 };
+                hour.rain = None;
+            })
+            .rainy_days()
+            .without_forecast3h(),
+    );
+    let app = create_router(state);
+
+    let user = || async {
+        let (_, body) = post(&app, "/preferences", json!({})).await;
+        body["user_id"].as_str().unwrap().parse::<uuid::Uuid>().unwrap()
+    };
+    let (happy, unhappy) = (user().await, user().await);
+    for (hour, (user_id, rating)) in [(happy, 5), (happy, 4), (unhappy, 2), (unhappy, 1)].into_iter().enumerate() {
+        store
+            .create_feedback(CreateFeedback {
+                user_id: Some(user_id),
+                window_id: format!("window_{}_3", hour * 3600),
+                feedback_text: "rated".to_string(),
+                satisfaction_rating: Some(rating),
+                drying_result: None,
+                weather_temp_c: None,
+                weather_humidity: None,
+                weather_wind_ms: None,
+                weather_rain_mm: None,
+                predicted_score: None,
+                actual_outcome: None,
+            })
+            .await
+            .unwrap();
+    }
+
+    let next_good_for = |user_id: uuid::Uuid| {
+        let app = app.clone();
+        async move {
+            let (status, body) = get(&app, &format!("/recommendations?{}&user_id={}", BANGKOK, user_id)).await;
+            assert_eq!(status, StatusCode::OK);
+            body["next_good_window"].clone()
+        }
+    };
+    let happy_next = next_good_for(happy).await;
+    let unhappy_next = next_good_for(unhappy).await;
+
+    // The drizzly first day is good enough for a happy user, but vetoed for
+    // an unhappy one, who has to wait for the dry day after
+    assert_eq!(happy_next["starts_in_hours"], 0);
+    let unhappy_start = unhappy_next["starts_in_hours"].as_u64().expect("the dry day is still good");
+    assert!(unhappy_start >= 20);
+    assert!(unhappy_next["score"].as_f64().unwrap() > 0.9);
+
+    // Once they recover, the cached stricter result is no longer served
+    for hour in 10..16 {
+        store
+            .create_feedback(CreateFeedback {
+                user_id: Some(unhappy),
+                window_id: format!("window_{}_3", hour * 3600),
+                feedback_text: "rated".to_string(),
+                satisfaction_rating: Some(5),
+                drying_result: None,
+                weather_temp_c: None,
+                weather_humidity: None,
+                weather_wind_ms: None,
+                weather_rain_mm: None,
+                predicted_score: None,
+                actual_outcome: None,
+            })
+            .await
+            .unwrap();
+    }
+    assert_eq!(next_good_for(unhappy).await["starts_in_hours"], 0);
+}
+
+#[tokio::test]
+async fn recommendations_pick_among_saved_locations() {
+    let app = create_router(test_state(test_config(&[]), sqlite_store().await));
+    let (_, user) = post(&app, "/preferences", json!({ "location_lat": 13.75, "location_lon": 100.5 })).await;
+    let user_id = user["user_id"].as_str().unwrap();
+    let locations = format!("/preferences/{}/locations", user_id);
+
+    for (label, lat, lon) in [("home", 13.75, 100.5), ("holiday", 7.88, 98.39)] {
+        let (status, saved) = send(
+            &app,
+            Method::PUT,
+            &format!("{}/{}", locations, label),
+            Some(json!({ "lat": lat, "lon": lon, "timezone": "Asia/Bangkok" })),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(saved["label"], label);
+    }
+    // Saving under an existing label moves it rather than adding another
+    let (_, moved) = send(&app, Method::PUT, &format!("{}/holiday", locations), Some(json!({ "lat": 7.9, "lon": 98.4 }))).await;
+    assert_eq!(moved["lat"], 7.9);
+
+    let (status, listed) = get(&app, &locations).await;
+    assert_eq!(status, StatusCode::OK);
+    let labels: Vec<&str> = listed.as_array().unwrap().iter().map(|l| l["label"].as_str().unwrap()).collect();
+    assert_eq!(labels, ["holiday", "home"]);
+
+    let (status, body) =
+        get(&app, &format!("/recommendations?user_id={}&location_label=holiday", user_id)).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["location"]["lat"], 7.9);
+    assert_eq!(body["location"]["name"], "holiday");
+
+    let (status, body) = get(&app, &format!("/recommendations?user_id={}&location_label=office", user_id)).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    assert_eq!(body["code"], "location_not_found");
+    let (status, _) = get(&app, "/recommendations?location_label=home").await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    let (status, _) = send(&app, Method::PUT, &format!("{}/home", locations), Some(json!({ "lat": 95.0, "lon": 0.0 }))).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    let (status, _) = send(&app, Method::DELETE, &format!("{}/holiday", locations), None).await;
+    assert_eq!(status, StatusCode::NO_CONTENT);
+    let (status, _) = send(&app, Method::DELETE, &format!("{}/holiday", locations), None).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    let (_, listed) = get(&app, &locations).await;
+    assert_eq!(listed.as_array().unwrap().len(), 1);
+
+    // The single preferences location is left as it was
+    let (_, prefs) = get(&app, &format!("/preferences/{}", user_id)).await;
+    assert_eq!(prefs["location_lat"], 13.75);
+}
+
+#[tokio::test]
+async fn responses_report_forecast_source_and_cache_hits() {
+    let app = mock_app();
+
+    let (status, first) = get(&app, &format!("/forecast?{}", BANGKOK)).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(first["source"]["provider"], "mock");
+    assert_eq!(first["source"]["cache_hit"], false);
+    assert_eq!(first["source"]["stale"], false);
+
+    // The same ~1 km spot reuses that fetch, whichever endpoint asks
+    let (_, second) = get(&app, &format!("/forecast?{}", BANGKOK)).await;
+    assert_eq!(second["source"]["cache_hit"], true);
+    assert_eq!(second["source"]["fetched_at"], first["source"]["fetched_at"]);
+    let (_, windows) = get(&app, "/drying-windows?lat=13.751&lon=100.501").await;
+    assert_eq!(windows["source"]["cache_hit"], true);
+
+    let (_, first) = get(&app, "/recommendations?lat=18.79&lon=98.98").await;
+    assert_eq!(first["source"]["cache_hit"], false);
+    let (_, second) = get(&app, "/recommendations?lat=18.79&lon=98.98").await;
+    assert_eq!(second["source"]["cache_hit"], true);
+}
+
+#[tokio::test]
+async fn max_age_secs_forces_a_refetch() {
+    let app = mock_app();
+
+    let (_, first) = get(&app, &format!("/forecast?{}", BANGKOK)).await;
+    assert_eq!(first["source"]["cache_hit"], false);
+    let (_, cached) = get(&app, &format!("/forecast?{}", BANGKOK)).await;
+    assert_eq!(cached["source"]["cache_hit"], true);
+
+    // Nothing cached is young enough for zero seconds
+    for uri in [
+        format!("/forecast?{}&max_age_secs=0", BANGKOK),
+        format!("/forecast?{}&max_age_secs=0", BANGKOK),
+        format!("/drying-windows?{}&max_age_secs=0", BANGKOK),
+        format!("/recommendations?{}&max_age_secs=0", BANGKOK),
+        format!("/recommendations?{}&max_age_secs=0", BANGKOK),
+    ] {
+        let (status, body) = get(&app, &uri).await;
+        assert_eq!(status, StatusCode::OK, "{}", uri);
+        assert_eq!(body["source"]["cache_hit"], false, "{}", uri);
+    }
+
+    // A generous limit still accepts the cached forecast
+    let (_, cached) = get(&app, &format!("/forecast?{}&max_age_secs=3600", BANGKOK)).await;
+    assert_eq!(cached["source"]["cache_hit"], true);
+
+    let (status, body) = get(&app, &format!("/forecast?{}&max_age_secs=86401", BANGKOK)).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(body["code"], "invalid_parameter");
+}
+
+#[tokio::test]
+async fn admin_purge_clears_cached_forecasts() {
+    let state = test_state(test_config(&[("ADMIN_API_KEY", "s3cret")]), Arc::new(MockDatabase::new()));
+    let app = create_router(state);
+    let purge = |uri: &'static str, key: &'static str| {
+        let app = app.clone();
+        async move { send_with_headers(&app, Method::POST, uri, &[("x-api-key", key)]).await }
+    };
+
+    get(&app, &format!("/forecast?{}", BANGKOK)).await;
+    get(&app, &format!("/recommendations?{}", BANGKOK)).await;
+    get(&app, "/forecast?lat=18.79&lon=98.98").await;
+
+    let (status, body) = purge("/admin/cache/purge", "wrong").await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+    assert_eq!(body["code"], "unauthorized");
+    let (status, _) = send(&app, Method::POST, "/admin/cache/purge", None).await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+    let (_, cached) = get(&app, &format!("/forecast?{}", BANGKOK)).await;
+    assert_eq!(cached["source"]["cache_hit"], true);
+
+    // One location leaves the other cached
+    let (status, body) = purge("/admin/cache/purge?lat=13.75&lon=100.5", "s3cret").await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["forecasts_purged"], 1);
+    assert_eq!(body["recommendations_purged"], 1);
+    let (_, refetched) = get(&app, &format!("/forecast?{}", BANGKOK)).await;
+    assert_eq!(refetched["source"]["cache_hit"], false);
+    let (_, other) = get(&app, "/forecast?lat=18.79&lon=98.98").await;
+    assert_eq!(other["source"]["cache_hit"], true);
+
+    let (_, body) = purge("/admin/cache/purge", "s3cret").await;
+    assert_eq!(body["forecasts_purged"], 2);
+    let (_, refetched) = get(&app, "/forecast?lat=18.79&lon=98.98").await;
+    assert_eq!(refetched["source"]["cache_hit"], false);
+
+    let (status, body) = purge("/admin/cache/purge?lat=13.75", "s3cret").await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(body["code"], "invalid_parameter");
+}
+
+#[tokio::test]
+async fn admin_endpoints_are_hidden_without_a_key() {
+    let (status, _) = send_with_headers(&mock_app(), Method::POST, "/admin/cache/purge", &[("x-api-key", "")]).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn mock_scenarios_script_the_weather() {
+    let state = test_state(test_config(&[("DEBUG_ENDPOINTS", "true")]), Arc::new(MockDatabase::new()));
+    let app = create_router(state);
+
+    let (status, body) = get(&app, &format!("/drying-windows?{}&scenario=rainy", BANGKOK)).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["source"]["provider"], "mock");
+    let windows = body["windows"].as_array().unwrap();
+    assert!(!windows.is_empty());
+    for window in windows {
+        assert_eq!(window["score"]["unsafe_window"], true);
+        assert!(window["score"]["veto_reason"].is_string());
+    }
+
+    let (_, body) = get(&app, &format!("/drying-windows?{}&scenario=perfect", BANGKOK)).await;
+    assert!(body["windows"].as_array().unwrap().iter().all(|w| w["score"]["unsafe_window"] == false));
+
+    // Nor remembered as the location's forecast
+    let (status, _) = get(&app, &format!("/forecast?{}&scenario=rainy", BANGKOK)).await;
+    assert_eq!(status, StatusCode::OK);
+    let (_, body) = get(&app, &format!("/forecast/changes?{}", BANGKOK)).await;
+    assert!(body["previous_fetched_at"].is_null());
+
+    // Never in place of real weather
+    let (status, body) = get(&mock_app(), &format!("/forecast?{}&scenario=rainy", BANGKOK)).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(body["code"], "invalid_parameter");
+}
+
+#[tokio::test]
+async fn retuned_weights_bypass_cached_recommendations() {
+    let app = mock_app();
+    let (_, user) = post(&app, "/preferences", json!({ "location_lat": 13.75 })).await;
+    let user_id = user["user_id"].as_str().unwrap();
+    let uri = format!("/recommendations?{}&user_id={}", BANGKOK, user_id);
+
+    let (_, first) = get(&app, &uri).await;
+    let (_, cached) = get(&app, &uri).await;
+    assert_eq!(cached["generated_at"], first["generated_at"]);
+
+    let (status, _) = post(&app, &format!("/preferences/{}/weights", user_id), json!({ "w0": -0.1 })).await;
+    assert_eq!(status, StatusCode::OK);
+
+    // A new recommendation scored with the new weights, from the same forecast fetch
+    let (_, fresh) = get(&app, &uri).await;
+    assert_ne!(fresh["generated_at"], first["generated_at"]);
+    let best_score = |body: &serde_json::Value| body["best_windows"][0]["score"]["score"].as_f64().unwrap();
+    assert!(best_score(&fresh) < best_score(&first));
+    assert_eq!(fresh["source"]["fetched_at"], first["source"]["fetched_at"]);
+    assert_eq!(fresh["source"]["cache_hit"], true);
+    let (_, cached) = get(&app, &uri).await;
+    assert_eq!(cached["generated_at"], fresh["generated_at"]);
+}
+
+#[tokio::test]
+async fn ai_sees_the_scored_window_weather() {
+    let ai = Arc::new(FakeAi::new());
+    let mut state = test_state(test_config(&[("DEBUG_ENDPOINTS", "true")]), Arc::new(MockDatabase::new()));
+    state.ai_client = ai.clone();
+    let app = create_router(state);
+
+    let (status, body) = get(&app, &format!("/recommendations?{}&scenario=rainy&verbose=true", BANGKOK)).await;
+    assert_eq!(status, StatusCode::OK);
+    let raw = &body["best_windows"][0]["score"]["raw"];
+    let reported = |field: &str| raw[field].as_f64().unwrap();
+
+    let weather = ai.weather.lock().unwrap();
+    assert_eq!(weather.len(), 2, "one explanation and one tips request");
+    for seen in weather.iter() {
+        // The window's own forecast, as scored and reported (to output precision)
+        assert!((seen.cloud - reported("cloud")).abs() < 0.01, "cloud {}", seen.cloud);
+        assert!((seen.rain_p - reported("rain_p")).abs() < 0.01, "rain_p {}", seen.rain_p);
+        assert!((seen.rain_mm - reported("rain_mm")).abs() < 0.1, "rain_mm {}", seen.rain_mm);
+        // Not the stand-ins for weather users report: 50% cloud, 80% chance when wet
+        assert!(seen.cloud > 0.85 && seen.rain_p != 0.8);
+    }
+}
+
+#[tokio::test]
+async fn liveness_and_readiness_probes() {
+    let app = test_app().await;
+
+    let (status, body) = get(&app, "/livez").await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["status"], "alive");
+
+    let (status, body) = get(&app, "/readyz").await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["status"], "ready");
+    assert_eq!(body["checks"], json!([{ "name": "database", "ok": true, "error": null }]));
+}
+
+#[tokio::test]
+async fn readiness_fails_when_the_weather_provider_is_down() {
+    let config = || test_config(&[("READINESS_CHECK_WEATHER", "true")]);
+    let mut state = test_state(config(), Arc::new(MockDatabase::new()));
+    let weather = Arc::new(FakeWeather::new());
+    state.weather_client = weather.clone();
+    let app = create_router(state);
+    let (status, body) = get(&app, "/readyz").await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["checks"][1]["name"], "weather");
+    // Probes in quick succession share one upstream check
+    let (status, _) = get(&app, "/readyz").await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(weather.calls.load(Ordering::SeqCst), 1);
+
+    let mut state = test_state(config(), Arc::new(MockDatabase::new()));
+    state.weather_client = Arc::new(FakeWeather::new().without_forecast3h());
+    let app = create_router(state);
+    let (status, body) = get(&app, "/readyz").await;
+    assert_eq!(status, StatusCode::SERVICE_UNAVAILABLE);
+    assert_eq!(body["status"], "not_ready");
+    assert_eq!(body["checks"][0]["ok"], true);
+    assert_eq!(body["checks"][1]["ok"], false);
+    assert!(body["checks"][1]["error"].is_string());
+
+    // Still alive: restarting would not bring the provider back
+    let (status, _) = get(&app, "/livez").await;
+    assert_eq!(status, StatusCode::OK);
+}
+
+#[tokio::test]
+async fn draining_fails_readiness_but_keeps_serving() {
+    let state = test_state(test_config(&[]), Arc::new(MockDatabase::new()));
+    let app = create_router(state.clone());
+    state.begin_draining();
+
+    let (status, body) = get(&app, "/readyz").await;
+    assert_eq!(status, StatusCode::SERVICE_UNAVAILABLE);
+    assert_eq!(body["status"], "draining");
+
+    // In-flight and straggling requests are still answered
+    for uri in ["/livez", "/health"] {
+        let (status, _) = get(&app, uri).await;
+        assert_eq!(status, StatusCode::OK, "{}", uri);
+    }
+    let (status, _) = get(&app, &format!("/forecast?{}", BANGKOK)).await;
+    assert_eq!(status, StatusCode::OK);
+}